@@ -0,0 +1,242 @@
+//! Lets players configure logic blocks by interacting with them.
+
+use bevy::prelude::*;
+use cosmos_core::{
+    block::{
+        Block,
+        block_events::{BlockInteractMessage, BlockMessagesSet},
+    },
+    events::cancellable::Cancellable,
+    logic::{
+        constant::SetLogicConstant,
+        sensor::{MAX_PROXIMITY_SENSOR_RANGE, ProximitySensorRange, SetProximitySensorRange},
+        timer::{LogicTimerPeriod, MAX_LOGIC_TIMER_PERIOD, MIN_LOGIC_TIMER_PERIOD, SetLogicTimerPeriod},
+        wireless::{FACTION_BROADCAST_CHANNEL, LogicChannel, SetLogicChannel},
+    },
+    netty::sync::events::client_event::NettyMessageWriter,
+    prelude::{Structure, StructureBlock},
+    registry::{Registry, identifiable::Identifiable},
+    state::GameState,
+};
+
+use crate::ui::components::{
+    modal::{
+        Modal,
+        text_modal::{TextModal, TextModalComplete},
+    },
+    text_input::InputType,
+};
+
+const TIMED_LOGIC_BLOCK_IDS: [&str; 3] = ["cosmos:logic_clock", "cosmos:logic_delay", "cosmos:pulse_extender"];
+const LOGIC_CONSTANT_ID: &str = "cosmos:logic_constant";
+const PROXIMITY_SENSOR_ID: &str = "cosmos:proximity_sensor";
+const WIRELESS_LOGIC_BLOCK_IDS: [&str; 2] = ["cosmos:logic_transmitter", "cosmos:logic_receiver"];
+
+/// The block that was interacted with, if it is one of the given blocks.
+fn interacted_block(
+    ev: &BlockInteractMessage,
+    block_ids: &[&str],
+    q_structure: &Query<&Structure>,
+    blocks: &Registry<Block>,
+) -> Option<StructureBlock> {
+    let block = ev.block?;
+    let structure = q_structure.get(block.structure()).ok()?;
+
+    block_ids
+        .contains(&structure.block_at(block.coords(), blocks).unlocalized_name())
+        .then_some(block)
+}
+
+fn open_timer_period_modal(
+    mut evr_interact: MessageReader<Cancellable<BlockInteractMessage>>,
+    q_structure: Query<&Structure>,
+    q_period: Query<&LogicTimerPeriod>,
+    blocks: Res<Registry<Block>>,
+    mut commands: Commands,
+) {
+    for ev in evr_interact.read().flatten() {
+        let Some(block) = interacted_block(ev, &TIMED_LOGIC_BLOCK_IDS, &q_structure, &blocks) else {
+            continue;
+        };
+
+        let period = q_structure
+            .get(block.structure())
+            .ok()
+            .and_then(|structure| structure.query_block_data(block.coords(), &q_period))
+            .copied()
+            .unwrap_or_default();
+
+        commands
+            .spawn((
+                Name::new("Logic Timer Period Modal"),
+                Modal {
+                    title: "Timer Period".into(),
+                },
+                TextModal {
+                    prompt: "Logic Ticks".into(),
+                    starting_value: period.ticks().to_string(),
+                    input_type: InputType::Integer {
+                        min: MIN_LOGIC_TIMER_PERIOD as i64,
+                        max: MAX_LOGIC_TIMER_PERIOD as i64,
+                    },
+                    ..Default::default()
+                },
+            ))
+            .observe(
+                move |ev: On<TextModalComplete>, mut nevw_set_period: NettyMessageWriter<SetLogicTimerPeriod>| {
+                    let Ok(ticks) = ev.text.parse::<u32>() else {
+                        return;
+                    };
+
+                    nevw_set_period.write(SetLogicTimerPeriod { block, ticks });
+                },
+            );
+    }
+}
+
+fn open_logic_constant_modal(
+    mut evr_interact: MessageReader<Cancellable<BlockInteractMessage>>,
+    q_structure: Query<&Structure>,
+    blocks: Res<Registry<Block>>,
+    mut commands: Commands,
+) {
+    for ev in evr_interact.read().flatten() {
+        let Some(block) = interacted_block(ev, &[LOGIC_CONSTANT_ID], &q_structure, &blocks) else {
+            continue;
+        };
+
+        // The signal a constant outputs isn't synced to clients, so there is nothing to prefill.
+        commands
+            .spawn((
+                Name::new("Logic Constant Modal"),
+                Modal {
+                    title: "Logic Constant".into(),
+                },
+                TextModal {
+                    prompt: "Output Signal".into(),
+                    input_type: InputType::Integer {
+                        min: i32::MIN as i64,
+                        max: i32::MAX as i64,
+                    },
+                    ..Default::default()
+                },
+            ))
+            .observe(
+                move |ev: On<TextModalComplete>, mut nevw_set_constant: NettyMessageWriter<SetLogicConstant>| {
+                    let Ok(value) = ev.text.parse::<i32>() else {
+                        return;
+                    };
+
+                    nevw_set_constant.write(SetLogicConstant { block, value });
+                },
+            );
+    }
+}
+
+fn open_proximity_sensor_range_modal(
+    mut evr_interact: MessageReader<Cancellable<BlockInteractMessage>>,
+    q_structure: Query<&Structure>,
+    q_range: Query<&ProximitySensorRange>,
+    blocks: Res<Registry<Block>>,
+    mut commands: Commands,
+) {
+    for ev in evr_interact.read().flatten() {
+        let Some(block) = interacted_block(ev, &[PROXIMITY_SENSOR_ID], &q_structure, &blocks) else {
+            continue;
+        };
+
+        let range = q_structure
+            .get(block.structure())
+            .ok()
+            .and_then(|structure| structure.query_block_data(block.coords(), &q_range))
+            .copied()
+            .unwrap_or_default();
+
+        commands
+            .spawn((
+                Name::new("Proximity Sensor Range Modal"),
+                Modal {
+                    title: "Proximity Sensor".into(),
+                },
+                TextModal {
+                    prompt: "Range (Blocks)".into(),
+                    starting_value: range.blocks().to_string(),
+                    input_type: InputType::Integer {
+                        min: 0,
+                        max: MAX_PROXIMITY_SENSOR_RANGE as i64,
+                    },
+                    ..Default::default()
+                },
+            ))
+            .observe(
+                move |ev: On<TextModalComplete>, mut nevw_set_range: NettyMessageWriter<SetProximitySensorRange>| {
+                    let Ok(blocks) = ev.text.parse::<u32>() else {
+                        return;
+                    };
+
+                    nevw_set_range.write(SetProximitySensorRange { block, blocks });
+                },
+            );
+    }
+}
+
+fn open_logic_channel_modal(
+    mut evr_interact: MessageReader<Cancellable<BlockInteractMessage>>,
+    q_structure: Query<&Structure>,
+    q_channel: Query<&LogicChannel>,
+    blocks: Res<Registry<Block>>,
+    mut commands: Commands,
+) {
+    for ev in evr_interact.read().flatten() {
+        let Some(block) = interacted_block(ev, &WIRELESS_LOGIC_BLOCK_IDS, &q_structure, &blocks) else {
+            continue;
+        };
+
+        let channel = q_structure
+            .get(block.structure())
+            .ok()
+            .and_then(|structure| structure.query_block_data(block.coords(), &q_channel))
+            .copied()
+            .unwrap_or_default();
+
+        commands
+            .spawn((
+                Name::new("Logic Channel Modal"),
+                Modal {
+                    title: "Wireless Channel".into(),
+                },
+                TextModal {
+                    prompt: format!("Channel ({FACTION_BROADCAST_CHANNEL} reaches all your structures)"),
+                    starting_value: channel.0.to_string(),
+                    input_type: InputType::Integer {
+                        min: 0,
+                        max: u32::MAX as i64,
+                    },
+                    ..Default::default()
+                },
+            ))
+            .observe(
+                move |ev: On<TextModalComplete>, mut nevw_set_channel: NettyMessageWriter<SetLogicChannel>| {
+                    let Ok(channel) = ev.text.parse::<u32>() else {
+                        return;
+                    };
+
+                    nevw_set_channel.write(SetLogicChannel { block, channel });
+                },
+            );
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (
+            open_timer_period_modal,
+            open_logic_constant_modal,
+            open_proximity_sensor_range_modal,
+            open_logic_channel_modal,
+        )
+            .in_set(BlockMessagesSet::UpdateBlocksWithinStructures)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
@@ -2,10 +2,12 @@ use bevy::prelude::*;
 
 mod dye_machine;
 mod logic;
+mod logic_config;
 mod numeric_display;
 
 pub(super) fn register(app: &mut App) {
     dye_machine::register(app);
     logic::register(app);
+    logic_config::register(app);
     numeric_display::register(app);
 }
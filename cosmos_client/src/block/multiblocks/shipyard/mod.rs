@@ -3,7 +3,10 @@ use cosmos_core::{
     block::{
         Block,
         data::BlockData,
-        multiblock::prelude::{ClientFriendlyShipyardState, ClientSetShipyardState, SetShipyardBlueprint, ShowShipyardUi},
+        multiblock::prelude::{
+            ClientFriendlyShipyardState, ClientSetShipyardState, RegisterShipyardShip, RepairShipyardShip, SetShipyardBlueprint,
+            ShowShipyardUi,
+        },
     },
     faction::Factions,
     inventory::Inventory,
@@ -197,6 +200,69 @@ fn create_shipyard_ui(
 ) {
     match state {
         None => {
+            p.spawn((
+                Name::new("Register btn"),
+                CosmosButton {
+                    text: Some((
+                        "Register Ship for Repairs".into(),
+                        TextFont {
+                            font: font.get(),
+                            font_size: 20.0,
+                            ..Default::default()
+                        },
+                        Default::default(),
+                    )),
+                    ..Default::default()
+                },
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..Default::default()
+                },
+                BorderColor::all(css::AQUA),
+            ))
+            .observe(
+                move |ev: On<ButtonEvent>, mut nevw_register_ship: NettyMessageWriter<RegisterShipyardShip>| {
+                    info!("Registering shipyard ship for repairs ({ev:?})");
+                    nevw_register_ship.write(RegisterShipyardShip { shipyard_block: block });
+                },
+            );
+
+            p.spawn((
+                Name::new("Repair btn"),
+                CosmosButton {
+                    text: Some((
+                        "Repair Ship (as registered)".into(),
+                        TextFont {
+                            font: font.get(),
+                            font_size: 20.0,
+                            ..Default::default()
+                        },
+                        Default::default(),
+                    )),
+                    ..Default::default()
+                },
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..Default::default()
+                },
+                BorderColor::all(css::YELLOW),
+            ))
+            .observe(
+                move |ev: On<ButtonEvent>, mut nevw_repair_ship: NettyMessageWriter<RepairShipyardShip>| {
+                    info!("Repairing shipyard ship ({ev:?})");
+                    nevw_repair_ship.write(RepairShipyardShip {
+                        shipyard_block: block,
+                        blueprint_slot: None,
+                    });
+                },
+            );
+
             p.spawn((
                 Text::new("Select Blueprint"),
                 TextFont {
@@ -298,6 +364,39 @@ fn create_shipyard_ui(
                             });
                         },
                     );
+
+                    p.spawn((
+                        Name::new("Repair to blueprint btn"),
+                        CosmosButton {
+                            text: Some((
+                                "Repair".into(),
+                                TextFont {
+                                    font: font.get(),
+                                    font_size: 20.0,
+                                    ..Default::default()
+                                },
+                                Default::default(),
+                            )),
+                            ..Default::default()
+                        },
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Px(50.0),
+                            margin: UiRect::top(Val::Px(10.0)),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..Default::default()
+                        },
+                        BorderColor::all(css::YELLOW),
+                    ))
+                    .observe(
+                        move |ev: On<ButtonEvent>, mut nevw_repair_ship: NettyMessageWriter<RepairShipyardShip>| {
+                            info!("Repairing shipyard ship to blueprint ({ev:?})");
+                            nevw_repair_ship.write(RepairShipyardShip {
+                                shipyard_block: block,
+                                blueprint_slot: Some(slot as u32),
+                            });
+                        },
+                    );
                 });
             }
         }
@@ -543,6 +642,98 @@ fn create_shipyard_ui(
                     );
             });
         }
+        Some(ClientFriendlyShipyardState::Repairing(r)) => {
+            p.spawn((
+                Text::new("Repairing"),
+                TextFont {
+                    font_size: 32.0,
+                    font: font.get(),
+                    ..Default::default()
+                },
+                Node {
+                    margin: UiRect::all(Val::Px(20.0)),
+                    ..Default::default()
+                },
+            ));
+
+            p.spawn((
+                ScrollBox::default(),
+                Node {
+                    flex_grow: 1.0,
+                    ..Default::default()
+                },
+            ))
+            .with_children(|p| {
+                // Sort by amt remaining
+                let mut blocks_remaining = r
+                    .remaining_blocks
+                    .iter()
+                    .map(|(a, b)| (blocks.from_numeric_id(*a), *b))
+                    .collect::<Vec<_>>();
+
+                blocks_remaining.sort_unstable_by_key(|x| !x.1);
+
+                for (block, qty) in blocks_remaining {
+                    p.spawn((
+                        Text::new(format!("{} - {}", lang.get_name_or_unlocalized(block), qty)),
+                        TextFont {
+                            font_size: 24.0,
+                            font: font.get(),
+                            ..Default::default()
+                        },
+                        Node {
+                            margin: UiRect::all(Val::Px(25.0)),
+                            ..Default::default()
+                        },
+                    ));
+                }
+            });
+
+            p.spawn((
+                CosmosButton {
+                    text: Some((
+                        "Stop".into(),
+                        TextFont {
+                            font_size: 20.0,
+                            font: font.get(),
+                            ..Default::default()
+                        },
+                        Default::default(),
+                    )),
+                    ..Default::default()
+                },
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Auto),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..Default::default()
+                },
+                BorderColor::all(css::RED),
+            ))
+            .observe(move |_trigger: On<ButtonEvent>, mut commands: Commands| {
+                commands
+                    .spawn((
+                        Modal {
+                            title: "Stop Shipyard".into(),
+                        },
+                        ConfirmModal {
+                            prompt: "Are you sure you want stop this repair?".into(),
+                            ..Default::default()
+                        },
+                    ))
+                    .observe(
+                        move |ev: On<ConfirmModalComplete>, mut nevw_change_shipyard_state: NettyMessageWriter<ClientSetShipyardState>| {
+                            if !ev.confirmed {
+                                return;
+                            }
+
+                            info!("Stop shipyard!");
+                            nevw_change_shipyard_state.write(ClientSetShipyardState::Stop { controller: block });
+                        },
+                    );
+            });
+        }
         Some(ClientFriendlyShipyardState::Deconstructing(e)) => {
             p.spawn(Text::new(format!("DECONSTRUCTING TODO {e:?}")));
         }
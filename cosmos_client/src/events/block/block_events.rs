@@ -112,6 +112,8 @@ fn show_errors(
     for ev in nevr_block_place_error.read() {
         let reason = match ev {
             InvalidBlockPlaceMessageReason::DifferentFaction => "This structure belongs to a different faction.",
+            InvalidBlockPlaceMessageReason::InsufficientFactionRank => "Your faction rank does not allow building here.",
+            InvalidBlockPlaceMessageReason::NotTrusted => "The owner of this structure has not trusted you to build on it.",
             InvalidBlockPlaceMessageReason::CoreBlock => "Cannot place another core block on this structure.",
         };
 
@@ -121,6 +123,8 @@ fn show_errors(
     for ev in nevr_block_interact_error.read() {
         let reason = match ev {
             InvalidBlockInteractMessageReason::DifferentFaction => "This structure belongs to a different faction.",
+            InvalidBlockInteractMessageReason::InsufficientFactionRank => "Your faction rank does not allow accessing storage.",
+            InvalidBlockInteractMessageReason::NotTrusted => "The owner of this structure has not trusted you to use it.",
         };
 
         hud_messages.display_message(HudMessage::with_colored_string(reason, css::RED.into()));
@@ -129,6 +133,8 @@ fn show_errors(
     for ev in nevr_block_break_error.read() {
         let reason = match ev {
             InvalidBlockBreakMessageReason::DifferentFaction => "This structure belongs to a different faction.",
+            InvalidBlockBreakMessageReason::InsufficientFactionRank => "Your faction rank does not allow building here.",
+            InvalidBlockBreakMessageReason::NotTrusted => "The owner of this structure has not trusted you to build on it.",
             InvalidBlockBreakMessageReason::StructureCore => "The core of this structure must be the last block mined.",
         };
 
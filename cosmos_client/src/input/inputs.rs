@@ -163,6 +163,9 @@ pub enum CosmosInputs {
     /// Opens the ship's configuration menu
     OpenShipConfiguration,
 
+    /// Toggles whether the piloted ship follows nearby fleet warps
+    ToggleFollowFleetWarp,
+
     /// Uses the player's held item
     UseHeldItem,
 
@@ -267,6 +270,8 @@ fn init_input(mut input_handler: ResMut<CosmosInputHandler>) {
 
     input_handler.set_keycode(CosmosInputs::OpenShipConfiguration, KeyCode::Tab);
 
+    input_handler.set_keycode(CosmosInputs::ToggleFollowFleetWarp, KeyCode::KeyJ);
+
     input_handler.set_mouse_button(CosmosInputs::UseHeldItem, MouseButton::Right);
 
     input_handler.set_keycode(CosmosInputs::ToggleFullscreen, KeyCode::F11);
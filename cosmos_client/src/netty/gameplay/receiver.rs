@@ -45,7 +45,7 @@ use cosmos_core::{
     state::GameState,
     structure::{
         ChunkInitMessage, Structure,
-        block_health::events::BlockTakeDamageMessage,
+        block_health::events::{BlockRepairedMessage, BlockTakeDamageMessage},
         block_storage::BlockStorer,
         chunk::Chunk,
         dynamic_structure::DynamicStructure,
@@ -181,12 +181,14 @@ pub(crate) fn client_sync_players(
         mut take_damage_event_writer,
         mut set_terrain_data_ev_writer,
         mut evw_block_data_changed,
+        mut evw_block_repaired,
     ): (
         MessageWriter<ChunkInitMessage>,
         MessageWriter<BlockChangedMessage>,
         MessageWriter<BlockTakeDamageMessage>,
         MessageWriter<SetTerrainGenData>,
         MessageWriter<BlockDataChangedMessage>,
+        MessageWriter<BlockRepairedMessage>,
     ),
     (q_default_rapier_context, query_player, q_structure_systems, mut q_inventory, mut q_structure): (
         Query<Entity, With<DefaultRapierContext>>,
@@ -652,6 +654,18 @@ pub(crate) fn client_sync_players(
                         })
                 }));
             }
+            ServerReliableMessages::BlockHealthRestored { changes } => {
+                evw_block_repaired.write_batch(changes.into_iter().filter_map(|ev| {
+                    network_mapping
+                        .client_from_server(&ev.structure_entity)
+                        .map(|structure_entity| BlockRepairedMessage {
+                            structure_entity,
+                            block: ev.block,
+                            new_health: ev.new_health,
+                            repairer: ev.causer.and_then(|x| network_mapping.client_from_server(&x)),
+                        })
+                }));
+            }
             ServerReliableMessages::TerrainGenerationShaders {
                 shaders,
                 permutation_table,
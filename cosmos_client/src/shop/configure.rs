@@ -0,0 +1,461 @@
+//! The window the owner of a player-owned shop uses to change its name and what it buys and sells
+
+use bevy::{color::palettes::css, prelude::*};
+use bevy_renet::RenetClient;
+use cosmos_core::{
+    ecs::NeedsDespawned,
+    inventory::{Inventory, held_item_slot::HeldItemSlot},
+    item::Item,
+    netty::{NettyChannelClient, client::LocalPlayer, cosmos_encoder},
+    registry::{Registry, identifiable::Identifiable},
+    shop::{Shop, ShopEntry, netty::ClientShopMessages},
+    state::GameState,
+    structure::structure_block::StructureBlock,
+};
+
+use crate::{
+    lang::Lang,
+    ui::{
+        OpenMenu, UiSystemSet,
+        components::{
+            button::{ButtonEvent, ButtonStyles, CosmosButton},
+            scollable_container::ScrollBox,
+            text_input::{InputType, InputValue, TextInput},
+            window::GuiWindow,
+        },
+        font::DefaultFont,
+        hud::error::ShowInfoPopup,
+    },
+};
+
+#[derive(Component, Debug)]
+/// The window the owner of a shop uses to change what it buys and sells.
+///
+/// Nothing is changed until this is saved, which sends the new configuration to the server.
+pub(super) struct ConfigureShopUi {
+    /// # ⚠️ WARNING ⚠️
+    ///
+    /// This refers to the server's entity NOT the client's
+    structure_block: StructureBlock,
+    name: String,
+    contents: Vec<ShopEntry>,
+}
+
+impl ConfigureShopUi {
+    /// Starts configuring this shop from what it currently buys and sells
+    pub(super) fn new(shop: &Shop, structure_block: StructureBlock) -> Self {
+        Self {
+            structure_block,
+            name: shop.name.clone(),
+            contents: shop.contents.clone(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct ConfigureShopEntities {
+    name_input: Entity,
+    price_input: Entity,
+    entries_list: Entity,
+}
+
+#[derive(Component)]
+struct ConfigureUiEntity(Entity);
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+/// Adds the held item to what the shop sells or buys
+enum AddEntryButton {
+    Sell,
+    Buy,
+}
+
+#[derive(Component)]
+struct RemoveEntryButton(usize);
+
+fn button_styles(color: Srgba) -> Option<ButtonStyles> {
+    Some(ButtonStyles {
+        background_color: color.into(),
+        hover_background_color: color.into(),
+        press_background_color: color.into(),
+        ..Default::default()
+    })
+}
+
+fn render_configure_ui(
+    mut commands: Commands,
+    q_configure_ui: Query<(Entity, &ConfigureShopUi), Added<ConfigureShopUi>>,
+    q_open_configure_uis: Query<Entity, With<ConfigureShopUi>>,
+    default_font: Res<DefaultFont>,
+) {
+    for (ui_ent, configure_ui) in q_configure_ui.iter() {
+        // Only one shop can be configured at a time
+        for ent in q_open_configure_uis.iter().filter(|&ent| ent != ui_ent) {
+            commands.entity(ent).insert(NeedsDespawned);
+        }
+
+        let text_style = TextFont {
+            font_size: 24.0,
+            font: default_font.get(),
+            ..Default::default()
+        };
+
+        let input_node = Node {
+            flex_grow: 1.0,
+            border: UiRect::all(Val::Px(2.0)),
+            padding: UiRect::all(Val::Px(4.0)),
+            ..Default::default()
+        };
+
+        let mut entities = ConfigureShopEntities {
+            name_input: Entity::PLACEHOLDER,
+            price_input: Entity::PLACEHOLDER,
+            entries_list: Entity::PLACEHOLDER,
+        };
+
+        commands
+            .entity(ui_ent)
+            .insert((
+                Name::new("Configure Shop UI"),
+                OpenMenu::new(0),
+                BackgroundColor(Srgba::hex("2D2D2D").unwrap().into()),
+                Node {
+                    width: Val::Px(700.0),
+                    height: Val::Px(600.0),
+                    margin: UiRect::all(Val::Auto),
+                    position_type: PositionType::Absolute,
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..Default::default()
+                },
+                BorderColor::all(Color::BLACK),
+                GuiWindow {
+                    title: "Configure Shop".into(),
+                    body_styles: Node {
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(10.0)),
+                        row_gap: Val::Px(10.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ))
+            .with_children(|p| {
+                p.spawn(Node {
+                    column_gap: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                })
+                .with_children(|p| {
+                    p.spawn((Text::new("Name"), text_style.clone()));
+
+                    entities.name_input = p
+                        .spawn((
+                            Name::new("Shop Name"),
+                            TextInput {
+                                input_type: InputType::Text { max_length: Some(30) },
+                                ..Default::default()
+                            },
+                            InputValue::new(configure_ui.name.clone()),
+                            BorderColor::all(Srgba::hex("111111").unwrap()),
+                            BackgroundColor(Srgba::hex("555555").unwrap().into()),
+                            text_style.clone(),
+                            input_node.clone(),
+                        ))
+                        .id();
+                });
+
+                p.spawn(Node {
+                    column_gap: Val::Px(10.0),
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                })
+                .with_children(|p| {
+                    p.spawn((Text::new("Price $"), text_style.clone()));
+
+                    entities.price_input = p
+                        .spawn((
+                            Name::new("Price Per Item"),
+                            TextInput {
+                                input_type: InputType::Integer {
+                                    min: 0,
+                                    max: u32::MAX as i64,
+                                },
+                                ..Default::default()
+                            },
+                            InputValue::new("1"),
+                            BorderColor::all(Srgba::hex("111111").unwrap()),
+                            BackgroundColor(Srgba::hex("555555").unwrap().into()),
+                            text_style.clone(),
+                            input_node.clone(),
+                        ))
+                        .id();
+
+                    for (button, text, color) in [
+                        (AddEntryButton::Sell, "Sell Held Item", css::DARK_GREEN),
+                        (AddEntryButton::Buy, "Buy Held Item", Srgba::hex("880000").unwrap()),
+                    ] {
+                        p.spawn((
+                            button,
+                            ConfigureUiEntity(ui_ent),
+                            Node {
+                                padding: UiRect::all(Val::Px(8.0)),
+                                ..Default::default()
+                            },
+                            CosmosButton {
+                                button_styles: button_styles(color),
+                                text: Some((text.into(), text_style.clone(), Default::default())),
+                                ..Default::default()
+                            },
+                        ))
+                        .observe(on_add_entry);
+                    }
+                });
+
+                p.spawn((
+                    Name::new("Entries"),
+                    Node {
+                        flex_grow: 1.0,
+                        ..Default::default()
+                    },
+                    ScrollBox::default(),
+                ))
+                .with_children(|p| {
+                    entities.entries_list = p
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(5.0),
+                            ..Default::default()
+                        })
+                        .id();
+                });
+
+                p.spawn(Node {
+                    column_gap: Val::Px(10.0),
+                    ..Default::default()
+                })
+                .with_children(|p| {
+                    p.spawn((
+                        ConfigureUiEntity(ui_ent),
+                        Node {
+                            flex_grow: 1.0,
+                            padding: UiRect::all(Val::Px(8.0)),
+                            ..Default::default()
+                        },
+                        CosmosButton {
+                            button_styles: button_styles(css::DARK_GREY),
+                            text: Some(("Cancel".into(), text_style.clone(), Default::default())),
+                            ..Default::default()
+                        },
+                    ))
+                    .observe(on_cancel);
+
+                    p.spawn((
+                        ConfigureUiEntity(ui_ent),
+                        Node {
+                            flex_grow: 1.0,
+                            padding: UiRect::all(Val::Px(8.0)),
+                            ..Default::default()
+                        },
+                        CosmosButton {
+                            button_styles: button_styles(css::AQUA),
+                            text: Some(("Save".into(), text_style.clone(), TextColor(css::BLACK.into()))),
+                            ..Default::default()
+                        },
+                    ))
+                    .observe(on_save);
+                });
+            })
+            .insert(entities);
+    }
+}
+
+fn render_entries(
+    mut commands: Commands,
+    q_configure_ui: Query<(Entity, &ConfigureShopUi, &ConfigureShopEntities), Changed<ConfigureShopUi>>,
+    items: Res<Registry<Item>>,
+    lang: Res<Lang<Item>>,
+    default_font: Res<DefaultFont>,
+) {
+    for (ui_ent, configure_ui, entities) in q_configure_ui.iter() {
+        let text_style = TextFont {
+            font_size: 24.0,
+            font: default_font.get(),
+            ..Default::default()
+        };
+
+        commands
+            .entity(entities.entries_list)
+            .despawn_related::<Children>()
+            .with_children(|p| {
+                if configure_ui.contents.is_empty() {
+                    p.spawn((Text::new("Hold an item and choose to sell or buy it."), text_style.clone()));
+                }
+
+                for (index, entry) in configure_ui.contents.iter().enumerate() {
+                    let (verb, item_id, price_per) = match *entry {
+                        ShopEntry::Selling { item_id, price_per, .. } => ("Selling", item_id, price_per),
+                        ShopEntry::Buying { item_id, price_per, .. } => ("Buying", item_id, price_per),
+                    };
+
+                    let item = items.from_numeric_id(item_id);
+                    let item_name = lang.get_name(item).unwrap_or(item.unlocalized_name());
+
+                    p.spawn(Node {
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(10.0),
+                        ..Default::default()
+                    })
+                    .with_children(|p| {
+                        p.spawn((
+                            Text::new(format!("{verb} {item_name} for ${price_per} each")),
+                            text_style.clone(),
+                            Node {
+                                flex_grow: 1.0,
+                                ..Default::default()
+                            },
+                        ));
+
+                        p.spawn((
+                            RemoveEntryButton(index),
+                            ConfigureUiEntity(ui_ent),
+                            Node {
+                                padding: UiRect::all(Val::Px(4.0)),
+                                ..Default::default()
+                            },
+                            CosmosButton {
+                                button_styles: button_styles(css::DARK_GREY),
+                                text: Some(("Remove".into(), text_style.clone(), Default::default())),
+                                ..Default::default()
+                            },
+                        ))
+                        .observe(on_remove_entry);
+                    });
+                }
+            });
+    }
+}
+
+fn on_add_entry(
+    ev: On<ButtonEvent>,
+    q_button: Query<(&AddEntryButton, &ConfigureUiEntity)>,
+    mut q_configure_ui: Query<(&mut ConfigureShopUi, &ConfigureShopEntities)>,
+    q_input_value: Query<&InputValue>,
+    q_player: Query<(&Inventory, &HeldItemSlot), With<LocalPlayer>>,
+    mut evw_popup: MessageWriter<ShowInfoPopup>,
+) {
+    let Ok((&button, ui_ent)) = q_button.get(ev.0) else {
+        return;
+    };
+
+    let Ok((mut configure_ui, entities)) = q_configure_ui.get_mut(ui_ent.0) else {
+        return;
+    };
+
+    let Ok((inventory, held_item_slot)) = q_player.single() else {
+        return;
+    };
+
+    let Some(item_id) = inventory.itemstack_at(held_item_slot.slot() as usize).map(|is| is.item_id()) else {
+        evw_popup.write(ShowInfoPopup::error("Hold the item you want this shop to trade."));
+        return;
+    };
+
+    let Some(price_per) = q_input_value
+        .get(entities.price_input)
+        .ok()
+        .and_then(|value| value.value().parse::<u32>().ok())
+    else {
+        evw_popup.write(ShowInfoPopup::error("Enter a price first."));
+        return;
+    };
+
+    let selling = button == AddEntryButton::Sell;
+
+    // A shop can only have one entry for selling and one for buying each item, so this replaces the old one
+    configure_ui.contents.retain(|entry| match *entry {
+        ShopEntry::Selling { item_id: id, .. } => !selling || id != item_id,
+        ShopEntry::Buying { item_id: id, .. } => selling || id != item_id,
+    });
+
+    configure_ui.contents.push(if selling {
+        // The stock is filled in from the shop's storage by the server
+        ShopEntry::Selling {
+            item_id,
+            max_quantity_selling: 0,
+            price_per,
+        }
+    } else {
+        ShopEntry::Buying {
+            item_id,
+            max_quantity_buying: None,
+            price_per,
+        }
+    });
+}
+
+fn on_remove_entry(
+    ev: On<ButtonEvent>,
+    q_button: Query<(&RemoveEntryButton, &ConfigureUiEntity)>,
+    mut q_configure_ui: Query<&mut ConfigureShopUi>,
+) {
+    let Ok((remove, ui_ent)) = q_button.get(ev.0) else {
+        return;
+    };
+
+    let Ok(mut configure_ui) = q_configure_ui.get_mut(ui_ent.0) else {
+        return;
+    };
+
+    if remove.0 < configure_ui.contents.len() {
+        configure_ui.contents.remove(remove.0);
+    }
+}
+
+fn on_cancel(ev: On<ButtonEvent>, q_ui_ent: Query<&ConfigureUiEntity>, mut commands: Commands) {
+    if let Ok(ui_ent) = q_ui_ent.get(ev.0) {
+        commands.entity(ui_ent.0).insert(NeedsDespawned);
+    }
+}
+
+fn on_save(
+    ev: On<ButtonEvent>,
+    q_ui_ent: Query<&ConfigureUiEntity>,
+    q_configure_ui: Query<(&ConfigureShopUi, &ConfigureShopEntities)>,
+    q_input_value: Query<&InputValue>,
+    mut client: ResMut<RenetClient>,
+    mut commands: Commands,
+) {
+    let Ok(ui_ent) = q_ui_ent.get(ev.0) else {
+        return;
+    };
+
+    let Ok((configure_ui, entities)) = q_configure_ui.get(ui_ent.0) else {
+        return;
+    };
+
+    let name = q_input_value
+        .get(entities.name_input)
+        .map(|value| value.value().to_owned())
+        .unwrap_or_else(|_| configure_ui.name.clone());
+
+    client.send_message(
+        NettyChannelClient::Shop,
+        cosmos_encoder::serialize(&ClientShopMessages::Configure {
+            shop_block: configure_ui.structure_block.coords(),
+            structure_entity: configure_ui.structure_block.structure(),
+            name,
+            contents: configure_ui.contents.clone(),
+        }),
+    );
+
+    commands.entity(ui_ent.0).insert(NeedsDespawned);
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        Update,
+        (render_configure_ui, render_entries)
+            .chain()
+            .in_set(UiSystemSet::PreDoUi)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
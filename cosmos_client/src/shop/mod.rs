@@ -9,6 +9,7 @@ use cosmos_core::{
     structure::coordinates::BlockCoordinate,
 };
 
+mod configure;
 mod netty;
 mod ui;
 
@@ -40,6 +41,7 @@ pub struct SoldMessage {
 
 pub(super) fn register(app: &mut App) {
     ui::register(app);
+    configure::register(app);
     netty::register(app);
 
     app.add_message::<PurchasedMessage>().add_message::<SoldMessage>();
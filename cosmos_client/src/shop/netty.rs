@@ -2,11 +2,13 @@ use bevy::prelude::*;
 use bevy_renet::RenetClient;
 use cosmos_core::{
     netty::{NettyChannelServer, cosmos_encoder, system_sets::NetworkingSystemsSet},
-    shop::netty::ServerShopMessages,
+    shop::netty::{ServerShopMessages, ShopConfigureError},
     state::GameState,
     structure::structure_block::StructureBlock,
 };
 
+use crate::ui::hud::error::ShowInfoPopup;
+
 use super::{PurchasedMessage, SoldMessage, ui::OpenShopUiMessage};
 
 fn shop_listen_netty(
@@ -14,6 +16,7 @@ fn shop_listen_netty(
     mut ev_writer_open_shop_ui: MessageWriter<OpenShopUiMessage>,
     mut ev_writer_purchased: MessageWriter<PurchasedMessage>,
     mut ev_writer_sold: MessageWriter<SoldMessage>,
+    mut evw_popup: MessageWriter<ShowInfoPopup>,
 ) {
     while let Some(message) = client.receive_message(NettyChannelServer::Shop) {
         let msg: ServerShopMessages = cosmos_encoder::deserialize(&message).expect("Bad shop message");
@@ -23,10 +26,12 @@ fn shop_listen_netty(
                 shop_block,
                 structure_entity,
                 shop_data,
+                is_owner,
             } => {
                 ev_writer_open_shop_ui.write(OpenShopUiMessage {
                     shop: shop_data,
                     structure_block: StructureBlock::new(shop_block, structure_entity),
+                    is_owner,
                 });
             }
             ServerShopMessages::PurchaseResult {
@@ -51,6 +56,26 @@ fn shop_listen_netty(
                     structure_entity,
                 });
             }
+            ServerShopMessages::ConfigureResult {
+                shop_block,
+                structure_entity,
+                details,
+            } => match details {
+                Ok(shop) => {
+                    ev_writer_open_shop_ui.write(OpenShopUiMessage {
+                        shop,
+                        structure_block: StructureBlock::new(shop_block, structure_entity),
+                        is_owner: true,
+                    });
+                }
+                Err(err) => {
+                    evw_popup.write(ShowInfoPopup::error(match err {
+                        ShopConfigureError::NotOwner => "Only the owner of this shop can configure it.",
+                        ShopConfigureError::CannotClaim => "You cannot claim this shop.",
+                        ShopConfigureError::InvalidItem(_) => "That item does not exist.",
+                    }));
+                }
+            },
         }
     }
 }
@@ -31,12 +31,14 @@ use crate::{
     },
 };
 
-use super::{PurchasedMessage, SoldMessage};
+use super::{PurchasedMessage, SoldMessage, configure::ConfigureShopUi};
 
 #[derive(Message)]
 pub(super) struct OpenShopUiMessage {
     pub shop: Shop,
     pub structure_block: StructureBlock,
+    /// If the local player owns this shop, and can configure it
+    pub is_owner: bool,
 }
 
 #[derive(Component, Debug)]
@@ -47,6 +49,7 @@ struct ShopUi {
     /// This refers to the server's entity NOT the client's
     structure_block: StructureBlock,
     selected_item: Option<SelectedItem>,
+    is_owner: bool,
 }
 
 #[derive(Reflect, Component, PartialEq, Eq, Default)]
@@ -209,6 +212,7 @@ fn open_shop_ui(mut commands: Commands, mut ev_reader: MessageMutator<OpenShopUi
                 shop,
                 selected_item: None,
                 structure_block: ev.structure_block,
+                is_owner: ev.is_owner,
             },
         ));
     }
@@ -331,6 +335,46 @@ fn render_shop_ui(
                     },
                 ))
                 .observe(click_buy_tab);
+
+                if !shop_ui.shop.is_player_owned() {
+                    p.spawn((
+                        ShopUiEntity(ui_ent),
+                        Node {
+                            flex_grow: 1.0,
+                            ..Default::default()
+                        },
+                        CosmosButton {
+                            button_styles: Some(ButtonStyles {
+                                background_color: css::DARK_SLATE_GRAY.into(),
+                                hover_background_color: css::DARK_SLATE_GRAY.into(),
+                                press_background_color: css::DARK_SLATE_GRAY.into(),
+                                ..Default::default()
+                            }),
+                            text: Some(("Claim".into(), text_style.clone(), Default::default())),
+                            ..Default::default()
+                        },
+                    ))
+                    .observe(click_claim);
+                } else if shop_ui.is_owner {
+                    p.spawn((
+                        ShopUiEntity(ui_ent),
+                        Node {
+                            flex_grow: 1.0,
+                            ..Default::default()
+                        },
+                        CosmosButton {
+                            button_styles: Some(ButtonStyles {
+                                background_color: css::DARK_SLATE_GRAY.into(),
+                                hover_background_color: css::DARK_SLATE_GRAY.into(),
+                                press_background_color: css::DARK_SLATE_GRAY.into(),
+                                ..Default::default()
+                            }),
+                            text: Some(("Configure".into(), text_style.clone(), Default::default())),
+                            ..Default::default()
+                        },
+                    ))
+                    .observe(click_configure);
+                }
             });
 
             p.spawn((
@@ -826,11 +870,13 @@ fn on_change_selected_item(
             continue;
         };
 
+        let price_per = shop_ui.shop.price_of(&selected_item.entry);
+
         let item_id = match selected_item.entry {
             ShopEntry::Buying {
                 item_id,
                 max_quantity_buying,
-                price_per,
+                price_per: _,
             } => {
                 let items_of_this_type = inventory
                     .iter()
@@ -847,7 +893,7 @@ fn on_change_selected_item(
             ShopEntry::Selling {
                 item_id,
                 max_quantity_selling,
-                price_per,
+                price_per: _,
             } => {
                 selected_item_max_quantity.0 = max_quantity_selling.min((credits.amount() as u32).checked_div(price_per).unwrap_or(10000));
                 shop_price_per.0 = price_per;
@@ -907,32 +953,33 @@ fn update_search(
                 let search = search_item_query.0.to_lowercase();
 
                 for shop_entry in shop_ui.shop.contents.iter() {
-                    let (item_id, price_per) = match *shop_mode {
+                    let item_id = match *shop_mode {
                         ShopMode::Buy => {
                             let ShopEntry::Selling {
                                 item_id,
                                 max_quantity_selling: _,
-                                price_per,
+                                price_per: _,
                             } = shop_entry
                             else {
                                 continue;
                             };
 
-                            (*item_id, *price_per)
+                            *item_id
                         }
                         ShopMode::Sell => {
                             let ShopEntry::Buying {
                                 item_id,
                                 max_quantity_buying: _,
-                                price_per,
+                                price_per: _,
                             } = shop_entry
                             else {
                                 continue;
                             };
 
-                            (*item_id, *price_per)
+                            *item_id
                         }
                     };
+                    let price_per = shop_ui.shop.price_of(shop_entry);
 
                     let item = items.from_numeric_id(item_id);
                     let display_name = lang.get_name(item).unwrap_or(item.unlocalized_name());
@@ -1119,6 +1166,33 @@ fn click_sell_tab(ev: On<ButtonEvent>, mut q_shop_mode: Query<&mut ShopMode>, q_
     }
 }
 
+fn click_claim(ev: On<ButtonEvent>, mut client: ResMut<RenetClient>, q_shop_ui: Query<&ShopUi>, q_shop_ui_entity: Query<&ShopUiEntity>) {
+    let Ok(shop_ui) = q_shop_ui_entity.get(ev.0).and_then(|ent| q_shop_ui.get(ent.0)) else {
+        return;
+    };
+
+    client.send_message(
+        NettyChannelClient::Shop,
+        cosmos_encoder::serialize(&ClientShopMessages::Claim {
+            shop_block: shop_ui.structure_block.coords(),
+            structure_entity: shop_ui.structure_block.structure(),
+        }),
+    );
+}
+
+fn click_configure(ev: On<ButtonEvent>, mut commands: Commands, q_shop_ui: Query<&ShopUi>, q_shop_ui_entity: Query<&ShopUiEntity>) {
+    let Ok(shop_ui_ent) = q_shop_ui_entity.get(ev.0) else {
+        return;
+    };
+    let Ok(shop_ui) = q_shop_ui.get(shop_ui_ent.0) else {
+        return;
+    };
+
+    // The shop is reopened with its new contents once the server accepts the configuration
+    commands.entity(shop_ui_ent.0).insert(NeedsDespawned);
+    commands.spawn(ConfigureShopUi::new(&shop_ui.shop, shop_ui.structure_block));
+}
+
 /*
 SelectedItemName::default(),
 SelectedItemDescription::default(),
@@ -2,7 +2,10 @@ use bevy::prelude::*;
 use cosmos_core::{
     block::{Block, block_events::BlockMessagesSet},
     registry::Registry,
-    structure::{Structure, block_health::events::BlockTakeDamageMessage},
+    structure::{
+        Structure,
+        block_health::events::{BlockRepairedMessage, BlockTakeDamageMessage},
+    },
 };
 
 // TODO: Do we need this?
@@ -22,10 +25,26 @@ fn take_damage_reader(
         }
     }
 }
+
+fn repaired_reader(
+    mut structure_query: Query<&mut Structure>,
+    mut event_reader: MessageReader<BlockRepairedMessage>,
+    blocks: Res<Registry<Block>>,
+) {
+    for ev in event_reader.read() {
+        let Ok(mut structure) = structure_query.get_mut(ev.structure_entity) else {
+            continue;
+        };
+
+        structure.set_block_health(ev.block.coords(), ev.new_health, &blocks);
+    }
+}
+
 pub(super) fn register(app: &mut App) {
     app.add_systems(
         Update,
-        take_damage_reader
+        (take_damage_reader, repaired_reader)
+            .chain()
             .after(BlockMessagesSet::ProcessMessages)
             .run_if(resource_exists::<Registry<Block>>),
     );
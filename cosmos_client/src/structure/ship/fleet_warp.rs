@@ -0,0 +1,45 @@
+//! Lets the pilot choose whether their ship follows fleet warps
+
+use bevy::prelude::*;
+use cosmos_core::{
+    netty::client::LocalPlayer,
+    state::GameState,
+    structure::{
+        shared::build_mode::BuildMode,
+        ship::{pilot::Pilot, warp::FollowFleetWarp},
+    },
+};
+
+use crate::{
+    input::inputs::{CosmosInputs, InputChecker, InputHandler},
+    ui::message::{HudMessage, HudMessages},
+};
+
+fn toggle_follow_fleet_warp(
+    inputs: InputChecker,
+    q_local_pilot: Query<&Pilot, (With<LocalPlayer>, Without<BuildMode>)>,
+    q_follow: Query<&FollowFleetWarp>,
+    mut hud_messages: ResMut<HudMessages>,
+    mut commands: Commands,
+) {
+    if !inputs.check_just_pressed(CosmosInputs::ToggleFollowFleetWarp) {
+        return;
+    }
+
+    let Ok(pilot) = q_local_pilot.single() else {
+        return;
+    };
+
+    let follow = !q_follow.get(pilot.entity).is_ok_and(|follow| follow.0);
+    commands.entity(pilot.entity).insert(FollowFleetWarp(follow));
+
+    hud_messages.display_message(HudMessage::with_string(if follow {
+        "This ship will now follow fleet warps."
+    } else {
+        "This ship will no longer follow fleet warps."
+    }));
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(Update, toggle_follow_fleet_warp.run_if(in_state(GameState::Playing)));
+}
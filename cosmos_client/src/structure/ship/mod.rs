@@ -12,6 +12,7 @@ use cosmos_core::{
 
 mod client_ship_builder;
 pub mod create_ship;
+mod fleet_warp;
 mod pilot;
 pub mod ship_movement;
 pub mod ui;
@@ -117,6 +118,7 @@ pub(super) fn register(app: &mut App) {
     client_ship_builder::register(app);
     ship_movement::register(app);
     create_ship::register(app);
+    fleet_warp::register(app);
     pilot::register(app);
     ui::register(app);
 
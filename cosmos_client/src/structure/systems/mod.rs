@@ -9,6 +9,7 @@ pub mod mining_laser_system;
 pub mod missile_launcher_system;
 pub mod player_interactions;
 mod railgun_system;
+mod repair_beam_system;
 mod shield_system;
 mod sync;
 pub mod thruster_system;
@@ -30,6 +31,7 @@ pub(super) fn register(app: &mut App) {
     camera_system::register(app);
     laser_cannon_system::register(app);
     mining_laser_system::register(app);
+    repair_beam_system::register(app);
     energy_generation_system::register(app);
     energy_storage_system::register(app);
     missile_launcher_system::register(app);
@@ -0,0 +1,193 @@
+//! Client-side repair beam system logic
+
+use bevy::{
+    light::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+};
+use bevy_rapier3d::{
+    geometry::{CollisionGroups, Group},
+    pipeline::QueryFilter,
+    plugin::{RapierContextEntityLink, ReadRapierContext},
+};
+use cosmos_core::{
+    block::block_direction::BlockDirection,
+    ecs::{NeedsDespawned, compute_totally_accurate_global_transform, sets::FixedUpdateSet},
+    state::GameState,
+    structure::{
+        Structure,
+        shared::DespawnWithStructure,
+        shields::SHIELD_COLLISION_GROUP,
+        systems::{StructureSystem, SystemActive, repair_beam_system::RepairBeamSystem},
+    },
+};
+
+use super::sync::sync_system;
+
+const BEAM_SIZE: f32 = 0.15;
+
+/// TODO: sync from server
+const BEAM_MAX_RANGE: f32 = 250.0;
+
+#[derive(Resource)]
+struct RepairBeamMesh(Handle<Mesh>);
+
+#[derive(Resource)]
+struct RepairBeamMaterial(Handle<StandardMaterial>);
+
+#[derive(Component)]
+struct RepairBeam {
+    /// Relative to structure
+    start_loc: Vec3,
+    laser_direction: BlockDirection,
+}
+
+#[derive(Component, Debug)]
+struct ActiveRepairBeams(Vec<Entity>);
+
+fn remove_dead_repair_beams(
+    mut commands: Commands,
+    q_has_beams: Query<&ActiveRepairBeams>,
+    mut q_deactivated_systems: RemovedComponents<SystemActive>,
+) {
+    for deactivated_system in q_deactivated_systems.read() {
+        let Ok(active_beams) = q_has_beams.get(deactivated_system) else {
+            continue;
+        };
+
+        for beam in active_beams.0.iter() {
+            if let Ok(mut beam) = commands.get_entity(*beam) {
+                beam.insert(NeedsDespawned);
+            }
+        }
+
+        commands.entity(deactivated_system).remove::<ActiveRepairBeams>();
+    }
+}
+
+fn create_repair_beams(
+    q_repair_beams: Query<(Entity, &StructureSystem, &RepairBeamSystem), Added<SystemActive>>,
+    q_structure: Query<(&Structure, &RapierContextEntityLink)>,
+    mesh: Res<RepairBeamMesh>,
+    material: Res<RepairBeamMaterial>,
+    mut commands: Commands,
+) {
+    for (system_entity, structure_system, repair_beam_system) in q_repair_beams.iter() {
+        if repair_beam_system.lines.is_empty() {
+            continue;
+        }
+
+        let Ok((structure, physics_world)) = q_structure.get(structure_system.structure_entity()) else {
+            continue;
+        };
+
+        let mut active_beams = Vec::with_capacity(repair_beam_system.lines.len());
+
+        commands.entity(structure_system.structure_entity()).with_children(|p| {
+            for line in &repair_beam_system.lines {
+                let beam_start = structure.block_relative_position(line.start);
+
+                let beam_ent = p
+                    .spawn((
+                        Transform::from_translation(beam_start).looking_to(line.direction.as_vec3(), Vec3::Y),
+                        MeshMaterial3d(material.0.clone()),
+                        Mesh3d(mesh.0.clone()),
+                        NotShadowCaster,
+                        NotShadowReceiver,
+                        RepairBeam {
+                            laser_direction: line.direction,
+                            start_loc: beam_start,
+                        },
+                        DespawnWithStructure,
+                        *physics_world,
+                    ))
+                    .id();
+
+                active_beams.push(beam_ent);
+            }
+        });
+
+        commands.entity(system_entity).insert(ActiveRepairBeams(active_beams));
+    }
+}
+
+fn resize_repair_beams(
+    q_parent: Query<&ChildOf>,
+    mut q_beams: Query<(&mut Transform, &RapierContextEntityLink, &RepairBeam, &ChildOf)>,
+    q_global_trans: Query<&GlobalTransform>,
+    rapier_context_access: ReadRapierContext,
+    q_transform: Query<(&Transform, Option<&ChildOf>), Without<RepairBeam>>,
+) {
+    for (mut trans, phys_world, repair_beam, parent) in q_beams.iter_mut() {
+        let parent_structure_ent = parent.parent();
+
+        let Some(parent_g_trans) = compute_totally_accurate_global_transform(parent_structure_ent, &q_transform) else {
+            continue;
+        };
+
+        let g_trans = parent_g_trans * *trans;
+
+        let Ok(parent_g_trans) = q_global_trans.get(parent_structure_ent) else {
+            continue;
+        };
+
+        let beam_start = parent_g_trans.translation() + Quat::from_affine3(&parent_g_trans.affine()).mul_vec3(repair_beam.start_loc);
+
+        let toi = match rapier_context_access.get(*phys_world).cast_ray(
+            beam_start,
+            g_trans.forward().into(),
+            BEAM_MAX_RANGE,
+            true,
+            QueryFilter::predicate(QueryFilter::default(), &|entity| {
+                if parent_structure_ent == entity {
+                    false
+                } else if let Ok(parent) = q_parent.get(entity) {
+                    parent.parent() != parent_structure_ent
+                } else {
+                    false
+                }
+            })
+            .groups(CollisionGroups::new(
+                Group::ALL & !SHIELD_COLLISION_GROUP,
+                Group::ALL & !SHIELD_COLLISION_GROUP,
+            )),
+        ) {
+            Some((_, toi)) => toi,
+            _ => BEAM_MAX_RANGE,
+        };
+
+        trans.scale.z = toi * 2.0;
+        match repair_beam.laser_direction {
+            BlockDirection::PosX => trans.translation.x = repair_beam.start_loc.x + toi / 2.0,
+            BlockDirection::NegX => trans.translation.x = repair_beam.start_loc.x - toi / 2.0,
+            BlockDirection::PosY => trans.translation.y = repair_beam.start_loc.y + toi / 2.0,
+            BlockDirection::NegY => trans.translation.y = repair_beam.start_loc.y - toi / 2.0,
+            BlockDirection::PosZ => trans.translation.z = repair_beam.start_loc.z + toi / 2.0,
+            BlockDirection::NegZ => trans.translation.z = repair_beam.start_loc.z - toi / 2.0,
+        }
+    }
+}
+
+fn create_repair_beam_assets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.insert_resource(RepairBeamMesh(meshes.add(Cuboid::new(BEAM_SIZE, BEAM_SIZE, 0.5))));
+
+    let color = Srgba::new(0.2, 1.0, 0.4, 0.6);
+    commands.insert_resource(RepairBeamMaterial(materials.add(StandardMaterial {
+        unlit: true,
+        base_color: color.into(),
+        emissive: color.into(),
+        alpha_mode: AlphaMode::Add,
+        ..Default::default()
+    })));
+}
+
+pub(super) fn register(app: &mut App) {
+    sync_system::<RepairBeamSystem>(app);
+
+    app.add_systems(Startup, create_repair_beam_assets).add_systems(
+        FixedUpdate,
+        (create_repair_beams, resize_repair_beams, remove_dead_repair_beams)
+            .chain()
+            .after(FixedUpdateSet::LocationSyncingPostPhysics)
+            .run_if(in_state(GameState::Playing).or(in_state(GameState::LoadingWorld))),
+    );
+}
@@ -1,7 +1,9 @@
 use bevy::prelude::*;
 
+mod warp_disruptor;
 mod warp_drive;
 
 pub(super) fn register(app: &mut App) {
+    warp_disruptor::register(app);
     warp_drive::register(app);
 }
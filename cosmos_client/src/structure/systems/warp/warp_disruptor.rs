@@ -0,0 +1,8 @@
+use bevy::app::App;
+use cosmos_core::structure::systems::warp::warp_disruptor::WarpDisruptorSystem;
+
+use crate::structure::systems::sync::sync_system;
+
+pub(super) fn register(app: &mut App) {
+    sync_system::<WarpDisruptorSystem>(app);
+}
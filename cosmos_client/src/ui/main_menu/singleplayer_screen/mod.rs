@@ -713,6 +713,7 @@ fn create_menu(p: &mut RelatedSpawnerCommands<ChildOf>, default_font: &DefaultFo
                                                 planets: q_planets.single().unwrap().get(),
                                                 peaceful: q_peaceful.single().unwrap().get(),
                                                 merchant_ships: q_merchants.single().unwrap().get(),
+                                                ..Default::default()
                                             },
                                             seed: if seed.0.is_empty() { None } else { Some(seed.0.as_str()) },
                                         };
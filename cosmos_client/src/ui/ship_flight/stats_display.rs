@@ -5,7 +5,10 @@ use cosmos_core::{
     netty::client::LocalPlayer,
     structure::{
         ship::pilot::Pilot,
-        systems::{StructureSystems, energy_storage_system::EnergyStorageSystem},
+        systems::{
+            StructureSystems,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerBudget},
+        },
     },
 };
 
@@ -18,6 +21,9 @@ struct EnergyText;
 #[derive(Component)]
 struct SpeedText;
 
+#[derive(Component)]
+struct PowerText;
+
 fn create_nodes(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -40,6 +46,15 @@ fn create_nodes(
             },
         );
 
+        let text_style_power = (
+            TextColor(css::ORANGE_RED.into()),
+            TextFont {
+                font_size: 24.0,
+                font: font.clone(),
+                ..Default::default()
+            },
+        );
+
         let text_style_speed = (
             TextColor(css::AQUAMARINE.into()),
             TextFont {
@@ -65,6 +80,7 @@ fn create_nodes(
             ))
             .with_children(|p| {
                 p.spawn((Name::new("Energy Text"), EnergyText, Text::new(""), text_style_energy));
+                p.spawn((Name::new("Power Text"), PowerText, Text::new(""), text_style_power));
                 p.spawn((Name::new("Speed Text"), SpeedText, Text::new(""), text_style_speed));
             });
     }
@@ -72,9 +88,10 @@ fn create_nodes(
 
 fn update_nodes(
     piloting: Query<&Pilot, With<LocalPlayer>>,
-    q_piloting: Query<(&Velocity, &StructureSystems)>,
-    mut q_energy_text: Query<&mut Text, (With<EnergyText>, Without<SpeedText>)>,
-    mut q_speed_text: Query<&mut Text, (With<SpeedText>, Without<EnergyText>)>,
+    q_piloting: Query<(&Velocity, &StructureSystems, Option<&PowerBudget>)>,
+    mut q_energy_text: Query<&mut Text, (With<EnergyText>, Without<SpeedText>, Without<PowerText>)>,
+    mut q_speed_text: Query<&mut Text, (With<SpeedText>, Without<EnergyText>, Without<PowerText>)>,
+    mut q_power_text: Query<&mut Text, (With<PowerText>, Without<EnergyText>, Without<SpeedText>)>,
 
     q_energy_storage_system: Query<&EnergyStorageSystem>,
 ) {
@@ -82,7 +99,7 @@ fn update_nodes(
         return;
     };
 
-    let Ok((piloting_vel, piloting_systems)) = q_piloting.get(piloting.entity) else {
+    let Ok((piloting_vel, piloting_systems, power_budget)) = q_piloting.get(piloting.entity) else {
         return;
     };
 
@@ -101,6 +118,22 @@ fn update_nodes(
 
         text.0 = format!("Energy {}%", (percent * 100.0).round());
     }
+
+    if let Ok(mut text) = q_power_text.single_mut() {
+        let browning_out = power_budget
+            .map(|budget| budget.browning_out().map(|c| c.name()).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let new_text = if browning_out.is_empty() {
+            String::new()
+        } else {
+            format!("Low power: {}", browning_out.join(", "))
+        };
+
+        if text.0 != new_text {
+            text.0 = new_text;
+        }
+    }
 }
 
 fn despawn_nodes(
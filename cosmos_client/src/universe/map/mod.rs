@@ -13,7 +13,7 @@ use bevy::{
 // use bevy_mod_billboard::{BillboardDepth, BillboardTextBundle};
 use cosmos_core::{
     ecs::NeedsDespawned,
-    faction::FactionRelation,
+    faction::{Faction, FactionId, FactionRelation, Factions},
     netty::{client::LocalPlayer, sync::events::client_event::NettyMessageWriter},
     physics::location::{Location, SECTOR_DIMENSIONS, SYSTEM_SECTORS, Sector, SectorUnit},
     registry::{Registry, identifiable::Identifiable},
@@ -494,28 +494,36 @@ fn render_galaxy_map(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     q_changed_map: Query<(Entity, &GalaxyMapDisplay), Changed<GalaxyMapDisplay>>,
-    q_player_loc: Query<&Location, With<LocalPlayer>>,
+    q_player_loc: Query<(&Location, Option<&FactionId>), With<LocalPlayer>>,
     mut q_camera: Query<&mut MapCamera>,
     biospheres: Res<Registry<Biosphere>>,
     biosphere_color: Res<Registry<BiosphereColor>>,
     asset_server: Res<AssetServer>,
     q_claimed_territory: Query<&FactionClaimedTerritory>,
+    factions: Res<Factions>,
 ) {
     for (ent, galaxy_map_display) in q_changed_map.iter() {
         let GalaxyMapDisplay::Map { galaxy_map, system_map } = galaxy_map_display else {
             continue;
         };
 
-        let Ok(player) = q_player_loc.single() else {
+        let Ok((player, player_faction)) = q_player_loc.single() else {
             return;
         };
+        let player_faction = player_faction.and_then(|id| factions.from_id(id));
 
         let Ok(mut cam) = q_camera.single_mut() else {
             return;
         };
 
         if let Ok(claimed_territory) = q_claimed_territory.single() {
-            for (territory, _faction) in claimed_territory.iter() {
+            for (territory, faction) in claimed_territory.iter() {
+                let base_color = match Faction::relation_with_option(player_faction, factions.from_id(faction)) {
+                    FactionRelation::Ally => css::GREEN,
+                    FactionRelation::Neutral => css::BLUE,
+                    FactionRelation::Enemy => css::RED,
+                };
+
                 let middle_sector = territory.negative_most_sector()
                     + Sector::new(
                         SYSTEM_SECTORS as SectorUnit / 2,
@@ -533,20 +541,12 @@ fn render_galaxy_map(
                         Name::new("Faction Territory"),
                         RenderLayers::from_layers(&[CAMERA_LAYER]), // https://github.com/bevyengine/bevy/issues/12461
                         Mesh3d(meshes.add(Cuboid::new(SYSTEM_SCALE, SYSTEM_SCALE, SYSTEM_SCALE))),
-                        MeshMaterial3d(
-                            materials.add(StandardMaterial {
-                                base_color: Srgba {
-                                    red: 0.0,
-                                    blue: 1.0,
-                                    green: 0.0,
-                                    alpha: 0.05,
-                                }
-                                .into(),
-                                unlit: true,
-                                alpha_mode: AlphaMode::Blend,
-                                ..Default::default()
-                            }),
-                        ),
+                        MeshMaterial3d(materials.add(StandardMaterial {
+                            base_color: base_color.with_alpha(0.05).into(),
+                            unlit: true,
+                            alpha_mode: AlphaMode::Blend,
+                            ..Default::default()
+                        })),
                         transform,
                     ));
                 });
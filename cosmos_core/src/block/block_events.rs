@@ -26,6 +26,10 @@ pub struct BlockBreakMessage {
 pub enum InvalidBlockBreakMessageReason {
     /// The structure this block was broken on does not allow breaking by this player
     DifferentFaction,
+    /// The player's faction rank does not allow them to build on their faction's structures
+    InsufficientFactionRank,
+    /// The structure belongs to another player who has not trusted this player to build on it
+    NotTrusted,
     /// The structure's core block (ship core or station core) must be the last block a player
     /// breaks.
     StructureCore,
@@ -64,6 +68,10 @@ pub struct BlockInteractMessage {
 pub enum InvalidBlockInteractMessageReason {
     /// The structure this block was interacted with does not allow interactions by this player
     DifferentFaction,
+    /// The player's faction rank does not allow them to access their faction's storage
+    InsufficientFactionRank,
+    /// The structure belongs to another player who has not trusted this player to interact with it
+    NotTrusted,
 }
 
 impl IdentifiableMessage for InvalidBlockInteractMessageReason {
@@ -100,6 +108,10 @@ pub struct BlockPlaceMessage {
 pub enum InvalidBlockPlaceMessageReason {
     /// The structure this block was placed on does not allow placements by this player
     DifferentFaction,
+    /// The player's faction rank does not allow them to build on their faction's structures
+    InsufficientFactionRank,
+    /// The structure belongs to another player who has not trusted this player to build on it
+    NotTrusted,
     /// The player tried to manually place a core block (ship or station)
     CoreBlock,
 }
@@ -149,6 +149,7 @@ fn add_cosmos_blocks(
     blocks.register(
         BlockBuilder::new("cosmos:thruster", 2.0, 20.0, 10.0)
             .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
             .add_connection_group("cosmos:consumes_power")
             .with_category("cosmos:utility")
             .create(),
@@ -329,6 +330,16 @@ fn add_cosmos_blocks(
             .create(),
     );
 
+    blocks.register(
+        BlockBuilder::new("cosmos:repair_beam", 2.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FaceFront)
+            .add_connection_group("cosmos:uses_logic")
+            .add_connection_group("cosmos:consumes_power")
+            .with_category("cosmos:utility")
+            .create(),
+    );
+
     blocks.register(
         BlockBuilder::new("cosmos:shop", 2.0, 20.0, 5.0)
             .add_property(BlockProperty::Full)
@@ -337,6 +348,13 @@ fn add_cosmos_blocks(
             .create(),
     );
 
+    blocks.register(
+        BlockBuilder::new("cosmos:claim_beacon", 4.0, 100.0, 20.0)
+            .add_property(BlockProperty::Full)
+            .with_category("cosmos:utility")
+            .create(),
+    );
+
     blocks.register(
         BlockBuilder::new("cosmos:camera", 2.0, 20.0, 5.0)
             .add_property(BlockProperty::Full)
@@ -380,6 +398,7 @@ fn add_cosmos_blocks(
     blocks.register(
         BlockBuilder::new("cosmos:shield_generator", 2.0, 20.0, 5.0)
             .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
             .add_connection_group("cosmos:consumes_power")
             .with_category("cosmos:utility")
             .create(),
@@ -407,6 +426,7 @@ fn add_cosmos_blocks(
         BlockBuilder::new("cosmos:ship_dock", 2.0, 20.0, 5.0)
             .add_property(BlockProperty::Full)
             .add_property(BlockProperty::FaceFront)
+            .add_connection_group("cosmos:uses_logic")
             .with_category("cosmos:utility")
             .create(),
     );
@@ -415,6 +435,7 @@ fn add_cosmos_blocks(
         BlockBuilder::new("cosmos:pan_dock", 2.0, 20.0, 5.0)
             .add_property(BlockProperty::Full)
             .add_property(BlockProperty::FaceFront)
+            .add_connection_group("cosmos:uses_logic")
             .with_category("cosmos:utility")
             .create(),
     );
@@ -529,15 +550,195 @@ fn add_cosmos_blocks(
             .create(),
     );
 
+    blocks.register(
+        BlockBuilder::new("cosmos:logic_clock", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
+            .with_interactable()
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:logic_delay", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FullyRotatable)
+            .add_connection_group("cosmos:uses_logic")
+            .with_interactable()
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:pulse_extender", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FullyRotatable)
+            .add_connection_group("cosmos:uses_logic")
+            .with_interactable()
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:logic_constant", 0.1, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
+            .with_interactable()
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:adder", 0.1, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FullyRotatable)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:subtractor", 0.1, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FullyRotatable)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:multiplier", 0.1, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FullyRotatable)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:greater_than_comparator", 0.1, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FullyRotatable)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:less_than_comparator", 0.1, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FullyRotatable)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:equal_to_comparator", 0.1, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FullyRotatable)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:energy_sensor", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:shield_sensor", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:hull_sensor", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:speed_sensor", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:fluid_sensor", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FullyRotatable)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:proximity_sensor", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
+            .with_interactable()
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:logic_transmitter", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
+            .with_interactable()
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:logic_receiver", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
+            .with_interactable()
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:logic_register", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FullyRotatable)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:logic_memory", 5.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::FullyRotatable)
+            .add_connection_group("cosmos:uses_logic")
+            .with_category("cosmos:logic")
+            .create(),
+    );
+
     blocks.register(
         BlockBuilder::new("cosmos:door", 4.0, 100.0, 10.0)
             .add_property(BlockProperty::Full)
+            .add_connection_group("cosmos:uses_logic")
             .with_interactable()
             .with_category("cosmos:utility")
             .create(),
     );
     blocks.register(
         BlockBuilder::new("cosmos:door_open", 4.0, 100.0, 10.0)
+            .add_connection_group("cosmos:uses_logic")
             .add_connection_group("cosmos:door_open")
             .with_interactable()
             .connect_to_group("cosmos:door_open")
@@ -644,6 +845,7 @@ fn add_cosmos_blocks(
 
     blocks.register(
         BlockBuilder::new("cosmos:warp_drive", 2.0, 20.0, 5.0)
+            .add_connection_group("cosmos:uses_logic")
             .with_category("cosmos:utility")
             .add_property(BlockProperty::Full)
             .create(),
@@ -660,6 +862,7 @@ fn add_cosmos_blocks(
         BlockBuilder::new("cosmos:railgun_launcher", 2.0, 20.0, 5.0)
             .add_property(BlockProperty::Full)
             .add_property(BlockProperty::FaceFront)
+            .add_connection_group("cosmos:consumes_power")
             .with_category("cosmos:weapons")
             .create(),
     );
@@ -717,6 +920,22 @@ fn add_cosmos_blocks(
             .create(),
     );
 
+    blocks.register(
+        BlockBuilder::new("cosmos:jump_gate_controller", 2.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .with_interactable()
+            .with_category("cosmos:utility")
+            .create(),
+    );
+
+    blocks.register(
+        BlockBuilder::new("cosmos:jump_gate_frame", 2.0, 20.0, 5.0)
+            .add_property(BlockProperty::Full)
+            .add_property(BlockProperty::Transparent)
+            .with_category("cosmos:utility")
+            .create(),
+    );
+
     blocks.register(
         BlockBuilder::new("cosmos:numeric_display", 0.1, 20.0, 5.0)
             .add_property(BlockProperty::Full)
@@ -162,6 +162,11 @@ impl Block {
         self.connect_to_groups.iter().any(|group| other.connection_groups.contains(group))
     }
 
+    /// Returns true if this block is a part of this connection group
+    pub fn is_in_connection_group(&self, group: &ConnectionGroup) -> bool {
+        self.connection_groups.contains(group)
+    }
+
     #[inline(always)]
     /// Returns true if this block can be seen through
     pub fn is_see_through(&self) -> bool {
@@ -0,0 +1,132 @@
+//! The jump gate multiblock logic
+
+use crate::{
+    block::{data::BlockData, multiblock::rectangle::RectangleMultiblockBounds},
+    netty::sync::{IdentifiableComponent, SyncableComponent, sync_component},
+    prelude::BlockCoordinate,
+};
+use bevy::{ecs::lifecycle::HookContext, prelude::*};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Reflect, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy)]
+/// Uniquely identifies a [`JumpGate`], even while the structure it is on is not loaded
+pub struct JumpGateId(Uuid);
+
+impl JumpGateId {
+    /// Generates a new, unique jump gate id
+    pub fn generate_new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+#[derive(Debug, Component, Reflect, Serialize, Deserialize, PartialEq, Eq, Clone)]
+/// A frame that instantly transports ships flying through it to the jump gate it is linked to
+pub struct JumpGate {
+    id: JumpGateId,
+    controller: BlockCoordinate,
+    bounds: RectangleMultiblockBounds,
+    faction_only: bool,
+}
+
+impl JumpGate {
+    /// Creates a new jump gate based on these conditions
+    pub fn new(bounds: RectangleMultiblockBounds, controller: BlockCoordinate) -> Self {
+        Self {
+            id: JumpGateId::generate_new(),
+            bounds,
+            controller,
+            faction_only: false,
+        }
+    }
+
+    /// Checks if this block coordinate is within the bounds of this jump gate (including the frame)
+    pub fn coordinate_within(&self, coord: BlockCoordinate) -> bool {
+        coord.within(self.bounds.negative_coords, self.bounds.positive_coords) || coord == self.controller
+    }
+
+    /// Returns the unique id of this jump gate
+    pub fn id(&self) -> JumpGateId {
+        self.id
+    }
+
+    /// Returns the coordinate of this jump gate's controller
+    pub fn controller(&self) -> BlockCoordinate {
+        self.controller
+    }
+
+    /// Returns the bounds of this jump gate (including frame)
+    pub fn bounds(&self) -> RectangleMultiblockBounds {
+        self.bounds
+    }
+
+    /// If this is true, only ships allied with this gate's faction can use it
+    pub fn faction_only(&self) -> bool {
+        self.faction_only
+    }
+
+    /// Sets if only ships allied with this gate's faction can use it
+    pub fn set_faction_only(&mut self, faction_only: bool) {
+        self.faction_only = faction_only;
+    }
+}
+
+impl IdentifiableComponent for JumpGate {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:jump_gate"
+    }
+}
+
+impl SyncableComponent for JumpGate {
+    fn get_sync_type() -> crate::netty::sync::SyncType {
+        crate::netty::sync::SyncType::ServerAuthoritative
+    }
+}
+
+#[derive(Debug, Component, Reflect)]
+/// Contains a list of all [`JumpGate`]s this structure has
+pub struct JumpGates(Vec<Entity>);
+
+impl JumpGates {
+    /// Iterates over all the [`JumpGate`]s this structure has
+    pub fn iter(&self) -> impl Iterator<Item = Entity> {
+        self.0.iter().copied()
+    }
+}
+
+fn register_jump_gate_component_hooks(world: &mut World) {
+    world
+        .register_component_hooks::<JumpGate>()
+        .on_add(|mut world, HookContext { entity, .. }| {
+            let Some(block_data) = world.get::<BlockData>(entity) else {
+                error!("Jump gate missing block data!");
+                return;
+            };
+            let structure = block_data.identifier.block.structure();
+            if let Some(mut jump_gates) = world.get_mut::<JumpGates>(structure) {
+                jump_gates.0.push(entity);
+            } else {
+                world.commands().entity(structure).insert(JumpGates(vec![entity]));
+            }
+        })
+        .on_remove(|mut world, HookContext { entity, .. }| {
+            let Some(block_data) = world.get::<BlockData>(entity) else {
+                error!("Jump gate missing block data!");
+                return;
+            };
+            let structure = block_data.identifier.block.structure();
+            if let Some(mut jump_gates) = world.get_mut::<JumpGates>(structure)
+                && let Some((idx, _)) = jump_gates.0.iter().enumerate().find(|x| *x.1 == entity)
+            {
+                jump_gates.0.swap_remove(idx);
+            }
+        });
+}
+
+pub(super) fn register(app: &mut App) {
+    sync_component::<JumpGate>(app);
+
+    app.register_type::<JumpGate>()
+        .register_type::<JumpGates>()
+        .add_systems(Startup, register_jump_gate_component_hooks);
+}
@@ -2,6 +2,7 @@
 
 use bevy::prelude::App;
 
+pub mod jump_gate;
 pub mod prelude;
 pub mod reactor;
 pub mod rectangle;
@@ -13,6 +14,7 @@ pub mod shipyard;
 // }
 
 pub(super) fn register(app: &mut App) {
+    jump_gate::register(app);
     reactor::register(app);
     shipyard::register(app);
 }
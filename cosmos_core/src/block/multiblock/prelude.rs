@@ -1,5 +1,6 @@
 //! prelude
 
+pub use super::jump_gate::*;
 pub use super::reactor::*;
 pub use super::rectangle::*;
 pub use super::shipyard::*;
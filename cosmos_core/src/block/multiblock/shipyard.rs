@@ -97,6 +97,10 @@ pub enum ShipyardState {
     Building(ShipyardDoingBlueprint),
     /// The shipyard is currently removing the blocks of whatever ship is inside of its bounds
     Deconstructing(Entity),
+    /// The shipyard is currently restoring the ship inside of its bounds to match a blueprint.
+    ///
+    /// `creating` is the ship being repaired, and `blocks_todo` contains every block that is missing, wrong or damaged.
+    Repairing(ShipyardDoingBlueprint),
 }
 
 impl ShipyardState {
@@ -112,6 +116,10 @@ impl ShipyardState {
                 creating: p.creating,
             }),
             Self::Deconstructing(p) => ClientFriendlyShipyardState::Deconstructing(*p),
+            Self::Repairing(p) => ClientFriendlyShipyardState::Repairing(ClientFriendlyShipyardDoingBlueprint {
+                remaining_blocks: p.total_blocks_count.clone(),
+                creating: p.creating,
+            }),
         }
     }
 }
@@ -126,6 +134,8 @@ pub enum ClientFriendlyShipyardState {
     Building(ClientFriendlyShipyardDoingBlueprint),
     /// See [`ShipyardState::Deconstructing`]
     Deconstructing(Entity),
+    /// See [`ShipyardState::Repairing`]
+    Repairing(ClientFriendlyShipyardDoingBlueprint),
 }
 
 impl IdentifiableComponent for ShipyardState {
@@ -166,6 +176,13 @@ impl SyncableComponent for ClientFriendlyShipyardState {
                 let entity = mapping.client_from_server(&e)?;
                 Some(Self::Deconstructing(entity))
             }
+            ClientFriendlyShipyardState::Repairing(d) => {
+                let creating = mapping.client_from_server(&d.creating)?;
+                Some(Self::Repairing(ClientFriendlyShipyardDoingBlueprint {
+                    creating,
+                    remaining_blocks: d.remaining_blocks,
+                }))
+            }
         }
     }
 }
@@ -313,6 +330,87 @@ impl NettyMessage for SetShipyardBlueprint {
     }
 }
 
+#[derive(Message, Debug, Serialize, Deserialize, Clone, Copy)]
+/// Client->Server
+///
+/// Requests the server to repair the ship inside of this shipyard.
+pub struct RepairShipyardShip {
+    /// The shipyard controller's block coordinate
+    pub shipyard_block: StructureBlock,
+    /// The slot in the player's inventory of the blueprint to repair the ship to (should be a `cosmos:blueprint`).
+    ///
+    /// If this is `None`, the ship is repaired to how it was when it was last registered with [`RegisterShipyardShip`]
+    /// or finished being repaired.
+    pub blueprint_slot: Option<u32>,
+}
+
+impl IdentifiableMessage for RepairShipyardShip {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:repair_shipyard_ship"
+    }
+}
+
+impl NettyMessage for RepairShipyardShip {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+
+    #[cfg(feature = "client")]
+    fn needs_entity_conversion() -> bool {
+        true
+    }
+
+    #[cfg(feature = "client")]
+    fn convert_entities_client_to_server(self, mapping: &crate::netty::sync::mapping::NetworkMapping) -> Option<Self> {
+        use crate::netty::sync::mapping::Mappable;
+
+        self.shipyard_block
+            .map_to_server(mapping)
+            .map(|shipyard_block| Self {
+                shipyard_block,
+                blueprint_slot: self.blueprint_slot,
+            })
+            .ok()
+    }
+}
+
+#[derive(Message, Debug, Serialize, Deserialize, Clone, Copy)]
+/// Client->Server
+///
+/// Requests the server to remember how the ship inside of this shipyard currently looks, so it can be repaired
+/// back to that later.
+pub struct RegisterShipyardShip {
+    /// The shipyard controller's block coordinate
+    pub shipyard_block: StructureBlock,
+}
+
+impl IdentifiableMessage for RegisterShipyardShip {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:register_shipyard_ship"
+    }
+}
+
+impl NettyMessage for RegisterShipyardShip {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+
+    #[cfg(feature = "client")]
+    fn needs_entity_conversion() -> bool {
+        true
+    }
+
+    #[cfg(feature = "client")]
+    fn convert_entities_client_to_server(self, mapping: &crate::netty::sync::mapping::NetworkMapping) -> Option<Self> {
+        use crate::netty::sync::mapping::Mappable;
+
+        self.shipyard_block
+            .map_to_server(mapping)
+            .map(|shipyard_block| Self { shipyard_block })
+            .ok()
+    }
+}
+
 fn register_shipyard_component_hooks(world: &mut World) {
     world
         .register_component_hooks::<Shipyard>()
@@ -352,5 +450,7 @@ pub(super) fn register(app: &mut App) {
         .add_systems(Startup, register_shipyard_component_hooks)
         .add_netty_message::<ClientSetShipyardState>()
         .add_netty_message::<SetShipyardBlueprint>()
+        .add_netty_message::<RepairShipyardShip>()
+        .add_netty_message::<RegisterShipyardShip>()
         .add_netty_message::<ShowShipyardUi>();
 }
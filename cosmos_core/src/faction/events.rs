@@ -4,7 +4,8 @@ use bevy::prelude::*;
 use serde::*;
 
 use crate::{
-    faction::FactionId,
+    entities::EntityId,
+    faction::{FactionId, FactionRelation},
     netty::sync::events::netty_event::{IdentifiableMessage, NettyMessage, SyncedMessageImpl},
 };
 
@@ -153,6 +154,215 @@ impl NettyMessage for PlayerDeclineFactionInvitation {
     }
 }
 
+/// Promotes another player in your faction one rank. See [`crate::faction::Faction::promote`] for who can be promoted.
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerPromoteFactionMemberMessage {
+    /// The player being promoted. This is their id rather than their entity, since they may be offline.
+    pub player: EntityId,
+}
+
+impl IdentifiableMessage for PlayerPromoteFactionMemberMessage {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:player_promote_faction_member"
+    }
+}
+
+impl NettyMessage for PlayerPromoteFactionMemberMessage {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+}
+
+/// Demotes another player in your faction one rank. See [`crate::faction::Faction::demote`] for who can be demoted.
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerDemoteFactionMemberMessage {
+    /// The player being demoted. This is their id rather than their entity, since they may be offline.
+    pub player: EntityId,
+}
+
+impl IdentifiableMessage for PlayerDemoteFactionMemberMessage {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:player_demote_faction_member"
+    }
+}
+
+impl NettyMessage for PlayerDemoteFactionMemberMessage {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+}
+
+/// Kicks another player out of your faction. See [`crate::faction::Faction::kick`] for who can be kicked.
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerKickFromFactionMessage {
+    /// The player being kicked. This is their id rather than their entity, since they may be offline.
+    pub player: EntityId,
+}
+
+impl IdentifiableMessage for PlayerKickFromFactionMessage {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:player_kick_from_faction"
+    }
+}
+
+impl NettyMessage for PlayerKickFromFactionMessage {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+}
+
+/// Moves credits from the player into their faction's treasury
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerDepositToFactionTreasuryMessage {
+    /// How many credits to deposit. The player must have at least this many.
+    pub amount: u64,
+}
+
+impl IdentifiableMessage for PlayerDepositToFactionTreasuryMessage {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:player_deposit_to_faction_treasury"
+    }
+}
+
+impl NettyMessage for PlayerDepositToFactionTreasuryMessage {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+}
+
+/// Moves credits from the player's faction's treasury to the player. This requires the
+/// [`crate::faction::FactionPermission::WithdrawTreasury`] permission.
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerWithdrawFromFactionTreasuryMessage {
+    /// How many credits to withdraw. The treasury must have at least this many.
+    pub amount: u64,
+}
+
+impl IdentifiableMessage for PlayerWithdrawFromFactionTreasuryMessage {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:player_withdraw_from_faction_treasury"
+    }
+}
+
+impl NettyMessage for PlayerWithdrawFromFactionTreasuryMessage {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+}
+
+/// Moves items from one of the player's inventory slots into their faction's shared storage
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerDepositToFactionStorageMessage {
+    /// The slot in the player's inventory to take the items from
+    pub slot: u32,
+    /// How many items to deposit. If the slot has fewer than this, all of them are deposited.
+    pub quantity: u16,
+}
+
+impl IdentifiableMessage for PlayerDepositToFactionStorageMessage {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:player_deposit_to_faction_storage"
+    }
+}
+
+impl NettyMessage for PlayerDepositToFactionStorageMessage {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+}
+
+/// Moves items from the player's faction's shared storage into the player's inventory. This requires the
+/// [`crate::faction::FactionPermission::AccessStorage`] permission.
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerWithdrawFromFactionStorageMessage {
+    /// The numeric id of the item to withdraw
+    pub item_id: u16,
+    /// How many items to withdraw. The storage must have at least this many, and they must all fit in the
+    /// player's inventory.
+    pub quantity: u16,
+}
+
+impl IdentifiableMessage for PlayerWithdrawFromFactionStorageMessage {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:player_withdraw_from_faction_storage"
+    }
+}
+
+impl NettyMessage for PlayerWithdrawFromFactionStorageMessage {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+}
+
+/// Proposes a new relation between the player's faction and another faction. See
+/// [`crate::faction::Factions::propose_relation`].
+///
+/// This requires the [`crate::faction::FactionPermission::ChangeRelations`] permission.
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerProposeFactionRelationMessage {
+    /// The faction the proposal is made to
+    pub faction: FactionId,
+    /// The proposed relation. This must be [`FactionRelation::Ally`] or [`FactionRelation::Neutral`].
+    pub relation: FactionRelation,
+}
+
+impl IdentifiableMessage for PlayerProposeFactionRelationMessage {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:player_propose_faction_relation"
+    }
+}
+
+impl NettyMessage for PlayerProposeFactionRelationMessage {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+}
+
+/// Accepts or rejects the relation another faction has proposed to the player's faction.
+///
+/// This requires the [`crate::faction::FactionPermission::ChangeRelations`] permission.
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerRespondToFactionRelationMessage {
+    /// The faction that made the proposal
+    pub faction: FactionId,
+    /// If the proposal is accepted (true) or rejected (false)
+    pub accept: bool,
+}
+
+impl IdentifiableMessage for PlayerRespondToFactionRelationMessage {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:player_respond_to_faction_relation"
+    }
+}
+
+impl NettyMessage for PlayerRespondToFactionRelationMessage {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+}
+
+/// Declares war on another faction on behalf of the player's faction. The war only starts after a delay,
+/// giving both factions time to prepare.
+///
+/// This requires the [`crate::faction::FactionPermission::ChangeRelations`] permission.
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerDeclareWarMessage {
+    /// The faction war is being declared on
+    pub faction: FactionId,
+}
+
+impl IdentifiableMessage for PlayerDeclareWarMessage {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:player_declare_war"
+    }
+}
+
+impl NettyMessage for PlayerDeclareWarMessage {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+}
+
 #[derive(Message, Debug, Serialize, Deserialize, Clone)]
 /// Changes a structure to the player's faction or removes the faction
 pub struct SwapToPlayerFactionMessage {
@@ -196,6 +406,16 @@ pub(super) fn register(app: &mut App) {
         .add_netty_message::<PlayerInviteToFactionMessage>()
         .add_netty_message::<PlayerCreateFactionMessage>()
         .add_netty_message::<PlayerLeaveFactionMessage>()
+        .add_netty_message::<PlayerPromoteFactionMemberMessage>()
+        .add_netty_message::<PlayerDemoteFactionMemberMessage>()
+        .add_netty_message::<PlayerKickFromFactionMessage>()
+        .add_netty_message::<PlayerDepositToFactionTreasuryMessage>()
+        .add_netty_message::<PlayerWithdrawFromFactionTreasuryMessage>()
+        .add_netty_message::<PlayerDepositToFactionStorageMessage>()
+        .add_netty_message::<PlayerWithdrawFromFactionStorageMessage>()
+        .add_netty_message::<PlayerProposeFactionRelationMessage>()
+        .add_netty_message::<PlayerRespondToFactionRelationMessage>()
+        .add_netty_message::<PlayerDeclareWarMessage>()
         // Server -> Client
         .add_netty_message::<PlayerCreateFactionMessageResponse>();
 }
@@ -8,13 +8,17 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
+    economy::Credits,
     entities::{EntityId, player::Player},
+    item::Item,
     netty::sync::{
         IdentifiableComponent, SyncableComponent,
         resources::{SyncableResource, sync_resource},
         sync_component,
     },
     physics::location::Location,
+    registry::identifiable::Identifiable,
+    time::UniverseTimestamp,
 };
 
 pub mod events;
@@ -38,6 +42,95 @@ pub struct FactionSettings {
     pub default_enemy: bool,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
+/// Something a player in a faction may or may not be allowed to do, depending on their [`FactionRank`]
+pub enum FactionPermission {
+    /// Invite players to the faction
+    Invite,
+    /// Kick players with a lower rank out of the faction
+    Kick,
+    /// Change the faction's relations with other factions
+    ChangeRelations,
+    /// Set or remove the faction's capitol
+    SetCapitol,
+    /// Place and break blocks on structures that belong to the faction
+    BuildOnStructures,
+    /// Open storage on structures that belong to the faction, and take items out of the faction's shared storage
+    AccessStorage,
+    /// Withdraw credits from the faction's treasury, and have purchases from shops on the faction's
+    /// structures paid for by it
+    WithdrawTreasury,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Reflect, Default)]
+/// A player's standing within their faction. Ranks are ordered from lowest ([`Self::Recruit`]) to
+/// highest ([`Self::Leader`]).
+pub enum FactionRank {
+    #[default]
+    /// A player that has just joined the faction
+    Recruit,
+    /// A trusted player in the faction
+    Member,
+    /// Helps the leader manage the faction
+    Officer,
+    /// Runs the faction. Every faction with players has exactly one leader.
+    Leader,
+}
+
+impl FactionRank {
+    /// The [`FactionPermission`]s players of this rank have
+    pub fn permissions(&self) -> &'static [FactionPermission] {
+        use FactionPermission as P;
+
+        match self {
+            Self::Recruit => &[P::BuildOnStructures],
+            Self::Member => &[P::Invite, P::BuildOnStructures, P::AccessStorage],
+            Self::Officer => &[
+                P::Invite,
+                P::Kick,
+                P::ChangeRelations,
+                P::BuildOnStructures,
+                P::AccessStorage,
+                P::WithdrawTreasury,
+            ],
+            Self::Leader => &[
+                P::Invite,
+                P::Kick,
+                P::ChangeRelations,
+                P::SetCapitol,
+                P::BuildOnStructures,
+                P::AccessStorage,
+                P::WithdrawTreasury,
+            ],
+        }
+    }
+
+    /// Returns true if players of this rank have this permission
+    pub fn has_permission(&self, permission: FactionPermission) -> bool {
+        self.permissions().contains(&permission)
+    }
+
+    /// The rank directly above this one, or `None` if this is [`Self::Leader`]
+    pub fn promoted(&self) -> Option<Self> {
+        match self {
+            Self::Recruit => Some(Self::Member),
+            Self::Member => Some(Self::Officer),
+            Self::Officer => Some(Self::Leader),
+            Self::Leader => None,
+        }
+    }
+
+    /// The rank directly below this one, or `None` if this is [`Self::Recruit`]
+    pub fn demoted(&self) -> Option<Self> {
+        match self {
+            Self::Recruit => None,
+            Self::Member => Some(Self::Recruit),
+            Self::Officer => Some(Self::Member),
+            Self::Leader => Some(Self::Officer),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Reflect)]
 /// A player in a faction.
 ///
@@ -47,15 +140,18 @@ pub struct FactionPlayer {
     pub entity_id: EntityId,
     /// This name may be out of date, but good enough for easy display
     pub name: String,
+    /// This player's rank within the faction
+    pub rank: FactionRank,
 }
 
 impl FactionPlayer {
-    /// Creates a new faction player referring to this player. Please make sure this entity id
-    /// matches this player
+    /// Creates a new faction player referring to this player, with the [`FactionRank::Recruit`] rank.
+    /// Please make sure this entity id matches this player
     pub fn new(entity_id: EntityId, player: &Player) -> Self {
         Self {
             entity_id,
             name: player.name().to_owned(),
+            rank: FactionRank::Recruit,
         }
     }
 
@@ -63,6 +159,78 @@ impl FactionPlayer {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Returns this player's rank within the faction
+    pub fn rank(&self) -> FactionRank {
+        self.rank
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// Why a player's rank could not be changed, or why they could not be kicked
+pub enum FactionRankError {
+    /// One of the players is not in this faction
+    NotInFaction,
+    /// Players can only manage players with a lower rank than their own
+    InsufficientRank,
+    /// The player is already at the highest or lowest rank
+    NoSuchRank,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// Why a diplomatic action between two factions could not happen
+pub enum DiplomacyError {
+    /// One of the factions does not exist
+    NoSuchFaction,
+    /// A faction cannot change its relation with itself
+    SameFaction,
+    /// Only alliances and peace can be proposed - war is declared instead
+    InvalidProposal,
+    /// The factions already have this relation
+    AlreadyInRelation,
+    /// There is no proposal from that faction to respond to
+    NoProposal,
+    /// That faction has no players, so nobody could ever respond to a proposal
+    NpcFaction,
+    /// War has already been declared on that faction, and will start soon
+    WarAlreadyDeclared,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// What happened after one faction proposed a new relation to another
+pub enum ProposalOutcome {
+    /// The other faction must now accept or reject the proposal
+    Proposed,
+    /// The other faction had already proposed the same thing, so the new relation is now in effect
+    Accepted,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// What caused credits to move in or out of a faction's treasury
+pub enum FactionTransactionKind {
+    /// A player put their own credits into the treasury
+    Deposit,
+    /// A player took credits out of the treasury
+    Withdraw,
+    /// The treasury paid for something a player bought from a shop on one of the faction's structures
+    ShopPurchase,
+    /// A player bought something from a player-owned shop on one of the faction's structures
+    ShopRevenue,
+    /// The treasury paid a player for something they sold to a player-owned shop on one of the faction's structures
+    ShopPayment,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// A record of credits moving in or out of a faction's treasury
+pub struct FactionTransaction {
+    /// The player responsible for this transaction
+    pub player: EntityId,
+    /// What caused this transaction
+    pub kind: FactionTransactionKind,
+    /// How many credits were moved
+    pub amount: u64,
+    /// When this happened
+    pub timestamp: UniverseTimestamp,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Reflect)]
@@ -77,6 +245,13 @@ pub struct Faction {
     at_war_with: Vec<EntityId>,
     settings: FactionSettings,
     capitol: Option<Location>,
+    treasury: Credits,
+    /// Relations other factions have proposed to this faction that have not been accepted or rejected yet
+    relation_proposals: HashMap<FactionId, FactionRelation>,
+    /// Wars this faction has declared, and when they take effect
+    pending_wars: HashMap<FactionId, UniverseTimestamp>,
+    /// Items shared by everyone in this faction, stored by each item's unlocalized name
+    storage: HashMap<String, u64>,
 }
 
 impl Faction {
@@ -90,7 +265,7 @@ impl Faction {
         relationships: HashMap<FactionId, FactionRelation>,
         settings: FactionSettings,
     ) -> Self {
-        Self {
+        let mut faction = Self {
             id: FactionId::generate_new(),
             name,
             players,
@@ -98,6 +273,61 @@ impl Faction {
             at_war_with: vec![],
             settings,
             capitol: None,
+            treasury: Credits::default(),
+            relation_proposals: Default::default(),
+            pending_wars: Default::default(),
+            storage: Default::default(),
+        };
+
+        faction.ensure_leader();
+
+        faction
+    }
+
+    /// Recreates a faction that was saved before it had a treasury, shared storage, or any diplomacy in progress.
+    ///
+    /// Everything not given here starts out empty. Its players keep the ranks they are given, so make sure to call
+    /// [`Self::ensure_leader`] once it is loaded.
+    pub fn from_saved(
+        id: FactionId,
+        name: String,
+        players: Vec<FactionPlayer>,
+        relationships: HashMap<FactionId, FactionRelation>,
+        at_war_with: Vec<EntityId>,
+        settings: FactionSettings,
+        capitol: Option<Location>,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            players,
+            relationships,
+            at_war_with,
+            settings,
+            capitol,
+            treasury: Credits::default(),
+            relation_proposals: Default::default(),
+            pending_wars: Default::default(),
+            storage: Default::default(),
+        }
+    }
+
+    /// Makes sure a faction with players has exactly one leader, promoting the highest ranked
+    /// (and longest standing) player if it has none.
+    ///
+    /// Factions saved before ranks existed have no leader, so this should also be called when they are loaded.
+    pub fn ensure_leader(&mut self) {
+        let mut leaders = self.players.iter_mut().filter(|x| x.rank == FactionRank::Leader);
+        if leaders.next().is_some() {
+            for extra_leader in leaders {
+                extra_leader.rank = FactionRank::Officer;
+            }
+            return;
+        }
+
+        // `max_by_key` returns the last max, and the earliest player should be chosen
+        if let Some(new_leader) = self.players.iter_mut().rev().max_by_key(|x| x.rank) {
+            new_leader.rank = FactionRank::Leader;
         }
     }
 
@@ -116,6 +346,56 @@ impl Faction {
         self.capitol
     }
 
+    /// The credits shared by everyone in this faction
+    pub fn treasury(&self) -> Credits {
+        self.treasury
+    }
+
+    /// The credits shared by everyone in this faction
+    pub fn treasury_mut(&mut self) -> &mut Credits {
+        &mut self.treasury
+    }
+
+    /// Every item in this faction's shared storage, along with how many of it are stored
+    pub fn storage(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.storage.iter().map(|(item, &quantity)| (item.as_str(), quantity))
+    }
+
+    /// How many of this item are in this faction's shared storage
+    pub fn stored_quantity(&self, item: &Item) -> u64 {
+        self.storage.get(item.unlocalized_name()).copied().unwrap_or(0)
+    }
+
+    /// Puts this many of the item into this faction's shared storage
+    pub fn store_items(&mut self, item: &Item, quantity: u64) {
+        if quantity == 0 {
+            return;
+        }
+
+        let stored = self.storage.entry(item.unlocalized_name().to_owned()).or_default();
+        *stored = stored.saturating_add(quantity);
+    }
+
+    /// Takes this many of the item out of this faction's shared storage.
+    ///
+    /// Returns false and takes nothing if there aren't enough of the item stored.
+    pub fn take_stored_items(&mut self, item: &Item, quantity: u64) -> bool {
+        let Some(stored) = self.storage.get_mut(item.unlocalized_name()) else {
+            return quantity == 0;
+        };
+
+        if *stored < quantity {
+            return false;
+        }
+
+        *stored -= quantity;
+        if *stored == 0 {
+            self.storage.remove(item.unlocalized_name());
+        }
+
+        true
+    }
+
     /// Returns the [`FactionId`] of this faction
     #[inline(always)]
     pub fn id(&self) -> FactionId {
@@ -170,6 +450,16 @@ impl Faction {
         }
     }
 
+    /// Iterates over the relations other factions have proposed to this faction that are still awaiting a response
+    pub fn relation_proposals(&self) -> impl Iterator<Item = (FactionId, FactionRelation)> {
+        self.relation_proposals.iter().map(|(id, rel)| (*id, *rel))
+    }
+
+    /// Iterates over the factions this faction has declared war on, and when each war will start
+    pub fn pending_wars(&self) -> impl Iterator<Item = (FactionId, UniverseTimestamp)> {
+        self.pending_wars.iter().map(|(id, when)| (*id, *when))
+    }
+
     /// Returns the player-chosen name of this faction
     pub fn name(&self) -> &str {
         &self.name
@@ -182,11 +472,95 @@ impl Faction {
         }
     }
 
-    /// Removes a player from this faction if they are in it
+    /// Removes a player from this faction if they are in it.
+    ///
+    /// If the leader is removed, the highest ranked remaining player becomes the leader.
     pub fn remove_player(&mut self, player_id: EntityId) {
         if let Some((idx, _)) = self.players.iter().enumerate().find(|(_, x)| x.entity_id == player_id) {
             self.players.remove(idx);
+            self.ensure_leader();
+        }
+    }
+
+    /// Gets this player's information if they are in this faction
+    pub fn player(&self, player_id: EntityId) -> Option<&FactionPlayer> {
+        self.players.iter().find(|x| x.entity_id == player_id)
+    }
+
+    /// Gets the rank of this player, if they are in this faction
+    pub fn rank_of(&self, player_id: EntityId) -> Option<FactionRank> {
+        self.player(player_id).map(|x| x.rank)
+    }
+
+    /// Returns true if this player is in this faction and their rank has this permission
+    pub fn has_permission(&self, player_id: EntityId, permission: FactionPermission) -> bool {
+        self.rank_of(player_id).is_some_and(|rank| rank.has_permission(permission))
+    }
+
+    /// Returns the ranks of `actor` and `target` if `actor` is allowed to manage `target` (they must strictly outrank them).
+    fn managing_rank(&self, actor: EntityId, target: EntityId) -> Result<(FactionRank, FactionRank), FactionRankError> {
+        let (Some(actor_rank), Some(target_rank)) = (self.rank_of(actor), self.rank_of(target)) else {
+            return Err(FactionRankError::NotInFaction);
+        };
+
+        if actor_rank <= target_rank || actor_rank < FactionRank::Officer {
+            return Err(FactionRankError::InsufficientRank);
+        }
+
+        Ok((actor_rank, target_rank))
+    }
+
+    fn set_rank(&mut self, player_id: EntityId, rank: FactionRank) {
+        if let Some(player) = self.players.iter_mut().find(|x| x.entity_id == player_id) {
+            player.rank = rank;
+        }
+    }
+
+    /// Has `actor` promote `target` one rank. Players can only promote those below them, and only
+    /// up to the rank below their own.
+    ///
+    /// If the leader promotes an officer, leadership is handed over and the old leader becomes an officer.
+    ///
+    /// Returns the target's new rank.
+    pub fn promote(&mut self, actor: EntityId, target: EntityId) -> Result<FactionRank, FactionRankError> {
+        let (actor_rank, target_rank) = self.managing_rank(actor, target)?;
+        let new_rank = target_rank.promoted().ok_or(FactionRankError::NoSuchRank)?;
+
+        if new_rank == FactionRank::Leader {
+            self.set_rank(actor, FactionRank::Officer);
+        } else if new_rank >= actor_rank {
+            return Err(FactionRankError::InsufficientRank);
         }
+
+        self.set_rank(target, new_rank);
+
+        Ok(new_rank)
+    }
+
+    /// Has `actor` demote `target` one rank. Players can only demote those below them.
+    ///
+    /// Returns the target's new rank.
+    pub fn demote(&mut self, actor: EntityId, target: EntityId) -> Result<FactionRank, FactionRankError> {
+        let (_, target_rank) = self.managing_rank(actor, target)?;
+        let new_rank = target_rank.demoted().ok_or(FactionRankError::NoSuchRank)?;
+
+        self.set_rank(target, new_rank);
+
+        Ok(new_rank)
+    }
+
+    /// Has `actor` kick `target` out of this faction. The actor needs the [`FactionPermission::Kick`]
+    /// permission, and can only kick players below them.
+    pub fn kick(&mut self, actor: EntityId, target: EntityId) -> Result<(), FactionRankError> {
+        self.managing_rank(actor, target)?;
+
+        if !self.has_permission(actor, FactionPermission::Kick) {
+            return Err(FactionRankError::InsufficientRank);
+        }
+
+        self.remove_player(target);
+
+        Ok(())
     }
 
     /// Iterates over all players in this faction
@@ -308,10 +682,151 @@ impl Factions {
         }
     }
 
+    /// Has faction `from` propose a new `relation` with faction `to`. Only [`FactionRelation::Ally`] and
+    /// [`FactionRelation::Neutral`] (peace) can be proposed - see [`Self::declare_war`] for wars.
+    ///
+    /// If `to` had already proposed the same relation to `from`, it takes effect immediately.
+    pub fn propose_relation(
+        &mut self,
+        from: FactionId,
+        to: FactionId,
+        relation: FactionRelation,
+    ) -> Result<ProposalOutcome, DiplomacyError> {
+        if relation == FactionRelation::Enemy {
+            return Err(DiplomacyError::InvalidProposal);
+        }
+
+        let current = self.relation_between(from, to)?;
+        if current == relation {
+            return Err(DiplomacyError::AlreadyInRelation);
+        }
+
+        if self.0.get(&to).is_some_and(|fac| fac.is_empty()) {
+            return Err(DiplomacyError::NpcFaction);
+        }
+
+        if self.0.get(&from).and_then(|fac| fac.relation_proposals.get(&to)) == Some(&relation) {
+            self.accept_proposal(from, to)?;
+            return Ok(ProposalOutcome::Accepted);
+        }
+
+        if let Some(to) = self.0.get_mut(&to) {
+            to.relation_proposals.insert(from, relation);
+        }
+
+        Ok(ProposalOutcome::Proposed)
+    }
+
+    /// Has `faction` accept the relation proposed to it by `proposer`, putting it into effect.
+    ///
+    /// This cancels any wars either faction has declared on the other.
+    ///
+    /// Returns the new relation between the two factions.
+    pub fn accept_proposal(&mut self, faction: FactionId, proposer: FactionId) -> Result<FactionRelation, DiplomacyError> {
+        let relation = self
+            .0
+            .get_mut(&faction)
+            .ok_or(DiplomacyError::NoSuchFaction)?
+            .relation_proposals
+            .remove(&proposer)
+            .ok_or(DiplomacyError::NoProposal)?;
+
+        self.set_relation(&faction, Some(&proposer), None, relation);
+        self.clear_diplomacy_between(faction, proposer);
+
+        Ok(relation)
+    }
+
+    /// Has `faction` reject the relation proposed to it by `proposer`.
+    ///
+    /// Returns the relation that was rejected.
+    pub fn reject_proposal(&mut self, faction: FactionId, proposer: FactionId) -> Result<FactionRelation, DiplomacyError> {
+        self.0
+            .get_mut(&faction)
+            .ok_or(DiplomacyError::NoSuchFaction)?
+            .relation_proposals
+            .remove(&proposer)
+            .ok_or(DiplomacyError::NoProposal)
+    }
+
+    /// Has faction `from` declare war on faction `to`. The war starts at `starts_at`, once
+    /// [`Self::start_due_wars`] is called after it.
+    ///
+    /// Declaring war withdraws any proposals between the two factions.
+    pub fn declare_war(&mut self, from: FactionId, to: FactionId, starts_at: UniverseTimestamp) -> Result<(), DiplomacyError> {
+        if self.relation_between(from, to)? == FactionRelation::Enemy {
+            return Err(DiplomacyError::AlreadyInRelation);
+        }
+
+        if self.0.get(&from).is_some_and(|fac| fac.pending_wars.contains_key(&to)) {
+            return Err(DiplomacyError::WarAlreadyDeclared);
+        }
+
+        self.clear_diplomacy_between(from, to);
+
+        if let Some(from) = self.0.get_mut(&from) {
+            from.pending_wars.entry(to).or_insert(starts_at);
+        }
+
+        Ok(())
+    }
+
+    /// Starts every declared war whose start time is at or before `now`.
+    ///
+    /// Returns the (declaring faction, other faction) pairs of the wars that started.
+    pub fn start_due_wars(&mut self, now: UniverseTimestamp) -> Vec<(FactionId, FactionId)> {
+        let due = self
+            .0
+            .values()
+            .flat_map(|fac| {
+                fac.pending_wars
+                    .iter()
+                    .filter(|(_, starts_at)| **starts_at <= now)
+                    .map(move |(other, _)| (fac.id, *other))
+            })
+            .collect::<Vec<_>>();
+
+        for &(from, to) in due.iter() {
+            if let Some(fac) = self.0.get_mut(&from) {
+                fac.pending_wars.remove(&to);
+            }
+            self.set_relation(&from, Some(&to), None, FactionRelation::Enemy);
+        }
+
+        due
+    }
+
+    fn relation_between(&self, a: FactionId, b: FactionId) -> Result<FactionRelation, DiplomacyError> {
+        if a == b {
+            return Err(DiplomacyError::SameFaction);
+        }
+
+        let (Some(a), Some(b)) = (self.0.get(&a), self.0.get(&b)) else {
+            return Err(DiplomacyError::NoSuchFaction);
+        };
+
+        Ok(a.relation_with(Some(b)))
+    }
+
+    /// Removes all proposals and declared wars between these two factions
+    fn clear_diplomacy_between(&mut self, a: FactionId, b: FactionId) {
+        for (this, other) in [(a, b), (b, a)] {
+            if let Some(fac) = self.0.get_mut(&this) {
+                fac.relation_proposals.remove(&other);
+                fac.pending_wars.remove(&other);
+            }
+        }
+    }
+
     /// Iterates over all factions
     pub fn iter(&self) -> impl Iterator<Item = &Faction> {
         self.0.values()
     }
+
+    /// Iterates over all factions mutably
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Faction> {
+        self.0.values_mut()
+    }
 }
 
 impl SyncableResource for Factions {
@@ -382,8 +897,174 @@ pub(super) fn register(app: &mut App) {
     sync_component::<FactionInvites>(app);
 
     app.register_type::<FactionRelation>()
+        .register_type::<FactionRank>()
+        .register_type::<FactionPermission>()
         .register_type::<Faction>()
         .register_type::<Uuid>()
         .register_type::<FactionId>()
         .register_type::<FactionInvites>();
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn player(name: &str, rank: FactionRank) -> FactionPlayer {
+        FactionPlayer {
+            entity_id: EntityId::generate(),
+            name: name.into(),
+            rank,
+        }
+    }
+
+    fn faction(players: Vec<FactionPlayer>) -> Faction {
+        Faction::new("Test".into(), players, Default::default(), Default::default())
+    }
+
+    #[test]
+    fn first_player_leads_new_faction() {
+        let founder = player("founder", FactionRank::Recruit);
+        let id = founder.entity_id;
+
+        assert_eq!(faction(vec![founder]).rank_of(id), Some(FactionRank::Leader));
+    }
+
+    #[test]
+    fn cannot_promote_to_own_rank() {
+        let leader = player("leader", FactionRank::Leader);
+        let officer = player("officer", FactionRank::Officer);
+        let member = player("member", FactionRank::Member);
+        let (leader_id, officer_id, member_id) = (leader.entity_id, officer.entity_id, member.entity_id);
+        let mut fac = faction(vec![leader, officer, member]);
+
+        assert_eq!(fac.promote(officer_id, member_id), Err(FactionRankError::InsufficientRank));
+        assert_eq!(fac.promote(member_id, officer_id), Err(FactionRankError::InsufficientRank));
+        assert_eq!(fac.promote(leader_id, member_id), Ok(FactionRank::Officer));
+        assert_eq!(fac.demote(officer_id, member_id), Err(FactionRankError::InsufficientRank));
+    }
+
+    #[test]
+    fn promoting_officer_hands_over_leadership() {
+        let leader = player("leader", FactionRank::Leader);
+        let officer = player("officer", FactionRank::Officer);
+        let (leader_id, officer_id) = (leader.entity_id, officer.entity_id);
+        let mut fac = faction(vec![leader, officer]);
+
+        assert_eq!(fac.promote(leader_id, officer_id), Ok(FactionRank::Leader));
+        assert_eq!(fac.rank_of(leader_id), Some(FactionRank::Officer));
+    }
+
+    #[test]
+    fn kicking_requires_higher_rank() {
+        let leader = player("leader", FactionRank::Leader);
+        let officer = player("officer", FactionRank::Officer);
+        let recruit = player("recruit", FactionRank::Recruit);
+        let (leader_id, officer_id, recruit_id) = (leader.entity_id, officer.entity_id, recruit.entity_id);
+        let mut fac = faction(vec![leader, officer, recruit]);
+
+        assert_eq!(fac.kick(officer_id, leader_id), Err(FactionRankError::InsufficientRank));
+        assert_eq!(fac.kick(officer_id, recruit_id), Ok(()));
+        assert!(fac.player(recruit_id).is_none());
+    }
+
+    #[test]
+    fn leader_leaving_promotes_highest_rank() {
+        let leader = player("leader", FactionRank::Leader);
+        let member = player("member", FactionRank::Member);
+        let officer = player("officer", FactionRank::Officer);
+        let (leader_id, officer_id) = (leader.entity_id, officer.entity_id);
+        let mut fac = faction(vec![leader, member, officer]);
+
+        fac.remove_player(leader_id);
+
+        assert_eq!(fac.rank_of(officer_id), Some(FactionRank::Leader));
+    }
+
+    fn two_factions() -> (Factions, FactionId, FactionId) {
+        let mut factions = Factions::default();
+        let a = Faction::new(
+            "A".into(),
+            vec![player("a", FactionRank::Leader)],
+            Default::default(),
+            Default::default(),
+        );
+        let b = Faction::new(
+            "B".into(),
+            vec![player("b", FactionRank::Leader)],
+            Default::default(),
+            Default::default(),
+        );
+        let (a_id, b_id) = (a.id(), b.id());
+        factions.add_new_faction(a);
+        factions.add_new_faction(b);
+        (factions, a_id, b_id)
+    }
+
+    #[test]
+    fn matching_proposals_form_alliance() {
+        let (mut factions, a, b) = two_factions();
+
+        assert_eq!(
+            factions.propose_relation(a, b, FactionRelation::Ally),
+            Ok(ProposalOutcome::Proposed)
+        );
+        assert_eq!(factions.relation_between(a, b), Ok(FactionRelation::Neutral));
+        assert_eq!(
+            factions.propose_relation(b, a, FactionRelation::Ally),
+            Ok(ProposalOutcome::Accepted)
+        );
+        assert_eq!(factions.relation_between(a, b), Ok(FactionRelation::Ally));
+        assert_eq!(factions.from_id(&a).unwrap().relation_proposals().count(), 0);
+        assert_eq!(
+            factions.propose_relation(a, b, FactionRelation::Enemy),
+            Err(DiplomacyError::InvalidProposal)
+        );
+    }
+
+    #[test]
+    fn war_starts_after_delay_and_peace_ends_it() {
+        let (mut factions, a, b) = two_factions();
+
+        factions.declare_war(a, b, UniverseTimestamp::new(10)).unwrap();
+        assert_eq!(
+            factions.declare_war(a, b, UniverseTimestamp::new(20)),
+            Err(DiplomacyError::WarAlreadyDeclared)
+        );
+        assert!(factions.start_due_wars(UniverseTimestamp::new(9)).is_empty());
+        assert_eq!(factions.start_due_wars(UniverseTimestamp::new(10)), vec![(a, b)]);
+        assert_eq!(factions.relation_between(a, b), Ok(FactionRelation::Enemy));
+
+        factions.propose_relation(b, a, FactionRelation::Neutral).unwrap();
+        assert_eq!(factions.accept_proposal(a, b), Ok(FactionRelation::Neutral));
+        assert_eq!(factions.relation_between(a, b), Ok(FactionRelation::Neutral));
+        assert_eq!(factions.accept_proposal(a, b), Err(DiplomacyError::NoProposal));
+    }
+
+    #[test]
+    fn npc_factions_cannot_receive_proposals() {
+        let (mut factions, a, _) = two_factions();
+        let npc = Faction::new("NPC".into(), vec![], Default::default(), Default::default());
+        let npc_id = npc.id();
+        factions.add_new_faction(npc);
+
+        assert_eq!(
+            factions.propose_relation(a, npc_id, FactionRelation::Ally),
+            Err(DiplomacyError::NpcFaction)
+        );
+        assert_eq!(factions.from_id(&npc_id).unwrap().relation_proposals().count(), 0);
+    }
+
+    #[test]
+    fn shared_storage_cannot_be_overdrawn() {
+        let mut fac = faction(vec![player("founder", FactionRank::Recruit)]);
+        let item = Item::new("cosmos:test", 64, None);
+
+        fac.store_items(&item, 100);
+        assert!(!fac.take_stored_items(&item, 101));
+        assert_eq!(fac.stored_quantity(&item), 100);
+
+        assert!(fac.take_stored_items(&item, 100));
+        assert_eq!(fac.stored_quantity(&item), 0);
+        assert_eq!(fac.storage().count(), 0);
+    }
+}
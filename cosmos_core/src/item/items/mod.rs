@@ -52,6 +52,13 @@ fn add_cosmos_items(
             .create(),
     );
 
+    items.register(
+        ItemBuilder::new("cosmos:repair_tool")
+            .with_category("cosmos:utility")
+            .with_stack_size(1)
+            .create(),
+    );
+
     loading.finish_loading(id, &mut end_writer);
 }
 
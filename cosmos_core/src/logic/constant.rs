@@ -0,0 +1,46 @@
+//! Shared data for the logic constant block, which outputs a configurable signal.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    netty::sync::events::netty_event::{IdentifiableMessage, MessageReceiver, NettyMessage, SyncedMessageImpl},
+    prelude::StructureBlock,
+};
+
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+/// Sent by the client to change the signal a logic constant block outputs.
+pub struct SetLogicConstant {
+    /// The logic constant block being configured
+    pub block: StructureBlock,
+    /// The signal this block should output
+    pub value: i32,
+}
+
+impl IdentifiableMessage for SetLogicConstant {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:set_logic_constant"
+    }
+}
+
+impl NettyMessage for SetLogicConstant {
+    fn event_receiver() -> MessageReceiver {
+        MessageReceiver::Server
+    }
+
+    #[cfg(feature = "client")]
+    fn needs_entity_conversion() -> bool {
+        true
+    }
+
+    #[cfg(feature = "client")]
+    fn convert_entities_client_to_server(self, mapping: &crate::netty::sync::mapping::NetworkMapping) -> Option<Self> {
+        use crate::netty::sync::mapping::Mappable;
+
+        self.block.map_to_server(mapping).ok().map(|block| Self { block, ..self })
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_netty_message::<SetLogicConstant>();
+}
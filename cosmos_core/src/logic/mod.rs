@@ -5,6 +5,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::{netty::sync::IdentifiableComponent, structure::chunk::BlockInfo};
 
+pub mod constant;
+pub mod sensor;
+pub mod timer;
+pub mod wireless;
+
 #[derive(Component, Clone, Copy, Reflect, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
 /// The logic signal this block is holding.
 ///
@@ -60,5 +65,10 @@ impl IdentifiableComponent for BlockLogicData {
 }
 
 pub(super) fn register(app: &mut App) {
+    constant::register(app);
+    sensor::register(app);
+    timer::register(app);
+    wireless::register(app);
+
     app.register_type::<BlockLogicData>();
 }
@@ -0,0 +1,90 @@
+//! Shared data for logic sensor blocks, which read the state of their structure into the logic graph.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    netty::sync::{
+        IdentifiableComponent, SyncableComponent,
+        events::netty_event::{IdentifiableMessage, MessageReceiver, NettyMessage, SyncedMessageImpl},
+        sync_component,
+    },
+    prelude::StructureBlock,
+};
+
+/// The largest distance (in blocks) a proximity sensor can detect enemies within.
+pub const MAX_PROXIMITY_SENSOR_RANGE: u32 = 2000;
+
+#[derive(Component, Clone, Copy, Reflect, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// How many blocks away a proximity sensor will detect enemies from.
+pub struct ProximitySensorRange(u32);
+
+impl ProximitySensorRange {
+    /// Creates a new range, capped at [`MAX_PROXIMITY_SENSOR_RANGE`].
+    pub fn new(blocks: u32) -> Self {
+        Self(blocks.min(MAX_PROXIMITY_SENSOR_RANGE))
+    }
+
+    /// The number of blocks this range covers.
+    pub fn blocks(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Default for ProximitySensorRange {
+    fn default() -> Self {
+        Self(200)
+    }
+}
+
+impl IdentifiableComponent for ProximitySensorRange {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:proximity_sensor_range"
+    }
+}
+
+impl SyncableComponent for ProximitySensorRange {
+    fn get_sync_type() -> crate::netty::sync::SyncType {
+        crate::netty::sync::SyncType::ServerAuthoritative
+    }
+}
+
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+/// Sent by the client to change the [`ProximitySensorRange`] of a proximity sensor.
+pub struct SetProximitySensorRange {
+    /// The proximity sensor being configured
+    pub block: StructureBlock,
+    /// The new range, in blocks. This will be capped by the server.
+    pub blocks: u32,
+}
+
+impl IdentifiableMessage for SetProximitySensorRange {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:set_proximity_sensor_range"
+    }
+}
+
+impl NettyMessage for SetProximitySensorRange {
+    fn event_receiver() -> MessageReceiver {
+        MessageReceiver::Server
+    }
+
+    #[cfg(feature = "client")]
+    fn needs_entity_conversion() -> bool {
+        true
+    }
+
+    #[cfg(feature = "client")]
+    fn convert_entities_client_to_server(self, mapping: &crate::netty::sync::mapping::NetworkMapping) -> Option<Self> {
+        use crate::netty::sync::mapping::Mappable;
+
+        self.block.map_to_server(mapping).ok().map(|block| Self { block, ..self })
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    sync_component::<ProximitySensorRange>(app);
+
+    app.add_netty_message::<SetProximitySensorRange>()
+        .register_type::<ProximitySensorRange>();
+}
@@ -0,0 +1,97 @@
+//! Shared data for logic blocks that act over time, such as the clock, delay line, and pulse extender.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    netty::sync::{
+        IdentifiableComponent, SyncableComponent,
+        events::netty_event::{IdentifiableMessage, MessageReceiver, NettyMessage, SyncedMessageImpl},
+        sync_component,
+    },
+    prelude::StructureBlock,
+};
+
+/// The smallest period (in logic ticks) a timed logic block can have.
+pub const MIN_LOGIC_TIMER_PERIOD: u32 = 1;
+/// The largest period (in logic ticks) a timed logic block can have.
+///
+/// This caps the memory used by the delay line, which stores one signal per tick of delay.
+pub const MAX_LOGIC_TIMER_PERIOD: u32 = 1200;
+
+#[derive(Component, Clone, Copy, Reflect, PartialEq, Eq, Debug, Serialize, Deserialize)]
+/// The number of logic ticks a timed logic block waits for.
+///
+/// - Clock: Outputs a single-tick pulse every this many ticks.
+/// - Delay line: Outputs the input it received this many ticks ago.
+/// - Pulse extender: Keeps its output on for this many ticks after its input was last on.
+pub struct LogicTimerPeriod(u32);
+
+impl LogicTimerPeriod {
+    /// Creates a new period, clamped between [`MIN_LOGIC_TIMER_PERIOD`] and [`MAX_LOGIC_TIMER_PERIOD`].
+    pub fn new(ticks: u32) -> Self {
+        Self(ticks.clamp(MIN_LOGIC_TIMER_PERIOD, MAX_LOGIC_TIMER_PERIOD))
+    }
+
+    /// The number of logic ticks this period lasts.
+    pub fn ticks(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Default for LogicTimerPeriod {
+    fn default() -> Self {
+        Self(20)
+    }
+}
+
+impl IdentifiableComponent for LogicTimerPeriod {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:logic_timer_period"
+    }
+}
+
+impl SyncableComponent for LogicTimerPeriod {
+    fn get_sync_type() -> crate::netty::sync::SyncType {
+        crate::netty::sync::SyncType::ServerAuthoritative
+    }
+}
+
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+/// Sent by the client to change the [`LogicTimerPeriod`] of a timed logic block.
+pub struct SetLogicTimerPeriod {
+    /// The timed logic block being configured
+    pub block: StructureBlock,
+    /// The new period, in logic ticks. This will be clamped to a valid range by the server.
+    pub ticks: u32,
+}
+
+impl IdentifiableMessage for SetLogicTimerPeriod {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:set_logic_timer_period"
+    }
+}
+
+impl NettyMessage for SetLogicTimerPeriod {
+    fn event_receiver() -> MessageReceiver {
+        MessageReceiver::Server
+    }
+
+    #[cfg(feature = "client")]
+    fn needs_entity_conversion() -> bool {
+        true
+    }
+
+    #[cfg(feature = "client")]
+    fn convert_entities_client_to_server(self, mapping: &crate::netty::sync::mapping::NetworkMapping) -> Option<Self> {
+        use crate::netty::sync::mapping::Mappable;
+
+        self.block.map_to_server(mapping).ok().map(|block| Self { block, ..self })
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    sync_component::<LogicTimerPeriod>(app);
+
+    app.add_netty_message::<SetLogicTimerPeriod>().register_type::<LogicTimerPeriod>();
+}
@@ -0,0 +1,79 @@
+//! Shared data for wireless logic transmitters and receivers, which send signals between structures of the same faction.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    netty::sync::{
+        IdentifiableComponent, SyncableComponent,
+        events::netty_event::{IdentifiableMessage, MessageReceiver, NettyMessage, SyncedMessageImpl},
+        sync_component,
+    },
+    prelude::StructureBlock,
+};
+
+/// Signals sent on this channel reach every structure of the transmitter's faction or owner, regardless of distance.
+pub const FACTION_BROADCAST_CHANNEL: u32 = 0;
+
+#[derive(Component, Clone, Copy, Reflect, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+/// The channel a wireless transmitter sends on, or a wireless receiver listens to.
+///
+/// See [`FACTION_BROADCAST_CHANNEL`] for the faction-wide channel.
+pub struct LogicChannel(pub u32);
+
+impl Default for LogicChannel {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl IdentifiableComponent for LogicChannel {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:logic_channel"
+    }
+}
+
+impl SyncableComponent for LogicChannel {
+    fn get_sync_type() -> crate::netty::sync::SyncType {
+        crate::netty::sync::SyncType::ServerAuthoritative
+    }
+}
+
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+/// Sent by the client to change the [`LogicChannel`] of a wireless transmitter or receiver.
+pub struct SetLogicChannel {
+    /// The transmitter or receiver being configured
+    pub block: StructureBlock,
+    /// The new channel
+    pub channel: u32,
+}
+
+impl IdentifiableMessage for SetLogicChannel {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:set_logic_channel"
+    }
+}
+
+impl NettyMessage for SetLogicChannel {
+    fn event_receiver() -> MessageReceiver {
+        MessageReceiver::Server
+    }
+
+    #[cfg(feature = "client")]
+    fn needs_entity_conversion() -> bool {
+        true
+    }
+
+    #[cfg(feature = "client")]
+    fn convert_entities_client_to_server(self, mapping: &crate::netty::sync::mapping::NetworkMapping) -> Option<Self> {
+        use crate::netty::sync::mapping::Mappable;
+
+        self.block.map_to_server(mapping).ok().map(|block| Self { block, ..self })
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    sync_component::<LogicChannel>(app);
+
+    app.add_netty_message::<SetLogicChannel>().register_type::<LogicChannel>();
+}
@@ -195,4 +195,9 @@ pub enum ServerReliableMessages {
         /// The Permutation table the client should send to the GPU when generating the terrain
         permutation_table: GpuPermutationTable,
     },
+    /// Sent whenever damaged blocks have their health restored
+    BlockHealthRestored {
+        /// All the repaired blocks packed into a vec
+        changes: Vec<BlockHealthUpdate>,
+    },
 }
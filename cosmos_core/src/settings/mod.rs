@@ -16,6 +16,7 @@ pub enum WorldGamemode {
 ///
 /// Any setting saved in the file will be overridden by the arguments passed to the server
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct WorldSettings {
     /// The gamemode the players will be set to next time they connect
     pub gamemode: WorldGamemode,
@@ -27,6 +28,10 @@ pub struct WorldSettings {
     pub planets: bool,
     /// If any merchant ships should spawn
     pub merchant_ships: bool,
+    /// If ships that melt down should leave a derelict wreck behind that can be salvaged
+    pub derelicts: bool,
+    /// How many seconds a derelict wreck will stay around before it is cleaned up
+    pub derelict_lifetime_secs: u64,
 }
 
 impl Default for WorldSettings {
@@ -37,6 +42,8 @@ impl Default for WorldSettings {
             peaceful: false,
             merchant_ships: true,
             asteroids: true,
+            derelicts: true,
+            derelict_lifetime_secs: 60 * 30,
         }
     }
 }
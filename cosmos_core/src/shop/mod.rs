@@ -1,9 +1,9 @@
 //! Facilitate the trading of goods
 
-use bevy::{app::App, ecs::component::Component, reflect::Reflect};
+use bevy::{app::App, ecs::component::Component, platform::collections::HashMap, reflect::Reflect};
 use serde::{Deserialize, Serialize};
 
-use crate::economy::Credits;
+use crate::{economy::Credits, entities::EntityId, netty::sync::IdentifiableComponent, time::UniverseTimestamp};
 
 use self::netty::{ShopPurchaseError, ShopSellError};
 
@@ -18,7 +18,7 @@ pub enum ShopEntry {
         item_id: u16,
         /// The maximum amount the shop is selling
         max_quantity_selling: u32,
-        /// The price per item
+        /// The base price per item. See [`Shop::price_of`] for what it currently costs.
         price_per: u32,
     },
     /// This shop is buying this
@@ -27,11 +27,18 @@ pub enum ShopEntry {
         item_id: u16,
         /// The maximum amount of this item the shop is buying
         max_quantity_buying: Option<u32>,
-        /// The price this shop is willing to pay per item
+        /// The base price this shop is willing to pay per item. See [`Shop::price_of`] for what it currently pays.
         price_per: u32,
     },
 }
 
+/// Every item bought from a shop raises that item's prices by this fraction, and every item sold to a shop lowers them by it.
+pub const PRICE_CHANGE_PER_ITEM: f64 = 0.001;
+/// How often (in seconds) shops recover their prices and stock.
+pub const SHOP_RESTOCK_INTERVAL_SECS: u64 = 60;
+/// Every restock interval, shops close this fraction of the gap between their current prices/stock and their base values.
+pub const SHOP_RECOVERY_PER_INTERVAL: f64 = 0.05;
+
 #[derive(Debug, Serialize, Deserialize, Reflect, Default, Component, Clone)]
 /// Block data that indiciates this is a shop
 pub struct Shop {
@@ -39,11 +46,139 @@ pub struct Shop {
     pub name: String,
     /// What the shop is buying/selling
     pub contents: Vec<ShopEntry>,
+    /// The last time this shop recovered its prices and stock. See [`Shop::restock`].
+    pub last_restocked: UniverseTimestamp,
+    /// The player that owns this shop.
+    ///
+    /// Shops without an owner are run by NPCs, have infinite credits, and restock over time.
+    /// Player-owned shops trade out of their structure's storage, pay their owner (or their structure's faction, if it
+    /// has one), and have fixed prices.
+    pub owner: Option<EntityId>,
+    /// Credits this shop has earned that have not been paid to its owner yet.
+    ///
+    /// Shops on a faction's structures pay their revenue straight into that faction's treasury instead.
+    pub pending_revenue: u64,
+    /// How far the prices of each item (by id) have drifted from their base prices. Items without one are at their base prices.
+    ///
+    /// Every entry for an item drifts together. This is kept as a fraction rather than rounding the prices after every trade,
+    /// so that trades still move the prices of cheap items.
+    pub price_multipliers: HashMap<u16, f64>,
+}
+
+impl IdentifiableComponent for Shop {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:shop"
+    }
+}
+
+/// Returns the total price of `quantity` items, where each item changes the price of the next by `factor`,
+/// along with how much the price of the item after those has changed.
+fn price_after_trade(price: f64, quantity: u32, factor: f64) -> (u64, f64) {
+    let multiplier = factor.powf(quantity as f64);
+
+    let total = if factor == 1.0 {
+        price * quantity as f64
+    } else {
+        price * (multiplier - 1.0) / (factor - 1.0)
+    };
+
+    (total.round() as u64, multiplier)
+}
+
+/// Price multipliers this close to 1 are considered fully recovered
+const RECOVERED_MULTIPLIER_EPSILON: f64 = 1e-4;
+
+/// Moves `current` towards `base` by `fraction` of the distance between them, always moving at least 1 if they differ.
+fn recover_towards(current: u32, base: u32, fraction: f64) -> u32 {
+    let distance = current.abs_diff(base);
+    let step = ((distance as f64 * fraction).ceil() as u32).min(distance);
+
+    if current < base { current + step } else { current - step }
 }
 
 impl Shop {
+    /// Creates a shop with these entries at their base prices and stock.
+    pub fn new(name: impl Into<String>, contents: Vec<ShopEntry>, now: UniverseTimestamp) -> Self {
+        Self {
+            name: name.into(),
+            contents,
+            last_restocked: now,
+            owner: None,
+            pending_revenue: 0,
+            price_multipliers: Default::default(),
+        }
+    }
+
+    /// Creates an empty shop owned by this player. The owner configures what it buys and sells.
+    pub fn new_player_owned(name: impl Into<String>, owner: EntityId, now: UniverseTimestamp) -> Self {
+        Self {
+            owner: Some(owner),
+            ..Self::new(name, vec![], now)
+        }
+    }
+
+    /// Returns true if this shop is owned by a player rather than an NPC
+    pub fn is_player_owned(&self) -> bool {
+        self.owner.is_some()
+    }
+
+    /// Prices only drift for NPC shops - players set the prices of their own shops.
+    fn price_change_per_item(&self) -> f64 {
+        if self.is_player_owned() { 0.0 } else { PRICE_CHANGE_PER_ITEM }
+    }
+
+    /// Sets how many of this item the shop has available to sell.
+    pub fn set_stock(&mut self, item_id: u16, quantity: u32) {
+        for entry in self.contents.iter_mut() {
+            if let ShopEntry::Selling {
+                item_id: entry_id,
+                max_quantity_selling,
+                ..
+            } = entry
+                && *entry_id == item_id
+            {
+                *max_quantity_selling = quantity;
+            }
+        }
+    }
+
+    /// How far the prices of this item have drifted from their base prices
+    pub fn price_multiplier(&self, item_id: u16) -> f64 {
+        self.price_multipliers.get(&item_id).copied().unwrap_or(1.0)
+    }
+
+    /// The exact (unrounded) price per item of this entry
+    fn exact_price_of(&self, entry: &ShopEntry) -> f64 {
+        let (ShopEntry::Selling { item_id, price_per, .. } | ShopEntry::Buying { item_id, price_per, .. }) = *entry;
+
+        price_per as f64 * self.price_multiplier(item_id)
+    }
+
+    /// What this entry currently costs (or pays) per item, after its price has drifted from its base price.
+    ///
+    /// Prices never drift down to 0.
+    pub fn price_of(&self, entry: &ShopEntry) -> u32 {
+        let (ShopEntry::Selling { price_per, .. } | ShopEntry::Buying { price_per, .. }) = *entry;
+        if price_per == 0 {
+            return 0;
+        }
+
+        self.exact_price_of(entry).round().clamp(1.0, u32::MAX as f64) as u32
+    }
+
+    /// Multiplies the prices of every entry for this item by `multiplier`.
+    fn scale_prices(&mut self, item_id: u16, multiplier: f64) {
+        *self.price_multipliers.entry(item_id).or_insert(1.0) *= multiplier;
+    }
+
     /// Buys an item from this shop, or returns an error if the purchase was unsuccessful
+    ///
+    /// For NPC shops, each item bought makes the next one more expensive (see [`PRICE_CHANGE_PER_ITEM`]).
     pub fn buy(&mut self, item_id: u16, quantity: u32, credits: &mut Credits) -> Result<(), ShopPurchaseError> {
+        let price_change = self.price_change_per_item();
+
+        let price_multiplier = self.price_multiplier(item_id);
+
         for entry in self.contents.iter_mut() {
             let ShopEntry::Selling {
                 item_id: entry_id,
@@ -57,7 +192,7 @@ impl Shop {
                 continue;
             }
 
-            let cost = *price_per as u64 * quantity as u64;
+            let (cost, multiplier) = price_after_trade(*price_per as f64 * price_multiplier, quantity, 1.0 + price_change);
 
             if *max_quantity_selling < quantity {
                 return Err(ShopPurchaseError::NoStock(self.clone()));
@@ -69,6 +204,8 @@ impl Shop {
 
             *max_quantity_selling -= quantity;
 
+            self.scale_prices(item_id, multiplier);
+
             return Ok(());
         }
 
@@ -76,7 +213,14 @@ impl Shop {
     }
 
     /// Sells an item to this shop, or returns an error if the selling was unsuccessful
+    ///
+    /// For NPC shops, each item sold makes the shop pay less for the next one (see [`PRICE_CHANGE_PER_ITEM`]),
+    /// and adds to the stock the shop is selling.
     pub fn sell(&mut self, item_id: u16, quantity: u32, credits: &mut Credits) -> Result<(), ShopSellError> {
+        let price_change = self.price_change_per_item();
+
+        let price_multiplier = self.price_multiplier(item_id);
+
         for entry in self.contents.iter_mut() {
             let ShopEntry::Buying {
                 item_id: entry_id,
@@ -90,7 +234,7 @@ impl Shop {
                 continue;
             }
 
-            let credits_gain = *price_per as u64 * quantity as u64;
+            let (credits_gain, multiplier) = price_after_trade(*price_per as f64 * price_multiplier, quantity, 1.0 - price_change);
 
             if max_quantity_buying.unwrap_or(u32::MAX) < quantity {
                 return Err(ShopSellError::NotWillingToBuyThatMany(self.clone()));
@@ -102,13 +246,133 @@ impl Shop {
 
             credits.increase(credits_gain);
 
+            self.scale_prices(item_id, multiplier);
+
+            for entry in self.contents.iter_mut() {
+                if let ShopEntry::Selling {
+                    item_id: entry_id,
+                    max_quantity_selling,
+                    ..
+                } = entry
+                    && *entry_id == item_id
+                {
+                    *max_quantity_selling = max_quantity_selling.saturating_add(quantity);
+                }
+            }
+
             return Ok(());
         }
 
         Err(ShopSellError::NotWillingToBuyThatMany(self.clone()))
     }
+
+    /// Moves this shop's prices and stock back towards their base values in `defaults`,
+    /// based on how much time has passed since this was last called.
+    ///
+    /// Recovery happens in steps of [`SHOP_RESTOCK_INTERVAL_SECS`], so calling this often does not slow it down.
+    ///
+    /// Player-owned shops never restock - their stock is whatever is in their storage.
+    pub fn restock(&mut self, defaults: &[ShopEntry], now: UniverseTimestamp) {
+        if self.is_player_owned() {
+            return;
+        }
+
+        let Some(elapsed) = now - self.last_restocked else {
+            // Time went backwards (such as an older world being loaded) - start counting again from now.
+            self.last_restocked = now;
+            return;
+        };
+
+        let intervals = elapsed.as_secs() / SHOP_RESTOCK_INTERVAL_SECS;
+        if intervals == 0 {
+            return;
+        }
+
+        self.last_restocked.advance_by(intervals * SHOP_RESTOCK_INTERVAL_SECS);
+
+        let fraction = 1.0 - (1.0 - SHOP_RECOVERY_PER_INTERVAL).powf(intervals as f64);
+
+        self.price_multipliers.retain(|_, multiplier| {
+            *multiplier += (1.0 - *multiplier) * fraction;
+            (*multiplier - 1.0).abs() > RECOVERED_MULTIPLIER_EPSILON
+        });
+
+        for entry in self.contents.iter_mut() {
+            match entry {
+                ShopEntry::Selling {
+                    item_id,
+                    max_quantity_selling,
+                    price_per,
+                } => {
+                    let Some(&ShopEntry::Selling {
+                        max_quantity_selling: base_quantity,
+                        price_per: base_price,
+                        ..
+                    }) = defaults
+                        .iter()
+                        .find(|x| matches!(x, ShopEntry::Selling { item_id: id, .. } if *id == *item_id))
+                    else {
+                        continue;
+                    };
+
+                    *max_quantity_selling = recover_towards(*max_quantity_selling, base_quantity, fraction);
+                    *price_per = base_price;
+                }
+                ShopEntry::Buying {
+                    item_id,
+                    max_quantity_buying,
+                    price_per,
+                } => {
+                    let Some(&ShopEntry::Buying {
+                        max_quantity_buying: base_quantity,
+                        price_per: base_price,
+                        ..
+                    }) = defaults
+                        .iter()
+                        .find(|x| matches!(x, ShopEntry::Buying { item_id: id, .. } if *id == *item_id))
+                    else {
+                        continue;
+                    };
+
+                    *max_quantity_buying = match (*max_quantity_buying, base_quantity) {
+                        (Some(quantity), Some(base_quantity)) => Some(recover_towards(quantity, base_quantity, fraction)),
+                        (_, base_quantity) => base_quantity,
+                    };
+                    *price_per = base_price;
+                }
+            }
+        }
+    }
 }
 
 pub(super) fn register(app: &mut App) {
     app.register_type::<Shop>().register_type::<ShopEntry>();
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{economy::Credits, time::UniverseTimestamp};
+
+    use super::{SHOP_RESTOCK_INTERVAL_SECS, Shop, ShopEntry};
+
+    #[test]
+    fn cheap_prices_drift_and_recover() {
+        let entries = vec![ShopEntry::Selling {
+            item_id: 1,
+            max_quantity_selling: 10000,
+            price_per: 3,
+        }];
+        let mut shop = Shop::new("Shop", entries.clone(), UniverseTimestamp::new(0));
+
+        // Rounding each trade's price would keep a price of 3 from ever changing
+        for _ in 0..200 {
+            shop.buy(1, 1, &mut Credits::new(u64::MAX)).unwrap();
+        }
+        assert!(shop.price_multiplier(1) > 1.2);
+        assert_eq!(shop.price_of(&shop.contents[0]), 4);
+
+        shop.restock(&entries, UniverseTimestamp::new(1000 * SHOP_RESTOCK_INTERVAL_SECS));
+        assert_eq!(shop.price_multiplier(1), 1.0);
+        assert_eq!(shop.price_of(&shop.contents[0]), 3);
+    }
+}
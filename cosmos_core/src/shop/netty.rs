@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::structure::coordinates::BlockCoordinate;
 
-use super::Shop;
+use super::{Shop, ShopEntry};
 
 #[derive(Debug, Serialize, Deserialize)]
 /// An error occurred when trying to buy something from the shop
@@ -21,16 +21,32 @@ pub enum ShopPurchaseError {
 #[derive(Debug, Serialize, Deserialize)]
 /// An error occurred when trying to sell something to the shop
 pub enum ShopSellError {
-    /// never thrown yet (eventually shops will have their own money)
+    /// The owner of a player-owned shop cannot afford to pay for these items
     InsufficientFunds,
+    /// The owner of a player-owned shop is offline, and the shop hasn't earned enough to pay for these items itself.
+    ///
+    /// Shops only hold the credits they earn until their owner is back online, so they can't spend their owner's
+    /// credits while they are away. Shops on a faction's structures are paid for by its treasury, so never have this problem.
+    OwnerOffline,
     /// The buyer did not have enough items to sell
     NotEnoughItems,
-    /// never thrown yet (eventually shops will store their items in an inventory)
+    /// A player-owned shop's storage doesn't have enough room for these items
     NotEnoughInventorySpace,
     /// The shop isn't willing to buy that many items
     NotWillingToBuyThatMany(Shop),
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+/// An error occurred when trying to configure a shop
+pub enum ShopConfigureError {
+    /// Only the owner of a player-owned shop can configure it
+    NotOwner,
+    /// The shop already has an owner, or the player is not allowed to build on the shop's structure
+    CannotClaim,
+    /// One of the entries refers to an item that doesn't exist
+    InvalidItem(u16),
+}
+
 #[derive(Debug, Serialize, Deserialize, Component)]
 /// Messages about shops the server will send to the player
 pub enum ServerShopMessages {
@@ -42,6 +58,8 @@ pub enum ServerShopMessages {
         structure_entity: Entity,
         /// The data about the shop
         shop_data: Shop,
+        /// If the player this is sent to owns this shop, and can configure it
+        is_owner: bool,
     },
     /// Sent whenever an attempt to purchase something from the shop is handled
     PurchaseResult {
@@ -61,6 +79,15 @@ pub enum ServerShopMessages {
         /// The details about the selling
         details: Result<Shop, ShopSellError>,
     },
+    /// Sent whenever an attempt to claim or configure the shop is handled
+    ConfigureResult {
+        /// The shop's block
+        shop_block: BlockCoordinate,
+        /// The shop's entity
+        structure_entity: Entity,
+        /// The shop after being configured
+        details: Result<Shop, ShopConfigureError>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, Component)]
@@ -88,4 +115,24 @@ pub enum ClientShopMessages {
         /// The quantity they want to sell
         quantity: u32,
     },
+    /// Client wants to become the owner of a shop that isn't owned by a player yet.
+    ///
+    /// This is only allowed if they can build on the shop's structure. The shop is emptied, and can then be configured.
+    Claim {
+        /// The shop's block coordinates
+        shop_block: BlockCoordinate,
+        /// The shop's structure entity
+        structure_entity: Entity,
+    },
+    /// The owner of a player-owned shop wants to change what it buys and sells
+    Configure {
+        /// The shop's block coordinates
+        shop_block: BlockCoordinate,
+        /// The shop's structure entity
+        structure_entity: Entity,
+        /// The new name of the shop
+        name: String,
+        /// The new entries of the shop. The stock of anything it sells is taken from its structure's storage.
+        contents: Vec<ShopEntry>,
+    },
 }
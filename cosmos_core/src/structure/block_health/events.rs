@@ -31,6 +31,21 @@ pub struct BlockTakeDamageMessage {
     pub causer: Option<Entity>,
 }
 
+/// This event is sent when a damaged block has some of its health restored
+#[derive(Debug, Message)]
+pub struct BlockRepairedMessage {
+    /// The structure that had its block repaired
+    pub structure_entity: Entity,
+    /// The block that was repaired
+    pub block: StructureBlock,
+    /// The block's new health
+    pub new_health: f32,
+    /// The entity that repaired this block if there is one (such as the ship with the repair beam)
+    pub repairer: Option<Entity>,
+}
+
 pub(super) fn register(app: &mut App) {
-    app.add_message::<BlockDestroyedMessage>().add_message::<BlockTakeDamageMessage>();
+    app.add_message::<BlockDestroyedMessage>()
+        .add_message::<BlockTakeDamageMessage>()
+        .add_message::<BlockRepairedMessage>();
 }
@@ -48,6 +48,21 @@ impl SerializedBlockData {
         self.save_data.entry(block).or_default().serialize_data(data_id, data);
     }
 
+    /// Calls `cosmos_encoder::serialize` on the passed in data and saves it at the given data id,
+    /// alongside the version of the data's format.
+    pub fn serialize_versioned_data(
+        &mut self,
+        block: ChunkBlockCoordinate,
+        data_id: impl Into<String>,
+        version: u32,
+        data: &impl Serialize,
+    ) {
+        self.save_data
+            .entry(block)
+            .or_default()
+            .serialize_versioned_data(data_id, version, data);
+    }
+
     /// Calls `cosmos_encoder::serialize` on the passed in data.
     /// Then sends that data into the `save` method, with the given data id.
     ///
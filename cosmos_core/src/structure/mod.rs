@@ -55,6 +55,7 @@ pub mod full_structure;
 pub mod loading;
 pub mod lod;
 pub mod lod_chunk;
+pub mod ownership;
 pub mod persistence;
 pub mod planet;
 pub mod prelude;
@@ -114,7 +115,11 @@ impl IdentifiableComponent for Structure {
     }
 }
 
-fn register_structure_hooks(world: &mut World) {
+/// Makes every [`Structure`] store the entity it was added to.
+///
+/// This is done automatically on startup - only call this yourself if you are building a world without the
+/// structure plugin (such as a headless test world).
+pub fn register_structure_hooks(world: &mut World) {
     world
         .register_component_hooks::<Structure>()
         .on_add(|mut world, HookContext { entity, .. }| {
@@ -1111,6 +1116,7 @@ pub(super) fn register(app: &mut App) {
     block_health::register(app);
     structure_block::register(app);
     bevy_systems::register(app);
+    ownership::register(app);
 
     use StructureTypeSet as S;
 
@@ -0,0 +1,171 @@
+//! Personal ownership of structures, and who else the owner trusts with them.
+//!
+//! Structures that belong to a faction are protected by that faction. Structures without a
+//! faction are protected by their [`StructureOwner`] instead, who can grant other players access
+//! through the structure's [`StructureTrustList`].
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entities::EntityId,
+    netty::sync::{
+        IdentifiableComponent, SyncableComponent,
+        events::netty_event::{IdentifiableMessage, NettyMessage, SyncedMessageImpl},
+        sync_component,
+    },
+};
+
+#[derive(Component, Debug, Reflect, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+/// The player that created this structure.
+///
+/// The owner always has full access to their structure, even if it is not in their faction.
+pub struct StructureOwner(pub EntityId);
+
+impl IdentifiableComponent for StructureOwner {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:structure_owner"
+    }
+}
+
+impl SyncableComponent for StructureOwner {
+    fn get_sync_type() -> crate::netty::sync::SyncType {
+        crate::netty::sync::SyncType::ServerAuthoritative
+    }
+}
+
+#[derive(Debug, Reflect, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+/// How much a player is trusted with a structure. Each level includes every level below it.
+pub enum TrustLevel {
+    /// Can interact with blocks, such as opening storage
+    Interact,
+    /// Can place and break blocks
+    Build,
+    /// Can pilot the structure
+    Pilot,
+}
+
+#[derive(Component, Debug, Reflect, Serialize, Deserialize, PartialEq, Eq, Clone, Default)]
+/// The players (other than the [`StructureOwner`]) that are trusted with this structure.
+///
+/// Being trusted grants access even if the player is not in the structure's faction.
+pub struct StructureTrustList(HashMap<EntityId, TrustLevel>);
+
+impl StructureTrustList {
+    /// Sets how much this player is trusted. `None` removes them from the trust list.
+    pub fn set_trust(&mut self, player: EntityId, level: Option<TrustLevel>) {
+        match level {
+            Some(level) => {
+                self.0.insert(player, level);
+            }
+            None => {
+                self.0.remove(&player);
+            }
+        }
+    }
+
+    /// Returns how much this player is trusted, if at all
+    pub fn trust_level(&self, player: &EntityId) -> Option<TrustLevel> {
+        self.0.get(player).copied()
+    }
+
+    /// Returns true if this player is trusted at least at this level
+    pub fn allows(&self, player: &EntityId, level: TrustLevel) -> bool {
+        self.trust_level(player).is_some_and(|trusted| trusted >= level)
+    }
+
+    /// Iterates over every trusted player and how much they are trusted
+    pub fn iter(&self) -> impl Iterator<Item = (&EntityId, &TrustLevel)> {
+        self.0.iter()
+    }
+}
+
+impl IdentifiableComponent for StructureTrustList {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:structure_trust_list"
+    }
+}
+
+impl SyncableComponent for StructureTrustList {
+    fn get_sync_type() -> crate::netty::sync::SyncType {
+        crate::netty::sync::SyncType::ServerAuthoritative
+    }
+}
+
+/// Returns true if this player is the structure's owner, or is on its trust list at least at this level.
+///
+/// This does not take factions into account.
+pub fn is_trusted(owner: Option<&StructureOwner>, trust_list: Option<&StructureTrustList>, player: &EntityId, level: TrustLevel) -> bool {
+    owner.is_some_and(|owner| owner.0 == *player) || trust_list.is_some_and(|trust_list| trust_list.allows(player, level))
+}
+
+#[derive(Message, Debug, Serialize, Deserialize, Clone)]
+/// Sent by the owner of a structure to change how much another player is trusted with it
+pub struct SetStructureTrustMessage {
+    /// The structure whose trust list is being changed
+    pub structure: Entity,
+    /// The player being trusted. This is their id rather than their entity, since they may be offline.
+    pub player: EntityId,
+    /// How much they are trusted. `None` removes them from the trust list.
+    pub level: Option<TrustLevel>,
+}
+
+impl IdentifiableMessage for SetStructureTrustMessage {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:set_structure_trust"
+    }
+}
+
+impl NettyMessage for SetStructureTrustMessage {
+    fn event_receiver() -> crate::netty::sync::events::netty_event::MessageReceiver {
+        crate::netty::sync::events::netty_event::MessageReceiver::Server
+    }
+
+    #[cfg(feature = "client")]
+    fn needs_entity_conversion() -> bool {
+        true
+    }
+
+    #[cfg(feature = "client")]
+    fn convert_entities_client_to_server(self, mapping: &crate::netty::sync::mapping::NetworkMapping) -> Option<Self> {
+        mapping
+            .server_from_client(&self.structure)
+            .map(|structure| Self { structure, ..self })
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    sync_component::<StructureOwner>(app);
+    sync_component::<StructureTrustList>(app);
+
+    app.add_netty_message::<SetStructureTrustMessage>()
+        .register_type::<StructureOwner>()
+        .register_type::<TrustLevel>()
+        .register_type::<StructureTrustList>();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::entities::EntityId;
+
+    use super::{StructureOwner, StructureTrustList, TrustLevel, is_trusted};
+
+    #[test]
+    fn trust_levels_include_lower_levels() {
+        let owner = StructureOwner(EntityId::generate());
+        let builder = EntityId::generate();
+        let stranger = EntityId::generate();
+
+        let mut trust_list = StructureTrustList::default();
+        trust_list.set_trust(builder, Some(TrustLevel::Build));
+
+        assert!(is_trusted(Some(&owner), Some(&trust_list), &owner.0, TrustLevel::Pilot));
+        assert!(is_trusted(Some(&owner), Some(&trust_list), &builder, TrustLevel::Interact));
+        assert!(is_trusted(Some(&owner), Some(&trust_list), &builder, TrustLevel::Build));
+        assert!(!is_trusted(Some(&owner), Some(&trust_list), &builder, TrustLevel::Pilot));
+        assert!(!is_trusted(Some(&owner), Some(&trust_list), &stranger, TrustLevel::Interact));
+
+        trust_list.set_trust(builder, None);
+        assert!(!is_trusted(Some(&owner), Some(&trust_list), &builder, TrustLevel::Interact));
+    }
+}
@@ -13,14 +13,21 @@ pub mod palette;
 ///
 /// To extract your component, use
 /// [`save_data.deserialize_data::<Component>("modid:component_unlocalized_name")`]
-pub struct SaveData(pub HashMap<String, Vec<u8>>);
+pub struct SaveData {
+    data: HashMap<String, Vec<u8>>,
+    /// The version of each data id's format. Data ids without an entry are version 0.
+    #[serde(default)]
+    versions: HashMap<String, u32>,
+}
 
 impl SaveData {
-    /// Saves the data to that data id. Will overwrite any existing data at that id.
+    /// Saves the data to that data id. Will overwrite any existing data (and its version) at that id.
     ///
     /// Will only save if `should_save()` returns true.
     pub fn save(&mut self, data_id: impl Into<String>, data: Vec<u8>) {
-        self.0.insert(data_id.into(), data);
+        let data_id = data_id.into();
+        self.versions.remove(&data_id);
+        self.data.insert(data_id, data);
     }
 
     /// Calls `cosmos_encoder::serialize` on the passed in data.
@@ -31,9 +38,35 @@ impl SaveData {
         self.save(data_id, cosmos_encoder::serialize(data));
     }
 
+    /// Calls `cosmos_encoder::serialize` on the passed in data and saves it at the given data id,
+    /// alongside the version of the data's format.
+    ///
+    /// The version is read back via [`SaveData::data_version`] so older data can be migrated.
+    pub fn serialize_versioned_data(&mut self, data_id: impl Into<String>, version: u32, data: &impl Serialize) {
+        let data_id = data_id.into();
+        self.serialize_data(data_id.clone(), data);
+
+        // Version 0 is never stored, since data saved before versions existed is treated as version 0.
+        if version != 0 {
+            self.versions.insert(data_id, version);
+        }
+    }
+
+    /// Returns the version of the data's format stored at this data id.
+    ///
+    /// If no version was stored, the data is assumed to be version 0.
+    pub fn data_version(&self, data_id: &str) -> u32 {
+        self.versions.get(data_id).copied().unwrap_or(0)
+    }
+
     /// Reads the data as raw bytes at the given data id. Use `deserialize_data` for a streamlined way to read the data.
     pub fn read_data(&self, data_id: &str) -> Option<&Vec<u8>> {
-        self.0.get(data_id)
+        self.data.get(data_id)
+    }
+
+    /// Iterates over every data id and the raw bytes saved at it
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<u8>)> {
+        self.data.iter()
     }
 
     /// Deserializes the data as the given type (via `cosmos_encoder::deserialize`) at the given id. If there is no id of this type,
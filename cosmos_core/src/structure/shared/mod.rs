@@ -8,9 +8,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     ecs::NeedsDespawned,
-    netty::sync::{IdentifiableComponent, SyncableComponent},
+    netty::sync::{IdentifiableComponent, SyncableComponent, sync_component},
     physics::structure_physics::ChunkPhysicsPart,
     structure::{chunk::ChunkEntity, systems::StructureSystem},
+    time::UniverseTimestamp,
 };
 
 use super::Structure;
@@ -33,6 +34,28 @@ impl SyncableComponent for MeltingDown {
     }
 }
 
+#[derive(Component, Reflect, Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+/// The wreck of a ship that melted down.
+///
+/// Derelicts belong to no faction and cannot be piloted. Until they are cleaned up, their blocks can be
+/// broken by ordinary mining lasers, which yield each block's normal drops.
+pub struct Derelict {
+    /// When this wreck will be despawned
+    pub despawn_at: UniverseTimestamp,
+}
+
+impl IdentifiableComponent for Derelict {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:derelict"
+    }
+}
+
+impl SyncableComponent for Derelict {
+    fn get_sync_type() -> crate::netty::sync::SyncType {
+        crate::netty::sync::SyncType::ServerAuthoritative
+    }
+}
+
 #[derive(Component)]
 /// Marks a child of a structure as needing to be despawned when the structure itself is despawned.
 ///
@@ -64,6 +87,10 @@ fn save_the_kids(
 }
 
 pub(super) fn register(app: &mut App) {
-    app.add_systems(PostUpdate, save_the_kids).register_type::<MeltingDown>();
+    sync_component::<Derelict>(app);
+
+    app.add_systems(PostUpdate, save_the_kids)
+        .register_type::<MeltingDown>()
+        .register_type::<Derelict>();
     build_mode::register(app);
 }
@@ -24,8 +24,27 @@ impl SyncableComponent for DesiredLocation {
     }
 }
 
+#[derive(Component, Debug, Reflect, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default)]
+/// Set by the pilot of a ship to make it follow fleet warps.
+///
+/// When a ship in the same faction warps nearby, ships that follow fleet warps will jump with it.
+pub struct FollowFleetWarp(pub bool);
+
+impl IdentifiableComponent for FollowFleetWarp {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:follow_fleet_warp"
+    }
+}
+
+impl SyncableComponent for FollowFleetWarp {
+    fn get_sync_type() -> crate::netty::sync::SyncType {
+        crate::netty::sync::SyncType::ClientAuthoritative(crate::netty::sync::ClientAuthority::Piloting)
+    }
+}
+
 pub(super) fn register(app: &mut App) {
     sync_component::<DesiredLocation>(app);
+    sync_component::<FollowFleetWarp>(app);
 
-    app.register_type::<DesiredLocation>();
+    app.register_type::<DesiredLocation>().register_type::<FollowFleetWarp>();
 }
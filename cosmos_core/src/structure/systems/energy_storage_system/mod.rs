@@ -3,10 +3,21 @@
 use bevy::{platform::collections::HashMap, prelude::*};
 use serde::{Deserialize, Serialize};
 
-use crate::{block::Block, prelude::StructureSystems, registry::identifiable::Identifiable, structure::systems::dock_system::Docked};
+use crate::{
+    block::Block,
+    prelude::{BlockCoordinate, StructureSystems},
+    registry::identifiable::Identifiable,
+    structure::systems::dock_system::Docked,
+};
 
 use super::{StructureSystemImpl, sync::SyncableSystem};
 
+pub mod power_allocation;
+pub mod power_grid;
+
+use power_allocation::{PowerAllocator, PowerBudget, PowerCategory, PowerPriority};
+use power_grid::{PowerConsumer, PowerGrids};
+
 #[derive(Default, Reflect, Clone, Copy)]
 /// Every block that can store energy should have this property
 pub struct EnergyStorageProperty {
@@ -34,9 +45,18 @@ impl EnergyStorageBlocks {
 
 #[derive(Component, Default, Reflect, Serialize, Deserialize, Debug)]
 /// Represents the energy storage of a structure
+///
+/// The energy and capacity are the totals of every [`power_grid::PowerGrid`] on the structure. The grids
+/// themselves are only known to the server, which recomputes them whenever a power block changes.
 pub struct EnergyStorageSystem {
     energy: f32,
     capacity: f32,
+    #[serde(skip)]
+    #[reflect(ignore)]
+    grids: PowerGrids,
+    #[serde(skip)]
+    #[reflect(ignore)]
+    allocator: PowerAllocator,
 }
 
 impl SyncableSystem for EnergyStorageSystem {}
@@ -48,8 +68,10 @@ impl StructureSystemImpl for EnergyStorageSystem {
 }
 
 impl EnergyStorageSystem {
-    /// Gets the current stored energy of the system, including all entities this is docked to
+    /// Gets the stored energy this consumer can use, including all entities this is docked to
     pub fn compute_total_energy_recursive(
+        category: PowerCategory,
+        consumer: PowerConsumer,
         base_structure: Entity,
         q_ess: &Query<&EnergyStorageSystem>,
         q_systems: &Query<(&StructureSystems, Option<&Docked>)>,
@@ -64,19 +86,23 @@ impl EnergyStorageSystem {
             return amount;
         };
 
-        amount += ess.get_energy();
+        amount += ess.energy_available_for(category, consumer);
 
         if let Some(docked) = docked {
-            amount + Self::compute_total_energy_recursive(docked.to, q_ess, q_systems)
+            amount + Self::compute_total_energy_recursive(category, PowerConsumer::Any, docked.to, q_ess, q_systems)
         } else {
             amount
         }
     }
 
-    /// Decreases the energy stored in this system and all systems this is docked to - does not go below 0.
+    /// Decreases the energy this consumer can use in this system and all systems this is docked to - does not go below 0.
+    ///
+    /// Structures this is docked to supply energy from all of their power grids.
     ///
     /// Returns 0.0 if there is enough power to perform this operation, or however much power was not able to be taken if not.
     pub fn decrease_energy_recursive(
+        category: PowerCategory,
+        consumer: PowerConsumer,
         mut amount: f32,
         base_structure: Entity,
         q_ess: &mut Query<&mut EnergyStorageSystem>,
@@ -90,14 +116,14 @@ impl EnergyStorageSystem {
             return amount;
         };
 
-        amount = ess.decrease_energy(amount);
+        amount = ess.decrease_energy_for(category, consumer, amount);
 
         if amount == 0.0 {
             return 0.0;
         }
 
         if let Some(docked) = docked {
-            Self::decrease_energy_recursive(amount, docked.to, q_ess, q_systems)
+            Self::decrease_energy_recursive(category, PowerConsumer::Any, amount, docked.to, q_ess, q_systems)
         } else {
             amount
         }
@@ -118,9 +144,38 @@ impl EnergyStorageSystem {
         self.energy == self.capacity
     }
 
-    /// Increases the energy stored in this system
+    /// Increases the energy stored in this system, filling up its power grids one after another
     pub fn increase_energy(&mut self, delta: f32) {
-        self.energy = self.capacity.min(self.energy + delta);
+        if self.grids.is_empty() {
+            self.energy = self.capacity.min(self.energy + delta);
+        } else {
+            self.grids.increase_energy(delta);
+            self.energy = self.grids.total_energy();
+        }
+    }
+
+    /// Increases the energy stored in the power grid this block is a part of.
+    ///
+    /// Does nothing if this block isn't a part of a power grid.
+    pub fn increase_energy_at(&mut self, coord: BlockCoordinate, delta: f32) {
+        if self.grids.is_empty() {
+            self.increase_energy(delta);
+        } else {
+            self.grids.increase_energy_at(coord, delta);
+            self.energy = self.grids.total_energy();
+        }
+    }
+
+    /// Gives every power grid the energy its own generators produced over `delta_secs`.
+    ///
+    /// If the power grids have not been computed yet, `generation_rate` is used for the whole system instead.
+    pub fn generate_energy(&mut self, generation_rate: f32, delta_secs: f32) {
+        if self.grids.is_empty() {
+            self.increase_energy(generation_rate * delta_secs);
+        } else {
+            self.grids.generate(delta_secs);
+            self.energy = self.grids.total_energy();
+        }
     }
 
     /// Decreases the energy stored in this system - does not go below 0.
@@ -129,10 +184,112 @@ impl EnergyStorageSystem {
     ///
     /// Returns 0.0 if there is enough power to perform this operation, however much power was not able to be taken if not.
     pub fn decrease_energy(&mut self, delta: f32) -> f32 {
-        let amount_left = self.energy - delta;
-        self.energy = amount_left.max(0.0);
+        self.decrease_energy_for(PowerCategory::Utility, PowerConsumer::Any, delta)
+    }
+
+    /// Decreases the energy stored in the power grids that supply this consumer - does not go below 0.
+    ///
+    /// Energy reserved in a grid for categories with a higher [`PowerPriority`] than `category` will not be taken.
+    ///
+    /// Returns 0.0 if there is enough power to perform this operation, however much power was not able to be taken if not.
+    pub fn decrease_energy_for(&mut self, category: PowerCategory, consumer: PowerConsumer, delta: f32) -> f32 {
+        let supplying = self.supplying_grids(consumer);
+        let mut leftover = delta;
+
+        for &grid in supplying.iter() {
+            let usable = self.grid_energy_available_for(grid, category).min(leftover);
+            if usable > 0.0 {
+                self.take_energy(grid, usable);
+                self.allocator.record_grid(grid, category, usable, usable);
+                leftover -= usable;
+            }
+        }
 
-        if amount_left < 0.0 { -amount_left } else { 0.0 }
+        // Whatever couldn't be provided is asked of the grid this consumer draws from first, so it can be reserved there next tick
+        if let Some(&first) = supplying.first() {
+            self.allocator.record_grid(first, category, leftover, 0.0);
+        }
+
+        self.allocator.record(category, delta, delta - leftover);
+
+        leftover
+    }
+
+    /// The grids that supply this consumer. If the power grids haven't been computed, the whole system is treated as grid 0.
+    fn supplying_grids(&self, consumer: PowerConsumer) -> Vec<usize> {
+        if self.grids.is_empty() {
+            vec![0]
+        } else {
+            self.grids.supplying(consumer).collect()
+        }
+    }
+
+    fn grid_energy(&self, grid: usize) -> f32 {
+        if self.grids.is_empty() {
+            self.energy
+        } else {
+            self.grids.energy_of(grid)
+        }
+    }
+
+    fn grid_energy_available_for(&self, grid: usize, category: PowerCategory) -> f32 {
+        (self.grid_energy(grid) - self.allocator.reserved_ahead_of(grid, category)).max(0.0)
+    }
+
+    fn take_energy(&mut self, grid: usize, delta: f32) {
+        if self.grids.is_empty() {
+            self.energy = (self.energy - delta).max(0.0);
+        } else {
+            self.grids.decrease_energy_of(grid, delta);
+            self.energy = self.grids.total_energy();
+        }
+    }
+
+    /// Gets the energy stored in the power grids that supply this consumer, minus what is reserved in each of
+    /// those grids for categories with a higher [`PowerPriority`] than `category`
+    pub fn energy_available_for(&self, category: PowerCategory, consumer: PowerConsumer) -> f32 {
+        self.supplying_grids(consumer)
+            .into_iter()
+            .map(|grid| self.grid_energy_available_for(grid, category))
+            .sum()
+    }
+
+    /// Summarizes the energy every [`PowerCategory`] asked for since this was last called, and reserves
+    /// energy in each power grid for them in `priority` order based on that.
+    ///
+    /// This should be called once per tick, before systems use their energy.
+    pub fn allocate_power(&mut self, priority: &PowerPriority, delta_secs: f32) -> PowerBudget {
+        let grid_energy = if self.grids.is_empty() {
+            vec![self.energy]
+        } else {
+            self.grids.iter().map(|g| g.energy()).collect()
+        };
+
+        self.allocator.allocate(priority, &grid_energy, delta_secs)
+    }
+
+    /// Gets every power grid on this structure.
+    ///
+    /// This will be empty on the client, or if the structure has no power blocks.
+    pub fn power_grids(&self) -> &PowerGrids {
+        &self.grids
+    }
+
+    /// Replaces the power grids of this system with freshly computed ones.
+    ///
+    /// Each energy storage block keeps how full its old grid was, so splitting a grid splits its energy.
+    pub fn set_power_grids(&mut self, mut grids: PowerGrids) {
+        let fallback_fill = if self.grids.is_empty() && self.capacity > 0.0 {
+            (self.energy / self.capacity).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        grids.redistribute_energy(&self.grids, fallback_fill);
+        self.energy = grids.total_energy();
+        self.grids = grids;
+        // The old grid indices no longer mean anything
+        self.allocator.clear_grids();
     }
 
     /// Gets the current stored energy of the system
@@ -153,6 +310,8 @@ fn name_system(mut commands: Commands, q_added: Query<Entity, Added<EnergyStorag
 }
 
 pub(super) fn register(app: &mut App) {
+    power_allocation::register(app);
+
     app.insert_resource(EnergyStorageBlocks::default())
         .add_systems(Update, name_system)
         .register_type::<EnergyStorageSystem>()
@@ -0,0 +1,368 @@
+//! Decides which systems on a structure get energy first when there isn't enough for all of them.
+//!
+//! Every tick, the server looks at how much energy each [`PowerCategory`] asked for during the previous
+//! tick, and reserves energy for them in the structure's [`PowerPriority`] order. A system can only use
+//! energy that isn't reserved for a category with a higher priority than its own, so lower priority
+//! systems are the first to brown out.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::netty::sync::{ClientAuthority, IdentifiableComponent, SyncType, SyncableComponent, sync_component};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
+/// The kinds of systems that compete for a structure's energy
+pub enum PowerCategory {
+    /// Shield generators recharging shields
+    Shields,
+    /// Thrusters moving the structure
+    Thrusters,
+    /// Laser cannons, missile launchers and railguns
+    Weapons,
+    /// Mining lasers
+    Mining,
+    /// Everything else, such as warp drives and claim beacons
+    Utility,
+}
+
+impl PowerCategory {
+    /// Every category, in the default priority order
+    pub const ALL: [Self; 5] = [Self::Shields, Self::Thrusters, Self::Weapons, Self::Mining, Self::Utility];
+
+    const fn index(&self) -> usize {
+        match self {
+            Self::Shields => 0,
+            Self::Thrusters => 1,
+            Self::Weapons => 2,
+            Self::Mining => 3,
+            Self::Utility => 4,
+        }
+    }
+
+    /// A human-readable name for this category
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Shields => "Shields",
+            Self::Thrusters => "Thrusters",
+            Self::Weapons => "Weapons",
+            Self::Mining => "Mining",
+            Self::Utility => "Utility",
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+/// The order the systems on this structure receive energy in when there isn't enough for all of them.
+///
+/// The first category has the highest priority. This can be changed by the pilot of the structure.
+pub struct PowerPriority(Vec<PowerCategory>);
+
+impl Default for PowerPriority {
+    fn default() -> Self {
+        Self(PowerCategory::ALL.to_vec())
+    }
+}
+
+impl PowerPriority {
+    /// Creates a priority order, where the first category has the highest priority.
+    ///
+    /// Returns `None` if this doesn't contain every category exactly once.
+    pub fn new(order: Vec<PowerCategory>) -> Option<Self> {
+        let priority = Self(order);
+        priority.validate().then_some(priority)
+    }
+
+    /// Every category, from highest to lowest priority
+    pub fn order(&self) -> &[PowerCategory] {
+        &self.0
+    }
+}
+
+impl IdentifiableComponent for PowerPriority {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:power_priority"
+    }
+}
+
+impl SyncableComponent for PowerPriority {
+    fn get_sync_type() -> SyncType {
+        SyncType::BothAuthoritative(ClientAuthority::Piloting)
+    }
+
+    fn validate(&self) -> bool {
+        self.0.len() == PowerCategory::ALL.len() && PowerCategory::ALL.iter().all(|c| self.0.contains(c))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+/// How much energy a single [`PowerCategory`] wanted and received
+pub struct PowerAllocation {
+    /// The category this is for
+    pub category: PowerCategory,
+    /// The energy per second the systems in this category asked for
+    pub requested: f32,
+    /// The energy per second the systems in this category actually received
+    pub allocated: f32,
+}
+
+impl PowerAllocation {
+    /// How much energy per second this category was short
+    pub fn deficit(&self) -> f32 {
+        (self.requested - self.allocated).max(0.0)
+    }
+}
+
+#[derive(Component, Debug, Clone, Default, PartialEq, Serialize, Deserialize, Reflect)]
+/// How a structure's energy was divided between its systems over the last tick.
+///
+/// The allocations are sorted from highest to lowest priority.
+pub struct PowerBudget {
+    /// The energy each category asked for and received
+    pub allocations: Vec<PowerAllocation>,
+}
+
+impl PowerBudget {
+    /// The energy per second every category asked for combined
+    pub fn total_requested(&self) -> f32 {
+        self.allocations.iter().map(|a| a.requested).sum()
+    }
+
+    /// The energy per second that was asked for but couldn't be provided
+    pub fn total_deficit(&self) -> f32 {
+        self.allocations.iter().map(|a| a.deficit()).sum()
+    }
+
+    /// Every category that didn't get all the energy it asked for, from highest to lowest priority
+    pub fn browning_out(&self) -> impl Iterator<Item = PowerCategory> + '_ {
+        // Ignore tiny floating point differences
+        self.allocations
+            .iter()
+            .filter(|a| a.deficit() > a.requested * 0.01)
+            .map(|a| a.category)
+    }
+}
+
+impl IdentifiableComponent for PowerBudget {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:power_budget"
+    }
+}
+
+impl SyncableComponent for PowerBudget {
+    fn get_sync_type() -> SyncType {
+        SyncType::ServerAuthoritative
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The energy every category asked for from, and has reserved in, a single power grid
+struct GridAllocation {
+    reserved: [f32; PowerCategory::ALL.len()],
+    requested: [f32; PowerCategory::ALL.len()],
+    granted: [f32; PowerCategory::ALL.len()],
+}
+
+#[derive(Debug, Clone, Default)]
+/// Tracks the energy every category asks for, and how much is reserved for them this tick.
+///
+/// Energy can only flow within a power grid, so reservations are made separately for every grid.
+pub(super) struct PowerAllocator {
+    /// The rank of each category - lower ranks have a higher priority
+    ranks: [usize; PowerCategory::ALL.len()],
+    requested: [f32; PowerCategory::ALL.len()],
+    granted: [f32; PowerCategory::ALL.len()],
+    grids: Vec<GridAllocation>,
+}
+
+impl PowerAllocator {
+    /// The energy in this grid that is still reserved for categories with a higher priority than this one
+    pub(super) fn reserved_ahead_of(&self, grid: usize, category: PowerCategory) -> f32 {
+        let Some(allocation) = self.grids.get(grid) else {
+            return 0.0;
+        };
+
+        let rank = self.ranks[category.index()];
+
+        PowerCategory::ALL
+            .iter()
+            .filter(|c| self.ranks[c.index()] < rank)
+            .map(|c| (allocation.reserved[c.index()] - allocation.granted[c.index()]).max(0.0))
+            .sum()
+    }
+
+    /// Records the energy a category asked for and received in total
+    pub(super) fn record(&mut self, category: PowerCategory, requested: f32, granted: f32) {
+        self.requested[category.index()] += requested;
+        self.granted[category.index()] += granted;
+    }
+
+    /// Records the energy a category asked for and received from a single grid
+    pub(super) fn record_grid(&mut self, grid: usize, category: PowerCategory, requested: f32, granted: f32) {
+        if self.grids.len() <= grid {
+            self.grids.resize_with(grid + 1, Default::default);
+        }
+
+        let allocation = &mut self.grids[grid];
+        allocation.requested[category.index()] += requested;
+        allocation.granted[category.index()] += granted;
+    }
+
+    /// Forgets everything asked for from the power grids, because they were just recomputed.
+    pub(super) fn clear_grids(&mut self) {
+        self.grids.clear();
+    }
+
+    /// Summarizes what was asked for since the last call, then reserves the energy in each grid for this tick
+    /// based on what was asked of that grid.
+    ///
+    /// `grid_energy` is the energy currently stored in every grid.
+    pub(super) fn allocate(&mut self, priority: &PowerPriority, grid_energy: &[f32], delta_secs: f32) -> PowerBudget {
+        let per_second = if delta_secs > 0.0 { 1.0 / delta_secs } else { 0.0 };
+
+        let budget = PowerBudget {
+            allocations: priority
+                .order()
+                .iter()
+                .map(|&category| PowerAllocation {
+                    category,
+                    requested: self.requested[category.index()] * per_second,
+                    allocated: self.granted[category.index()] * per_second,
+                })
+                .collect(),
+        };
+
+        for (rank, category) in priority.order().iter().enumerate() {
+            self.ranks[category.index()] = rank;
+        }
+
+        self.grids.resize_with(grid_energy.len(), Default::default);
+
+        for (allocation, mut energy) in self.grids.iter_mut().zip(grid_energy.iter().copied()) {
+            for category in priority.order() {
+                let idx = category.index();
+
+                allocation.reserved[idx] = allocation.requested[idx].min(energy);
+                energy -= allocation.reserved[idx];
+            }
+
+            allocation.requested = Default::default();
+            allocation.granted = Default::default();
+        }
+
+        self.requested = Default::default();
+        self.granted = Default::default();
+
+        budget
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    sync_component::<PowerPriority>(app);
+    sync_component::<PowerBudget>(app);
+
+    app.register_type::<PowerPriority>().register_type::<PowerBudget>();
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::platform::collections::HashMap;
+
+    use super::{PowerCategory, PowerPriority};
+    use crate::{
+        prelude::BlockCoordinate,
+        structure::systems::energy_storage_system::{
+            EnergyStorageSystem,
+            power_grid::{PowerBlock, PowerConsumer, compute_power_grids},
+        },
+    };
+
+    #[test]
+    fn higher_priorities_are_powered_first() {
+        let mut ess = EnergyStorageSystem {
+            energy: 100.0,
+            capacity: 100.0,
+            ..Default::default()
+        };
+
+        // Shields and weapons both want 80 energy every tick
+        let draw = |ess: &mut EnergyStorageSystem| {
+            let weapons = ess.decrease_energy_for(PowerCategory::Weapons, PowerConsumer::Any, 80.0);
+            let shields = ess.decrease_energy_for(PowerCategory::Shields, PowerConsumer::Any, 80.0);
+            (weapons, shields)
+        };
+
+        // Nothing is reserved until a tick has been seen, so the weapons that draw first win
+        assert_eq!(draw(&mut ess), (0.0, 60.0));
+
+        ess.energy = 100.0;
+        let budget = ess.allocate_power(&PowerPriority::default(), 1.0);
+        assert_eq!(budget.total_requested(), 160.0);
+        assert_eq!(budget.browning_out().collect::<Vec<_>>(), vec![PowerCategory::Shields]);
+
+        // Now the shields' energy is reserved, even though the weapons still draw first
+        assert_eq!(draw(&mut ess), (60.0, 0.0));
+
+        ess.energy = 100.0;
+        let budget = ess.allocate_power(&PowerPriority::default(), 1.0);
+        assert_eq!(budget.browning_out().collect::<Vec<_>>(), vec![PowerCategory::Weapons]);
+
+        // Giving weapons a higher priority starves the shields instead
+        assert_eq!(draw(&mut ess), (60.0, 0.0));
+        ess.energy = 100.0;
+        let weapons_first = PowerPriority::new(vec![
+            PowerCategory::Weapons,
+            PowerCategory::Shields,
+            PowerCategory::Thrusters,
+            PowerCategory::Mining,
+            PowerCategory::Utility,
+        ])
+        .unwrap();
+        ess.allocate_power(&weapons_first, 1.0);
+        assert_eq!(draw(&mut ess), (0.0, 60.0));
+
+        assert!(PowerPriority::new(vec![PowerCategory::Weapons]).is_none());
+    }
+
+    #[test]
+    fn reservations_are_made_per_grid() {
+        const CABLE: u16 = 1;
+        const CELL: u16 = 2;
+        const CONSUMER: u16 = 3;
+
+        // cell - cable - shield generator    cell - cable - laser cannon
+        let mut power_blocks = HashMap::default();
+        for (x, id) in [CELL, CABLE, CONSUMER, 0, CELL, CABLE, CONSUMER].into_iter().enumerate() {
+            if id == 0 {
+                continue;
+            }
+
+            let block = PowerBlock {
+                cable: id == CABLE,
+                capacity: if id == CELL { 100.0 } else { 0.0 },
+                generation_rate: 0.0,
+            };
+            power_blocks.insert(BlockCoordinate::new(x as u64, 0, 0), (id, block));
+        }
+
+        let mut ess = EnergyStorageSystem {
+            energy: 200.0,
+            capacity: 200.0,
+            ..Default::default()
+        };
+        ess.set_power_grids(compute_power_grids(&power_blocks));
+
+        let shields = PowerConsumer::Block(BlockCoordinate::new(2, 0, 0));
+        let weapons = PowerConsumer::Block(BlockCoordinate::new(6, 0, 0));
+
+        assert_eq!(ess.decrease_energy_for(PowerCategory::Shields, shields, 80.0), 0.0);
+        assert_eq!(ess.decrease_energy_for(PowerCategory::Weapons, weapons, 80.0), 0.0);
+
+        ess.increase_energy(200.0);
+        ess.allocate_power(&PowerPriority::default(), 1.0);
+
+        // The shields' reservation is only in their own grid, so it doesn't take anything from the weapons
+        assert_eq!(ess.energy_available_for(PowerCategory::Weapons, weapons), 100.0);
+        assert_eq!(ess.energy_available_for(PowerCategory::Weapons, shields), 20.0);
+        assert_eq!(ess.decrease_energy_for(PowerCategory::Weapons, weapons, 80.0), 0.0);
+    }
+}
@@ -0,0 +1,368 @@
+//! Power grids are groups of power blocks on a structure that are wired together with power cables.
+//!
+//! Energy can only flow within a grid, so generators and energy cells only power the consumers they are
+//! connected to. Structures without any power cables are treated as a single grid, which is how every
+//! structure worked before cables existed.
+
+use bevy::platform::collections::{HashMap, HashSet};
+
+use crate::{
+    block::{Block, ConnectionGroup, block_direction::ALL_BLOCK_DIRECTIONS},
+    prelude::{BlockCoordinate, Structure},
+    registry::{Registry, identifiable::Identifiable},
+    structure::systems::energy_generation_system::EnergyGenerationBlocks,
+};
+
+use super::EnergyStorageBlocks;
+
+/// Blocks in this connection group carry power between the blocks they touch
+pub const POWER_CABLE_GROUP: &str = "cosmos:power_cable";
+/// Blocks in these groups are a part of a power grid, even if they don't store or generate energy
+const POWER_GROUPS: [&str; 3] = ["cosmos:consumes_power", "cosmos:produces_power", "cosmos:stores_power"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Something that wants to take energy from a structure's power grids
+pub enum PowerConsumer {
+    /// Takes energy from every grid on the structure.
+    ///
+    /// Used by systems that are not made of power-consuming blocks, and for structures this one is docked to.
+    Any,
+    /// Takes energy from every grid that contains at least one block with this id
+    BlockType(u16),
+    /// Takes energy only from the grid this block is a part of
+    Block(BlockCoordinate),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+/// How a block takes part in a power grid
+pub struct PowerBlock {
+    /// If this block carries power to every power block it touches
+    pub cable: bool,
+    /// How much energy this block can store
+    pub capacity: f32,
+    /// How much energy this block generates every second
+    pub generation_rate: f32,
+}
+
+impl PowerBlock {
+    /// Returns how this block takes part in a power grid, or `None` if it has nothing to do with power.
+    pub fn of(block: &Block, storage_blocks: &EnergyStorageBlocks, generation_blocks: &EnergyGenerationBlocks) -> Option<Self> {
+        let power_block = Self {
+            cable: block.is_in_connection_group(&ConnectionGroup::new(POWER_CABLE_GROUP)),
+            capacity: storage_blocks.get(block).map(|p| p.capacity).unwrap_or(0.0),
+            generation_rate: generation_blocks.get(block).map(|p| p.generation_rate).unwrap_or(0.0),
+        };
+
+        let is_power_block = power_block.cable
+            || power_block.capacity != 0.0
+            || power_block.generation_rate != 0.0
+            || POWER_GROUPS
+                .iter()
+                .any(|group| block.is_in_connection_group(&ConnectionGroup::new(*group)));
+
+        is_power_block.then_some(power_block)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A group of power blocks that share their energy
+pub struct PowerGrid {
+    energy: f32,
+    capacity: f32,
+    generation_rate: f32,
+    block_types: HashSet<u16>,
+    storage_blocks: Vec<(BlockCoordinate, f32)>,
+}
+
+impl PowerGrid {
+    fn add_block(&mut self, coord: BlockCoordinate, id: u16, block: PowerBlock) {
+        self.capacity += block.capacity;
+        self.generation_rate += block.generation_rate;
+        self.block_types.insert(id);
+
+        if block.capacity != 0.0 {
+            self.storage_blocks.push((coord, block.capacity));
+        }
+    }
+
+    fn fill(&self) -> f32 {
+        if self.capacity > 0.0 {
+            (self.energy / self.capacity).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Adds energy to this grid, returning however much didn't fit
+    fn increase_energy(&mut self, delta: f32) -> f32 {
+        let new_energy = self.energy + delta;
+        self.energy = new_energy.min(self.capacity);
+
+        (new_energy - self.energy).max(0.0)
+    }
+
+    /// Takes energy from this grid, returning however much it was short
+    fn decrease_energy(&mut self, delta: f32) -> f32 {
+        let amount_left = self.energy - delta;
+        self.energy = amount_left.max(0.0);
+
+        if amount_left < 0.0 { -amount_left } else { 0.0 }
+    }
+
+    /// The energy currently stored in this grid
+    pub fn energy(&self) -> f32 {
+        self.energy
+    }
+
+    /// The most energy this grid can store
+    pub fn capacity(&self) -> f32 {
+        self.capacity
+    }
+
+    /// How much energy this grid's generators produce every second
+    pub fn generation_rate(&self) -> f32 {
+        self.generation_rate
+    }
+
+    /// Returns true if this grid contains at least one block with this id
+    pub fn contains_block_type(&self, block_id: u16) -> bool {
+        self.block_types.contains(&block_id)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Every power grid on a structure
+pub struct PowerGrids {
+    grid_of: HashMap<BlockCoordinate, usize>,
+    grids: Vec<PowerGrid>,
+}
+
+impl PowerGrids {
+    /// Returns true if there are no power grids
+    pub fn is_empty(&self) -> bool {
+        self.grids.is_empty()
+    }
+
+    /// Iterates over every power grid
+    pub fn iter(&self) -> impl Iterator<Item = &PowerGrid> {
+        self.grids.iter()
+    }
+
+    /// Returns the grid this block is a part of, if it is a part of one
+    pub fn grid_at(&self, coord: BlockCoordinate) -> Option<&PowerGrid> {
+        self.grid_of.get(&coord).map(|&idx| &self.grids[idx])
+    }
+
+    fn supplies(&self, idx: usize, consumer: PowerConsumer) -> bool {
+        match consumer {
+            PowerConsumer::Any => true,
+            PowerConsumer::BlockType(id) => self.grids[idx].contains_block_type(id),
+            PowerConsumer::Block(coord) => self.grid_of.get(&coord) == Some(&idx),
+        }
+    }
+
+    pub(super) fn total_energy(&self) -> f32 {
+        self.grids.iter().map(|g| g.energy).sum()
+    }
+
+    /// Every grid that supplies this consumer, in the order energy is taken from them
+    pub(super) fn supplying(&self, consumer: PowerConsumer) -> impl Iterator<Item = usize> + '_ {
+        (0..self.grids.len()).filter(move |&idx| self.supplies(idx, consumer))
+    }
+
+    pub(super) fn energy_of(&self, idx: usize) -> f32 {
+        self.grids[idx].energy
+    }
+
+    /// Takes energy from a single grid, returning however much it was short
+    pub(super) fn decrease_energy_of(&mut self, idx: usize, delta: f32) -> f32 {
+        self.grids[idx].decrease_energy(delta)
+    }
+
+    /// Fills up the grids one after another
+    pub(super) fn increase_energy(&mut self, mut delta: f32) {
+        for grid in self.grids.iter_mut() {
+            if delta <= 0.0 {
+                break;
+            }
+
+            delta = grid.increase_energy(delta);
+        }
+    }
+
+    pub(super) fn increase_energy_at(&mut self, coord: BlockCoordinate, delta: f32) {
+        if let Some(&idx) = self.grid_of.get(&coord) {
+            self.grids[idx].increase_energy(delta);
+        }
+    }
+
+    pub(super) fn generate(&mut self, delta_secs: f32) {
+        for grid in self.grids.iter_mut() {
+            grid.increase_energy(grid.generation_rate * delta_secs);
+        }
+    }
+
+    /// Gives every grid the energy its storage blocks held in the old grids.
+    ///
+    /// Storage blocks that were not a part of the old grids are `fallback_fill` full.
+    pub(super) fn redistribute_energy(&mut self, old: &PowerGrids, fallback_fill: f32) {
+        for grid in self.grids.iter_mut() {
+            let energy: f32 = grid
+                .storage_blocks
+                .iter()
+                .map(|&(coord, capacity)| capacity * old.grid_at(coord).map(|g| g.fill()).unwrap_or(fallback_fill))
+                .sum();
+
+            grid.energy = energy.min(grid.capacity);
+        }
+    }
+}
+
+/// Splits these power blocks into the grids they are wired into.
+///
+/// Power cables connect to every power block they touch, and power blocks of the same type that touch
+/// each other (such as a bank of energy cells) are connected without needing cables in between.
+/// If there are no power cables at all, every power block is a part of the same grid.
+pub fn compute_power_grids(power_blocks: &HashMap<BlockCoordinate, (u16, PowerBlock)>) -> PowerGrids {
+    let mut grids = PowerGrids::default();
+
+    if power_blocks.is_empty() {
+        return grids;
+    }
+
+    if !power_blocks.values().any(|(_, block)| block.cable) {
+        let mut grid = PowerGrid::default();
+        for (&coord, &(id, block)) in power_blocks.iter() {
+            grid.add_block(coord, id, block);
+            grids.grid_of.insert(coord, 0);
+        }
+        grids.grids.push(grid);
+
+        return grids;
+    }
+
+    for &start in power_blocks.keys() {
+        if grids.grid_of.contains_key(&start) {
+            continue;
+        }
+
+        let idx = grids.grids.len();
+        let mut grid = PowerGrid::default();
+        let mut todo = vec![start];
+        grids.grid_of.insert(start, idx);
+
+        while let Some(coord) = todo.pop() {
+            let (id, block) = power_blocks[&coord];
+            grid.add_block(coord, id, block);
+
+            for dir in ALL_BLOCK_DIRECTIONS {
+                let Ok(neighbor) = BlockCoordinate::try_from(dir.to_coordinates() + coord) else {
+                    continue;
+                };
+
+                let Some(&(neighbor_id, neighbor_block)) = power_blocks.get(&neighbor) else {
+                    continue;
+                };
+
+                if grids.grid_of.contains_key(&neighbor) {
+                    continue;
+                }
+
+                if block.cable || neighbor_block.cable || neighbor_id == id {
+                    grids.grid_of.insert(neighbor, idx);
+                    todo.push(neighbor);
+                }
+            }
+        }
+
+        grids.grids.push(grid);
+    }
+
+    grids
+}
+
+/// Computes every power grid on this structure.
+///
+/// This looks at every block on the structure, so only call it when a power block has changed.
+pub fn compute_structure_power_grids(
+    structure: &Structure,
+    blocks: &Registry<Block>,
+    storage_blocks: &EnergyStorageBlocks,
+    generation_blocks: &EnergyGenerationBlocks,
+) -> PowerGrids {
+    let mut block_kinds = HashMap::<u16, Option<PowerBlock>>::default();
+
+    let power_blocks = structure
+        .all_blocks_iter(false)
+        .filter_map(|coord| {
+            let id = structure.block_id_at(coord);
+            let kind = *block_kinds
+                .entry(id)
+                .or_insert_with(|| PowerBlock::of(blocks.from_numeric_id(id), storage_blocks, generation_blocks));
+
+            kind.map(|kind| (coord, (id, kind)))
+        })
+        .collect::<HashMap<_, _>>();
+
+    compute_power_grids(&power_blocks)
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::platform::collections::HashMap;
+
+    use crate::prelude::BlockCoordinate;
+
+    use super::{PowerBlock, PowerConsumer, PowerGrids, compute_power_grids};
+
+    const CABLE: u16 = 1;
+    const CELL: u16 = 2;
+    const LASER: u16 = 3;
+
+    fn available(grids: &PowerGrids, consumer: PowerConsumer) -> f32 {
+        grids.supplying(consumer).map(|idx| grids.energy_of(idx)).sum()
+    }
+
+    fn power_block(id: u16) -> PowerBlock {
+        PowerBlock {
+            cable: id == CABLE,
+            capacity: if id == CELL { 100.0 } else { 0.0 },
+            generation_rate: 0.0,
+        }
+    }
+
+    #[test]
+    fn cables_split_grids() {
+        // cell - cable - cable - laser    laser (not wired)
+        let mut power_blocks = HashMap::default();
+        for (x, id) in [CELL, CABLE, CABLE, LASER].into_iter().enumerate() {
+            power_blocks.insert(BlockCoordinate::new(x as u64, 0, 0), (id, power_block(id)));
+        }
+        power_blocks.insert(BlockCoordinate::new(5, 0, 0), (LASER, power_block(LASER)));
+
+        let mut grids = compute_power_grids(&power_blocks);
+        assert_eq!(grids.iter().count(), 2);
+        grids.increase_energy(1000.0);
+        assert_eq!(grids.total_energy(), 100.0);
+        assert_eq!(grids.grid_at(BlockCoordinate::new(3, 0, 0)).unwrap().capacity(), 100.0);
+        assert_eq!(grids.grid_at(BlockCoordinate::new(5, 0, 0)).unwrap().capacity(), 0.0);
+
+        // Destroying a cable leaves the laser unpowered
+        power_blocks.remove(&BlockCoordinate::new(2, 0, 0));
+        let mut split = compute_power_grids(&power_blocks);
+        assert_eq!(split.iter().count(), 3);
+
+        split.redistribute_energy(&grids, 0.0);
+        assert_eq!(available(&split, PowerConsumer::Block(BlockCoordinate::new(3, 0, 0))), 0.0);
+        assert_eq!(available(&split, PowerConsumer::Block(BlockCoordinate::new(0, 0, 0))), 100.0);
+        assert_eq!(available(&split, PowerConsumer::BlockType(LASER)), 0.0);
+        assert_eq!(available(&split, PowerConsumer::Any), 100.0);
+        let cell_grid = split.supplying(PowerConsumer::Block(BlockCoordinate::new(0, 0, 0))).next().unwrap();
+        assert_eq!(split.decrease_energy_of(cell_grid, 10.0), 0.0);
+        assert_eq!(available(&split, PowerConsumer::Any), 90.0);
+
+        // Without any cables, everything shares one grid
+        power_blocks.retain(|_, (id, _)| *id != CABLE);
+        assert_eq!(compute_power_grids(&power_blocks).iter().count(), 1);
+    }
+}
@@ -38,6 +38,7 @@ pub mod line_system;
 pub mod mining_laser_system;
 pub mod missile_launcher_system;
 pub mod railgun_system;
+pub mod repair_beam_system;
 pub mod shield_system;
 pub mod sync;
 pub mod thruster_system;
@@ -798,6 +799,7 @@ pub(super) fn register(app: &mut App) {
     missile_launcher_system::register(app);
     laser_cannon_system::register(app);
     mining_laser_system::register(app);
+    repair_beam_system::register(app);
     dock_system::register(app);
     railgun_system::register(app);
     warp::register(app);
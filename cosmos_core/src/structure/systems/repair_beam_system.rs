@@ -0,0 +1,65 @@
+//! Represents all the repair beams on a structure
+
+use bevy::prelude::*;
+use bevy::reflect::Reflect;
+use serde::{Deserialize, Serialize};
+
+use super::StructureSystemsSet;
+use super::{
+    line_system::{LineProperty, LinePropertyCalculator, LineSystem},
+    sync::SyncableSystem,
+};
+
+/// A ship system that stores information about the repair beams
+pub type RepairBeamSystem = LineSystem<RepairBeamProperty, RepairBeamPropertyCalculator>;
+
+impl SyncableSystem for RepairBeamSystem {}
+
+#[derive(Debug, Default, Reflect, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// Every block that is a repair beam should have this property
+pub struct RepairBeamProperty {
+    /// How much energy is consumed per second repairing
+    pub energy_per_second: f32,
+    /// How much block health this restores per second
+    pub repair_per_second: f32,
+}
+
+impl LineProperty for RepairBeamProperty {}
+
+#[derive(Default, Reflect, Debug)]
+/// Used internally by repair beam system, but must be public for compiler to be happy.
+///
+/// A simple strategy pattern that is never initialized
+pub struct RepairBeamPropertyCalculator;
+
+impl LinePropertyCalculator<RepairBeamProperty> for RepairBeamPropertyCalculator {
+    fn calculate_property(properties: &[RepairBeamProperty]) -> RepairBeamProperty {
+        properties
+            .iter()
+            .copied()
+            .reduce(|a, b| RepairBeamProperty {
+                repair_per_second: a.repair_per_second + b.repair_per_second,
+                energy_per_second: a.energy_per_second + b.energy_per_second,
+            })
+            .unwrap_or_default()
+    }
+
+    fn unlocalized_name() -> &'static str {
+        "cosmos:repair_beam_system"
+    }
+}
+
+fn name_repair_beam_system(mut commands: Commands, q_added: Query<Entity, Added<RepairBeamSystem>>) {
+    for e in q_added.iter() {
+        commands.entity(e).insert(Name::new("Repair Beam System"));
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.register_type::<RepairBeamSystem>().add_systems(
+        FixedUpdate,
+        name_repair_beam_system
+            .ambiguous_with_all() // doesn't matter if this is 1-frame delayed
+            .after(StructureSystemsSet::InitSystems),
+    );
+}
@@ -1,6 +1,9 @@
 //! Represents the shield functionality
 
-use bevy::{platform::collections::HashMap, prelude::*};
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
 use bigdecimal::num_traits::Pow;
 use serde::{Deserialize, Serialize};
 
@@ -52,6 +55,9 @@ pub struct ShieldGeneratorBlocks(pub HashMap<u16, ShieldGeneratorProperty>);
 pub struct ShieldSystem {
     projectors: HashMap<BlockCoordinate, ShieldProjectorProperty>,
     generators: HashMap<BlockCoordinate, ShieldGeneratorProperty>,
+    /// This is derived from the structure's logic, so it isn't saved.
+    #[serde(skip)]
+    disabled_generators: HashSet<BlockCoordinate>,
 
     needs_shields_recalculated: bool,
     shields: Vec<(BlockCoordinate, ShieldDetails)>,
@@ -93,9 +99,40 @@ impl ShieldSystem {
     /// Call this whenever a generator block is removed from the structure
     pub fn generator_removed(&mut self, coords: BlockCoordinate) {
         self.generators.remove(&coords);
+        self.disabled_generators.remove(&coords);
         self.needs_shields_recalculated = true;
     }
 
+    /// Enables or disables the generator at these coordinates.
+    ///
+    /// Disabled generators still connect projectors into the same shield, but do not contribute any
+    /// power to it.
+    pub fn set_generator_enabled(&mut self, coords: BlockCoordinate, enabled: bool) {
+        if !self.generators.contains_key(&coords) {
+            return;
+        }
+
+        let changed = if enabled {
+            self.disabled_generators.remove(&coords)
+        } else {
+            self.disabled_generators.insert(coords)
+        };
+
+        if changed {
+            self.needs_shields_recalculated = true;
+        }
+    }
+
+    /// Returns true if the generator at these coordinates has been disabled via [`Self::set_generator_enabled`].
+    pub fn is_generator_enabled(&self, coords: BlockCoordinate) -> bool {
+        !self.disabled_generators.contains(&coords)
+    }
+
+    /// Iterates over the coordinates of every generator in this system
+    pub fn generator_coords(&self) -> impl Iterator<Item = BlockCoordinate> + '_ {
+        self.generators.keys().copied()
+    }
+
     /// Call this whenever a generator block is added to the structure
     pub fn generator_added(&mut self, property: ShieldGeneratorProperty, coords: BlockCoordinate) {
         self.generators.insert(coords, property);
@@ -210,7 +247,7 @@ impl ShieldSystem {
                     if let Ok(bc) = BlockCoordinate::try_from(dir + projector_coord) {
                         if self.projectors.contains_key(&bc) {
                             touching_projectors += 1;
-                        } else if self.generators.contains_key(&bc) {
+                        } else if self.generators.contains_key(&bc) && !self.disabled_generators.contains(&bc) {
                             let value = *group_generators.entry(bc).or_default() + 1;
                             group_generators.insert(bc, value);
                         }
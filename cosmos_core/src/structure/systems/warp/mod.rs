@@ -2,8 +2,10 @@
 
 use bevy::prelude::*;
 
+pub mod warp_disruptor;
 pub mod warp_drive;
 
 pub(super) fn register(app: &mut App) {
+    warp_disruptor::register(app);
     warp_drive::register(app);
 }
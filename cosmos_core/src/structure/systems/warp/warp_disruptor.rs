@@ -0,0 +1,115 @@
+//! Warp disruptors create fields that pull warping ships out of warp
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ecs::name,
+    physics::location::SECTOR_DIMENSIONS,
+    prelude::BlockCoordinate,
+    structure::systems::{StructureSystemImpl, sync::SyncableSystem},
+};
+
+/// The radius of a field created by a single warp disruptor
+const BASE_FIELD_RADIUS: f32 = SECTOR_DIMENSIONS / 4.0;
+/// How much larger the field gets for every additional warp disruptor
+const FIELD_RADIUS_PER_DISRUPTOR: f32 = 500.0;
+/// A field can never be larger than this, no matter how many disruptors power it
+const MAX_FIELD_RADIUS: f32 = SECTOR_DIMENSIONS;
+/// The energy each warp disruptor needs every second to keep the field up
+const ENERGY_PER_DISRUPTOR_PER_SEC: f32 = 100.0;
+
+#[derive(Component, Default, Reflect, Serialize, Deserialize, Debug, Clone)]
+/// Every warp disruptor on a structure, which together create an interdiction field around it.
+///
+/// Any ship whose warp path passes through a powered field will be dropped out of warp at its edge.
+pub struct WarpDisruptorSystem {
+    disruptors: Vec<BlockCoordinate>,
+    powered: bool,
+}
+
+impl StructureSystemImpl for WarpDisruptorSystem {
+    fn unlocalized_name() -> &'static str {
+        "cosmos:warp_disruptor_system"
+    }
+}
+
+impl SyncableSystem for WarpDisruptorSystem {}
+
+impl WarpDisruptorSystem {
+    /// Call this whenever a warp disruptor is added to the structure
+    pub fn add_disruptor(&mut self, coordinate: BlockCoordinate) {
+        if !self.disruptors.contains(&coordinate) {
+            self.disruptors.push(coordinate);
+        }
+    }
+
+    /// Call this whenever a warp disruptor is removed from the structure
+    pub fn remove_disruptor(&mut self, coordinate: BlockCoordinate) {
+        if let Some((idx, _)) = self.disruptors.iter().enumerate().find(|(_, c)| **c == coordinate) {
+            self.disruptors.remove(idx);
+        }
+    }
+
+    /// Checks if this system has no warp disruptors
+    pub fn empty(&self) -> bool {
+        self.disruptors.is_empty()
+    }
+
+    /// The radius of the interdiction field, or 0.0 if there are no disruptors
+    pub fn field_radius(&self) -> f32 {
+        if self.empty() {
+            return 0.0;
+        }
+
+        (BASE_FIELD_RADIUS + FIELD_RADIUS_PER_DISRUPTOR * (self.disruptors.len() - 1) as f32).min(MAX_FIELD_RADIUS)
+    }
+
+    /// The energy this system needs every second to keep its field up
+    pub fn energy_per_second(&self) -> f32 {
+        ENERGY_PER_DISRUPTOR_PER_SEC * self.disruptors.len() as f32
+    }
+
+    /// Returns true if this system had enough energy to keep its field up
+    pub fn powered(&self) -> bool {
+        self.powered
+    }
+
+    /// Sets if this system had enough energy to keep its field up
+    pub fn set_powered(&mut self, powered: bool) {
+        self.powered = powered;
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.register_type::<WarpDisruptorSystem>()
+        .add_systems(Update, name::<WarpDisruptorSystem>("Warp Disruptor System"));
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::BlockCoordinate;
+
+    use super::{BASE_FIELD_RADIUS, FIELD_RADIUS_PER_DISRUPTOR, MAX_FIELD_RADIUS, WarpDisruptorSystem};
+
+    #[test]
+    fn field_grows_with_disruptors() {
+        let mut system = WarpDisruptorSystem::default();
+        assert_eq!(system.field_radius(), 0.0);
+
+        system.add_disruptor(BlockCoordinate::new(0, 0, 0));
+        system.add_disruptor(BlockCoordinate::new(0, 0, 0));
+        assert_eq!(system.field_radius(), BASE_FIELD_RADIUS);
+
+        system.add_disruptor(BlockCoordinate::new(1, 0, 0));
+        assert_eq!(system.field_radius(), BASE_FIELD_RADIUS + FIELD_RADIUS_PER_DISRUPTOR);
+
+        for x in 2..1000 {
+            system.add_disruptor(BlockCoordinate::new(x, 0, 0));
+        }
+        assert_eq!(system.field_radius(), MAX_FIELD_RADIUS);
+
+        system.remove_disruptor(BlockCoordinate::new(0, 0, 0));
+        assert_eq!(system.disruptors.len(), 999);
+    }
+}
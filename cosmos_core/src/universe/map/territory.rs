@@ -27,6 +27,11 @@ impl FactionClaimedTerritory {
         self.0.insert(system, fac);
     }
 
+    /// Removes any faction's claim on this system, returning the faction that owned it
+    pub fn unclaim(&mut self, system: SystemCoordinate) -> Option<FactionId> {
+        self.0.remove(&system)
+    }
+
     /// Checks if this system is claimed by any faction
     pub fn is_claimed(&self, system: SystemCoordinate) -> bool {
         self.0.contains_key(&system)
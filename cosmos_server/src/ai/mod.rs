@@ -5,6 +5,7 @@ use bevy::prelude::*;
 use crate::persistence::{
     SerializedData,
     loading::{LOADING_SCHEDULE, LoadingSystemSet, NeedsLoaded},
+    registry::register_persistent_data,
     saving::{SAVING_SCHEDULE, SavingSystemSet},
 };
 
@@ -32,6 +33,7 @@ fn on_load_ai_controlled(mut commands: Commands, query: Query<(Entity, &Serializ
 }
 
 pub(super) fn register(app: &mut App) {
+    register_persistent_data::<bool>(app, "cosmos:ai_controlled");
     app.add_systems(LOADING_SCHEDULE, on_load_ai_controlled.in_set(LoadingSystemSet::DoLoading));
     app.add_systems(SAVING_SCHEDULE, on_save_ai_controlled.in_set(SavingSystemSet::DoSaving));
 
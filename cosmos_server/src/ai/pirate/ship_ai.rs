@@ -11,7 +11,7 @@ use cosmos_core::{
     state::GameState,
     structure::{
         StructureTypeSet,
-        shared::{DespawnWithStructure, MeltingDown},
+        shared::{Derelict, DespawnWithStructure, MeltingDown},
         ship::{Ship, pilot::Pilot, ship_movement::ShipMovementSet},
     },
 };
@@ -20,6 +20,7 @@ use crate::{
     persistence::{
         SerializedData,
         loading::{LOADING_SCHEDULE, LoadingSystemSet, NeedsLoaded},
+        registry::register_persistent_data,
         saving::{SAVING_SCHEDULE, SavingSystemSet},
     },
     structure::ship::speed::{MaxShipSpeed, ShipSpeedModifier},
@@ -68,13 +69,34 @@ fn handle_pirate_targetting(
 
 fn add_pirate_targets(
     mut commands: Commands,
-    q_should_be_targets: Query<Entity, (Without<PirateTarget>, Or<(With<Player>, (With<Ship>, Without<Pirate>))>)>,
+    q_should_be_targets: Query<
+        Entity,
+        (
+            Without<PirateTarget>,
+            Or<(With<Player>, (With<Ship>, Without<Pirate>, Without<Derelict>))>,
+        ),
+    >,
 ) {
     for ent in &q_should_be_targets {
         commands.entity(ent).insert(PirateTarget);
     }
 }
 
+/// Wrecks aren't worth attacking, so pirates stop targeting ships once they become derelict
+fn remove_derelict_pirate_targets(
+    mut commands: Commands,
+    q_new_derelicts: Query<Entity, (With<PirateTarget>, Added<Derelict>)>,
+    q_pirates: Query<(Entity, &AiTargetting), With<Pirate>>,
+) {
+    for derelict in q_new_derelicts.iter() {
+        commands.entity(derelict).remove::<PirateTarget>();
+
+        for (pirate_ent, _) in q_pirates.iter().filter(|(_, target)| target.0 == derelict) {
+            commands.entity(pirate_ent).remove::<AiTargetting>();
+        }
+    }
+}
+
 fn add_pirate_ai(mut commands: Commands, q_needs_ai: Query<Entity, (With<Pirate>, Without<CombatAi>)>) {
     for ent in &q_needs_ai {
         let pilot_ent = commands
@@ -166,6 +188,8 @@ fn add_difficuly_increase(mut commands: Commands, q_merchant: Query<Entity, (Wit
 }
 
 pub(super) fn register(app: &mut App) {
+    register_persistent_data::<bool>(app, "cosmos:pirate");
+
     app.configure_sets(
         FixedUpdate,
         PirateSystemSet::PirateAiLogic
@@ -181,6 +205,7 @@ pub(super) fn register(app: &mut App) {
             add_pirate_ai,
             add_difficuly_increase,
             apply_pirate_faction,
+            remove_derelict_pirate_targets,
             add_pirate_targets,
             handle_pirate_targetting.before(ShipMovementSet::RemoveShipMovement),
         )
@@ -0,0 +1,309 @@
+//! Inspects and repairs a world's save files without starting the server.
+//!
+//! Make sure the server is not running on this world while using this, or your changes may be
+//! overwritten when it next saves.
+//!
+//! Run `cargo run --bin world_inspector -- --help` for a list of commands.
+
+#![warn(missing_docs)]
+
+use std::{ffi::OsStr, fs, path::Path};
+
+use anyhow::{Context, bail};
+use bevy::{app::App, math::Vec3};
+use clap::{Parser, Subcommand};
+use cosmos_core::{
+    netty::cosmos_encoder,
+    physics::location::{Location, Sector},
+};
+use cosmos_server::{
+    persistence::{
+        NORMAL_ENTITY_EXTENSION, OWNED_ENTITY_EXTENSION, SaveFileIdentifier, SerializedData, WorldRoot, registry::PersistenceRegistry,
+    },
+    plugin::server_plugin::register_server_logic,
+};
+use serde_json::{Map, Value, json};
+use walkdir::WalkDir;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Inspects and repairs Cosmos world saves without starting the server", long_about = None)]
+struct Args {
+    /// The world folder to inspect
+    #[arg(long, default_value_t = String::from("world"))]
+    world: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Lists every sector that has entities saved in it
+    Sectors,
+    /// Lists every entity saved in a sector
+    List {
+        /// The sector, formatted as `x,y,z`
+        #[arg(value_parser = parse_sector, allow_hyphen_values = true)]
+        sector: Sector,
+    },
+    /// Prints an entity's saved data as JSON
+    Dump {
+        /// The sector the entity is saved in, formatted as `x,y,z`
+        #[arg(value_parser = parse_sector, allow_hyphen_values = true)]
+        sector: Sector,
+        /// The entity's id
+        entity: String,
+        /// Also dumps every entity saved with this one (such as block data and docked ships)
+        #[arg(long, default_value_t = false)]
+        children: bool,
+    },
+    /// Deletes an entity and everything saved with it
+    Delete {
+        /// The sector the entity is saved in, formatted as `x,y,z`
+        #[arg(value_parser = parse_sector, allow_hyphen_values = true)]
+        sector: Sector,
+        /// The entity's id
+        entity: String,
+    },
+    /// Moves an entity (and everything saved with it) to a new location
+    Relocate {
+        /// The sector the entity is saved in, formatted as `x,y,z`
+        #[arg(value_parser = parse_sector, allow_hyphen_values = true)]
+        sector: Sector,
+        /// The entity's id
+        entity: String,
+        /// The sector to move the entity to, formatted as `x,y,z`
+        #[arg(long, value_parser = parse_sector, allow_hyphen_values = true)]
+        to: Sector,
+        /// The position within the new sector, formatted as `x,y,z`
+        #[arg(long, value_parser = parse_vec3, allow_hyphen_values = true, default_value = "0,0,0")]
+        local: Vec3,
+    },
+}
+
+fn parse_coords<T: std::str::FromStr>(s: &str) -> Result<[T; 3], String> {
+    let coords = s
+        .split(',')
+        .map(|c| c.trim().parse::<T>().map_err(|_| format!("Invalid coordinate `{c}`")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    coords
+        .try_into()
+        .map_err(|_| format!("Expected 3 coordinates formatted as `x,y,z` - got `{s}`"))
+}
+
+fn parse_sector(s: &str) -> Result<Sector, String> {
+    parse_coords(s).map(|[x, y, z]| Sector::new(x, y, z))
+}
+
+fn parse_vec3(s: &str) -> Result<Vec3, String> {
+    parse_coords(s).map(Vec3::from_array)
+}
+
+/// Every piece of data the server saves. Anything not in here is only listed with its size.
+///
+/// This registers the server's logic without running it, which is the only way to see everything it saves.
+fn persistence_registry() -> PersistenceRegistry {
+    let mut app = App::new();
+    register_server_logic(&mut app);
+
+    app.world_mut().remove_resource::<PersistenceRegistry>().unwrap_or_default()
+}
+
+fn read_serialized_data(path: &str) -> anyhow::Result<SerializedData> {
+    let data = fs::read(path).with_context(|| format!("Unable to read {path}"))?;
+    cosmos_encoder::deserialize::<SerializedData>(&data).map_err(|e| anyhow::anyhow!("Unable to decode {path} - {e:?}"))
+}
+
+fn dump_file(registry: &PersistenceRegistry, path: &str) -> anyhow::Result<Value> {
+    let serialized_data = read_serialized_data(path)?;
+    let save_data = serialized_data.save_data();
+
+    let mut saved = save_data.iter().collect::<Vec<_>>();
+    saved.sort_by_key(|(data_id, _)| *data_id);
+
+    let mut components = Map::new();
+    for (data_id, data) in saved {
+        let version = save_data.data_version(data_id);
+        let value = registry
+            .get(data_id)
+            .filter(|entry| entry.version == version)
+            .and_then(|entry| (entry.decode)(data));
+
+        components.insert(
+            data_id.clone(),
+            json!({
+                "bytes": data.len(),
+                "version": version,
+                "value": value,
+            }),
+        );
+    }
+
+    Ok(json!({
+        "path": path,
+        "components": components,
+    }))
+}
+
+fn is_entity_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == OsStr::new(NORMAL_ENTITY_EXTENSION) || ext == OsStr::new(OWNED_ENTITY_EXTENSION))
+}
+
+/// Every entity file saved within this directory, including nested ones
+fn entity_files_within(dir: &str) -> Vec<String> {
+    let mut files = WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter(|x| x.file_type().is_file() && is_entity_file(x.path()))
+        .map(|x| x.path().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+/// Every base entity saved directly in this sector
+fn entities_in_sector(world_root: &WorldRoot, sector: Sector) -> Vec<SaveFileIdentifier> {
+    let dir = world_root.path_for(&SaveFileIdentifier::get_sector_path(sector));
+
+    WalkDir::new(&dir)
+        .max_depth(1)
+        .into_iter()
+        .flatten()
+        .filter(|x| x.file_type().is_file() && x.path().extension() == Some(OsStr::new(NORMAL_ENTITY_EXTENSION)))
+        .filter_map(|x| {
+            let stem = x.path().file_stem()?.to_str()?;
+            SaveFileIdentifier::from_base_save_file_name(sector, stem)
+        })
+        .collect()
+}
+
+fn find_entity(world_root: &WorldRoot, sector: Sector, entity: &str) -> anyhow::Result<SaveFileIdentifier> {
+    entities_in_sector(world_root, sector)
+        .into_iter()
+        .find(|sfi| sfi.entity_id().is_some_and(|id| id.to_string() == entity))
+        .with_context(|| format!("No entity {entity} saved in sector {sector}"))
+}
+
+fn list_sectors(world_root: &WorldRoot) -> anyhow::Result<()> {
+    let mut sectors = fs::read_dir(world_root.get())
+        .with_context(|| format!("Unable to read world {world_root}"))?
+        .flatten()
+        .filter(|x| x.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|x| SaveFileIdentifier::sector_from_path(x.file_name().to_str()?))
+        .map(|sector| (sector, entities_in_sector(world_root, sector).len()))
+        .filter(|(_, n_entities)| *n_entities != 0)
+        .collect::<Vec<_>>();
+
+    sectors.sort_by_key(|(sector, _)| (sector.x(), sector.y(), sector.z()));
+
+    for (sector, n_entities) in sectors {
+        println!("{},{},{}\t{n_entities} entities", sector.x(), sector.y(), sector.z());
+    }
+
+    Ok(())
+}
+
+fn list_entities(world_root: &WorldRoot, sector: Sector) {
+    for sfi in entities_in_sector(world_root, sector) {
+        let Some(entity_id) = sfi.entity_id() else {
+            continue;
+        };
+
+        let n_children = entity_files_within(&world_root.path_for(&sfi.get_children_directory())).len();
+        println!("{entity_id}\t{n_children} children\t{}", sfi.get_save_file_path(world_root));
+    }
+}
+
+fn dump_entity(world_root: &WorldRoot, sector: Sector, entity: &str, children: bool) -> anyhow::Result<()> {
+    let sfi = find_entity(world_root, sector, entity)?;
+    let registry = persistence_registry();
+    let mut dumped = dump_file(&registry, &sfi.get_save_file_path(world_root))?;
+
+    if children {
+        let children = entity_files_within(&world_root.path_for(&sfi.get_children_directory()))
+            .iter()
+            .map(|path| dump_file(&registry, path).unwrap_or_else(|e| json!({ "path": path, "error": e.to_string() })))
+            .collect::<Vec<_>>();
+
+        dumped["children"] = Value::Array(children);
+    }
+
+    println!("{}", serde_json::to_string_pretty(&dumped)?);
+
+    Ok(())
+}
+
+fn delete_entity(world_root: &WorldRoot, sector: Sector, entity: &str) -> anyhow::Result<()> {
+    let sfi = find_entity(world_root, sector, entity)?;
+
+    let path = sfi.get_save_file_path(world_root);
+    fs::remove_file(&path).with_context(|| format!("Unable to delete {path}"))?;
+
+    let children_dir = world_root.path_for(&sfi.get_children_directory());
+    if fs::exists(&children_dir).unwrap_or(false) {
+        fs::remove_dir_all(&children_dir).with_context(|| format!("Unable to delete {children_dir}"))?;
+    }
+
+    println!("Deleted {entity} from sector {sector}.");
+
+    Ok(())
+}
+
+fn relocate_entity(world_root: &WorldRoot, sector: Sector, entity: &str, to: Location) -> anyhow::Result<()> {
+    let sfi = find_entity(world_root, sector, entity)?;
+    let (Some(entity_id), Some((_, load_distance))) = (sfi.entity_id(), sfi.base_sector_and_load_distance()) else {
+        bail!("{entity} is not a base entity");
+    };
+
+    let new_sfi = SaveFileIdentifier::new(Some(to.sector()), *entity_id, load_distance);
+    let old_path = sfi.get_save_file_path(world_root);
+    let new_path = new_sfi.get_save_file_path(world_root);
+
+    if old_path != new_path && fs::exists(&new_path).unwrap_or(false) {
+        bail!("An entity is already saved at {new_path}");
+    }
+
+    let mut serialized_data = read_serialized_data(&old_path)?;
+    serialized_data.set_location(&to);
+
+    let new_dir = world_root.path_for(&SaveFileIdentifier::get_sector_path(to.sector()));
+    fs::create_dir_all(&new_dir).with_context(|| format!("Unable to create {new_dir}"))?;
+    fs::write(&new_path, cosmos_encoder::serialize(&serialized_data)).with_context(|| format!("Unable to write {new_path}"))?;
+
+    if old_path != new_path {
+        let old_children_dir = world_root.path_for(&sfi.get_children_directory());
+        let new_children_dir = world_root.path_for(&new_sfi.get_children_directory());
+
+        if fs::exists(&old_children_dir).unwrap_or(false) {
+            fs::rename(&old_children_dir, &new_children_dir)
+                .with_context(|| format!("Unable to move {old_children_dir} to {new_children_dir}"))?;
+        }
+
+        fs::remove_file(&old_path).with_context(|| format!("Unable to delete {old_path}"))?;
+    }
+
+    println!("Moved {entity} to {to}.");
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let world_root = WorldRoot::dir_for_world_root(&args.world);
+
+    if !fs::exists(world_root.get()).unwrap_or(false) {
+        bail!("No world exists at {world_root}");
+    }
+
+    match args.command {
+        Command::Sectors => list_sectors(&world_root)?,
+        Command::List { sector } => list_entities(&world_root, sector),
+        Command::Dump { sector, entity, children } => dump_entity(&world_root, sector, &entity, children)?,
+        Command::Delete { sector, entity } => delete_entity(&world_root, sector, &entity)?,
+        Command::Relocate { sector, entity, to, local } => relocate_entity(&world_root, sector, &entity, Location::new(local, to))?,
+    }
+
+    Ok(())
+}
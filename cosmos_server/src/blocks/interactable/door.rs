@@ -14,8 +14,15 @@ use cosmos_core::{
     state::GameState,
 };
 
+use crate::logic::{LogicInputMessage, LogicSystemSet, logic_driver::LogicDriver};
+
 #[derive(Debug, Message)]
-struct ToggleDoorMessage(StructureBlock, Entity);
+struct ToggleDoorMessage {
+    block: StructureBlock,
+    reason: BlockChangedReason,
+    /// If this is set, the door will be opened (`true`) or closed (`false`) instead of toggled.
+    open: Option<bool>,
+}
 
 fn handle_door_block_event(
     mut interact_events: MessageReader<Cancellable<BlockInteractMessage>>,
@@ -39,7 +46,46 @@ fn handle_door_block_event(
             return;
         }
 
-        ev_writer.write(ToggleDoorMessage(s_block, ev.interactor));
+        ev_writer.write(ToggleDoorMessage {
+            block: s_block,
+            reason: BlockChangedReason::Entity(ev.interactor),
+            open: None,
+        });
+    }
+}
+
+/// Doors that are wired up are open while they receive any non-zero logic signal, and closed otherwise.
+///
+/// Doors without anything wired to them are left alone, so players can still open and close them by hand.
+fn door_input_event_listener(
+    mut evr_logic_input: MessageReader<LogicInputMessage>,
+    blocks: Res<Registry<Block>>,
+    q_structure: Query<(&Structure, &LogicDriver)>,
+    mut ev_writer: MessageWriter<ToggleDoorMessage>,
+) {
+    for ev in evr_logic_input.read() {
+        let Ok((structure, logic_driver)) = q_structure.get(ev.block.structure()) else {
+            continue;
+        };
+
+        let coords = ev.block.coords();
+        let un = structure.block_at(coords, &blocks).unlocalized_name();
+        if un != "cosmos:door" && un != "cosmos:door_open" {
+            continue;
+        }
+
+        let rotation = structure.block_rotation(coords);
+        if !logic_driver.has_connected_input(coords, rotation) {
+            continue;
+        }
+
+        let open = logic_driver.read_all_inputs(coords, rotation).iter().any(|signal| *signal != 0);
+
+        ev_writer.write(ToggleDoorMessage {
+            block: ev.block,
+            reason: BlockChangedReason::Update,
+            open: Some(open),
+        });
     }
 }
 
@@ -50,7 +96,7 @@ fn toggle_doors(
     blocks: Res<Registry<Block>>,
 ) {
     for ev in evr_door_toggle.read() {
-        let Ok(mut structure) = q_structure.get_mut(ev.0.structure()) else {
+        let Ok(mut structure) = q_structure.get_mut(ev.block.structure()) else {
             warn!("Not structure?");
             continue;
         };
@@ -65,11 +111,14 @@ fn toggle_doors(
         let door_id = door.id();
         let door_open_id = door_open.id();
 
-        let open = structure.block_id_at(ev.0.coords()) == door_open_id;
+        let open = structure.block_id_at(ev.block.coords()) == door_open_id;
+        if ev.open == Some(open) {
+            continue;
+        }
         let block = if open { door } else { door_open };
 
         let mut todo = HashSet::new();
-        todo.insert(ev.0.coords());
+        todo.insert(ev.block.coords());
 
         let mut done = HashSet::new();
         while !todo.is_empty() {
@@ -87,13 +136,7 @@ fn toggle_doors(
 
                 let block_info = structure.block_info_at(coord);
 
-                structure.set_block_and_info_at(
-                    coord,
-                    block,
-                    block_info,
-                    &blocks,
-                    Some((&mut evw_block_changed, BlockChangedReason::Entity(ev.1))),
-                );
+                structure.set_block_and_info_at(coord, block, block_info, &blocks, Some((&mut evw_block_changed, ev.reason)));
 
                 done.insert(coord);
 
@@ -121,5 +164,11 @@ pub(super) fn register(app: &mut App) {
             .chain()
             .run_if(in_state(GameState::Playing)),
     )
+    .add_systems(
+        FixedUpdate,
+        door_input_event_listener
+            .in_set(LogicSystemSet::Consume)
+            .ambiguous_with(LogicSystemSet::Consume),
+    )
     .add_message::<ToggleDoorMessage>();
 }
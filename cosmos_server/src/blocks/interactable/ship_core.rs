@@ -12,29 +12,31 @@ use cosmos_core::{
     state::GameState,
     structure::{
         Structure,
-        shared::build_mode::BuildMode,
+        ownership::TrustLevel,
+        shared::{Derelict, build_mode::BuildMode},
         ship::{Ship, pilot::Pilot},
     },
 };
 
-use crate::blocks::multiblock::shipyard::StructureBeingBuilt;
+use crate::{blocks::multiblock::shipyard::StructureBeingBuilt, structure::ownership::StructurePermissions};
 
 fn handle_block_event(
     mut interact_events: MessageReader<Cancellable<BlockInteractMessage>>,
     mut change_pilot_event: MessageWriter<ChangePilotMessage>,
-    q_ship: Query<(&Structure, Has<StructureBeingBuilt>), With<Ship>>,
+    q_ship: Query<(&Structure, Has<StructureBeingBuilt>, Has<Derelict>), With<Ship>>,
     q_can_be_pilot: Query<(), Without<Pilot>>,
     q_can_be_pilot_player: Query<(), Without<BuildMode>>,
     blocks: Res<Registry<Block>>,
     mut nevw_noticication: NettyMessageWriter<Notification>,
     q_player: Query<&Player>,
+    permissions: StructurePermissions,
 ) {
     for ev in interact_events.read().flatten() {
         let Some(s_block) = ev.block else {
             continue;
         };
 
-        let Ok((structure, being_built)) = q_ship.get(s_block.structure()) else {
+        let Ok((structure, being_built, derelict)) = q_ship.get(s_block.structure()) else {
             continue;
         };
 
@@ -57,6 +59,16 @@ fn handle_block_event(
             continue;
         }
 
+        if derelict {
+            nevw_noticication.write(Notification::error("Derelict ships cannot be piloted"), player.client_id());
+            continue;
+        }
+
+        if let Err(denied) = permissions.check(ev.interactor, s_block.structure(), None, TrustLevel::Pilot) {
+            nevw_noticication.write(Notification::error(denied.message()), player.client_id());
+            continue;
+        }
+
         if being_built {
             nevw_noticication.write(Notification::error("Cannot enter ship that is being built"), player.client_id());
             continue;
@@ -0,0 +1,431 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::common_conditions::on_timer};
+use bevy_rapier3d::prelude::Velocity;
+use cosmos_core::{
+    block::{
+        Block,
+        block_direction::ALL_BLOCK_DIRECTIONS,
+        block_events::{BlockInteractMessage, BlockMessagesSet},
+        blocks::AIR_BLOCK_ID,
+        data::BlockData,
+        multiblock::prelude::*,
+    },
+    ecs::{NeedsDespawned, sets::FixedUpdateSet},
+    entities::{EntityId, player::Player},
+    events::{block_events::BlockChangedMessage, cancellable::Cancellable, structure::structure_event::StructureMessageIterator},
+    faction::{Faction, FactionId, FactionRelation, Factions},
+    netty::sync::events::server_event::NettyMessageWriter,
+    notifications::Notification,
+    physics::location::Location,
+    prelude::{BlockCoordinate, Ship, Station, Structure, StructureSystems},
+    registry::{Registry, identifiable::Identifiable},
+    state::GameState,
+    structure::{
+        ship::pilot::Pilot,
+        systems::{
+            dock_system::Docked,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
+        },
+    },
+    universe::warp::WarpingSet,
+};
+use derive_more::{Display, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    persistence::saving::{NeedsSaved, SAVING_SCHEDULE, SavingSystemSet},
+    universe::warp::WarpAnchor,
+};
+
+use super::{JumpGateNetwork, JumpGateStation};
+
+/// The energy taken from the gate's structure each time a ship travels through it
+const JUMP_GATE_ENERGY_COST: f32 = 50_000.0;
+/// How far from the destination gate ships are placed, in the direction they were travelling
+const JUMP_GATE_EXIT_DISTANCE: f32 = 200.0;
+/// How many seconds a ship must wait before it can use a jump gate again
+const JUMP_GATE_COOLDOWN_SECS: f32 = 10.0;
+
+#[derive(Component, Debug)]
+/// The player is linking the jump gate with this id to the next jump gate they interact with
+struct LinkingJumpGate(JumpGateId);
+
+#[derive(Component, Debug)]
+/// This ship recently tried to use a jump gate, and cannot use one again for this many seconds
+struct JumpGateCooldown(f32);
+
+fn on_place_blocks_impacting_jump_gate(
+    mut evr_block_changed_event: MessageReader<BlockChangedMessage>,
+    mut q_jump_gates: Query<(&JumpGates, &mut Structure)>,
+    q_jump_gate: Query<(&JumpGate, Entity)>,
+    mut q_block_data: Query<&mut BlockData>,
+    q_has_jump_gate_data: Query<(), With<JumpGate>>,
+    mut network: ResMut<JumpGateNetwork>,
+    mut commands: Commands,
+) {
+    for (structure, bce) in evr_block_changed_event.read().group_by_structure() {
+        let Ok((jump_gates, mut structure)) = q_jump_gates.get_mut(structure) else {
+            continue;
+        };
+
+        // Any block changed in a jump gate will invalidate it
+        for bce in bce {
+            for (jump_gate, ent) in jump_gates
+                .iter()
+                .flat_map(|x| q_jump_gate.get(x))
+                .filter(|(g, _)| g.coordinate_within(bce.block.coords()))
+            {
+                let Ok(block_data) = q_block_data.get(ent) else {
+                    continue;
+                };
+                let block_coords = block_data.identifier.block.coords();
+
+                network.remove(jump_gate.id());
+                structure.remove_block_data::<JumpGate>(block_coords, &mut commands, &mut q_block_data, &q_has_jump_gate_data);
+            }
+        }
+    }
+}
+
+/// Destroyed stations take their jump gates with them.
+///
+/// Stations that are only being unloaded are saved first, so their gates stay a part of the network.
+fn on_jump_gate_station_destroyed(
+    q_destroyed: Query<&JumpGates, (With<NeedsDespawned>, Without<NeedsSaved>)>,
+    q_jump_gate: Query<&JumpGate>,
+    mut network: ResMut<JumpGateNetwork>,
+) {
+    for jump_gates in q_destroyed.iter() {
+        for jump_gate in jump_gates.iter().flat_map(|e| q_jump_gate.get(e)) {
+            network.remove(jump_gate.id());
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, Copy, Serialize, Deserialize, Display)]
+enum JumpGateError {
+    #[display("Controller Touching too many frames ({_0}/1)")]
+    ControllerTouchingTooManyFrames(#[error(not(source))] BlockCoordinate),
+    #[display("Frame is not clear of obstructions at {_0}")]
+    FrameNotClear(#[error(not(source))] BlockCoordinate),
+    #[display("Missing frames")]
+    MissingFrames,
+}
+
+fn compute_jump_gate(structure: &Structure, controller: BlockCoordinate, frame_id: u16) -> Result<JumpGate, JumpGateError> {
+    let mut starting_frame_block = ALL_BLOCK_DIRECTIONS.iter().flat_map(|x| {
+        BlockCoordinate::try_from(controller + x.to_coordinates())
+            .ok()
+            .filter(|c| structure.is_within_blocks(*c) && structure.block_id_at(*c) == frame_id)
+    });
+
+    let starting_frame_coord = match (starting_frame_block.next(), starting_frame_block.next()) {
+        (Some(c), None) => c,
+        (Some(_), Some(c)) => return Err(JumpGateError::ControllerTouchingTooManyFrames(c)),
+        (None, _) => return Err(JumpGateError::MissingFrames),
+    };
+
+    let bounds = check_is_valid_rectangle_outline_multiblock(structure, starting_frame_coord, &[frame_id], 5, usize::MAX).map_err(|e| {
+        error!("{e:?}");
+        JumpGateError::MissingFrames
+    })?;
+
+    let wall_error = bounds.check_walls_filled(
+        structure,
+        &[frame_id, AIR_BLOCK_ID],
+        &mut [RectangleLimit {
+            block: frame_id,
+            amount: bounds.perimeter() as usize,
+        }],
+    );
+
+    if let Some(e) = wall_error.or_else(|| bounds.check_inside_filled(structure, &[AIR_BLOCK_ID], &mut [])) {
+        match e {
+            RectangleMultiblockValidityError::BrokenLimit { block: _, coordinate }
+            | RectangleMultiblockValidityError::InvalidBlock(coordinate) => {
+                return Err(JumpGateError::FrameNotClear(coordinate));
+            }
+        }
+    }
+
+    Ok(JumpGate::new(bounds, controller))
+}
+
+/// The location at the center of this jump gate
+fn jump_gate_center(structure: &Structure, location: &Location, g_trans: &GlobalTransform, jump_gate: &JumpGate) -> Location {
+    let bounds = jump_gate.bounds();
+    let center =
+        (structure.block_relative_position(bounds.negative_coords) + structure.block_relative_position(bounds.positive_coords)) / 2.0;
+
+    *location + g_trans.rotation() * center
+}
+
+fn interact_with_jump_gate(
+    mut q_structure: Query<(&mut Structure, &Location, &GlobalTransform, Option<&EntityId>)>,
+    q_is_station: Query<(), With<Station>>,
+    mut q_jump_gate: Query<&mut JumpGate>,
+    mut evr_interact: MessageReader<Cancellable<BlockInteractMessage>>,
+    blocks: Res<Registry<Block>>,
+    (mut q_block_data, q_has_data): (Query<&mut BlockData>, Query<(), With<JumpGate>>),
+    q_player: Query<(&Player, Option<&LinkingJumpGate>)>,
+    mut network: ResMut<JumpGateNetwork>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    mut commands: Commands,
+    mut block_data_commands: Commands,
+) {
+    for ev in evr_interact.read().flatten() {
+        let Some(b) = ev.block else {
+            continue;
+        };
+
+        let Ok((player, linking)) = q_player.get(ev.interactor) else {
+            continue;
+        };
+
+        let Ok((mut structure, location, g_trans, entity_id)) = q_structure.get_mut(b.structure()) else {
+            continue;
+        };
+
+        let Some(block) = blocks.from_id("cosmos:jump_gate_controller") else {
+            error!("No jump gate controller block!");
+            return;
+        };
+
+        if structure.block_id_at(b.coords()) != block.id() {
+            continue;
+        }
+
+        if let Some(mut jump_gate) = structure.query_block_data_mut(b.coords(), &mut q_jump_gate, &mut block_data_commands) {
+            if ev.alternate {
+                let faction_only = !jump_gate.faction_only();
+                jump_gate.set_faction_only(faction_only);
+
+                let message = if faction_only {
+                    "Only ships allied with this station's faction can now use this jump gate."
+                } else {
+                    "Any ship can now use this jump gate."
+                };
+                nevw_notification.write(Notification::info(message), player.client_id());
+                continue;
+            }
+
+            let id = jump_gate.id();
+
+            match linking.map(|linking| linking.0) {
+                None => {
+                    commands.entity(ev.interactor).insert(LinkingJumpGate(id));
+                    nevw_notification.write(
+                        Notification::info("Interact with another jump gate's controller to link it to this one."),
+                        player.client_id(),
+                    );
+                }
+                Some(linking) if linking == id => {
+                    commands.entity(ev.interactor).remove::<LinkingJumpGate>();
+                    nevw_notification.write(Notification::info("Stopped linking this jump gate."), player.client_id());
+                }
+                Some(linking) => {
+                    commands.entity(ev.interactor).remove::<LinkingJumpGate>();
+                    if network.link(linking, id) {
+                        nevw_notification.write(Notification::info("The jump gates are now linked."), player.client_id());
+                    } else {
+                        nevw_notification.write(
+                            Notification::error("The jump gate you were linking no longer exists."),
+                            player.client_id(),
+                        );
+                    }
+                }
+            }
+
+            continue;
+        }
+
+        if !q_is_station.contains(b.structure()) {
+            nevw_notification.write(Notification::error("Jump gates can only be built on stations."), player.client_id());
+            continue;
+        }
+
+        let Some(frame) = blocks.from_id("cosmos:jump_gate_frame") else {
+            error!("No jump gate frame block!");
+            return;
+        };
+
+        let jump_gate = match compute_jump_gate(&structure, b.coords(), frame.id()) {
+            Err(e) => {
+                let message = match e {
+                    JumpGateError::MissingFrames => "The jump gate is missing frames (min size 5x5x5).".to_owned(),
+                    JumpGateError::FrameNotClear(block) => format!("The jump gate is not clear of blocks. ({block})"),
+                    JumpGateError::ControllerTouchingTooManyFrames(block) => {
+                        format!("The controller can only be used for one jump gate. ({block})")
+                    }
+                };
+                nevw_notification.write(Notification::error(message), player.client_id());
+
+                continue;
+            }
+            Ok(jump_gate) => jump_gate,
+        };
+
+        let station = entity_id.map(|&entity_id| JumpGateStation {
+            entity_id,
+            sector: location.sector(),
+        });
+        network.set_location(jump_gate.id(), station, jump_gate_center(&structure, location, g_trans, &jump_gate));
+        structure.insert_block_data(b.coords(), jump_gate, &mut block_data_commands, &mut q_block_data, &q_has_data);
+
+        nevw_notification.write(
+            Notification::info("Jump gate assembled. Interact with its controller to link it to another jump gate."),
+            player.client_id(),
+        );
+    }
+}
+
+/// Keeps the locations of loaded gates up to date, in case their structure has moved
+fn update_jump_gate_locations(
+    q_structure: Query<(&Structure, &Location, &GlobalTransform, Option<&EntityId>, &JumpGates)>,
+    q_jump_gate: Query<&JumpGate>,
+    mut network: ResMut<JumpGateNetwork>,
+) {
+    for (structure, location, g_trans, entity_id, jump_gates) in q_structure.iter() {
+        let station = entity_id.map(|&entity_id| JumpGateStation {
+            entity_id,
+            sector: location.sector(),
+        });
+
+        for jump_gate in jump_gates.iter().flat_map(|e| q_jump_gate.get(e)) {
+            let center = jump_gate_center(structure, location, g_trans, jump_gate);
+
+            if network.location(jump_gate.id()) != Some(center) || (station.is_some() && network.station(jump_gate.id()) != station) {
+                network.set_location(jump_gate.id(), station, center);
+            }
+        }
+    }
+}
+
+fn travel_through_jump_gates(
+    q_structure: Query<(Entity, &Structure, &Location, &GlobalTransform, &JumpGates, Option<&FactionId>), Without<Ship>>,
+    q_jump_gate: Query<&JumpGate>,
+    mut q_ships: Query<
+        (Entity, &mut Location, &Transform, &Velocity, Option<&FactionId>, Option<&Pilot>),
+        (With<Ship>, Without<ChildOf>, Without<JumpGateCooldown>),
+    >,
+    network: Res<JumpGateNetwork>,
+    factions: Res<Factions>,
+    (mut q_ess, q_systems): (Query<&mut EnergyStorageSystem>, Query<(&StructureSystems, Option<&Docked>)>),
+    q_player: Query<&Player>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    mut commands: Commands,
+) {
+    for (structure_ent, structure, location, g_trans, jump_gates, structure_faction) in q_structure.iter() {
+        for jump_gate in jump_gates.iter().flat_map(|e| q_jump_gate.get(e)) {
+            let Some(destination) = network.linked_to(jump_gate.id()).and_then(|linked| network.location(linked)) else {
+                continue;
+            };
+
+            let bounds = jump_gate.bounds();
+            let min = structure.block_relative_position(bounds.negative_coords) - Vec3::splat(0.5);
+            let max = structure.block_relative_position(bounds.positive_coords) + Vec3::splat(0.5);
+            let inv_rotation = g_trans.rotation().inverse();
+
+            for (ship, mut ship_loc, ship_trans, velocity, ship_faction, pilot) in q_ships.iter_mut() {
+                if !ship_loc.is_within_reasonable_range(location) {
+                    continue;
+                }
+
+                let relative = inv_rotation * (*ship_loc - *location).absolute_coords_f32();
+                if !relative.cmpge(min).all() || !relative.cmple(max).all() {
+                    continue;
+                }
+
+                commands.entity(ship).insert(JumpGateCooldown(JUMP_GATE_COOLDOWN_SECS));
+
+                let pilot_player = pilot.and_then(|pilot| q_player.get(pilot.entity).ok());
+
+                if jump_gate.faction_only()
+                    && let Some(gate_faction) = structure_faction.and_then(|f| factions.from_id(f))
+                    && Faction::relation_with_option(Some(gate_faction), ship_faction.and_then(|f| factions.from_id(f)))
+                        != FactionRelation::Ally
+                {
+                    if let Some(player) = pilot_player {
+                        nevw_notification.write(
+                            Notification::error("This jump gate only allows ships allied with its faction."),
+                            player.client_id(),
+                        );
+                    }
+                    continue;
+                }
+
+                if EnergyStorageSystem::compute_total_energy_recursive(
+                    PowerCategory::Utility,
+                    PowerConsumer::Any,
+                    structure_ent,
+                    &q_ess.as_readonly(),
+                    &q_systems,
+                ) < JUMP_GATE_ENERGY_COST
+                {
+                    if let Some(player) = pilot_player {
+                        nevw_notification.write(
+                            Notification::error("This jump gate does not have enough energy."),
+                            player.client_id(),
+                        );
+                    }
+                    continue;
+                }
+
+                EnergyStorageSystem::decrease_energy_recursive(
+                    PowerCategory::Utility,
+                    PowerConsumer::Any,
+                    JUMP_GATE_ENERGY_COST,
+                    structure_ent,
+                    &mut q_ess,
+                    &q_systems,
+                );
+
+                let direction = velocity.linvel.try_normalize().unwrap_or(ship_trans.rotation * Vec3::NEG_Z);
+                *ship_loc = destination + direction * JUMP_GATE_EXIT_DISTANCE;
+
+                commands.spawn((destination, WarpAnchor));
+            }
+        }
+    }
+}
+
+fn tick_jump_gate_cooldowns(mut q_cooldown: Query<(Entity, &mut JumpGateCooldown)>, time: Res<Time>, mut commands: Commands) {
+    for (ent, mut cooldown) in q_cooldown.iter_mut() {
+        cooldown.0 -= time.delta_secs();
+        if cooldown.0 <= 0.0 {
+            commands.entity(ent).remove::<JumpGateCooldown>();
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (on_place_blocks_impacting_jump_gate, interact_with_jump_gate)
+            .chain()
+            .in_set(BlockMessagesSet::ProcessMessages)
+            .before(FixedUpdateSet::PrePhysics)
+            .run_if(in_state(GameState::Playing)),
+    )
+    .add_systems(
+        FixedUpdate,
+        update_jump_gate_locations
+            .run_if(on_timer(Duration::from_secs(1)))
+            .in_set(FixedUpdateSet::Main)
+            .run_if(in_state(GameState::Playing)),
+    )
+    .add_systems(
+        FixedUpdate,
+        (tick_jump_gate_cooldowns, travel_through_jump_gates)
+            .chain()
+            .in_set(WarpingSet::PerformWarp)
+            .run_if(in_state(GameState::Playing)),
+    )
+    .add_systems(
+        SAVING_SCHEDULE,
+        on_jump_gate_station_destroyed
+            .before(SavingSystemSet::BeginSaving)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
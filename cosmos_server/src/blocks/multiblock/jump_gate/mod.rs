@@ -0,0 +1,249 @@
+//! The jump gate multiblock logic
+
+use std::{ffi::OsStr, fs};
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use cosmos_core::{
+    block::multiblock::prelude::{JumpGate, JumpGateId},
+    ecs::sets::FixedUpdateSet,
+    entities::EntityId,
+    netty::cosmos_encoder,
+    physics::location::{Location, Sector},
+    state::GameState,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::{
+    NORMAL_ENTITY_EXTENSION, SaveFileIdentifier, WorldRoot,
+    make_persistent::{DefaultPersistentComponent, make_persistent},
+};
+
+mod impls;
+
+impl DefaultPersistentComponent for JumpGate {}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+/// The station a [`JumpGate`] is built on
+pub struct JumpGateStation {
+    /// The station's entity id
+    pub entity_id: EntityId,
+    /// The sector the station is saved in
+    pub sector: Sector,
+}
+
+impl JumpGateStation {
+    /// Checks if this station has a save file in the world
+    fn is_saved(&self, world_root: &WorldRoot) -> bool {
+        let Ok(entries) = fs::read_dir(world_root.path_for(&SaveFileIdentifier::get_sector_path(self.sector))) else {
+            return false;
+        };
+
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension() == Some(OsStr::new(NORMAL_ENTITY_EXTENSION)))
+            .filter_map(|path| SaveFileIdentifier::from_base_save_file_name(self.sector, path.file_stem()?.to_str()?))
+            .any(|sfi| sfi.entity_id() == Some(&self.entity_id))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+struct JumpGateRecord {
+    location: Location,
+    linked_to: Option<JumpGateId>,
+    /// `None` until the station has an [`EntityId`], or for gates added before their stations were recorded
+    station: Option<JumpGateStation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// The format of [`JumpGateRecord`] before it stored the gate's station
+struct JumpGateRecordOld {
+    location: Location,
+    linked_to: Option<JumpGateId>,
+}
+
+#[derive(Resource, Debug, Serialize, Deserialize, Default)]
+/// Every [`JumpGate`] in the universe and what it is linked to.
+///
+/// This is kept separately from the gates themselves so ships can be sent to gates that are not loaded.
+pub struct JumpGateNetwork(HashMap<JumpGateId, JumpGateRecord>);
+
+impl JumpGateNetwork {
+    /// Adds this gate to the network, or updates its location if it is already a part of it.
+    ///
+    /// The gate's station is only updated if it is known, since stations that have never been saved have no [`EntityId`].
+    pub fn set_location(&mut self, gate: JumpGateId, station: Option<JumpGateStation>, location: Location) {
+        self.0
+            .entry(gate)
+            .and_modify(|record| {
+                record.location = location;
+                record.station = station.or(record.station);
+            })
+            .or_insert(JumpGateRecord {
+                location,
+                linked_to: None,
+                station,
+            });
+    }
+
+    /// Returns the location of the gate, if it is a part of the network
+    pub fn location(&self, gate: JumpGateId) -> Option<Location> {
+        self.0.get(&gate).map(|record| record.location)
+    }
+
+    /// Returns the station the gate is built on, if it is a part of the network and its station is known
+    pub fn station(&self, gate: JumpGateId) -> Option<JumpGateStation> {
+        self.0.get(&gate).and_then(|record| record.station)
+    }
+
+    /// Returns the gate this gate is linked to, if any
+    pub fn linked_to(&self, gate: JumpGateId) -> Option<JumpGateId> {
+        self.0.get(&gate).and_then(|record| record.linked_to)
+    }
+
+    /// Links these two gates to each other, unlinking any gates they were previously linked to.
+    ///
+    /// Returns false if either gate is not a part of the network.
+    pub fn link(&mut self, a: JumpGateId, b: JumpGateId) -> bool {
+        if a == b || !self.0.contains_key(&a) || !self.0.contains_key(&b) {
+            return false;
+        }
+
+        self.unlink(a);
+        self.unlink(b);
+
+        if let Some(record) = self.0.get_mut(&a) {
+            record.linked_to = Some(b);
+        }
+        if let Some(record) = self.0.get_mut(&b) {
+            record.linked_to = Some(a);
+        }
+
+        true
+    }
+
+    /// Unlinks this gate and whatever gate it was linked to
+    pub fn unlink(&mut self, gate: JumpGateId) {
+        let Some(other) = self.0.get_mut(&gate).and_then(|record| record.linked_to.take()) else {
+            return;
+        };
+
+        if let Some(record) = self.0.get_mut(&other)
+            && record.linked_to == Some(gate)
+        {
+            record.linked_to = None;
+        }
+    }
+
+    /// Removes this gate from the network, unlinking it from whatever it was linked to
+    pub fn remove(&mut self, gate: JumpGateId) {
+        self.unlink(gate);
+        self.0.remove(&gate);
+    }
+
+    /// Removes every gate whose station no longer exists, such as ones destroyed while the
+    /// network wasn't being kept up to date.
+    fn remove_missing_stations(&mut self, world_root: &WorldRoot) {
+        let missing = self
+            .0
+            .iter()
+            .filter(|(_, record)| record.station.is_some_and(|station| !station.is_saved(world_root)))
+            .map(|(&gate, _)| gate)
+            .collect::<Vec<_>>();
+
+        for gate in missing {
+            info!("Removing jump gate {gate:?} from the network, since its station no longer exists.");
+            self.remove(gate);
+        }
+    }
+}
+
+const JUMP_GATES_FILE: &str = "jump_gates.bin";
+
+fn load_jump_gate_network(mut commands: Commands, world_root: Res<WorldRoot>) {
+    let path = world_root.path_for(JUMP_GATES_FILE);
+    let mut network = if let Ok(data) = fs::read(&path) {
+        cosmos_encoder::deserialize::<JumpGateNetwork>(&data).unwrap_or_else(|_| {
+            let old = cosmos_encoder::deserialize::<HashMap<JumpGateId, JumpGateRecordOld>>(&data)
+                .unwrap_or_else(|e| panic!("Failed to deserialize jump gates in {path}. {e:?}"));
+
+            info!("Found jump gates in an old format - updating to the new format.");
+            JumpGateNetwork(
+                old.into_iter()
+                    .map(|(gate, record)| {
+                        (
+                            gate,
+                            JumpGateRecord {
+                                location: record.location,
+                                linked_to: record.linked_to,
+                                station: None,
+                            },
+                        )
+                    })
+                    .collect(),
+            )
+        })
+    } else {
+        JumpGateNetwork::default()
+    };
+
+    network.remove_missing_stations(&world_root);
+
+    commands.insert_resource(network);
+}
+
+fn save_jump_gate_network(network: Res<JumpGateNetwork>, world_root: Res<WorldRoot>) {
+    fs::write(world_root.path_for(JUMP_GATES_FILE), cosmos_encoder::serialize(network.as_ref())).expect("Failed to save jump gates.");
+}
+
+pub(super) fn register(app: &mut App) {
+    impls::register(app);
+
+    make_persistent::<JumpGate>(app);
+
+    app.add_systems(OnEnter(GameState::PostLoading), load_jump_gate_network)
+        .add_systems(
+            FixedUpdate,
+            save_jump_gate_network
+                .run_if(resource_changed::<JumpGateNetwork>)
+                .in_set(FixedUpdateSet::Main)
+                .run_if(in_state(GameState::Playing)),
+        );
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::{
+        block::multiblock::prelude::JumpGateId,
+        entities::EntityId,
+        physics::location::{Location, Sector},
+    };
+
+    use super::{JumpGateNetwork, JumpGateStation};
+
+    #[test]
+    fn linking_replaces_old_links() {
+        let (a, b, c) = (JumpGateId::generate_new(), JumpGateId::generate_new(), JumpGateId::generate_new());
+        let station = JumpGateStation {
+            entity_id: EntityId::generate(),
+            sector: Sector::default(),
+        };
+
+        let mut network = JumpGateNetwork::default();
+        for gate in [a, b, c] {
+            network.set_location(gate, Some(station), Location::default());
+        }
+
+        assert!(network.link(a, b));
+        assert_eq!(network.linked_to(a), Some(b));
+        assert_eq!(network.linked_to(b), Some(a));
+
+        assert!(network.link(a, c));
+        assert_eq!(network.linked_to(a), Some(c));
+        assert_eq!(network.linked_to(b), None);
+
+        network.remove(c);
+        assert_eq!(network.linked_to(a), None);
+        assert!(!network.link(a, c));
+    }
+}
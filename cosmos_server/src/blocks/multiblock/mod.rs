@@ -2,10 +2,12 @@
 
 use bevy::prelude::App;
 
+pub mod jump_gate;
 pub mod reactor;
 pub mod shipyard;
 
 pub(super) fn register(app: &mut App) {
+    jump_gate::register(app);
     reactor::register(app);
     shipyard::register(app);
 }
@@ -136,7 +136,8 @@ fn generate_power(
                 );
             }
 
-            system.increase_energy(reactor.power_per_second() * delta);
+            // A reactor only powers the grid its controller is wired into
+            system.increase_energy_at(reactor.controller_block(), reactor.power_per_second() * delta);
         }
     }
 }
@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use bevy::{ecs::lifecycle::HookContext, platform::collections::HashMap, prelude::*, time::common_conditions::on_timer};
 use bevy_rapier3d::{
-    plugin::{RapierContextEntityLink, ReadRapierContext},
+    plugin::{RapierContext, RapierContextEntityLink, ReadRapierContext},
     prelude::{QueryFilter, RigidBody, Velocity},
 };
 use cosmos_core::{
@@ -16,13 +16,13 @@ use cosmos_core::{
     },
     blockitems::BlockItems,
     ecs::{NeedsDespawned, sets::FixedUpdateSet},
-    entities::player::Player,
+    entities::{EntityId, player::Player},
     events::cancellable::Cancellable,
     events::{
         block_events::{BlockChangedMessage, BlockChangedReason},
         structure::structure_event::StructureMessageIterator,
     },
-    inventory::Inventory,
+    inventory::{Inventory, itemstack::ItemShouldHaveData},
     item::{Item, usable::blueprint::BlueprintItemData},
     netty::{
         server::ServerLobby,
@@ -33,16 +33,24 @@ use cosmos_core::{
         location::{Location, SetPosition},
         structure_physics::ChunkPhysicsPart,
     },
-    prelude::{BlockCoordinate, ChunkCoordinate, FullStructure, Ship, Structure, StructureLoadingSet, StructureTypeSet},
+    prelude::{
+        BlockCoordinate, ChunkCoordinate, FullStructure, Ship, Structure, StructureLoadingSet, StructureTypeSet, UnboundBlockCoordinate,
+    },
     registry::{Registry, identifiable::Identifiable},
+    structure::{block_health::events::BlockRepairedMessage, chunk::BlockInfo, ownership::StructureOwner},
 };
 use derive_more::{Display, Error};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    blocks::multiblock::shipyard::StructureBeingBuilt,
+    blocks::multiblock::shipyard::{RepairSnapshot, StructureBeingBuilt},
     persistence::loading::load_blueprint,
-    structure::{persistence::load_structure_with_palette, ship::loading::ShipNeedsCreated},
+    structure::{
+        block_health::repair::{missing_health, repair_block},
+        ownership::{StructurePermissions, player_entity_id},
+        persistence::load_structure_with_palette,
+        ship::loading::ShipNeedsCreated,
+    },
 };
 
 fn on_place_blocks_impacting_shipyard(
@@ -250,11 +258,12 @@ fn on_set_blueprint(
     mut q_block_data: Query<&mut BlockData>,
     q_has_shipyard_data: Query<(), With<ShipyardState>>,
     mut q_inventory: Query<&mut Inventory, With<BlockData>>,
-    (q_player_inventory, q_blueprint_item_data, q_shipyard, q_chunk_collider): (
+    (q_player_inventory, q_blueprint_item_data, q_shipyard, q_chunk_collider, q_player_id): (
         Query<&Inventory, (With<Player>, Without<BlockData>)>,
         Query<&BlueprintItemData>,
         Query<&Shipyard, Without<ShipyardState>>,
         Query<&ChunkPhysicsPart>,
+        Query<Option<&EntityId>, With<Player>>,
     ),
     mut commands: Commands,
     mut block_data_commands: Commands,
@@ -419,6 +428,13 @@ fn on_set_blueprint(
         let structure = Structure::Full(FullStructure::new(ChunkCoordinate::new(10, 10, 10)));
         let ship = Ship::new_for_structure(&structure);
 
+        let owner = players.player_from_id(ev.client_id).and_then(|player| {
+            q_player_id
+                .get(player)
+                .ok()
+                .map(|entity_id| StructureOwner(player_entity_id(player, entity_id, &mut commands)))
+        });
+
         let entity = commands
             .spawn((
                 Name::new("Ship being built"),
@@ -437,6 +453,10 @@ fn on_set_blueprint(
             ))
             .id();
 
+        if let Some(owner) = owner {
+            commands.entity(entity).insert(owner);
+        }
+
         shipyard_structure.insert_block_data(
             ev.shipyard_block.coords(),
             ShipyardState::Building(ShipyardDoingBlueprint {
@@ -451,6 +471,278 @@ fn on_set_blueprint(
     }
 }
 
+/// Finds every block on a ship that doesn't match `target`, and how many of each block that is.
+///
+/// `current` returns the block id at those coordinates on the ship and if that block is damaged, or `None`
+/// if the coordinates aren't on the ship. The ship core is never replaced.
+fn compute_repairs(
+    target: impl Iterator<Item = (BlockCoordinate, u16, BlockInfo)>,
+    ship_core_id: u16,
+    current: impl Fn(BlockCoordinate) -> Option<(u16, bool)>,
+) -> (Vec<(BlockCoordinate, u16, BlockInfo)>, HashMap<u16, u32>) {
+    let mut totals_count = HashMap::default();
+
+    let blocks_todo = target
+        .filter(|&(coords, id, _)| {
+            if id == ship_core_id {
+                return false;
+            }
+
+            match current(coords) {
+                Some((cur_id, damaged)) => cur_id != ship_core_id && (cur_id != id || damaged),
+                None => false,
+            }
+        })
+        .inspect(|&(_, id, _)| *totals_count.entry(id).or_default() += 1)
+        .collect::<Vec<_>>();
+
+    (blocks_todo, totals_count)
+}
+
+/// Finds the ship sitting inside of this shipyard, if there is one that `is_ship` accepts
+fn find_ship_in_shipyard(
+    shipyard: &Shipyard,
+    shipyard_structure: &Structure,
+    station_g_trans: &GlobalTransform,
+    context: &RapierContext,
+    q_chunk_collider: &Query<&ChunkPhysicsPart>,
+    is_ship: impl Fn(Entity) -> bool,
+) -> Option<Entity> {
+    let structure_ent = shipyard_structure.get_entity()?;
+
+    let half_size = shipyard.bounds().size();
+    let half_size = Vec3::new(half_size.x as f32 / 2.0, half_size.y as f32 / 2.0, half_size.z as f32 / 2.0);
+
+    let shipyard_world_pos = station_g_trans.translation()
+        + station_g_trans.rotation() * (shipyard_structure.block_relative_position(shipyard.bounds().negative_coords) + half_size);
+
+    let mut ship_ent = None;
+
+    context.intersect_shape(
+        shipyard_world_pos,
+        station_g_trans.rotation(),
+        &bevy_rapier3d::parry::shape::Cuboid::new(half_size.into()),
+        QueryFilter {
+            exclude_rigid_body: Some(structure_ent),
+            ..Default::default()
+        },
+        |e| {
+            if let Ok(c) = q_chunk_collider.get(e)
+                && c.structure_entity != structure_ent
+                && is_ship(c.structure_entity)
+            {
+                ship_ent = Some(c.structure_entity);
+                return false;
+            }
+
+            true
+        },
+    );
+
+    ship_ent
+}
+
+fn on_repair_ship(
+    players: Res<ServerLobby>,
+    blocks: Res<Registry<Block>>,
+    mut nevr_repair_ship: MessageReader<NettyMessageReceived<RepairShipyardShip>>,
+    mut q_structure: Query<(&GlobalTransform, &mut Structure, &RapierContextEntityLink)>,
+    mut q_block_data: Query<&mut BlockData>,
+    q_has_shipyard_data: Query<(), With<ShipyardState>>,
+    (q_player_inventory, q_blueprint_item_data, q_shipyard, q_chunk_collider, q_ship): (
+        Query<&Inventory, (With<Player>, Without<BlockData>)>,
+        Query<&BlueprintItemData>,
+        Query<&Shipyard, Without<ShipyardState>>,
+        Query<&ChunkPhysicsPart>,
+        Query<(&Ship, Option<&RepairSnapshot>), Without<StructureBeingBuilt>>,
+    ),
+    mut commands: Commands,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    read_context: ReadRapierContext,
+    permissions: StructurePermissions,
+) {
+    for ev in nevr_repair_ship.read() {
+        let Some(player) = players.player_from_id(ev.client_id) else {
+            continue;
+        };
+
+        let structure_ent = ev.shipyard_block.structure();
+        let Ok((station_g_trans, shipyard_structure, world)) = q_structure.get(structure_ent) else {
+            continue;
+        };
+        let Some(shipyard) = shipyard_structure.query_block_data(ev.shipyard_block.coords(), &q_shipyard) else {
+            nevw_notification.write(Notification::error("This shipyard is already working!"), ev.client_id);
+            continue;
+        };
+
+        let Some(ship_core_block) = blocks.from_id("cosmos:ship_core") else {
+            continue;
+        };
+
+        let context = read_context.get(*world);
+
+        let Some(ship_ent) = find_ship_in_shipyard(shipyard, shipyard_structure, station_g_trans, &context, &q_chunk_collider, |e| {
+            q_ship.contains(e)
+        }) else {
+            nevw_notification.write(Notification::error("There is no ship in this shipyard to repair!"), ev.client_id);
+            continue;
+        };
+
+        let Ok((ship, snapshot)) = q_ship.get(ship_ent) else {
+            continue;
+        };
+
+        // Repairing replaces blocks on the ship using the shipyard's materials, so the player needs to be able to build on both
+        if let Err(denied) = permissions
+            .check_build(player, structure_ent)
+            .and_then(|_| permissions.check_build(player, ship_ent))
+        {
+            nevw_notification.write(Notification::error(denied.message()), ev.client_id);
+            continue;
+        }
+
+        let Ok((_, ship_structure, _)) = q_structure.get(ship_ent) else {
+            continue;
+        };
+
+        let target = match ev.blueprint_slot {
+            Some(slot) => {
+                let Some(data) = q_player_inventory
+                    .get(player)
+                    .ok()
+                    .filter(|i| i.len() > slot as usize)
+                    .and_then(|i| i.query_itemstack_data(slot as usize, &q_blueprint_item_data))
+                else {
+                    error!("Invalid slot - {slot}");
+                    continue;
+                };
+
+                let path = data.get_blueprint_path();
+                let Some(bp_structure) = load_blueprint(&path)
+                    .ok()
+                    .and_then(|bp| load_structure_with_palette(bp.serialized_data(), &blocks))
+                else {
+                    nevw_notification.write(Notification::error("Invalid blueprint!"), ev.client_id);
+                    continue;
+                };
+
+                bp_structure
+                    .all_blocks_iter(false)
+                    .map(|c| (c, bp_structure.block_id_at(c), bp_structure.block_info_at(c)))
+                    .collect::<Vec<_>>()
+            }
+            None => {
+                let Some(snapshot) = snapshot else {
+                    nevw_notification.write(
+                        Notification::error(
+                            "This ship isn't registered for repairs - register it first, or choose a blueprint to repair it to.",
+                        ),
+                        ev.client_id,
+                    );
+                    continue;
+                };
+
+                snapshot.blocks(&blocks)
+            }
+        };
+
+        // Line the target's ship core up with the ship's, since blueprints can come from differently sized structures
+        let Some(target_core) = target.iter().find(|(_, id, _)| *id == ship_core_block.id()).map(|(c, _, _)| *c) else {
+            nevw_notification.write(Notification::error("Invalid blueprint!"), ev.client_id);
+            continue;
+        };
+        let offset = UnboundBlockCoordinate::from(ship.ship_core_block_coords(ship_structure)) - UnboundBlockCoordinate::from(target_core);
+
+        let (blocks_todo, totals_count) = compute_repairs(
+            target
+                .into_iter()
+                .flat_map(|(c, id, info)| BlockCoordinate::try_from(UnboundBlockCoordinate::from(c) + offset).map(|c| (c, id, info))),
+            ship_core_block.id(),
+            |c| {
+                ship_structure.is_within_blocks(c).then(|| {
+                    let block = ship_structure.block_at(c, &blocks);
+                    (block.id(), ship_structure.get_block_health(c, &blocks) < block.hardness())
+                })
+            },
+        );
+
+        if blocks_todo.is_empty() {
+            nevw_notification.write(Notification::info("This ship doesn't need any repairs."), ev.client_id);
+            continue;
+        }
+
+        commands.entity(ship_ent).insert(StructureBeingBuilt);
+
+        let Ok((_, mut shipyard_structure, _)) = q_structure.get_mut(structure_ent) else {
+            continue;
+        };
+
+        shipyard_structure.insert_block_data(
+            ev.shipyard_block.coords(),
+            ShipyardState::Repairing(ShipyardDoingBlueprint {
+                blocks_todo,
+                total_blocks_count: totals_count,
+                creating: ship_ent,
+            }),
+            &mut commands,
+            &mut q_block_data,
+            &q_has_shipyard_data,
+        );
+    }
+}
+
+/// Remembers what the ship in a shipyard currently looks like, so it can be repaired back to that later.
+fn on_register_ship(
+    players: Res<ServerLobby>,
+    blocks: Res<Registry<Block>>,
+    mut nevr_register_ship: MessageReader<NettyMessageReceived<RegisterShipyardShip>>,
+    q_structure: Query<(&GlobalTransform, &Structure, &RapierContextEntityLink)>,
+    q_shipyard: Query<&Shipyard>,
+    q_chunk_collider: Query<&ChunkPhysicsPart>,
+    q_ship: Query<(), (With<Ship>, Without<StructureBeingBuilt>)>,
+    mut commands: Commands,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    read_context: ReadRapierContext,
+    permissions: StructurePermissions,
+) {
+    for ev in nevr_register_ship.read() {
+        let Some(player) = players.player_from_id(ev.client_id) else {
+            continue;
+        };
+
+        let Ok((station_g_trans, shipyard_structure, world)) = q_structure.get(ev.shipyard_block.structure()) else {
+            continue;
+        };
+        let Some(shipyard) = shipyard_structure.query_block_data(ev.shipyard_block.coords(), &q_shipyard) else {
+            continue;
+        };
+
+        let context = read_context.get(*world);
+
+        let Some(ship_ent) = find_ship_in_shipyard(shipyard, shipyard_structure, station_g_trans, &context, &q_chunk_collider, |e| {
+            q_ship.contains(e)
+        }) else {
+            nevw_notification.write(Notification::error("There is no ship in this shipyard to register!"), ev.client_id);
+            continue;
+        };
+
+        if let Err(denied) = permissions.check_build(player, ship_ent) {
+            nevw_notification.write(Notification::error(denied.message()), ev.client_id);
+            continue;
+        }
+
+        let Ok((_, ship_structure, _)) = q_structure.get(ship_ent) else {
+            continue;
+        };
+
+        commands.entity(ship_ent).insert(RepairSnapshot::new(ship_structure, &blocks));
+        nevw_notification.write(
+            Notification::info("Registered this ship for repairs - it will be repaired back to how it looks now."),
+            ev.client_id,
+        );
+    }
+}
+
 fn dont_move_being_built(q_being_built: Query<Entity, Added<StructureBeingBuilt>>, mut commands: Commands) {
     for ent in q_being_built.iter() {
         commands.entity(ent).insert((RigidBody::KinematicVelocityBased, Velocity::zero()));
@@ -468,6 +760,9 @@ fn manage_shipyards(
     items: Res<Registry<Item>>,
     block_items: Res<BlockItems>,
     mut q_inventory: Query<&mut Inventory, With<BlockData>>,
+    mut evw_block_repaired: MessageWriter<BlockRepairedMessage>,
+    has_data: Res<ItemShouldHaveData>,
+    q_registered_for_repairs: Query<(), With<RepairSnapshot>>,
 ) {
     for (shipyard_ent, mut state, block_data) in q_shipyard_state.iter_mut() {
         match state.as_mut() {
@@ -544,6 +839,109 @@ fn manage_shipyards(
                     Some((&mut evw_block_change, BlockChangedReason::Entity(shipyard_ent))),
                 );
             }
+            ShipyardState::Repairing(doing_bp) => {
+                let Ok(mut structure) = q_structure.get_mut(doing_bp.creating) else {
+                    continue;
+                };
+
+                let Ok(shipyard_structure) = q_building.get(block_data.structure()) else {
+                    continue;
+                };
+
+                let Some((coords, block, info)) = doing_bp.blocks_todo.pop() else {
+                    info!("Done repairing ship in shipyard!");
+                    commands
+                        .entity(shipyard_ent)
+                        .remove::<ShipyardState>()
+                        .remove::<ClientFriendlyShipyardState>();
+                    commands
+                        .entity(doing_bp.creating)
+                        .remove::<StructureBeingBuilt>()
+                        .insert(RigidBody::Dynamic);
+
+                    // Snapshots are a full copy of the ship's blocks, so only ships registered for repairs keep one
+                    if q_registered_for_repairs.contains(doing_bp.creating) {
+                        commands.entity(doing_bp.creating).insert(RepairSnapshot::new(&structure, &blocks));
+                    }
+                    continue;
+                };
+
+                if let Some(count) = doing_bp.total_blocks_count.get_mut(&block) {
+                    if *count != 0 {
+                        *count -= 1;
+                    }
+                    if *count == 0 {
+                        doing_bp.total_blocks_count.remove(&block);
+                    }
+                }
+
+                if !structure.is_within_blocks(coords) {
+                    continue;
+                }
+
+                let Some(block) = blocks.try_from_numeric_id(block) else {
+                    error!("Missing block id {block}");
+                    continue;
+                };
+
+                if structure.block_id_at(coords) == block.id() {
+                    // The right block is already here, so it only needs its health restored
+                    let missing = missing_health(&structure, coords, &blocks);
+                    if missing > 0.0 {
+                        repair_block(
+                            &mut structure,
+                            coords,
+                            missing,
+                            Some(shipyard_ent),
+                            &blocks,
+                            &mut evw_block_repaired,
+                        );
+                    }
+                    continue;
+                }
+
+                let Some(block_item) = block_items.item_from_block(block).map(|id| items.from_numeric_id(id)) else {
+                    error!("Missing item for block {block:?}");
+                    continue;
+                };
+
+                // The wrong block that is here gets put back into the shipyard's storage, so wait until there's room for it
+                let replaced = Some(structure.block_id_at(coords))
+                    .filter(|&id| id != AIR_BLOCK_ID)
+                    .and_then(|id| block_items.item_from_block(blocks.from_numeric_id(id)))
+                    .map(|id| items.from_numeric_id(id))
+                    .map(|item| (item, storage_with_room(&q_inventory, block_data.coords(), shipyard_structure, item)));
+
+                if matches!(replaced, Some((_, None)))
+                    || !consume_item(
+                        &mut q_inventory,
+                        block_data.coords(),
+                        shipyard_structure,
+                        block_item,
+                        &mut block_data_commands,
+                        &mut commands,
+                    )
+                {
+                    doing_bp.blocks_todo.insert(0, (coords, block.id(), info));
+                    *doing_bp.total_blocks_count.entry(block.id()).or_default() += 1;
+                    continue;
+                }
+
+                if let Some((replaced_item, Some(storage_coords))) = replaced
+                    && let Some(mut inventory) =
+                        shipyard_structure.query_block_data_mut(storage_coords, &mut q_inventory, &mut block_data_commands)
+                {
+                    inventory.insert_item(replaced_item, 1, &mut commands, &has_data);
+                }
+
+                structure.set_block_and_info_at(
+                    coords,
+                    block,
+                    info,
+                    &blocks,
+                    Some((&mut evw_block_change, BlockChangedReason::Entity(shipyard_ent))),
+                );
+            }
             ShipyardState::Deconstructing(ent) => {
                 let Ok(mut structure) = q_structure.get_mut(*ent) else {
                     continue;
@@ -579,7 +977,7 @@ fn add_shipyard_state_hooks(world: &mut World) {
         .on_remove(|mut world, HookContext { entity, .. }| {
             let state = world.get::<ShipyardState>(entity).expect("Impossible to fail");
             match state {
-                ShipyardState::Building(d) | ShipyardState::Paused(d) => {
+                ShipyardState::Building(d) | ShipyardState::Paused(d) | ShipyardState::Repairing(d) => {
                     let creating = d.creating;
                     if let Ok(mut ecmds) = world.commands().get_entity(creating) {
                         ecmds.remove::<StructureBeingBuilt>().insert(RigidBody::Dynamic);
@@ -675,6 +1073,24 @@ fn consume_item(
     false
 }
 
+/// Finds a storage next to the shipyard's controller that has room for one of this item
+fn storage_with_room(
+    q_inventory: &Query<&mut Inventory, With<BlockData>>,
+    center: BlockCoordinate,
+    structure: &Structure,
+    item: &Item,
+) -> Option<BlockCoordinate> {
+    ALL_BLOCK_DIRECTIONS
+        .iter()
+        .flat_map(|dir| BlockCoordinate::try_from(dir.to_coordinates() + center))
+        .filter(|&coord| structure.is_within_blocks(coord))
+        .find(|&coord| {
+            structure
+                .query_block_data(coord, q_inventory)
+                .is_some_and(|inv| inv.can_insert(item, 1))
+        })
+}
+
 pub(super) fn register(app: &mut App) {
     app.add_systems(
         FixedUpdate,
@@ -691,7 +1107,12 @@ pub(super) fn register(app: &mut App) {
     )
     .add_systems(
         FixedUpdate,
-        (manage_shipyards.run_if(on_timer(Duration::from_millis(200))), on_set_blueprint)
+        (
+            manage_shipyards.run_if(on_timer(Duration::from_millis(200))),
+            on_set_blueprint,
+            on_repair_ship,
+            on_register_ship,
+        )
             .chain()
             .in_set(StructureLoadingSet::LoadStructure)
             .in_set(StructureTypeSet::Ship)
@@ -699,3 +1120,45 @@ pub(super) fn register(app: &mut App) {
     )
     .add_systems(Startup, add_shipyard_state_hooks);
 }
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::{prelude::BlockCoordinate, structure::chunk::BlockInfo};
+
+    use super::compute_repairs;
+
+    #[test]
+    fn repairs_missing_wrong_and_damaged_blocks() {
+        const CORE: u16 = 1;
+        const HULL: u16 = 2;
+        const GLASS: u16 = 3;
+
+        let target = vec![
+            (BlockCoordinate::new(0, 0, 0), CORE, BlockInfo::default()),
+            // Intact
+            (BlockCoordinate::new(1, 0, 0), HULL, BlockInfo::default()),
+            // Missing
+            (BlockCoordinate::new(2, 0, 0), HULL, BlockInfo::default()),
+            // Wrong block
+            (BlockCoordinate::new(3, 0, 0), GLASS, BlockInfo::default()),
+            // Damaged
+            (BlockCoordinate::new(4, 0, 0), HULL, BlockInfo::default()),
+            // Off of the ship
+            (BlockCoordinate::new(100, 0, 0), HULL, BlockInfo::default()),
+        ];
+
+        let (todo, counts) = compute_repairs(target.into_iter(), CORE, |c| match c.x {
+            0 => Some((CORE, false)),
+            1 => Some((HULL, false)),
+            2 => Some((0, false)),
+            3 => Some((HULL, false)),
+            4 => Some((HULL, true)),
+            _ => None,
+        });
+
+        assert_eq!(todo.iter().map(|(c, _, _)| c.x).collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(counts.get(&HULL), Some(&2));
+        assert_eq!(counts.get(&GLASS), Some(&1));
+        assert_eq!(counts.get(&CORE), None);
+    }
+}
@@ -2,11 +2,15 @@
 
 use bevy::{platform::collections::HashMap, prelude::*};
 use cosmos_core::{
-    block::multiblock::prelude::{Shipyard, ShipyardDoingBlueprint, ShipyardState},
+    block::{
+        Block,
+        multiblock::prelude::{Shipyard, ShipyardDoingBlueprint, ShipyardState},
+    },
     entities::EntityId,
     netty::sync::IdentifiableComponent,
-    prelude::BlockCoordinate,
-    structure::chunk::BlockInfo,
+    prelude::{BlockCoordinate, Structure},
+    registry::{Registry, identifiable::Identifiable},
+    structure::{chunk::BlockInfo, persistence::palette::Palette},
 };
 use serde::{Deserialize, Serialize};
 
@@ -25,6 +29,58 @@ impl IdentifiableComponent for StructureBeingBuilt {
 }
 
 impl DefaultPersistentComponent for StructureBeingBuilt {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Default)]
+/// The blocks a ship had when it was last registered for repairs at a shipyard, or last finished being repaired by one.
+///
+/// Shipyards repair ships back to this if no blueprint is chosen. This is a full copy of the ship's blocks, so only
+/// ships a player has registered for repairs have one.
+pub struct RepairSnapshot {
+    /// Maps the block ids below to their unlocalized names, since numeric block ids can change between runs
+    palette: Palette<Block>,
+    /// Every block the ship had (coordinate, block id, block info)
+    blocks: Vec<(BlockCoordinate, u16, BlockInfo)>,
+}
+
+impl RepairSnapshot {
+    /// Records every block currently on this structure
+    pub fn new(structure: &Structure, blocks: &Registry<Block>) -> Self {
+        Self {
+            palette: Palette::new_from_structure(structure, blocks),
+            blocks: structure
+                .all_blocks_iter(false)
+                .map(|c| (c, structure.block_id_at(c), structure.block_info_at(c)))
+                .collect(),
+        }
+    }
+
+    /// Every block the ship had (coordinate, block id, block info), using the current block ids.
+    ///
+    /// Blocks that no longer exist are skipped.
+    pub fn blocks(&self, blocks: &Registry<Block>) -> Vec<(BlockCoordinate, u16, BlockInfo)> {
+        let mut current_ids = HashMap::<u16, Option<u16>>::default();
+
+        self.blocks
+            .iter()
+            .filter_map(|&(coords, id, info)| {
+                let current_id = *current_ids
+                    .entry(id)
+                    .or_insert_with(|| self.palette.get(id).and_then(|name| blocks.from_id(name)).map(|block| block.id()));
+
+                current_id.map(|id| (coords, id, info))
+            })
+            .collect()
+    }
+}
+
+impl IdentifiableComponent for RepairSnapshot {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:repair_snapshot"
+    }
+}
+
+impl DefaultPersistentComponent for RepairSnapshot {}
+
 impl DefaultPersistentComponent for Shipyard {}
 
 #[derive(Debug, Reflect, Serialize, Deserialize)]
@@ -44,6 +100,8 @@ pub enum SerializedShipyardState {
     Building(SerializedShipyardDoingBlueprint),
     /// See [`ShipyardState`]
     Deconstructing(EntityId),
+    /// See [`ShipyardState`]
+    Repairing(SerializedShipyardDoingBlueprint),
 }
 
 impl PersistentComponent for ShipyardState {
@@ -71,6 +129,14 @@ impl PersistentComponent for ShipyardState {
                 })
                 .into()
             }),
+            Self::Repairing(d) => q_entity_ids.get(d.creating).ok().map(|&e| {
+                Self::SaveType::Repairing(SerializedShipyardDoingBlueprint {
+                    blocks_todo: d.blocks_todo.clone(),
+                    total_blocks_count: d.total_blocks_count.clone(),
+                    creating: e,
+                })
+                .into()
+            }),
         }
     }
 
@@ -94,6 +160,13 @@ impl PersistentComponent for ShipyardState {
                     creating: e,
                 })
             }),
+            SerializedShipyardState::Repairing(d) => entity_id_manager.entity_from_entity_id(&d.creating).map(|e| {
+                Self::Repairing(ShipyardDoingBlueprint {
+                    blocks_todo: d.blocks_todo.clone(),
+                    total_blocks_count: d.total_blocks_count.clone(),
+                    creating: e,
+                })
+            }),
         }
     }
 }
@@ -102,6 +175,7 @@ pub(super) fn register(app: &mut App) {
     impls::register(app);
 
     make_persistent::<StructureBeingBuilt>(app);
+    make_persistent::<RepairSnapshot>(app);
     make_persistent::<Shipyard>(app);
     make_persistent::<ShipyardState>(app);
 }
@@ -9,52 +9,55 @@ use cosmos_core::{
     },
     entities::player::Player,
     events::cancellable::{Cancellable, CancellableMessage},
-    faction::{FactionId, Factions},
+    faction::FactionPermission,
     netty::sync::events::{netty_event::NettyMessage, server_event::NettyMessageWriter},
     registry::{Registry, identifiable::Identifiable},
     state::GameState,
-    structure::{Structure, shared::MeltingDown, ship::Ship},
+    structure::{Structure, ownership::TrustLevel, ship::Ship},
 };
 
+use crate::structure::ownership::{PermissionDenied, StructurePermissions};
+
+/// Cancels the event if the player is not allowed to do this to the structure.
+///
+/// See [`StructurePermissions::check`] for who is allowed.
 fn maybe_cancel_faction<E: NettyMessage>(
     event: &mut impl CancellableMessage,
     player: Entity,
     structure: Entity,
-    q_factions: &Query<&FactionId>,
-    q_melting_down: &Query<(), With<MeltingDown>>,
+    permission: Option<FactionPermission>,
+    trust_level: TrustLevel,
+    permissions: &StructurePermissions,
     q_player: &Query<&Player>,
-    factions: &Factions,
     mw: &mut NettyMessageWriter<E>,
-    e: E,
+    different_faction: E,
+    insufficient_rank: E,
+    not_trusted: E,
 ) {
-    // we can break/place on melting down structures
-    if q_melting_down.contains(structure) {
+    let Err(denied) = permissions.check(player, structure, permission, trust_level) else {
         return;
-    }
+    };
 
-    if let Some(broken_fac) = q_factions.get(structure).ok().and_then(|id| factions.from_id(id))
-        && q_factions
-            .get(player)
-            .ok()
-            .and_then(|id| factions.from_id(id))
-            .map(|fac| fac.id() != broken_fac.id())
-            .unwrap_or(true)
-    {
-        if let Ok(player) = q_player.get(player) {
-            mw.write(e, player.client_id());
-        }
-        event.cancel();
+    let e = match denied {
+        PermissionDenied::DifferentFaction => different_faction,
+        PermissionDenied::InsufficientFactionRank => insufficient_rank,
+        PermissionDenied::NotTrusted => not_trusted,
     };
+
+    if let Ok(player) = q_player.get(player) {
+        mw.write(e, player.client_id());
+    }
+    event.cancel();
 }
 
 fn handle_placing_different_factions(
     mut evr_place: MessageMutator<Cancellable<BlockPlaceMessage>>,
     mut evr_break: MessageMutator<Cancellable<BlockBreakMessage>>,
     mut evr_interact: MessageMutator<Cancellable<BlockInteractMessage>>,
-    q_melting_down: Query<(), With<MeltingDown>>,
+    permissions: StructurePermissions,
     q_player: Query<&Player>,
-    q_faction: Query<&FactionId>,
-    factions: Res<Factions>,
+    q_structure: Query<&Structure>,
+    blocks: Res<Registry<Block>>,
     mut nevw_invalid_place: NettyMessageWriter<InvalidBlockPlaceMessageReason>,
     mut nevw_invalid_break: NettyMessageWriter<InvalidBlockBreakMessageReason>,
     mut nevw_invalid_interact: NettyMessageWriter<InvalidBlockInteractMessageReason>,
@@ -71,12 +74,14 @@ fn handle_placing_different_factions(
             place_event,
             player,
             structure,
-            &q_faction,
-            &q_melting_down,
+            Some(FactionPermission::BuildOnStructures),
+            TrustLevel::Build,
+            &permissions,
             &q_player,
-            &factions,
             &mut nevw_invalid_place,
             InvalidBlockPlaceMessageReason::DifferentFaction,
+            InvalidBlockPlaceMessageReason::InsufficientFactionRank,
+            InvalidBlockPlaceMessageReason::NotTrusted,
         );
     }
 
@@ -92,12 +97,14 @@ fn handle_placing_different_factions(
             break_event,
             player,
             structure,
-            &q_faction,
-            &q_melting_down,
+            Some(FactionPermission::BuildOnStructures),
+            TrustLevel::Build,
+            &permissions,
             &q_player,
-            &factions,
             &mut nevw_invalid_break,
             InvalidBlockBreakMessageReason::DifferentFaction,
+            InvalidBlockBreakMessageReason::InsufficientFactionRank,
+            InvalidBlockBreakMessageReason::NotTrusted,
         );
     }
 
@@ -112,16 +119,24 @@ fn handle_placing_different_factions(
             .unwrap_or(interact_event_data.block_including_fluids)
             .structure();
 
+        let opens_storage = interact_event_data.block.is_some_and(|block| {
+            q_structure
+                .get(block.structure())
+                .is_ok_and(|s| s.block_at(block.coords(), &blocks).unlocalized_name() == "cosmos:storage")
+        });
+
         maybe_cancel_faction(
             interact_event,
             player,
             structure,
-            &q_faction,
-            &q_melting_down,
+            opens_storage.then_some(FactionPermission::AccessStorage),
+            TrustLevel::Interact,
+            &permissions,
             &q_player,
-            &factions,
             &mut nevw_invalid_interact,
             InvalidBlockInteractMessageReason::DifferentFaction,
+            InvalidBlockInteractMessageReason::InsufficientFactionRank,
+            InvalidBlockInteractMessageReason::NotTrusted,
         );
     }
 }
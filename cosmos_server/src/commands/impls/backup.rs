@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+
+use crate::{
+    commands::SendCommandMessageMessage,
+    persistence::{
+        WorldRoot,
+        backup::{CreateWorldBackup, request_restore_on_next_start, restorable_backups},
+    },
+    server::stop::{StopServerMessage, StopServerSet},
+};
+
+use super::super::prelude::*;
+
+struct BackupCommand;
+
+impl CosmosCommandType for BackupCommand {
+    fn from_input(input: &CosmosCommandSent) -> Result<Self, ArgumentError> {
+        if !input.args.is_empty() {
+            return Err(ArgumentError::TooManyArguments);
+        }
+
+        Ok(BackupCommand)
+    }
+}
+
+fn send_create_backup(
+    mut evw_create_backup: MessageWriter<CreateWorldBackup>,
+    mut evr_command: MessageReader<CommandMessage<BackupCommand>>,
+    mut evw_send_message: MessageWriter<SendCommandMessageMessage>,
+) {
+    let Some(ev) = evr_command.read().next() else {
+        return;
+    };
+
+    evw_create_backup.write_default();
+    ev.sender.write("Backing up world", &mut evw_send_message);
+}
+
+struct RestoreCommand(Option<String>);
+
+impl CosmosCommandType for RestoreCommand {
+    fn from_input(input: &CosmosCommandSent) -> Result<Self, ArgumentError> {
+        if input.args.len() > 1 {
+            return Err(ArgumentError::TooManyArguments);
+        }
+
+        Ok(RestoreCommand(input.args.first().cloned()))
+    }
+}
+
+fn restore_backup(
+    world_root: Res<WorldRoot>,
+    mut evw_stop_server: MessageWriter<StopServerMessage>,
+    mut evr_command: MessageReader<CommandMessage<RestoreCommand>>,
+    mut evw_send_message: MessageWriter<SendCommandMessageMessage>,
+) {
+    let Some(ev) = evr_command.read().next() else {
+        return;
+    };
+
+    let backups = restorable_backups(&world_root);
+
+    let Some(backup) = &ev.command.0 else {
+        if backups.is_empty() {
+            ev.sender.write("There are no backups to restore.", &mut evw_send_message);
+        } else {
+            ev.sender.write("Backups:", &mut evw_send_message);
+            for backup in backups {
+                ev.sender.write(format!("\t{backup}"), &mut evw_send_message);
+            }
+        }
+        return;
+    };
+
+    if !backups.contains(backup) {
+        ev.sender.write(
+            format!("No backup `{backup}` exists. Use `restore` to list them."),
+            &mut evw_send_message,
+        );
+        return;
+    }
+
+    if let Err(e) = request_restore_on_next_start(&world_root, backup) {
+        ev.sender.write(format!("Unable to restore backup - {e:?}"), &mut evw_send_message);
+        return;
+    }
+
+    // The world can't be restored while entities from it are loaded, so this is done when the server next starts.
+    evw_stop_server.write_default();
+    ev.sender.write(
+        format!("Stopping the server. Backup {backup} will be restored when the server is next started."),
+        &mut evw_send_message,
+    );
+}
+
+pub(super) fn register(app: &mut App) {
+    create_cosmos_command::<BackupCommand, _>(
+        ServerCommand::new(
+            "cosmos:backup",
+            "",
+            "Backs up the saved world. This does not save the world first - use `save` for that.",
+        ),
+        app,
+        send_create_backup,
+    );
+
+    create_cosmos_command::<RestoreCommand, _>(
+        ServerCommand::new(
+            "cosmos:restore",
+            "(backup)",
+            "Lists every backup, or stops the server and restores the world to this backup when it next starts.",
+        ),
+        app,
+        restore_backup.before(StopServerSet::Stop),
+    );
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use cosmos_core::state::GameState;
 
+mod backup;
 mod ban;
 mod blueprint;
 mod blueprints;
@@ -21,6 +22,7 @@ mod say;
 mod spawn;
 mod stop;
 mod tp;
+mod trust;
 mod unban;
 mod whitelist;
 
@@ -49,6 +51,7 @@ pub(super) fn register(app: &mut App) {
     save::register(app);
     spawn::register(app);
     tp::register(app);
+    trust::register(app);
     kill::register(app);
     panic::register(app);
     whitelist::register(app);
@@ -56,4 +59,5 @@ pub(super) fn register(app: &mut App) {
     kick::register(app);
     unban::register(app);
     resave_all_bps::register(app);
+    backup::register(app);
 }
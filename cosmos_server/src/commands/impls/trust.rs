@@ -0,0 +1,128 @@
+use crate::{
+    commands::{CommandSender, SendCommandMessageMessage},
+    structure::ownership::{SetTrustError, TrustStructureQuery, set_structure_trust},
+};
+
+use super::super::prelude::*;
+use bevy::prelude::*;
+use cosmos_core::{
+    entities::{EntityId, player::Player},
+    faction::{FactionId, Factions},
+    structure::{Structure, ownership::TrustLevel, ship::pilot::Pilot},
+};
+
+struct TrustCommand {
+    player: String,
+    level: Option<TrustLevel>,
+}
+
+impl CosmosCommandType for TrustCommand {
+    fn from_input(ev: &CosmosCommandSent) -> Result<Self, ArgumentError> {
+        if ev.args.len() > 2 {
+            return Err(ArgumentError::TooManyArguments);
+        }
+
+        if ev.args.len() != 2 {
+            return Err(ArgumentError::TooFewArguments);
+        }
+
+        let level = match ev.args[1].to_lowercase().as_str() {
+            "interact" => Some(TrustLevel::Interact),
+            "build" => Some(TrustLevel::Build),
+            "pilot" => Some(TrustLevel::Pilot),
+            "none" => None,
+            _ => {
+                return Err(ArgumentError::InvalidType {
+                    arg_index: 1,
+                    type_name: "interact/build/pilot/none".into(),
+                });
+            }
+        };
+
+        Ok(TrustCommand {
+            player: ev.args[0].clone(),
+            level,
+        })
+    }
+
+    fn requires_operator() -> bool {
+        false
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    create_cosmos_command::<TrustCommand, _>(
+        ServerCommand::new(
+            "cosmos:trust",
+            "[player] [interact/build/pilot/none]",
+            "Sets how much this player is trusted with the structure you are piloting or standing on",
+        ),
+        app,
+        |mut evw_send_message: MessageWriter<SendCommandMessageMessage>,
+         mut evr_command: MessageReader<CommandMessage<TrustCommand>>,
+         q_players: Query<(&Player, &EntityId)>,
+         q_player: Query<(&EntityId, Option<&FactionId>), With<Player>>,
+         q_pilot: Query<&Pilot, With<Player>>,
+         q_parent: Query<&ChildOf, With<Player>>,
+         q_is_structure: Query<(), With<Structure>>,
+         mut q_structure: TrustStructureQuery,
+         factions: Res<Factions>,
+         mut commands: Commands| {
+            for ev in evr_command.read() {
+                let CommandSender::Player(sender) = ev.sender else {
+                    ev.sender
+                        .write("Only players can trust others with structures.", &mut evw_send_message);
+                    continue;
+                };
+
+                let Some(structure) = q_pilot
+                    .get(sender)
+                    .map(|pilot| pilot.entity)
+                    .ok()
+                    .or_else(|| q_parent.get(sender).ok().map(|parent| parent.parent()))
+                    .filter(|e| q_is_structure.contains(*e))
+                else {
+                    ev.sender
+                        .write("You must be piloting or standing on a structure.", &mut evw_send_message);
+                    continue;
+                };
+
+                let Some((player, player_id)) = q_players.iter().find(|(p, _)| p.name() == ev.command.player) else {
+                    ev.sender
+                        .write(format!("Unable to find player `{}`", ev.command.player), &mut evw_send_message);
+                    continue;
+                };
+
+                match set_structure_trust(
+                    sender,
+                    structure,
+                    *player_id,
+                    ev.command.level,
+                    &mut q_structure,
+                    &q_player,
+                    &factions,
+                    &mut commands,
+                ) {
+                    Ok(()) => match ev.command.level {
+                        Some(level) => ev
+                            .sender
+                            .write(format!("Trusted {} with {level:?} access.", player.name()), &mut evw_send_message),
+                        None => ev
+                            .sender
+                            .write(format!("{} is no longer trusted.", player.name()), &mut evw_send_message),
+                    },
+                    Err(SetTrustError::NotAllowed) => {
+                        ev.sender.write(
+                            "You are not allowed to change who is trusted with this structure.",
+                            &mut evw_send_message,
+                        );
+                    }
+                    Err(SetTrustError::NotAStructure) => {
+                        ev.sender
+                            .write("You must be piloting or standing on a structure.", &mut evw_send_message);
+                    }
+                }
+            }
+        },
+    );
+}
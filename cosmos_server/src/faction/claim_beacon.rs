@@ -0,0 +1,301 @@
+//! Claim beacons let player factions claim the system their station is in.
+//!
+//! A powered claim beacon on a station belonging to a faction claims the station's system for that faction
+//! once it has been held for [`CLAIM_HOLD_SECS`]. Systems claimed by an enemy faction can be taken over the same way,
+//! but only while none of the owner's beacons in that system are powered. If beacons of more than one faction
+//! are competing for a system, nobody makes progress until only one remains.
+//!
+//! Destroying the last beacon a faction has in a system it owns - or the station it is on, or that station leaving
+//! the faction - releases that system.
+
+use std::time::Duration;
+
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+    time::common_conditions::on_timer,
+};
+use cosmos_core::{
+    block::{Block, data::BlockData},
+    ecs::{NeedsDespawned, sets::FixedUpdateSet},
+    events::block_events::BlockChangedMessage,
+    faction::{FactionId, FactionRelation, Factions},
+    netty::sync::IdentifiableComponent,
+    physics::location::{Location, SystemCoordinate},
+    prelude::{Station, StructureSystems},
+    registry::{Registry, identifiable::Identifiable},
+    state::GameState,
+    structure::systems::{
+        dock_system::Docked,
+        energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
+    },
+    universe::map::territory::FactionClaimedTerritory,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blocks::data::utils::add_default_block_data_for_block,
+    persistence::{
+        make_persistent::{DefaultPersistentComponent, make_persistent},
+        saving::{NeedsSaved, SAVING_SCHEDULE, SavingSystemSet},
+    },
+};
+
+const BLOCK_ID: &str = "cosmos:claim_beacon";
+
+/// How often claim beacons are updated
+const UPDATE_INTERVAL_SECS: u64 = 1;
+/// How much energy each claim beacon uses per second to stay powered
+const POWER_PER_SEC: f32 = 100.0;
+/// How long (in seconds) a faction must hold a system with a powered beacon to claim it
+const CLAIM_HOLD_SECS: f32 = 600.0;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Marks the block data of a claim beacon block
+struct ClaimBeacon;
+
+impl IdentifiableComponent for ClaimBeacon {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:claim_beacon"
+    }
+}
+
+impl DefaultPersistentComponent for ClaimBeacon {}
+
+#[derive(Resource, Default, Debug)]
+/// How long each faction has been holding each system it is trying to claim.
+///
+/// This is not saved - restarting the server restarts any claims in progress.
+struct ClaimProgress(HashMap<(SystemCoordinate, FactionId), f32>);
+
+fn update_claim_beacons(
+    q_beacons: Query<&BlockData, With<ClaimBeacon>>,
+    q_station: Query<(&Location, &FactionId), With<Station>>,
+    mut q_ess: Query<&mut EnergyStorageSystem>,
+    q_systems: Query<(&StructureSystems, Option<&Docked>)>,
+    mut q_territory: Query<&mut FactionClaimedTerritory>,
+    factions: Res<Factions>,
+    mut progress: ResMut<ClaimProgress>,
+) {
+    let Ok(mut territory) = q_territory.single_mut() else {
+        return;
+    };
+
+    let power_needed = POWER_PER_SEC * UPDATE_INTERVAL_SECS as f32;
+
+    let mut powered = HashMap::<SystemCoordinate, HashSet<FactionId>>::default();
+    for bd in q_beacons.iter() {
+        let structure = bd.structure();
+        let Ok((location, faction_id)) = q_station.get(structure) else {
+            continue;
+        };
+        if factions.from_id(faction_id).is_none() {
+            continue;
+        }
+
+        if EnergyStorageSystem::decrease_energy_recursive(
+            PowerCategory::Utility,
+            PowerConsumer::Any,
+            power_needed,
+            structure,
+            &mut q_ess,
+            &q_systems,
+        ) == 0.0
+        {
+            powered.entry(location.get_system_coordinates()).or_default().insert(*faction_id);
+        }
+    }
+
+    // Unpowered beacons lose all their progress
+    progress
+        .0
+        .retain(|(system, faction_id), _| powered.get(system).is_some_and(|x| x.contains(faction_id)));
+
+    for (system, faction_ids) in powered {
+        let owner = territory.get_claim(system);
+
+        if owner.is_some_and(|owner| faction_ids.contains(&owner)) {
+            // The owner is defending this system
+            progress.0.retain(|(s, _), _| *s != system);
+            continue;
+        }
+
+        let owner_faction = owner.and_then(|owner| factions.from_id(&owner));
+        let challengers = faction_ids
+            .into_iter()
+            .filter(|faction_id| {
+                owner.is_none()
+                    || factions
+                        .from_id(faction_id)
+                        .is_some_and(|fac| fac.relation_with(owner_faction) == FactionRelation::Enemy)
+            })
+            .collect::<Vec<_>>();
+
+        let &[challenger] = challengers.as_slice() else {
+            // Either nobody can claim this system, or it is contested
+            continue;
+        };
+
+        let held = progress.0.entry((system, challenger)).or_default();
+        *held += UPDATE_INTERVAL_SECS as f32;
+
+        if *held >= CLAIM_HOLD_SECS {
+            progress.0.remove(&(system, challenger));
+
+            info!("Faction {challenger:?} has claimed system {system}.");
+            territory.claim(system, challenger);
+        }
+    }
+}
+
+/// Releases this faction's claim on the system if it has no beacons left there. Beacons `ignore` returns true for
+/// are about to go away, so they don't count.
+fn release_claim_without_beacons(
+    system: SystemCoordinate,
+    faction_id: FactionId,
+    ignore: impl Fn(&BlockData) -> bool,
+    q_beacons: &Query<&BlockData, With<ClaimBeacon>>,
+    q_station: &Query<(&Location, &FactionId), With<Station>>,
+    territory: &mut FactionClaimedTerritory,
+) {
+    if territory.get_claim(system) != Some(faction_id) {
+        return;
+    }
+
+    let has_other_beacon = q_beacons.iter().filter(|bd| !ignore(bd)).any(|bd| {
+        q_station
+            .get(bd.structure())
+            .is_ok_and(|(loc, fac)| *fac == faction_id && loc.get_system_coordinates() == system)
+    });
+
+    if !has_other_beacon {
+        info!("Faction {faction_id:?} has lost its claim on system {system}.");
+        territory.unclaim(system);
+    }
+}
+
+fn on_destroy_claim_beacon(
+    mut evr_block_changed: MessageReader<BlockChangedMessage>,
+    blocks: Res<Registry<Block>>,
+    q_beacons: Query<&BlockData, With<ClaimBeacon>>,
+    q_station: Query<(&Location, &FactionId), With<Station>>,
+    mut q_territory: Query<&mut FactionClaimedTerritory>,
+) {
+    let Some(beacon_id) = blocks.from_id(BLOCK_ID).map(|x| x.id()) else {
+        return;
+    };
+
+    for ev in evr_block_changed.read() {
+        if ev.old_block != beacon_id || ev.new_block == beacon_id {
+            continue;
+        }
+
+        let Ok((location, &faction_id)) = q_station.get(ev.block.structure()) else {
+            continue;
+        };
+
+        let Ok(mut territory) = q_territory.single_mut() else {
+            return;
+        };
+
+        release_claim_without_beacons(
+            location.get_system_coordinates(),
+            faction_id,
+            |bd| bd.identifier.block == ev.block,
+            &q_beacons,
+            &q_station,
+            &mut territory,
+        );
+    }
+}
+
+/// Destroyed stations no longer hold their system with their beacons.
+///
+/// Stations that are only being unloaded are saved first, so they keep holding their claims.
+fn on_claim_beacon_station_destroyed(
+    q_destroyed: Query<(Entity, &Location, &FactionId), (With<Station>, With<NeedsDespawned>, Without<NeedsSaved>)>,
+    q_beacons: Query<&BlockData, With<ClaimBeacon>>,
+    q_station: Query<(&Location, &FactionId), With<Station>>,
+    mut q_territory: Query<&mut FactionClaimedTerritory>,
+) {
+    let Ok(mut territory) = q_territory.single_mut() else {
+        return;
+    };
+
+    for (station, location, &faction_id) in q_destroyed.iter() {
+        if !q_beacons.iter().any(|bd| bd.structure() == station) {
+            continue;
+        }
+
+        release_claim_without_beacons(
+            location.get_system_coordinates(),
+            faction_id,
+            |bd| bd.structure() == station,
+            &q_beacons,
+            &q_station,
+            &mut territory,
+        );
+    }
+}
+
+/// Stations that leave the faction that claimed their system no longer hold it with their beacons.
+fn on_claim_beacon_station_change_faction(
+    q_changed_faction: Query<(Entity, &Location, Ref<FactionId>), (With<Station>, Changed<FactionId>)>,
+    mut removed_faction: RemovedComponents<FactionId>,
+    q_station_location: Query<&Location, With<Station>>,
+    q_beacons: Query<&BlockData, With<ClaimBeacon>>,
+    q_station: Query<(&Location, &FactionId), With<Station>>,
+    mut q_territory: Query<&mut FactionClaimedTerritory>,
+) {
+    let Ok(mut territory) = q_territory.single_mut() else {
+        return;
+    };
+
+    // The station's old faction isn't known anymore, but it only matters if that faction owned the system
+    let left_faction = q_changed_faction
+        .iter()
+        .filter(|(_, _, faction_id)| !faction_id.is_added())
+        .map(|(station, location, _)| (station, location))
+        .chain(
+            removed_faction
+                .read()
+                .filter_map(|station| q_station_location.get(station).ok().map(|location| (station, location))),
+        );
+
+    for (station, location) in left_faction {
+        if !q_beacons.iter().any(|bd| bd.structure() == station) {
+            continue;
+        }
+
+        let system = location.get_system_coordinates();
+        let Some(owner) = territory.get_claim(system) else {
+            continue;
+        };
+
+        release_claim_without_beacons(system, owner, |_| false, &q_beacons, &q_station, &mut territory);
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<ClaimBeacon>(app);
+    add_default_block_data_for_block::<ClaimBeacon>(app, |_, _| ClaimBeacon, BLOCK_ID);
+
+    app.init_resource::<ClaimProgress>()
+        .add_systems(
+            FixedUpdate,
+            (
+                on_destroy_claim_beacon,
+                update_claim_beacons.run_if(on_timer(Duration::from_secs(UPDATE_INTERVAL_SECS))),
+            )
+                .chain()
+                .in_set(FixedUpdateSet::Main)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(Update, on_claim_beacon_station_change_faction.run_if(in_state(GameState::Playing)))
+        .add_systems(
+            SAVING_SCHEDULE,
+            on_claim_beacon_station_destroyed
+                .before(SavingSystemSet::BeginSaving)
+                .run_if(in_state(GameState::Playing)),
+        );
+}
@@ -0,0 +1,281 @@
+//! Lets factions propose alliances and peace to each other, and declare war
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::common_conditions::on_timer};
+use cosmos_core::{
+    ecs::sets::FixedUpdateSet,
+    entities::{EntityId, player::Player},
+    faction::{
+        DiplomacyError, FactionId, FactionPermission, FactionRelation, Factions, ProposalOutcome,
+        events::{PlayerDeclareWarMessage, PlayerProposeFactionRelationMessage, PlayerRespondToFactionRelationMessage},
+    },
+    netty::{
+        server::ServerLobby,
+        sync::events::server_event::{NettyMessageReceived, NettyMessageWriter},
+    },
+    notifications::Notification,
+    state::GameState,
+    time::UniverseTimestamp,
+};
+use renet::ClientId;
+
+/// How long after war is declared it actually starts
+const WAR_DECLARATION_DELAY_SECS: u64 = 15 * 60;
+
+/// Online players and the faction they are in
+type FactionPlayersQuery<'w, 's> = Query<'w, 's, (&'static Player, &'static EntityId, &'static FactionId)>;
+
+/// Sends this notification to every online player in this faction
+fn notify_faction(
+    faction_id: FactionId,
+    notification: Notification,
+    q_players: &FactionPlayersQuery,
+    nevw_notification: &mut NettyMessageWriter<Notification>,
+) {
+    for (player, _, _) in q_players.iter().filter(|(_, _, fac_id)| **fac_id == faction_id) {
+        nevw_notification.write(notification.clone(), player.client_id());
+    }
+}
+
+fn faction_name(factions: &Factions, faction_id: FactionId) -> String {
+    factions
+        .from_id(&faction_id)
+        .map(|fac| fac.name().to_owned())
+        .unwrap_or_else(|| "Unknown Faction".into())
+}
+
+fn relation_name(relation: FactionRelation) -> &'static str {
+    match relation {
+        FactionRelation::Ally => "an alliance",
+        FactionRelation::Neutral => "peace",
+        FactionRelation::Enemy => "war",
+    }
+}
+
+fn error_message(error: DiplomacyError) -> &'static str {
+    match error {
+        DiplomacyError::NoSuchFaction => "That faction does not exist.",
+        DiplomacyError::SameFaction => "Your faction cannot change its relation with itself.",
+        DiplomacyError::InvalidProposal => "Only alliances and peace can be proposed. Declare war instead.",
+        DiplomacyError::AlreadyInRelation => "Your faction already has that relation with this faction.",
+        DiplomacyError::NoProposal => "That faction has not proposed anything to your faction.",
+        DiplomacyError::NpcFaction => "That faction does not respond to proposals.",
+        DiplomacyError::WarAlreadyDeclared => "Your faction has already declared war on that faction.",
+    }
+}
+
+/// Returns the player's faction if they are allowed to change its relations, letting them know if they are not
+fn diplomat_faction(
+    client_id: ClientId,
+    lobby: &ServerLobby,
+    q_players: &FactionPlayersQuery,
+    factions: &Factions,
+    nevw_notification: &mut NettyMessageWriter<Notification>,
+) -> Option<FactionId> {
+    let (_, ent_id, fac_id) = lobby.player_from_id(client_id).and_then(|player| q_players.get(player).ok())?;
+
+    if !factions
+        .from_id(fac_id)
+        .is_some_and(|fac| fac.has_permission(*ent_id, FactionPermission::ChangeRelations))
+    {
+        nevw_notification.write(
+            Notification::error("Your faction rank does not allow changing relations with other factions."),
+            client_id,
+        );
+        return None;
+    }
+
+    Some(*fac_id)
+}
+
+fn on_propose_relation(
+    mut nevr_propose: MessageReader<NettyMessageReceived<PlayerProposeFactionRelationMessage>>,
+    lobby: Res<ServerLobby>,
+    q_players: FactionPlayersQuery,
+    mut factions: ResMut<Factions>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+) {
+    for ev in nevr_propose.read() {
+        let Some(fac_id) = diplomat_faction(ev.client_id, &lobby, &q_players, &factions, &mut nevw_notification) else {
+            continue;
+        };
+
+        let outcome = match factions.propose_relation(fac_id, ev.faction, ev.relation) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                nevw_notification.write(Notification::error(error_message(e)), ev.client_id);
+                continue;
+            }
+        };
+
+        let (ours, theirs) = (faction_name(&factions, fac_id), faction_name(&factions, ev.faction));
+        let relation = relation_name(ev.relation);
+
+        match outcome {
+            ProposalOutcome::Proposed => {
+                notify_faction(
+                    fac_id,
+                    Notification::info(format!("Your faction has proposed {relation} to {theirs}.")),
+                    &q_players,
+                    &mut nevw_notification,
+                );
+                notify_faction(
+                    ev.faction,
+                    Notification::info(format!("{ours} has proposed {relation} with your faction.")),
+                    &q_players,
+                    &mut nevw_notification,
+                );
+            }
+            ProposalOutcome::Accepted => {
+                notify_faction(
+                    fac_id,
+                    Notification::info(format!("Your faction has agreed to {relation} with {theirs}.")),
+                    &q_players,
+                    &mut nevw_notification,
+                );
+                notify_faction(
+                    ev.faction,
+                    Notification::info(format!("Your faction has agreed to {relation} with {ours}.")),
+                    &q_players,
+                    &mut nevw_notification,
+                );
+            }
+        }
+    }
+}
+
+fn on_respond_to_relation(
+    mut nevr_respond: MessageReader<NettyMessageReceived<PlayerRespondToFactionRelationMessage>>,
+    lobby: Res<ServerLobby>,
+    q_players: FactionPlayersQuery,
+    mut factions: ResMut<Factions>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+) {
+    for ev in nevr_respond.read() {
+        let Some(fac_id) = diplomat_faction(ev.client_id, &lobby, &q_players, &factions, &mut nevw_notification) else {
+            continue;
+        };
+
+        let result = if ev.accept {
+            factions.accept_proposal(fac_id, ev.faction)
+        } else {
+            factions.reject_proposal(fac_id, ev.faction)
+        };
+
+        let relation = match result {
+            Ok(relation) => relation_name(relation),
+            Err(e) => {
+                nevw_notification.write(Notification::error(error_message(e)), ev.client_id);
+                continue;
+            }
+        };
+
+        let (ours, theirs) = (faction_name(&factions, fac_id), faction_name(&factions, ev.faction));
+        let (our_message, their_message) = if ev.accept {
+            (
+                format!("Your faction has agreed to {relation} with {theirs}."),
+                format!("{ours} has accepted your faction's proposal of {relation}."),
+            )
+        } else {
+            (
+                format!("Your faction has rejected {theirs}'s proposal of {relation}."),
+                format!("{ours} has rejected your faction's proposal of {relation}."),
+            )
+        };
+
+        notify_faction(fac_id, Notification::info(our_message), &q_players, &mut nevw_notification);
+        notify_faction(ev.faction, Notification::info(their_message), &q_players, &mut nevw_notification);
+    }
+}
+
+fn on_declare_war(
+    mut nevr_declare_war: MessageReader<NettyMessageReceived<PlayerDeclareWarMessage>>,
+    lobby: Res<ServerLobby>,
+    q_players: FactionPlayersQuery,
+    mut factions: ResMut<Factions>,
+    timestamp: Res<UniverseTimestamp>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+) {
+    for ev in nevr_declare_war.read() {
+        let Some(fac_id) = diplomat_faction(ev.client_id, &lobby, &q_players, &factions, &mut nevw_notification) else {
+            continue;
+        };
+
+        let mut starts_at = *timestamp;
+        starts_at.advance_by(WAR_DECLARATION_DELAY_SECS);
+
+        if let Err(e) = factions.declare_war(fac_id, ev.faction, starts_at) {
+            nevw_notification.write(Notification::error(error_message(e)), ev.client_id);
+            continue;
+        }
+
+        let (ours, theirs) = (faction_name(&factions, fac_id), faction_name(&factions, ev.faction));
+        let minutes = WAR_DECLARATION_DELAY_SECS / 60;
+
+        notify_faction(
+            fac_id,
+            Notification::info(format!(
+                "Your faction has declared war on {theirs}. The war starts in {minutes} minutes."
+            )),
+            &q_players,
+            &mut nevw_notification,
+        );
+        notify_faction(
+            ev.faction,
+            Notification::error(format!(
+                "{ours} has declared war on your faction! The war starts in {minutes} minutes."
+            )),
+            &q_players,
+            &mut nevw_notification,
+        );
+    }
+}
+
+fn start_declared_wars(
+    mut factions: ResMut<Factions>,
+    timestamp: Res<UniverseTimestamp>,
+    q_players: FactionPlayersQuery,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+) {
+    if !factions
+        .iter()
+        .any(|fac| fac.pending_wars().any(|(_, starts_at)| starts_at <= *timestamp))
+    {
+        return;
+    }
+
+    for (declarer, other) in factions.start_due_wars(*timestamp) {
+        let (declarer_name, other_name) = (faction_name(&factions, declarer), faction_name(&factions, other));
+
+        info!("{declarer_name} is now at war with {other_name}.");
+
+        notify_faction(
+            declarer,
+            Notification::error(format!("Your faction is now at war with {other_name}!")),
+            &q_players,
+            &mut nevw_notification,
+        );
+        notify_faction(
+            other,
+            Notification::error(format!("Your faction is now at war with {declarer_name}!")),
+            &q_players,
+            &mut nevw_notification,
+        );
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (
+            on_propose_relation,
+            on_respond_to_relation,
+            on_declare_war,
+            start_declared_wars.run_if(on_timer(Duration::from_secs(1))),
+        )
+            .chain()
+            .in_set(FixedUpdateSet::Main)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
@@ -3,16 +3,18 @@ use cosmos_core::{
     ecs::sets::FixedUpdateSet,
     entities::{EntityId, player::Player},
     faction::{
-        Faction, FactionId, FactionInvites, FactionPlayer, Factions,
+        Faction, FactionId, FactionInvites, FactionPermission, FactionPlayer, FactionRankError, Factions,
         events::{
             FactionSwapAction, PlayerAcceptFactionInvitation, PlayerCreateFactionMessage, PlayerCreateFactionMessageResponse,
-            PlayerDeclineFactionInvitation, PlayerInviteToFactionMessage, PlayerLeaveFactionMessage, SwapToPlayerFactionMessage,
+            PlayerDeclineFactionInvitation, PlayerDemoteFactionMemberMessage, PlayerInviteToFactionMessage, PlayerKickFromFactionMessage,
+            PlayerLeaveFactionMessage, PlayerPromoteFactionMemberMessage, SwapToPlayerFactionMessage,
         },
     },
     netty::{
         server::ServerLobby,
         sync::events::server_event::{NettyMessageReceived, NettyMessageWriter},
     },
+    notifications::Notification,
     prelude::{Ship, Station},
     state::GameState,
     structure::ship::pilot::Pilot,
@@ -23,7 +25,8 @@ fn on_swap_faction_from_player(
     q_can_set_faction: Query<(), Or<(With<Station>, With<Ship>)>>,
     q_faction: Query<&FactionId, Or<(With<Station>, With<Ship>)>>,
     lobby: Res<ServerLobby>,
-    q_player: Query<(&FactionId, &Pilot), With<Player>>,
+    q_player: Query<(&EntityId, &FactionId, &Pilot), With<Player>>,
+    factions: Res<Factions>,
     mut commands: Commands,
 ) {
     for ev in nevr.read() {
@@ -32,10 +35,17 @@ fn on_swap_faction_from_player(
         };
 
         // This will fail for non-pilot players.
-        let Ok((fac_id, pilot)) = q_player.get(player_ent) else {
+        let Ok((ent_id, fac_id, pilot)) = q_player.get(player_ent) else {
             continue;
         };
 
+        if !factions
+            .from_id(fac_id)
+            .is_some_and(|fac| fac.has_permission(*ent_id, FactionPermission::BuildOnStructures))
+        {
+            continue;
+        }
+
         if ev.to_swap != pilot.entity {
             // Can only change the ship you're piloting
             continue;
@@ -145,8 +155,9 @@ fn on_leave_faction(
 fn on_invite_player(
     mut nevr_leave_faction: MessageReader<NettyMessageReceived<PlayerInviteToFactionMessage>>,
     lobby: Res<ServerLobby>,
-    q_player_in_faction: Query<&FactionId, With<Player>>,
+    q_player_in_faction: Query<(&EntityId, &FactionId), With<Player>>,
     mut q_player_not_in_faction: Query<Option<&mut FactionInvites>, (With<Player>, Without<FactionId>)>,
+    factions: Res<Factions>,
     mut commands: Commands,
 ) {
     for ev in nevr_leave_faction.read() {
@@ -154,10 +165,17 @@ fn on_invite_player(
             continue;
         };
 
-        let Ok(fac_id) = q_player_in_faction.get(player) else {
+        let Ok((ent_id, fac_id)) = q_player_in_faction.get(player) else {
             continue;
         };
 
+        if !factions
+            .from_id(fac_id)
+            .is_some_and(|fac| fac.has_permission(*ent_id, FactionPermission::Invite))
+        {
+            continue;
+        }
+
         let Ok(inviting) = q_player_not_in_faction.get_mut(ev.inviting) else {
             continue;
         };
@@ -218,15 +236,125 @@ fn on_decline_invite(
     }
 }
 
+fn rank_error_message(error: FactionRankError) -> &'static str {
+    match error {
+        FactionRankError::NotInFaction => "That player is not in your faction.",
+        FactionRankError::InsufficientRank => "Your faction rank does not allow you to manage that player.",
+        FactionRankError::NoSuchRank => "That player's rank cannot be changed any further.",
+    }
+}
+
+fn on_promote_member(
+    mut nevr_promote: MessageReader<NettyMessageReceived<PlayerPromoteFactionMemberMessage>>,
+    lobby: Res<ServerLobby>,
+    q_player_in_faction: Query<(&EntityId, &FactionId), With<Player>>,
+    mut factions: ResMut<Factions>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+) {
+    for ev in nevr_promote.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            continue;
+        };
+
+        let Ok((ent_id, fac_id)) = q_player_in_faction.get(player) else {
+            continue;
+        };
+
+        let Some(faction) = factions.from_id_mut(fac_id) else {
+            continue;
+        };
+
+        if let Err(e) = faction.promote(*ent_id, ev.player) {
+            nevw_notification.write(Notification::error(rank_error_message(e)), ev.client_id);
+        }
+    }
+}
+
+fn on_demote_member(
+    mut nevr_demote: MessageReader<NettyMessageReceived<PlayerDemoteFactionMemberMessage>>,
+    lobby: Res<ServerLobby>,
+    q_player_in_faction: Query<(&EntityId, &FactionId), With<Player>>,
+    mut factions: ResMut<Factions>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+) {
+    for ev in nevr_demote.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            continue;
+        };
+
+        let Ok((ent_id, fac_id)) = q_player_in_faction.get(player) else {
+            continue;
+        };
+
+        let Some(faction) = factions.from_id_mut(fac_id) else {
+            continue;
+        };
+
+        if let Err(e) = faction.demote(*ent_id, ev.player) {
+            nevw_notification.write(Notification::error(rank_error_message(e)), ev.client_id);
+        }
+    }
+}
+
+fn on_kick_member(
+    mut nevr_kick: MessageReader<NettyMessageReceived<PlayerKickFromFactionMessage>>,
+    lobby: Res<ServerLobby>,
+    q_player_in_faction: Query<(Entity, &EntityId, &FactionId), With<Player>>,
+    mut factions: ResMut<Factions>,
+    mut commands: Commands,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+) {
+    for ev in nevr_kick.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            continue;
+        };
+
+        let Ok((_, ent_id, fac_id)) = q_player_in_faction.get(player) else {
+            continue;
+        };
+
+        let Some(faction) = factions.from_id_mut(fac_id) else {
+            continue;
+        };
+
+        if let Err(e) = faction.kick(*ent_id, ev.player) {
+            nevw_notification.write(Notification::error(rank_error_message(e)), ev.client_id);
+            continue;
+        }
+
+        // Offline players are removed from the faction when they next log in (see `remove_stale_faction_ids`)
+        if let Some((kicked_ent, _, _)) = q_player_in_faction.iter().find(|(_, id, _)| **id == ev.player) {
+            commands.entity(kicked_ent).remove::<FactionId>();
+        }
+    }
+}
+
+/// Players kicked while offline still have their old [`FactionId`] saved, so remove it once they're loaded.
+fn remove_stale_faction_ids(
+    q_loaded_player: Query<(Entity, &EntityId, &FactionId), (Added<FactionId>, With<Player>)>,
+    factions: Res<Factions>,
+    mut commands: Commands,
+) {
+    for (ent, ent_id, fac_id) in q_loaded_player.iter() {
+        if factions.from_id(fac_id).is_none_or(|fac| fac.player(*ent_id).is_none()) {
+            commands.entity(ent).remove::<FactionId>();
+        }
+    }
+}
+
 pub(super) fn register(app: &mut App) {
     app.add_systems(
         FixedUpdate,
         (
+            remove_stale_faction_ids,
             on_create_faction,
             on_leave_faction,
             on_invite_player,
             on_decline_invite,
             on_accept_invite,
+            on_promote_member,
+            on_demote_member,
+            on_kick_member,
             on_swap_faction_from_player,
         )
             .chain()
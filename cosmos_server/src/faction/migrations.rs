@@ -0,0 +1,188 @@
+//! Reads and writes the factions file, upgrading factions that were saved in an older format.
+//!
+//! `factions.bin` is not stored as a [`crate::persistence::make_persistent::PersistentComponent`], so it is
+//! versioned on its own. Whenever the layout of [`cosmos_core::faction::Faction`] (or anything it stores) changes, bump
+//! [`FACTIONS_VERSION`] and add a migration from the previous version to [`deserialize_factions`].
+
+use bevy::platform::collections::HashMap;
+use bincode::error::DecodeError;
+use cosmos_core::{
+    entities::EntityId,
+    faction::{Faction, FactionId, FactionPlayer, FactionRank, FactionRelation, FactionSettings, Factions},
+    netty::cosmos_encoder,
+    physics::location::Location,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Written before the version of every factions file. Factions saved before the file was versioned don't have this.
+const FACTIONS_MAGIC: [u8; 4] = *b"CFAC";
+/// The version factions are currently saved as
+const FACTIONS_VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+/// Something went wrong reading the factions file
+pub(super) enum FactionsLoadError {
+    /// The factions were saved by a newer version of the game than this one
+    #[error("factions were saved as version {found}, but the latest version this server understands is {latest}")]
+    NewerVersion {
+        /// The version the factions were saved as
+        found: u32,
+        /// The current version
+        latest: u32,
+    },
+    /// The factions could not be read as the format of the version they were saved as
+    #[error("unable to parse factions saved as version {version} - {error:?}")]
+    Parsing {
+        /// The version the factions were saved as
+        version: u32,
+        /// The underlying parsing error
+        error: Box<DecodeError>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+/// A [`FactionPlayer`] before ranks existed
+struct FactionPlayerV0 {
+    entity_id: EntityId,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+/// A [`Faction`] before the factions file was versioned
+struct FactionV0 {
+    id: FactionId,
+    name: String,
+    players: Vec<FactionPlayerV0>,
+    relationships: HashMap<FactionId, FactionRelation>,
+    at_war_with: Vec<EntityId>,
+    settings: FactionSettings,
+    capitol: Option<Location>,
+}
+
+impl From<FactionV0> for Faction {
+    fn from(old: FactionV0) -> Self {
+        Faction::from_saved(
+            old.id,
+            old.name,
+            // Every player starts out as a recruit - the faction's leader is chosen once it is loaded
+            old.players
+                .into_iter()
+                .map(|p| FactionPlayer {
+                    entity_id: p.entity_id,
+                    name: p.name,
+                    rank: FactionRank::Recruit,
+                })
+                .collect(),
+            old.relationships,
+            old.at_war_with,
+            old.settings,
+            old.capitol,
+        )
+    }
+}
+
+fn migrate_from_v0(data: &[u8]) -> Result<Factions, Box<DecodeError>> {
+    let old = cosmos_encoder::deserialize::<HashMap<FactionId, FactionV0>>(data)?;
+
+    let mut factions = Factions::default();
+    for faction in old.into_values() {
+        factions.add_new_faction(faction.into());
+    }
+
+    Ok(factions)
+}
+
+/// Serializes the factions in the latest format, along with that format's version
+pub(super) fn serialize_factions(factions: &Factions) -> Vec<u8> {
+    let mut data = FACTIONS_MAGIC.to_vec();
+    data.extend(FACTIONS_VERSION.to_le_bytes());
+    data.extend(cosmos_encoder::serialize(factions));
+    data
+}
+
+/// Reads factions saved by [`serialize_factions`], or by any older version of the game
+pub(super) fn deserialize_factions(data: &[u8]) -> Result<Factions, FactionsLoadError> {
+    let (version, data) = match data.split_first_chunk::<4>() {
+        Some((magic, rest)) if *magic == FACTIONS_MAGIC => {
+            let Some((version, rest)) = rest.split_first_chunk::<4>() else {
+                return Err(FactionsLoadError::Parsing {
+                    version: FACTIONS_VERSION,
+                    error: Box::new(DecodeError::Other("Missing factions version")),
+                });
+            };
+
+            (u32::from_le_bytes(*version), rest)
+        }
+        // Factions saved before the file was versioned are just the factions themselves
+        _ => (0, data),
+    };
+
+    let factions = match version {
+        0 => migrate_from_v0(data),
+        FACTIONS_VERSION => cosmos_encoder::deserialize::<Factions>(data),
+        found => {
+            return Err(FactionsLoadError::NewerVersion {
+                found,
+                latest: FACTIONS_VERSION,
+            });
+        }
+    };
+
+    factions.map_err(|error| FactionsLoadError::Parsing { version, error })
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::platform::collections::HashMap;
+    use cosmos_core::{
+        economy::Credits,
+        entities::EntityId,
+        faction::{FactionId, FactionRank, FactionSettings},
+        netty::cosmos_encoder,
+    };
+
+    use super::{FactionPlayerV0, FactionV0, FactionsLoadError, deserialize_factions, serialize_factions};
+
+    #[test]
+    fn migrates_unversioned_factions() {
+        let id = FactionId::generate_new();
+        let player = EntityId::generate();
+
+        let old = HashMap::from_iter([(
+            id,
+            FactionV0 {
+                id,
+                name: "Old Faction".into(),
+                players: vec![FactionPlayerV0 {
+                    entity_id: player,
+                    name: "Player".into(),
+                }],
+                relationships: Default::default(),
+                at_war_with: vec![],
+                settings: FactionSettings { default_enemy: true },
+                capitol: None,
+            },
+        )]);
+
+        let mut factions = deserialize_factions(&cosmos_encoder::serialize(&old)).unwrap();
+        let faction = factions.from_id_mut(&id).unwrap();
+        assert_eq!(faction.name(), "Old Faction");
+        assert_eq!(faction.rank_of(player), Some(FactionRank::Recruit));
+        assert_eq!(faction.treasury(), Credits::default());
+        assert_eq!(faction.relation_proposals().count(), 0);
+        assert_eq!(faction.pending_wars().count(), 0);
+        assert_eq!(faction.storage().count(), 0);
+
+        faction.ensure_leader();
+        assert_eq!(faction.rank_of(player), Some(FactionRank::Leader));
+
+        // Once saved again, the factions are read back as the latest version
+        let reloaded = deserialize_factions(&serialize_factions(&factions)).unwrap();
+        assert_eq!(reloaded.from_id(&id), factions.from_id(&id));
+
+        let mut newer = serialize_factions(&factions);
+        newer[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(deserialize_factions(&newer), Err(FactionsLoadError::NewerVersion { .. })));
+    }
+}
@@ -9,18 +9,43 @@ use cosmos_core::{
 };
 use std::fs;
 
-use crate::persistence::{
-    WorldRoot,
-    make_persistent::{DefaultPersistentComponent, make_persistent},
+use crate::{
+    faction::treasury::FactionTransactionLog,
+    persistence::{
+        WorldRoot,
+        make_persistent::{DefaultPersistentComponent, make_persistent},
+    },
 };
 
+mod claim_beacon;
+mod diplomacy;
 mod events;
+mod migrations;
+mod storage;
+pub(crate) mod treasury;
 
 fn load_factions(mut commands: Commands, world_root: Res<WorldRoot>) {
+    let log_path = world_root.path_for("faction_transactions.bin");
+    let transaction_log = if let Ok(data) = fs::read(&log_path) {
+        cosmos_encoder::deserialize::<FactionTransactionLog>(&data)
+            .unwrap_or_else(|e| panic!("Failed to deserialize faction transactions in {log_path}. {e:?}"))
+    } else {
+        FactionTransactionLog::default()
+    };
+
+    commands.insert_resource(transaction_log);
+
     let path = world_root.path_for("factions.bin");
     let factions = if let Ok(data) = fs::read(&path) {
         // We want to panic if something is corrupted
-        cosmos_encoder::deserialize::<Factions>(&data).unwrap_or_else(|e| panic!("Failed to deserialize faction data in {path}. {e:?}"))
+        let mut factions =
+            migrations::deserialize_factions(&data).unwrap_or_else(|e| panic!("Failed to deserialize faction data in {path}. {e}"));
+
+        for faction in factions.iter_mut() {
+            faction.ensure_leader();
+        }
+
+        factions
     } else {
         info!("Generating factions!");
 
@@ -46,8 +71,20 @@ fn load_factions(mut commands: Commands, world_root: Res<WorldRoot>) {
     commands.insert_resource(factions);
 }
 
-fn save_factions_on_change(factions: Res<Factions>, world_root: Res<WorldRoot>) {
-    fs::write(world_root.path_for("factions.bin"), cosmos_encoder::serialize(factions.as_ref())).expect("Failed to save factions.");
+fn save_factions_on_change(factions: Res<Factions>, transaction_log: Res<FactionTransactionLog>, world_root: Res<WorldRoot>) {
+    fs::write(
+        world_root.path_for("factions.bin"),
+        migrations::serialize_factions(factions.as_ref()),
+    )
+    .expect("Failed to save factions.");
+
+    if transaction_log.is_changed() {
+        fs::write(
+            world_root.path_for("faction_transactions.bin"),
+            cosmos_encoder::serialize(transaction_log.as_ref()),
+        )
+        .expect("Failed to save faction transactions.");
+    }
 }
 
 impl DefaultPersistentComponent for FactionId {}
@@ -55,6 +92,10 @@ impl DefaultPersistentComponent for FactionId {}
 pub(super) fn register(app: &mut App) {
     make_persistent::<FactionId>(app);
     events::register(app);
+    claim_beacon::register(app);
+    diplomacy::register(app);
+    storage::register(app);
+    treasury::register(app);
 
     app.add_systems(
         FixedUpdate,
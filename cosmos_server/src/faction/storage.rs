@@ -0,0 +1,138 @@
+//! Faction storage, which pools items from every player in a faction
+
+use bevy::prelude::*;
+use cosmos_core::{
+    ecs::sets::FixedUpdateSet,
+    entities::{EntityId, player::Player},
+    faction::{
+        FactionId, FactionPermission, Factions,
+        events::{PlayerDepositToFactionStorageMessage, PlayerWithdrawFromFactionStorageMessage},
+    },
+    inventory::{Inventory, itemstack::ItemShouldHaveData},
+    item::Item,
+    netty::{
+        server::ServerLobby,
+        sync::events::server_event::{NettyMessageReceived, NettyMessageWriter},
+    },
+    notifications::Notification,
+    registry::Registry,
+    state::GameState,
+};
+
+fn on_deposit(
+    mut nevr_deposit: MessageReader<NettyMessageReceived<PlayerDepositToFactionStorageMessage>>,
+    lobby: Res<ServerLobby>,
+    mut q_player: Query<(&EntityId, &FactionId, &mut Inventory), With<Player>>,
+    mut factions: ResMut<Factions>,
+    items: Res<Registry<Item>>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    mut commands: Commands,
+) {
+    for ev in nevr_deposit.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            continue;
+        };
+
+        let Ok((ent_id, fac_id, mut inventory)) = q_player.get_mut(player) else {
+            continue;
+        };
+
+        let Some(faction) = factions.from_id_mut(fac_id) else {
+            continue;
+        };
+
+        if ev.quantity == 0 || faction.player(*ent_id).is_none() {
+            continue;
+        }
+
+        let slot = ev.slot as usize;
+        if slot >= inventory.len() {
+            continue;
+        }
+
+        let Some(is) = inventory.itemstack_at(slot) else {
+            continue;
+        };
+
+        // Only the item itself is stored, so anything unique about this itemstack would be lost
+        if is.data_entity().is_some() {
+            nevw_notification.write(
+                Notification::error("This item cannot be put into your faction's storage."),
+                ev.client_id,
+            );
+            continue;
+        }
+
+        let item = items.from_numeric_id(is.item_id());
+        let quantity = ev.quantity.min(is.quantity());
+
+        inventory.decrease_quantity_at(slot, quantity, &mut commands);
+        faction.store_items(item, quantity as u64);
+    }
+}
+
+fn on_withdraw(
+    mut nevr_withdraw: MessageReader<NettyMessageReceived<PlayerWithdrawFromFactionStorageMessage>>,
+    lobby: Res<ServerLobby>,
+    mut q_player: Query<(&EntityId, &FactionId, &mut Inventory), With<Player>>,
+    mut factions: ResMut<Factions>,
+    items: Res<Registry<Item>>,
+    has_data: Res<ItemShouldHaveData>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    mut commands: Commands,
+) {
+    for ev in nevr_withdraw.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            continue;
+        };
+
+        let Ok((ent_id, fac_id, mut inventory)) = q_player.get_mut(player) else {
+            continue;
+        };
+
+        let Some(faction) = factions.from_id_mut(fac_id) else {
+            continue;
+        };
+
+        let Some(item) = items.try_from_numeric_id(ev.item_id) else {
+            continue;
+        };
+
+        if ev.quantity == 0 {
+            continue;
+        }
+
+        if !faction.has_permission(*ent_id, FactionPermission::AccessStorage) {
+            nevw_notification.write(
+                Notification::error("Your faction rank does not allow taking items out of its storage."),
+                ev.client_id,
+            );
+            continue;
+        }
+
+        if !inventory.can_insert(item, ev.quantity) {
+            nevw_notification.write(Notification::error("You do not have enough inventory space."), ev.client_id);
+            continue;
+        }
+
+        if !faction.take_stored_items(item, ev.quantity as u64) {
+            nevw_notification.write(
+                Notification::error("Your faction's storage does not have enough of this item."),
+                ev.client_id,
+            );
+            continue;
+        }
+
+        inventory.insert_item(item, ev.quantity, &mut commands, &has_data);
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (on_deposit, on_withdraw)
+            .chain()
+            .in_set(FixedUpdateSet::Main)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
@@ -0,0 +1,153 @@
+//! Faction treasuries, which pool credits from every player in a faction
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use cosmos_core::{
+    economy::Credits,
+    ecs::sets::FixedUpdateSet,
+    entities::{EntityId, player::Player},
+    faction::{
+        FactionId, FactionPermission, FactionTransaction, FactionTransactionKind, Factions,
+        events::{PlayerDepositToFactionTreasuryMessage, PlayerWithdrawFromFactionTreasuryMessage},
+    },
+    netty::{
+        server::ServerLobby,
+        sync::events::server_event::{NettyMessageReceived, NettyMessageWriter},
+    },
+    notifications::Notification,
+    state::GameState,
+    time::UniverseTimestamp,
+};
+use serde::{Deserialize, Serialize};
+
+/// Only the most recent transactions of each faction are kept
+const MAX_LOGGED_TRANSACTIONS: usize = 1000;
+
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+/// Every [`FactionTransaction`] that has been made with each faction's treasury, oldest first.
+///
+/// This is saved next to the factions.
+pub(crate) struct FactionTransactionLog(HashMap<FactionId, Vec<FactionTransaction>>);
+
+impl FactionTransactionLog {
+    /// Records a transaction made with this faction's treasury
+    pub(crate) fn record(&mut self, faction_id: FactionId, transaction: FactionTransaction) {
+        let transactions = self.0.entry(faction_id).or_default();
+        transactions.push(transaction);
+
+        if transactions.len() > MAX_LOGGED_TRANSACTIONS {
+            let excess = transactions.len() - MAX_LOGGED_TRANSACTIONS;
+            transactions.drain(..excess);
+        }
+    }
+}
+
+fn on_deposit(
+    mut nevr_deposit: MessageReader<NettyMessageReceived<PlayerDepositToFactionTreasuryMessage>>,
+    lobby: Res<ServerLobby>,
+    mut q_player: Query<(&EntityId, &FactionId, &mut Credits), With<Player>>,
+    mut factions: ResMut<Factions>,
+    mut log: ResMut<FactionTransactionLog>,
+    timestamp: Res<UniverseTimestamp>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+) {
+    for ev in nevr_deposit.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            continue;
+        };
+
+        let Ok((ent_id, fac_id, mut credits)) = q_player.get_mut(player) else {
+            continue;
+        };
+
+        let Some(faction) = factions.from_id_mut(fac_id) else {
+            continue;
+        };
+
+        if ev.amount == 0 || faction.player(*ent_id).is_none() {
+            continue;
+        }
+
+        if !credits.decrease(ev.amount) {
+            nevw_notification.write(Notification::error("You do not have enough credits."), ev.client_id);
+            continue;
+        }
+
+        faction.treasury_mut().increase(ev.amount);
+
+        log.record(
+            *fac_id,
+            FactionTransaction {
+                player: *ent_id,
+                kind: FactionTransactionKind::Deposit,
+                amount: ev.amount,
+                timestamp: *timestamp,
+            },
+        );
+    }
+}
+
+fn on_withdraw(
+    mut nevr_withdraw: MessageReader<NettyMessageReceived<PlayerWithdrawFromFactionTreasuryMessage>>,
+    lobby: Res<ServerLobby>,
+    mut q_player: Query<(&EntityId, &FactionId, &mut Credits), With<Player>>,
+    mut factions: ResMut<Factions>,
+    mut log: ResMut<FactionTransactionLog>,
+    timestamp: Res<UniverseTimestamp>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+) {
+    for ev in nevr_withdraw.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            continue;
+        };
+
+        let Ok((ent_id, fac_id, mut credits)) = q_player.get_mut(player) else {
+            continue;
+        };
+
+        let Some(faction) = factions.from_id_mut(fac_id) else {
+            continue;
+        };
+
+        if ev.amount == 0 {
+            continue;
+        }
+
+        if !faction.has_permission(*ent_id, FactionPermission::WithdrawTreasury) {
+            nevw_notification.write(
+                Notification::error("Your faction rank does not allow withdrawing from the treasury."),
+                ev.client_id,
+            );
+            continue;
+        }
+
+        if !faction.treasury_mut().decrease(ev.amount) {
+            nevw_notification.write(
+                Notification::error("Your faction's treasury does not have enough credits."),
+                ev.client_id,
+            );
+            continue;
+        }
+
+        credits.increase(ev.amount);
+
+        log.record(
+            *fac_id,
+            FactionTransaction {
+                player: *ent_id,
+                kind: FactionTransactionKind::Withdraw,
+                amount: ev.amount,
+                timestamp: *timestamp,
+            },
+        );
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (on_deposit, on_withdraw)
+            .chain()
+            .in_set(FixedUpdateSet::Main)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
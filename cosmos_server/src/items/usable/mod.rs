@@ -12,6 +12,7 @@ use cosmos_core::{
 };
 
 mod blueprint;
+mod repair_tool;
 
 fn on_use_item(
     mut nevr_req_use_item: MessageReader<NettyMessageReceived<PlayerRequestUseHeldItemMessage>>,
@@ -45,6 +46,7 @@ fn on_use_item(
 
 pub(super) fn register(app: &mut App) {
     blueprint::register(app);
+    repair_tool::register(app);
 
     app.add_systems(FixedUpdate, on_use_item.in_set(UseItemSet::SendUseItemMessages))
         .add_message::<UseHeldItemMessage>();
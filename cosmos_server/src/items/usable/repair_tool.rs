@@ -0,0 +1,132 @@
+//! The handheld repair tool, which restores the health of damaged blocks
+
+use bevy::prelude::*;
+use cosmos_core::{
+    block::Block,
+    entities::player::Player,
+    inventory::Inventory,
+    item::{Item, usable::UseItemSet},
+    netty::sync::events::server_event::NettyMessageWriter,
+    notifications::Notification,
+    prelude::Structure,
+    registry::{Registry, identifiable::Identifiable},
+    state::GameState,
+    structure::{
+        block_health::events::BlockRepairedMessage,
+        shared::MeltingDown,
+        systems::{
+            StructureSystems,
+            dock_system::Docked,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
+        },
+    },
+};
+
+use crate::{
+    items::usable::UseHeldItemMessage,
+    structure::block_health::repair::{REPAIR_MATERIAL, StoredRepairMaterial, missing_health, repair_block},
+};
+
+/// How much block health a single use of the repair tool restores
+const HEALTH_PER_USE: f32 = 25.0;
+/// How much energy a single use of the repair tool takes from the structure being repaired
+const ENERGY_PER_USE: f32 = 200.0;
+/// How far away (in meters) a block can be repaired from
+const MAX_REPAIR_DISTANCE: f32 = 8.0;
+
+fn on_use_repair_tool(
+    mut evr_use_item: MessageReader<UseHeldItemMessage>,
+    mut q_player: Query<(&Player, &mut Inventory, &mut StoredRepairMaterial, &GlobalTransform)>,
+    mut q_structure: Query<(&mut Structure, &GlobalTransform), (Without<MeltingDown>, Without<Player>)>,
+    q_systems: Query<(&StructureSystems, Option<&Docked>)>,
+    mut q_energy_storage_system: Query<&mut EnergyStorageSystem>,
+    items: Res<Registry<Item>>,
+    blocks: Res<Registry<Block>>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    mut evw_block_repaired: MessageWriter<BlockRepairedMessage>,
+    mut commands: Commands,
+) {
+    for ev in evr_use_item.read() {
+        let Some(repair_tool) = items.from_id("cosmos:repair_tool") else {
+            return;
+        };
+
+        if ev.item != Some(repair_tool.id()) {
+            continue;
+        }
+
+        let Some(block) = ev.looking_at_block else {
+            continue;
+        };
+
+        let Ok((player, mut inv, mut stored_material, player_g_trans)) = q_player.get_mut(ev.player) else {
+            continue;
+        };
+
+        let Ok((mut structure, structure_g_trans)) = q_structure.get_mut(block.structure()) else {
+            continue;
+        };
+
+        let block_pos = structure_g_trans.transform_point(structure.block_relative_position(block.coords()));
+        if block_pos.distance_squared(player_g_trans.translation()) > MAX_REPAIR_DISTANCE * MAX_REPAIR_DISTANCE {
+            continue;
+        }
+
+        let missing = missing_health(&structure, block.coords(), &blocks);
+        if missing <= 0.0 {
+            continue;
+        }
+
+        let Some(repair_material) = items.from_id(REPAIR_MATERIAL) else {
+            error!("Missing repair material {REPAIR_MATERIAL}!");
+            return;
+        };
+
+        // Players don't carry any energy, so the tool is powered by the structure being repaired
+        if EnergyStorageSystem::decrease_energy_recursive(
+            PowerCategory::Utility,
+            PowerConsumer::Any,
+            ENERGY_PER_USE,
+            block.structure(),
+            &mut q_energy_storage_system,
+            &q_systems,
+        ) != 0.0
+        {
+            nevw_notification.write(
+                Notification::error("This structure doesn't have enough energy to be repaired."),
+                player.client_id(),
+            );
+            continue;
+        }
+
+        let restored = stored_material.spend(missing.min(HEALTH_PER_USE), || {
+            inv.take_and_remove_item(repair_material, 1, &mut commands).0 == 0
+        });
+
+        if restored <= 0.0 {
+            nevw_notification.write(
+                Notification::error("You need iron bars in your inventory to repair blocks."),
+                player.client_id(),
+            );
+            continue;
+        }
+
+        repair_block(
+            &mut structure,
+            block.coords(),
+            restored,
+            Some(ev.player),
+            &blocks,
+            &mut evw_block_repaired,
+        );
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        on_use_repair_tool
+            .after(UseItemSet::SendUseItemMessages)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
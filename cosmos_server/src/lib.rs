@@ -0,0 +1,43 @@
+//! Contains all the logic for the server-side of Cosmos.
+//!
+//! The `cosmos_server` binary runs the server, and other binaries (such as `world_inspector`) use this to work with
+//! the server's data without running a server.
+
+#![feature(try_blocks)]
+#![feature(iter_array_chunks)]
+#![warn(missing_docs)]
+// This one has a stupid rule where if you have `fn (&self) -> HasLifetime`, you need to do `fn (&self) -> HasLifetime<'_>`. This is stupid.
+#![allow(mismatched_lifetime_syntaxes)]
+
+pub mod ai;
+pub mod blocks;
+pub mod chat;
+pub mod commands;
+pub mod coms;
+mod converters;
+pub mod crafting;
+pub mod creative;
+mod economy;
+pub mod entities;
+pub mod faction;
+pub mod fluid;
+pub mod init;
+pub mod inventory;
+pub mod items;
+pub mod local;
+pub mod logic;
+pub mod loot;
+pub mod netty;
+pub mod persistence;
+pub mod physics;
+pub mod plugin;
+pub mod projectiles;
+pub mod quest;
+pub mod rng;
+pub mod server;
+pub mod settings;
+pub mod shop;
+pub mod structure;
+pub mod universe;
+
+mod utility_runs;
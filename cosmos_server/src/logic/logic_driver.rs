@@ -57,6 +57,15 @@ impl LogicDriver {
         ALL_BLOCK_FACES.map(|face| self.read_input(coords, rotation.direction_of(face)))
     }
 
+    /// Returns true if any of the given block's input ports are connected to a block that can send them a signal.
+    pub fn has_connected_input(&self, coords: BlockCoordinate, rotation: BlockRotation) -> bool {
+        ALL_BLOCK_FACES.iter().any(|face| {
+            self.logic_graph
+                .group_of(&Port::new(coords, rotation.direction_of(*face)), PortType::Input)
+                .is_some_and(|group| !group.producers.is_empty())
+        })
+    }
+
     fn port_placed(
         &mut self,
         coords: BlockCoordinate,
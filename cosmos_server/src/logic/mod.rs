@@ -30,6 +30,9 @@ use std::collections::VecDeque;
 pub mod logic_driver;
 mod logic_graph;
 mod specific_blocks;
+#[cfg(test)]
+mod test_harness;
+mod timer;
 
 /// The bits to set or read the logic on/off value from the [`BlockInfo`] of a block.
 pub const LOGIC_BIT: u8 = 1 << 7;
@@ -539,10 +542,8 @@ fn register_logic_groups(mut logic_wire_colors: ResMut<Registry<LogicWireColor>>
 
 impl DefaultPersistentComponent for BlockLogicData {}
 
-pub(super) fn register(app: &mut App) {
-    specific_blocks::register(app);
-
-    make_persistent::<BlockLogicData>(app);
+/// Everything needed to build logic graphs and run logic ticks, without the behavior of any specific logic block.
+fn register_logic_graph(app: &mut App) {
     create_registry::<LogicBlock>(app, "cosmos:logic_blocks");
     create_registry::<LogicWireColor>(app, "cosmos:logic_wire_colors");
     app.init_resource::<LogicOutputMessageQueue>();
@@ -616,3 +617,11 @@ pub(super) fn register(app: &mut App) {
         LogicSystemRegistrySet::RegisterLogicBlocks.ambiguous_with(LogicSystemRegistrySet::RegisterLogicBlocks),
     );
 }
+
+pub(super) fn register(app: &mut App) {
+    specific_blocks::register(app);
+    timer::register(app);
+
+    make_persistent::<BlockLogicData>(app);
+    register_logic_graph(app);
+}
@@ -101,3 +101,36 @@ pub(super) fn register<T: States>(app: &mut App, post_loading_state: T) {
                 .ambiguous_with(LogicSystemSet::Produce),
         );
 }
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::{state::GameState, structure::coordinates::BlockCoordinate};
+
+    use crate::logic::test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_PROBE, TEST_SOURCE};
+
+    const LEFT_INPUT: BlockCoordinate = BlockCoordinate::new(4, 5, 5);
+    const GATE: BlockCoordinate = BlockCoordinate::new(5, 5, 5);
+    const RIGHT_INPUT: BlockCoordinate = BlockCoordinate::new(6, 5, 5);
+    const OUTPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 4);
+
+    #[test]
+    fn test_and_gate_truth_table() {
+        let mut harness = LogicTestHarness::new(
+            &[
+                (LEFT_INPUT, TEST_SOURCE),
+                (GATE, "cosmos:and_gate"),
+                (RIGHT_INPUT, TEST_SOURCE),
+                (OUTPUT, TEST_PROBE),
+            ],
+            |app| super::register(app, GameState::PostLoading),
+        );
+
+        for (left, right, expected) in [(0, 0, 0), (1, 0, 0), (0, 1, 0), (1, 1, 1), (5, -3, 1)] {
+            harness.set_source(LEFT_INPUT, left);
+            harness.set_source(RIGHT_INPUT, right);
+            harness.step(SETTLE_TICKS);
+
+            assert_eq!(harness.probe(OUTPUT), expected, "left: {left}, right: {right}");
+        }
+    }
+}
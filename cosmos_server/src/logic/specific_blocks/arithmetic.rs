@@ -0,0 +1,256 @@
+//! Logic behavior for the integer arithmetic and comparator blocks.
+//!
+//! Each of these blocks has left and right inputs and a front output, like the
+//! [`super::and_gate`], but operates on the full `i32` logic signal instead of treating it as a Boolean.
+//! Missing inputs are treated as `0`, and arithmetic wraps around on overflow.
+
+use bevy::prelude::*;
+
+use cosmos_core::{
+    block::{Block, block_face::BlockFace, data::BlockData},
+    registry::{Registry, identifiable::Identifiable},
+    state::GameState,
+    structure::Structure,
+};
+
+use crate::logic::{
+    BlockLogicData, LogicBlock, LogicConnection, LogicInputMessage, LogicOutputMessage, LogicSystemSet, PortType, QueueLogicInputMessage,
+    default_logic_block_output, logic_driver::LogicDriver,
+};
+
+/// A logic block that combines its left and right input signals into a single output signal.
+trait BinaryLogicOperation: Send + Sync + 'static {
+    /// The unlocalized name of the block that performs this operation.
+    const BLOCK_ID: &'static str;
+
+    /// Computes the output signal from the left and right input signals.
+    fn apply(left: i32, right: i32) -> i32;
+}
+
+/// Outputs `left + right`.
+struct Adder;
+
+impl BinaryLogicOperation for Adder {
+    const BLOCK_ID: &'static str = "cosmos:adder";
+
+    fn apply(left: i32, right: i32) -> i32 {
+        left.wrapping_add(right)
+    }
+}
+
+/// Outputs `left - right`.
+struct Subtractor;
+
+impl BinaryLogicOperation for Subtractor {
+    const BLOCK_ID: &'static str = "cosmos:subtractor";
+
+    fn apply(left: i32, right: i32) -> i32 {
+        left.wrapping_sub(right)
+    }
+}
+
+/// Outputs `left * right`.
+struct Multiplier;
+
+impl BinaryLogicOperation for Multiplier {
+    const BLOCK_ID: &'static str = "cosmos:multiplier";
+
+    fn apply(left: i32, right: i32) -> i32 {
+        left.wrapping_mul(right)
+    }
+}
+
+/// Outputs 1 if `left > right`, 0 otherwise.
+struct GreaterThan;
+
+impl BinaryLogicOperation for GreaterThan {
+    const BLOCK_ID: &'static str = "cosmos:greater_than_comparator";
+
+    fn apply(left: i32, right: i32) -> i32 {
+        (left > right) as i32
+    }
+}
+
+/// Outputs 1 if `left < right`, 0 otherwise.
+struct LessThan;
+
+impl BinaryLogicOperation for LessThan {
+    const BLOCK_ID: &'static str = "cosmos:less_than_comparator";
+
+    fn apply(left: i32, right: i32) -> i32 {
+        (left < right) as i32
+    }
+}
+
+/// Outputs 1 if `left == right`, 0 otherwise.
+struct EqualTo;
+
+impl BinaryLogicOperation for EqualTo {
+    const BLOCK_ID: &'static str = "cosmos:equal_to_comparator";
+
+    fn apply(left: i32, right: i32) -> i32 {
+        (left == right) as i32
+    }
+}
+
+fn register_logic_connections<T: BinaryLogicOperation>(blocks: Res<Registry<Block>>, mut registry: ResMut<Registry<LogicBlock>>) {
+    if let Some(block) = blocks.from_id(T::BLOCK_ID) {
+        registry.register(LogicBlock::new(
+            block,
+            [
+                Some(LogicConnection::Port(PortType::Input)),
+                Some(LogicConnection::Port(PortType::Input)),
+                None,
+                None,
+                Some(LogicConnection::Port(PortType::Output)),
+                None,
+            ],
+        ));
+    }
+}
+
+fn binary_operation_input_event_listener<T: BinaryLogicOperation>(
+    mut evr_logic_input: MessageReader<LogicInputMessage>,
+    blocks: Res<Registry<Block>>,
+    mut q_logic_driver: Query<&mut LogicDriver>,
+    mut q_structure: Query<&mut Structure>,
+    mut q_logic_data: Query<&mut BlockLogicData>,
+    mut commands: Commands,
+    q_has_data: Query<(), With<BlockLogicData>>,
+    mut q_block_data: Query<&mut BlockData>,
+) {
+    for ev in evr_logic_input.read() {
+        let Ok(mut structure) = q_structure.get_mut(ev.block.structure()) else {
+            continue;
+        };
+        if structure.block_at(ev.block.coords(), &blocks).unlocalized_name() != T::BLOCK_ID {
+            continue;
+        }
+        let Ok(logic_driver) = q_logic_driver.get_mut(ev.block.structure()) else {
+            continue;
+        };
+
+        let coords = ev.block.coords();
+        let rotation = structure.block_rotation(ev.block.coords());
+        let left = logic_driver.read_input(coords, rotation.direction_of(BlockFace::Left));
+        let right = logic_driver.read_input(coords, rotation.direction_of(BlockFace::Right));
+        let new_state = BlockLogicData(T::apply(left, right));
+
+        if let Some(mut logic_data) = structure.query_block_data_mut(ev.block.coords(), &mut q_logic_data, &mut commands) {
+            if **logic_data != new_state {
+                **logic_data = new_state;
+            }
+        } else if new_state.0 != 0 {
+            structure.insert_block_data(coords, new_state, &mut commands, &mut q_block_data, &q_has_data);
+        }
+    }
+}
+
+fn binary_operation_output_event_listener<T: BinaryLogicOperation>(
+    evr_logic_output: MessageReader<LogicOutputMessage>,
+    evw_queue_logic_input: MessageWriter<QueueLogicInputMessage>,
+    logic_blocks: Res<Registry<LogicBlock>>,
+    blocks: Res<Registry<Block>>,
+    q_structure: Query<(&mut Structure, &mut LogicDriver)>,
+    q_logic_data: Query<&BlockLogicData>,
+) {
+    default_logic_block_output(
+        T::BLOCK_ID,
+        evr_logic_output,
+        evw_queue_logic_input,
+        &logic_blocks,
+        &blocks,
+        q_structure,
+        q_logic_data,
+    );
+}
+
+fn register_binary_operation<T: BinaryLogicOperation>(app: &mut App) {
+    app.add_systems(OnEnter(GameState::PostLoading), register_logic_connections::<T>)
+        .add_systems(
+            FixedUpdate,
+            binary_operation_input_event_listener::<T>
+                .in_set(LogicSystemSet::Consume)
+                .ambiguous_with(LogicSystemSet::Consume),
+        )
+        .add_systems(
+            FixedUpdate,
+            binary_operation_output_event_listener::<T>
+                .in_set(LogicSystemSet::Produce)
+                .ambiguous_with(LogicSystemSet::Produce),
+        );
+}
+
+pub(super) fn register(app: &mut App) {
+    register_binary_operation::<Adder>(app);
+    register_binary_operation::<Subtractor>(app);
+    register_binary_operation::<Multiplier>(app);
+    register_binary_operation::<GreaterThan>(app);
+    register_binary_operation::<LessThan>(app);
+    register_binary_operation::<EqualTo>(app);
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::structure::coordinates::BlockCoordinate;
+
+    use crate::logic::test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_PROBE, TEST_SOURCE};
+
+    use super::{Adder, BinaryLogicOperation, EqualTo, GreaterThan, LessThan, Multiplier, Subtractor};
+
+    const LEFT_INPUT: BlockCoordinate = BlockCoordinate::new(4, 5, 5);
+    const BLOCK: BlockCoordinate = BlockCoordinate::new(5, 5, 5);
+    const RIGHT_INPUT: BlockCoordinate = BlockCoordinate::new(6, 5, 5);
+    const OUTPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 4);
+
+    /// Each case is `(left, right, expected output)`.
+    fn check_operation<T: BinaryLogicOperation>(cases: &[(i32, i32, i32)]) {
+        let mut harness = LogicTestHarness::new(
+            &[
+                (LEFT_INPUT, TEST_SOURCE),
+                (BLOCK, T::BLOCK_ID),
+                (RIGHT_INPUT, TEST_SOURCE),
+                (OUTPUT, TEST_PROBE),
+            ],
+            super::register,
+        );
+
+        for &(left, right, expected) in cases {
+            harness.set_source(LEFT_INPUT, left);
+            harness.set_source(RIGHT_INPUT, right);
+            harness.step(SETTLE_TICKS);
+
+            assert_eq!(harness.probe(OUTPUT), expected, "{}: left: {left}, right: {right}", T::BLOCK_ID);
+        }
+    }
+
+    #[test]
+    fn test_adder() {
+        check_operation::<Adder>(&[(2, 3, 5), (-4, 1, -3), (i32::MAX, 1, i32::MIN)]);
+    }
+
+    #[test]
+    fn test_subtractor() {
+        check_operation::<Subtractor>(&[(7, 3, 4), (3, 7, -4), (i32::MIN, 1, i32::MAX)]);
+    }
+
+    #[test]
+    fn test_multiplier() {
+        check_operation::<Multiplier>(&[(6, 7, 42), (-3, 4, -12), (5, 0, 0)]);
+    }
+
+    #[test]
+    fn test_greater_than() {
+        check_operation::<GreaterThan>(&[(2, 1, 1), (1, 2, 0), (3, 3, 0), (0, -1, 1)]);
+    }
+
+    #[test]
+    fn test_less_than() {
+        check_operation::<LessThan>(&[(1, 2, 1), (2, 1, 0), (3, 3, 0), (-1, 0, 1)]);
+    }
+
+    #[test]
+    fn test_equal_to() {
+        check_operation::<EqualTo>(&[(3, 3, 1), (3, 4, 0), (-2, -2, 1)]);
+    }
+}
@@ -30,3 +30,133 @@ fn register_logic_connections(
 pub(super) fn register<T: States>(app: &mut App, post_loading_state: T) {
     app.add_systems(OnEnter(post_loading_state), register_logic_connections);
 }
+
+#[cfg(test)]
+mod test {
+    use bevy::app::App;
+    use cosmos_core::{
+        block::{block_direction::BlockDirection, block_rotation::BlockRotation},
+        state::GameState,
+        structure::coordinates::BlockCoordinate,
+    };
+
+    use crate::logic::{
+        specific_blocks::logic_bus,
+        test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_PROBE, TEST_SOURCE},
+    };
+
+    const RED: &str = "cosmos:logic_wire_red";
+    const BLUE: &str = "cosmos:logic_wire_blue";
+    const BUS: &str = "cosmos:logic_bus";
+
+    fn register_wires(app: &mut App) {
+        super::register(app, GameState::PostLoading);
+        logic_bus::register(app, GameState::PostLoading);
+    }
+
+    /// Builds a straight line of blocks along the x axis, starting at x = 1.
+    fn line(blocks: &[&'static str]) -> LogicTestHarness {
+        let layout = blocks
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| (BlockCoordinate::new(i as u64 + 1, 5, 5), id))
+            .collect::<Vec<_>>();
+
+        LogicTestHarness::new(&layout, register_wires)
+    }
+
+    fn at(x: u64) -> BlockCoordinate {
+        BlockCoordinate::new(x, 5, 5)
+    }
+
+    #[test]
+    fn test_wire_carries_signal() {
+        let mut harness = line(&[TEST_SOURCE, RED, RED, RED, TEST_PROBE]);
+
+        harness.set_source(at(1), 3);
+        harness.step(SETTLE_TICKS);
+        assert_eq!(harness.read_input(at(5), BlockDirection::NegX), 3);
+
+        harness.set_source(at(1), 0);
+        harness.step(SETTLE_TICKS);
+        assert_eq!(harness.read_input(at(5), BlockDirection::NegX), 0);
+    }
+
+    #[test]
+    fn test_different_colors_do_not_connect() {
+        let mut harness = line(&[TEST_SOURCE, RED, BLUE, TEST_PROBE]);
+
+        harness.set_source(at(1), 1);
+        harness.step(SETTLE_TICKS);
+        assert_eq!(harness.probe(at(4)), 0);
+    }
+
+    #[test]
+    fn test_bus_carries_each_color() {
+        let mut harness = line(&[TEST_SOURCE, RED, BUS, BUS, RED, TEST_PROBE]);
+
+        harness.set_source(at(1), 2);
+        harness.step(SETTLE_TICKS);
+        assert_eq!(harness.probe(at(6)), 2);
+    }
+
+    #[test]
+    fn test_bus_does_not_mix_colors() {
+        let mut harness = line(&[TEST_SOURCE, RED, BUS, BLUE, TEST_PROBE]);
+
+        harness.set_source(at(1), 2);
+        harness.step(SETTLE_TICKS);
+        assert_eq!(harness.probe(at(5)), 0);
+    }
+
+    #[test]
+    fn test_bus_does_not_connect_to_ports() {
+        let mut harness = line(&[TEST_SOURCE, BUS, TEST_PROBE]);
+
+        harness.set_source(at(1), 1);
+        harness.step(SETTLE_TICKS);
+        assert_eq!(harness.probe(at(3)), 0);
+    }
+
+    #[test]
+    fn test_groups_merge_and_split() {
+        // Two wires with a gap between them, each with its own source and a probe above it.
+        let left_probe = BlockCoordinate::new(2, 6, 5);
+        let right_probe = BlockCoordinate::new(4, 6, 5);
+        let mut harness = LogicTestHarness::new(
+            &[
+                (at(1), TEST_SOURCE),
+                (at(2), RED),
+                (left_probe, TEST_PROBE),
+                (at(4), RED),
+                (at(5), TEST_SOURCE),
+                (right_probe, TEST_PROBE),
+            ],
+            register_wires,
+        );
+
+        harness.set_source(at(1), 3);
+        harness.set_source(at(5), 4);
+        harness.step(SETTLE_TICKS);
+        assert_eq!(harness.probe(left_probe), 3);
+        assert_eq!(harness.probe(right_probe), 4);
+
+        // Filling the gap merges both groups, so both probes see the sum of both sources.
+        harness.place(at(3), RED, BlockRotation::IDENTITY);
+        harness.step(SETTLE_TICKS);
+        assert_eq!(harness.probe(left_probe), 7);
+        assert_eq!(harness.probe(right_probe), 7);
+
+        // Removing it again splits them back apart.
+        harness.remove(at(3));
+        harness.step(SETTLE_TICKS);
+        assert_eq!(harness.probe(left_probe), 3);
+        assert_eq!(harness.probe(right_probe), 4);
+
+        // Removing a source only affects the group it was in.
+        harness.remove(at(5));
+        harness.step(SETTLE_TICKS);
+        assert_eq!(harness.probe(left_probe), 3);
+        assert_eq!(harness.probe(right_probe), 0);
+    }
+}
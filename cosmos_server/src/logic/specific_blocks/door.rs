@@ -0,0 +1,50 @@
+use bevy::{
+    app::App,
+    prelude::{OnEnter, Res, ResMut, States},
+};
+
+use cosmos_core::{block::Block, registry::Registry};
+
+use crate::logic::{LogicBlock, LogicConnection, PortType};
+
+fn register_logic_connections_for_door(blocks: Res<Registry<Block>>, mut registry: ResMut<Registry<LogicBlock>>) {
+    if let Some(block) = blocks.from_id("cosmos:door") {
+        registry.register(LogicBlock::new(block, [Some(LogicConnection::Port(PortType::Input)); 6]));
+    }
+
+    if let Some(block) = blocks.from_id("cosmos:door_open") {
+        registry.register(LogicBlock::new(block, [Some(LogicConnection::Port(PortType::Input)); 6]));
+    }
+}
+
+pub(super) fn register<T: States>(app: &mut App, post_loading_state: T) {
+    app.add_systems(OnEnter(post_loading_state), register_logic_connections_for_door);
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::{block::block_rotation::BlockRotation, state::GameState, structure::coordinates::BlockCoordinate};
+
+    use crate::logic::test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_SOURCE};
+
+    const DOOR: BlockCoordinate = BlockCoordinate::new(5, 5, 5);
+    const OTHER_DOOR: BlockCoordinate = BlockCoordinate::new(5, 6, 5);
+    const SOURCE: BlockCoordinate = BlockCoordinate::new(4, 5, 5);
+
+    #[test]
+    fn doors_are_only_driven_when_wired() {
+        let mut harness = LogicTestHarness::new(&[(DOOR, "cosmos:door"), (OTHER_DOOR, "cosmos:door_open")], |app| {
+            super::register(app, GameState::PostLoading)
+        });
+
+        // Neighboring doors only have inputs, so nothing can drive them
+        assert!(!harness.has_connected_input(DOOR));
+        assert!(!harness.has_connected_input(OTHER_DOOR));
+
+        harness.place(SOURCE, TEST_SOURCE, BlockRotation::IDENTITY);
+        harness.step(SETTLE_TICKS);
+
+        assert!(harness.has_connected_input(DOOR));
+        assert!(!harness.has_connected_input(OTHER_DOOR));
+    }
+}
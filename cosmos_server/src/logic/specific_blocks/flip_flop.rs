@@ -90,8 +90,8 @@ fn flip_flop_input_event_listener(
         }
 
         if let Some(mut old_data) = structure.query_block_data_mut(ev.block.coords(), &mut q_logic_data, &mut commands) {
-            if **old_data != new_state {
-                **old_data = new_state;
+            if **old_data != logic_data {
+                **old_data = logic_data;
             }
         } else if logic_data.0 != 0 {
             structure.insert_block_data(coords, logic_data, &mut commands, &mut q_block_data, &q_has_data);
@@ -137,3 +137,31 @@ pub(super) fn register(app: &mut App) {
                 .ambiguous_with(LogicSystemSet::Produce),
         );
 }
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::structure::coordinates::BlockCoordinate;
+
+    use crate::logic::test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_PROBE, TEST_SOURCE};
+
+    const INPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 4);
+    const FLIP_FLOP: BlockCoordinate = BlockCoordinate::new(5, 5, 5);
+    const OUTPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 6);
+
+    #[test]
+    fn test_flip_flop_toggles_on_rising_edges() {
+        let mut harness = LogicTestHarness::new(
+            &[(INPUT, TEST_SOURCE), (FLIP_FLOP, super::BLOCK_ID), (OUTPUT, TEST_PROBE)],
+            super::register,
+        );
+
+        assert_eq!(harness.probe(OUTPUT), 0);
+
+        for (input, expected) in [(1, 1), (0, 1), (1, 0), (0, 0), (1, 1), (1, 1), (0, 1)] {
+            harness.set_source(INPUT, input);
+            harness.step(SETTLE_TICKS);
+
+            assert_eq!(harness.probe(OUTPUT), expected, "input: {input}");
+        }
+    }
+}
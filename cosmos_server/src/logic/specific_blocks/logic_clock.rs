@@ -0,0 +1,152 @@
+//! Logic behavior for the "Logic Clock", a block that outputs a single-tick pulse on all 6 faces
+//! every [`LogicTimerPeriod`] logic ticks.
+
+use bevy::prelude::*;
+
+use cosmos_core::{
+    block::{Block, data::BlockData},
+    logic::{BlockLogicData, timer::LogicTimerPeriod},
+    netty::sync::IdentifiableComponent,
+    registry::Registry,
+    state::GameState,
+    structure::Structure,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blocks::data::utils::add_default_block_data_for_block,
+    logic::{
+        LogicBlock, LogicConnection, LogicOutputMessage, LogicSystemSet, PortType, QueueLogicInputMessage, default_logic_block_output,
+        logic_driver::LogicDriver,
+    },
+    persistence::make_persistent::{DefaultPersistentComponent, make_persistent},
+};
+
+const BLOCK_ID: &str = "cosmos:logic_clock";
+
+#[derive(Component, Debug, Default, Serialize, Deserialize)]
+/// The number of logic ticks since this clock last pulsed.
+struct ClockTicks(u32);
+
+impl IdentifiableComponent for ClockTicks {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:clock_ticks"
+    }
+}
+
+impl DefaultPersistentComponent for ClockTicks {}
+
+fn register_logic_connections(blocks: Res<Registry<Block>>, mut registry: ResMut<Registry<LogicBlock>>) {
+    if let Some(block) = blocks.from_id(BLOCK_ID) {
+        registry.register(LogicBlock::new(block, [Some(LogicConnection::Port(PortType::Output)); 6]));
+    }
+}
+
+fn tick_clocks(
+    mut q_clock: Query<(Entity, &LogicTimerPeriod, &mut ClockTicks)>,
+    mut q_block_data: Query<&mut BlockData>,
+    mut q_structure: Query<&mut Structure>,
+    mut q_logic_data: Query<&mut BlockLogicData>,
+    q_has_data: Query<(), With<BlockLogicData>>,
+    mut commands: Commands,
+) {
+    for (ent, period, mut ticks) in q_clock.iter_mut() {
+        let Ok(bd) = q_block_data.get(ent).copied() else {
+            continue;
+        };
+
+        ticks.0 += 1;
+        let pulse = ticks.0 >= period.ticks();
+        if pulse {
+            ticks.0 = 0;
+        }
+
+        let Ok(mut structure) = q_structure.get_mut(bd.structure()) else {
+            continue;
+        };
+
+        let coords = bd.coords();
+        let new_state = BlockLogicData(pulse as i32);
+
+        if let Some(mut logic_data) = structure.query_block_data_mut(coords, &mut q_logic_data, &mut commands) {
+            if **logic_data != new_state {
+                **logic_data = new_state;
+            }
+        } else if new_state.0 != 0 {
+            structure.insert_block_data(coords, new_state, &mut commands, &mut q_block_data, &q_has_data);
+        }
+    }
+}
+
+fn logic_clock_output_event_listener(
+    evr_logic_output: MessageReader<LogicOutputMessage>,
+    evw_queue_logic_input: MessageWriter<QueueLogicInputMessage>,
+    logic_blocks: Res<Registry<LogicBlock>>,
+    blocks: Res<Registry<Block>>,
+    q_structure: Query<(&mut Structure, &mut LogicDriver)>,
+    q_logic_data: Query<&BlockLogicData>,
+) {
+    default_logic_block_output(
+        BLOCK_ID,
+        evr_logic_output,
+        evw_queue_logic_input,
+        &logic_blocks,
+        &blocks,
+        q_structure,
+        q_logic_data,
+    );
+}
+
+/// Registers how this block behaves in a logic circuit, without any of the block data it needs
+fn register_logic(app: &mut App) {
+    app.add_systems(OnEnter(GameState::PostLoading), register_logic_connections)
+        .add_systems(
+            FixedUpdate,
+            (
+                tick_clocks.in_set(LogicSystemSet::PreLogicTick),
+                logic_clock_output_event_listener
+                    .in_set(LogicSystemSet::Produce)
+                    .ambiguous_with(LogicSystemSet::Produce),
+            ),
+        );
+}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<ClockTicks>(app);
+
+    add_default_block_data_for_block::<LogicTimerPeriod>(app, |_, _| LogicTimerPeriod::default(), BLOCK_ID);
+    add_default_block_data_for_block::<ClockTicks>(app, |_, _| ClockTicks::default(), BLOCK_ID);
+
+    register_logic(app);
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::{logic::timer::LogicTimerPeriod, structure::coordinates::BlockCoordinate};
+
+    use crate::logic::test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_PROBE};
+
+    use super::ClockTicks;
+
+    const CLOCK: BlockCoordinate = BlockCoordinate::new(5, 5, 5);
+    const OUTPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 4);
+
+    #[test]
+    fn test_clock_pulses_every_period() {
+        let mut harness = LogicTestHarness::new(&[(CLOCK, super::BLOCK_ID), (OUTPUT, TEST_PROBE)], super::register_logic);
+        harness.insert_block_data(CLOCK, LogicTimerPeriod::new(4));
+        harness.insert_block_data(CLOCK, ClockTicks::default());
+        harness.step(SETTLE_TICKS);
+
+        let pulses = (0..40)
+            .filter(|_| {
+                harness.step(1);
+                harness.probe(OUTPUT) != 0
+            })
+            .collect::<Vec<_>>();
+
+        // Each pulse lasts a single tick
+        assert_eq!(pulses.len(), 10, "pulses: {pulses:?}");
+        assert!(pulses.windows(2).all(|w| w[1] - w[0] == 4), "pulses: {pulses:?}");
+    }
+}
@@ -0,0 +1,152 @@
+//! Logic behavior for the "Logic Constant", a block that outputs a configurable signal on all 6 faces.
+//!
+//! The signal is stored as the block's [`BlockLogicData`] and is set by the client via [`SetLogicConstant`].
+
+use bevy::prelude::*;
+
+use cosmos_core::{
+    block::{Block, data::BlockData},
+    ecs::sets::FixedUpdateSet,
+    logic::{BlockLogicData, constant::SetLogicConstant},
+    netty::{
+        server::ServerLobby,
+        sync::events::server_event::{NettyMessageReceived, NettyMessageWriter},
+    },
+    notifications::Notification,
+    registry::{Registry, identifiable::Identifiable},
+    state::GameState,
+    structure::Structure,
+};
+
+use crate::{
+    logic::{
+        LogicBlock, LogicConnection, LogicOutputMessage, LogicSystemSet, PortType, QueueLogicInputMessage, default_logic_block_output,
+        logic_driver::LogicDriver,
+    },
+    structure::ownership::StructurePermissions,
+};
+
+const BLOCK_ID: &str = "cosmos:logic_constant";
+
+fn register_logic_connections(blocks: Res<Registry<Block>>, mut registry: ResMut<Registry<LogicBlock>>) {
+    if let Some(block) = blocks.from_id(BLOCK_ID) {
+        registry.register(LogicBlock::new(block, [Some(LogicConnection::Port(PortType::Output)); 6]));
+    }
+}
+
+fn on_set_logic_constant(
+    mut nevr_set_constant: MessageReader<NettyMessageReceived<SetLogicConstant>>,
+    lobby: Res<ServerLobby>,
+    blocks: Res<Registry<Block>>,
+    mut q_structure: Query<&mut Structure>,
+    mut q_logic_data: Query<&mut BlockLogicData>,
+    q_has_data: Query<(), With<BlockLogicData>>,
+    mut q_block_data: Query<&mut BlockData>,
+    permissions: StructurePermissions,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    mut commands: Commands,
+) {
+    for ev in nevr_set_constant.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            error!("Bad player ({})!", ev.client_id);
+            continue;
+        };
+
+        if let Err(denied) = permissions.check_build(player, ev.block.structure()) {
+            nevw_notification.write(Notification::error(denied.message()), ev.client_id);
+            continue;
+        }
+
+        let Ok(mut structure) = q_structure.get_mut(ev.block.structure()) else {
+            warn!("Tried to set logic constant on non-valid structure!");
+            continue;
+        };
+
+        let coords = ev.block.coords();
+        if structure.block_at(coords, &blocks).unlocalized_name() != BLOCK_ID {
+            warn!("Tried to set logic constant on non-constant block!");
+            continue;
+        }
+
+        let new_state = BlockLogicData(ev.value);
+
+        if let Some(mut logic_data) = structure.query_block_data_mut(coords, &mut q_logic_data, &mut commands) {
+            if **logic_data != new_state {
+                **logic_data = new_state;
+            }
+        } else if new_state.0 != 0 {
+            structure.insert_block_data(coords, new_state, &mut commands, &mut q_block_data, &q_has_data);
+        }
+    }
+}
+
+fn logic_constant_output_event_listener(
+    evr_logic_output: MessageReader<LogicOutputMessage>,
+    evw_queue_logic_input: MessageWriter<QueueLogicInputMessage>,
+    logic_blocks: Res<Registry<LogicBlock>>,
+    blocks: Res<Registry<Block>>,
+    q_structure: Query<(&mut Structure, &mut LogicDriver)>,
+    q_logic_data: Query<&BlockLogicData>,
+) {
+    default_logic_block_output(
+        BLOCK_ID,
+        evr_logic_output,
+        evw_queue_logic_input,
+        &logic_blocks,
+        &blocks,
+        q_structure,
+        q_logic_data,
+    );
+}
+
+/// Registers how this block behaves in a logic circuit, without the client being able to configure it
+fn register_logic(app: &mut App) {
+    app.add_systems(OnEnter(GameState::PostLoading), register_logic_connections)
+        .add_systems(
+            FixedUpdate,
+            logic_constant_output_event_listener
+                .in_set(LogicSystemSet::Produce)
+                .ambiguous_with(LogicSystemSet::Produce),
+        );
+}
+
+pub(super) fn register(app: &mut App) {
+    register_logic(app);
+
+    app.add_systems(
+        FixedUpdate,
+        on_set_logic_constant
+            .in_set(FixedUpdateSet::Main)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::{logic::BlockLogicData, structure::coordinates::BlockCoordinate};
+
+    use crate::logic::test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_PROBE};
+
+    const CONSTANT: BlockCoordinate = BlockCoordinate::new(5, 5, 5);
+    const FRONT_OUTPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 4);
+    const TOP_OUTPUT: BlockCoordinate = BlockCoordinate::new(5, 6, 5);
+
+    #[test]
+    fn test_constant_outputs_its_signal() {
+        let mut harness = LogicTestHarness::new(
+            &[(CONSTANT, super::BLOCK_ID), (FRONT_OUTPUT, TEST_PROBE), (TOP_OUTPUT, TEST_PROBE)],
+            super::register_logic,
+        );
+
+        assert_eq!(harness.probe(FRONT_OUTPUT), 0);
+
+        for signal in [42, -7, 0] {
+            // This is what setting the constant from the client does
+            harness.insert_block_data(CONSTANT, BlockLogicData(signal));
+            harness.step(SETTLE_TICKS);
+
+            assert_eq!(harness.probe(FRONT_OUTPUT), signal);
+            assert_eq!(harness.probe(TOP_OUTPUT), signal);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Logic behavior for the "Logic Delay", a block with a back input and a front output.
+//! Outputs the signal its input received [`LogicTimerPeriod`] logic ticks ago.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use cosmos_core::{
+    block::{Block, block_face::BlockFace, data::BlockData},
+    logic::{BlockLogicData, timer::LogicTimerPeriod},
+    netty::sync::IdentifiableComponent,
+    registry::Registry,
+    state::GameState,
+    structure::Structure,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blocks::data::utils::add_default_block_data_for_block,
+    logic::{
+        LogicBlock, LogicConnection, LogicOutputMessage, LogicSystemSet, PortType, QueueLogicInputMessage, default_logic_block_output,
+        logic_driver::LogicDriver,
+    },
+    persistence::make_persistent::{DefaultPersistentComponent, make_persistent},
+};
+
+const BLOCK_ID: &str = "cosmos:logic_delay";
+
+#[derive(Component, Debug, Default, Serialize, Deserialize)]
+/// Every input signal this delay line has received that has not been output yet, oldest first.
+struct DelayedSignals(VecDeque<i32>);
+
+impl DelayedSignals {
+    /// Drops the oldest signals until no more than `ticks` are being stored.
+    fn truncate(&mut self, ticks: u32) {
+        while self.0.len() > ticks as usize {
+            self.0.pop_front();
+        }
+    }
+}
+
+impl IdentifiableComponent for DelayedSignals {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:delayed_signals"
+    }
+}
+
+impl DefaultPersistentComponent for DelayedSignals {}
+
+fn register_logic_connections(blocks: Res<Registry<Block>>, mut registry: ResMut<Registry<LogicBlock>>) {
+    if let Some(block) = blocks.from_id(BLOCK_ID) {
+        registry.register(LogicBlock::new(
+            block,
+            [
+                None,
+                None,
+                None,
+                None,
+                Some(LogicConnection::Port(PortType::Output)),
+                Some(LogicConnection::Port(PortType::Input)),
+            ],
+        ));
+    }
+}
+
+fn tick_delays(
+    mut q_delay: Query<(Entity, &LogicTimerPeriod, &mut DelayedSignals)>,
+    mut q_block_data: Query<&mut BlockData>,
+    mut q_structure: Query<(&mut Structure, &LogicDriver)>,
+    mut q_logic_data: Query<&mut BlockLogicData>,
+    q_has_data: Query<(), With<BlockLogicData>>,
+    mut commands: Commands,
+) {
+    for (ent, period, mut delayed) in q_delay.iter_mut() {
+        let Ok(bd) = q_block_data.get(ent).copied() else {
+            continue;
+        };
+
+        let Ok((mut structure, logic_driver)) = q_structure.get_mut(bd.structure()) else {
+            continue;
+        };
+
+        let coords = bd.coords();
+        let rotation = structure.block_rotation(coords);
+        let input = logic_driver.read_input(coords, rotation.direction_of(BlockFace::Back));
+
+        delayed.0.push_back(input);
+        let output = if delayed.0.len() > period.ticks() as usize {
+            delayed.0.pop_front().unwrap_or_default()
+        } else {
+            0
+        };
+        delayed.truncate(period.ticks());
+
+        let new_state = BlockLogicData(output);
+
+        if let Some(mut logic_data) = structure.query_block_data_mut(coords, &mut q_logic_data, &mut commands) {
+            if **logic_data != new_state {
+                **logic_data = new_state;
+            }
+        } else if new_state.0 != 0 {
+            structure.insert_block_data(coords, new_state, &mut commands, &mut q_block_data, &q_has_data);
+        }
+    }
+}
+
+fn logic_delay_output_event_listener(
+    evr_logic_output: MessageReader<LogicOutputMessage>,
+    evw_queue_logic_input: MessageWriter<QueueLogicInputMessage>,
+    logic_blocks: Res<Registry<LogicBlock>>,
+    blocks: Res<Registry<Block>>,
+    q_structure: Query<(&mut Structure, &mut LogicDriver)>,
+    q_logic_data: Query<&BlockLogicData>,
+) {
+    default_logic_block_output(
+        BLOCK_ID,
+        evr_logic_output,
+        evw_queue_logic_input,
+        &logic_blocks,
+        &blocks,
+        q_structure,
+        q_logic_data,
+    );
+}
+
+/// Registers how this block behaves in a logic circuit, without any of the block data it needs
+fn register_logic(app: &mut App) {
+    app.add_systems(OnEnter(GameState::PostLoading), register_logic_connections)
+        .add_systems(
+            FixedUpdate,
+            (
+                tick_delays.in_set(LogicSystemSet::PreLogicTick),
+                logic_delay_output_event_listener
+                    .in_set(LogicSystemSet::Produce)
+                    .ambiguous_with(LogicSystemSet::Produce),
+            ),
+        );
+}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<DelayedSignals>(app);
+
+    add_default_block_data_for_block::<LogicTimerPeriod>(app, |_, _| LogicTimerPeriod::default(), BLOCK_ID);
+    add_default_block_data_for_block::<DelayedSignals>(app, |_, _| DelayedSignals::default(), BLOCK_ID);
+
+    register_logic(app);
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::{logic::timer::LogicTimerPeriod, structure::coordinates::BlockCoordinate};
+
+    use crate::logic::test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_PROBE, TEST_SOURCE};
+
+    use super::DelayedSignals;
+
+    const INPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 6);
+    const DELAY: BlockCoordinate = BlockCoordinate::new(5, 5, 5);
+    const OUTPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 4);
+
+    #[test]
+    fn test_delay_outputs_past_signals() {
+        const PERIOD: usize = 6;
+
+        let mut harness = LogicTestHarness::new(
+            &[(INPUT, TEST_SOURCE), (DELAY, super::BLOCK_ID), (OUTPUT, TEST_PROBE)],
+            super::register_logic,
+        );
+        harness.insert_block_data(DELAY, LogicTimerPeriod::new(PERIOD as u32));
+        harness.insert_block_data(DELAY, DelayedSignals::default());
+        harness.step(SETTLE_TICKS);
+
+        let inputs = [3, 3, -8, 0, 5];
+        let mut outputs = vec![];
+        for tick in 0..inputs.len() + PERIOD + SETTLE_TICKS {
+            harness.set_source(INPUT, inputs.get(tick).copied().unwrap_or(0));
+            harness.step(1);
+            outputs.push(harness.probe(OUTPUT));
+        }
+
+        // The inputs come out in the same order, but only once the delay has passed
+        let first = outputs
+            .iter()
+            .position(|&signal| signal != 0)
+            .expect("The delay should output its inputs.");
+        assert!(first >= PERIOD, "outputs: {outputs:?}");
+        assert_eq!(outputs[first..first + inputs.len()], inputs, "outputs: {outputs:?}");
+        assert!(
+            outputs[first + inputs.len()..].iter().all(|&signal| signal == 0),
+            "outputs: {outputs:?}"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Logic behavior for the "Logic Memory", a block that stores [`LOGIC_MEMORY_SIZE`] integer signals.
+//!
+//! - Left: The address to read from or write to. Addresses outside the memory read as `0` and ignore writes.
+//! - Right: The signal to write.
+//! - Top: While this is on, the right input is written to the current address.
+//! - Front: Outputs the signal stored at the current address.
+
+use bevy::prelude::*;
+
+use cosmos_core::{
+    block::{Block, block_face::BlockFace, data::BlockData},
+    logic::BlockLogicData,
+    netty::sync::IdentifiableComponent,
+    registry::{Registry, identifiable::Identifiable},
+    state::GameState,
+    structure::Structure,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blocks::data::utils::add_default_block_data_for_block,
+    logic::{
+        LogicBlock, LogicConnection, LogicInputMessage, LogicOutputMessage, LogicSystemSet, PortType, QueueLogicInputMessage,
+        default_logic_block_output, logic_driver::LogicDriver,
+    },
+    persistence::make_persistent::{DefaultPersistentComponent, make_persistent},
+};
+
+const BLOCK_ID: &str = "cosmos:logic_memory";
+
+/// The number of signals a single memory block can store.
+const LOGIC_MEMORY_SIZE: usize = 256;
+
+#[derive(Component, Debug, Serialize, Deserialize)]
+/// The signals stored in a memory block, indexed by address.
+struct LogicMemory(Vec<i32>);
+
+impl Default for LogicMemory {
+    fn default() -> Self {
+        Self(vec![0; LOGIC_MEMORY_SIZE])
+    }
+}
+
+impl LogicMemory {
+    fn read(&self, address: i32) -> i32 {
+        usize::try_from(address)
+            .ok()
+            .and_then(|address| self.0.get(address))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn write(&mut self, address: i32, signal: i32) {
+        if let Some(cell) = usize::try_from(address).ok().and_then(|address| self.0.get_mut(address)) {
+            *cell = signal;
+        }
+    }
+}
+
+impl IdentifiableComponent for LogicMemory {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:logic_memory"
+    }
+}
+
+impl DefaultPersistentComponent for LogicMemory {}
+
+fn register_logic_connections(blocks: Res<Registry<Block>>, mut registry: ResMut<Registry<LogicBlock>>) {
+    if let Some(block) = blocks.from_id(BLOCK_ID) {
+        registry.register(LogicBlock::new(
+            block,
+            [
+                Some(LogicConnection::Port(PortType::Input)),
+                Some(LogicConnection::Port(PortType::Input)),
+                Some(LogicConnection::Port(PortType::Input)),
+                None,
+                Some(LogicConnection::Port(PortType::Output)),
+                None,
+            ],
+        ));
+    }
+}
+
+fn memory_input_event_listener(
+    mut evr_logic_input: MessageReader<LogicInputMessage>,
+    blocks: Res<Registry<Block>>,
+    q_logic_driver: Query<&LogicDriver>,
+    mut q_structure: Query<&mut Structure>,
+    mut q_memory: Query<&mut LogicMemory>,
+    mut q_logic_data: Query<&mut BlockLogicData>,
+    mut commands: Commands,
+    q_has_data: Query<(), With<BlockLogicData>>,
+    mut q_block_data: Query<&mut BlockData>,
+) {
+    for ev in evr_logic_input.read() {
+        let Ok(mut structure) = q_structure.get_mut(ev.block.structure()) else {
+            continue;
+        };
+        if structure.block_at(ev.block.coords(), &blocks).unlocalized_name() != BLOCK_ID {
+            continue;
+        }
+        let Ok(logic_driver) = q_logic_driver.get(ev.block.structure()) else {
+            continue;
+        };
+
+        let coords = ev.block.coords();
+        let rotation = structure.block_rotation(coords);
+        let address = logic_driver.read_input(coords, rotation.direction_of(BlockFace::Left));
+        let signal = logic_driver.read_input(coords, rotation.direction_of(BlockFace::Right));
+        let write_enabled = logic_driver.read_input(coords, rotation.direction_of(BlockFace::Top)) != 0;
+
+        let new_state = {
+            let Some(mut memory) = structure.query_block_data_mut(coords, &mut q_memory, &mut commands) else {
+                continue;
+            };
+
+            if write_enabled && memory.read(address) != signal {
+                memory.write(address, signal);
+            }
+
+            BlockLogicData(memory.read(address))
+        };
+
+        if let Some(mut logic_data) = structure.query_block_data_mut(coords, &mut q_logic_data, &mut commands) {
+            if **logic_data != new_state {
+                **logic_data = new_state;
+            }
+        } else if new_state.0 != 0 {
+            structure.insert_block_data(coords, new_state, &mut commands, &mut q_block_data, &q_has_data);
+        }
+    }
+}
+
+fn memory_output_event_listener(
+    evr_logic_output: MessageReader<LogicOutputMessage>,
+    evw_queue_logic_input: MessageWriter<QueueLogicInputMessage>,
+    logic_blocks: Res<Registry<LogicBlock>>,
+    blocks: Res<Registry<Block>>,
+    q_structure: Query<(&mut Structure, &mut LogicDriver)>,
+    q_logic_data: Query<&BlockLogicData>,
+) {
+    default_logic_block_output(
+        BLOCK_ID,
+        evr_logic_output,
+        evw_queue_logic_input,
+        &logic_blocks,
+        &blocks,
+        q_structure,
+        q_logic_data,
+    );
+}
+
+/// Registers how this block behaves in a logic circuit, without any of the block data it needs
+fn register_logic(app: &mut App) {
+    app.add_systems(OnEnter(GameState::PostLoading), register_logic_connections)
+        .add_systems(
+            FixedUpdate,
+            memory_input_event_listener
+                .in_set(LogicSystemSet::Consume)
+                .ambiguous_with(LogicSystemSet::Consume),
+        )
+        .add_systems(
+            FixedUpdate,
+            memory_output_event_listener
+                .in_set(LogicSystemSet::Produce)
+                .ambiguous_with(LogicSystemSet::Produce),
+        );
+}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<LogicMemory>(app);
+
+    add_default_block_data_for_block::<LogicMemory>(app, |_, _| LogicMemory::default(), BLOCK_ID);
+
+    register_logic(app);
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::structure::coordinates::BlockCoordinate;
+
+    use crate::logic::test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_PROBE, TEST_SOURCE};
+
+    use super::{LOGIC_MEMORY_SIZE, LogicMemory};
+
+    const ADDRESS: BlockCoordinate = BlockCoordinate::new(4, 5, 5);
+    const MEMORY: BlockCoordinate = BlockCoordinate::new(5, 5, 5);
+    const SIGNAL: BlockCoordinate = BlockCoordinate::new(6, 5, 5);
+    const WRITE: BlockCoordinate = BlockCoordinate::new(5, 6, 5);
+    const OUTPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 4);
+
+    #[test]
+    fn test_memory_reads_and_writes_addresses() {
+        let mut harness = LogicTestHarness::new(
+            &[
+                (ADDRESS, TEST_SOURCE),
+                (MEMORY, super::BLOCK_ID),
+                (SIGNAL, TEST_SOURCE),
+                (WRITE, TEST_SOURCE),
+                (OUTPUT, TEST_PROBE),
+            ],
+            super::register_logic,
+        );
+        harness.insert_block_data(MEMORY, LogicMemory::default());
+        harness.step(SETTLE_TICKS);
+
+        let out_of_bounds = LOGIC_MEMORY_SIZE as i32;
+
+        // (address, signal, write, expected output)
+        for (address, signal, write, expected) in [
+            (3, 42, 0, 0),
+            (3, 42, 1, 42),
+            (3, 42, 0, 42),
+            (4, 42, 0, 0),
+            (4, -5, 1, -5),
+            (4, -5, 0, -5),
+            (3, -5, 0, 42),
+            (-1, -5, 0, 0),
+            (-1, 9, 1, 0),
+            (out_of_bounds, 9, 1, 0),
+            (out_of_bounds, 9, 0, 0),
+            (4, 9, 0, -5),
+        ] {
+            harness.set_source(ADDRESS, address);
+            harness.set_source(SIGNAL, signal);
+            harness.set_source(WRITE, write);
+            harness.step(SETTLE_TICKS);
+
+            assert_eq!(
+                harness.probe(OUTPUT),
+                expected,
+                "address: {address}, signal: {signal}, write: {write}"
+            );
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Logic behavior for the "Register", a block that stores an integer signal.
+//!
+//! When the clock input on its left face goes from off to on, the register latches the signal on its back
+//! input and outputs it from its front face until the next rising clock edge.
+
+use bevy::prelude::*;
+
+use cosmos_core::{
+    block::{Block, block_face::BlockFace, data::BlockData},
+    events::block_events::BlockDataChangedMessage,
+    logic::BlockLogicData,
+    registry::{Registry, identifiable::Identifiable},
+    state::GameState,
+    structure::Structure,
+};
+
+use crate::logic::{
+    LOGIC_BIT, LogicBlock, LogicConnection, LogicInputMessage, LogicOutputMessage, LogicSystemSet, PortType, QueueLogicInputMessage,
+    default_logic_block_output, logic_driver::LogicDriver,
+};
+
+const BLOCK_ID: &str = "cosmos:logic_register";
+
+/// Stores whether the clock input was on the last time this register's inputs changed.
+const LAST_CLOCK_BIT: u8 = LOGIC_BIT >> 1;
+
+fn register_logic_connections(blocks: Res<Registry<Block>>, mut registry: ResMut<Registry<LogicBlock>>) {
+    if let Some(block) = blocks.from_id(BLOCK_ID) {
+        registry.register(LogicBlock::new(
+            block,
+            [
+                None,
+                Some(LogicConnection::Port(PortType::Input)),
+                None,
+                None,
+                Some(LogicConnection::Port(PortType::Output)),
+                Some(LogicConnection::Port(PortType::Input)),
+            ],
+        ));
+    }
+}
+
+fn register_input_event_listener(
+    mut evr_logic_input: MessageReader<LogicInputMessage>,
+    blocks: Res<Registry<Block>>,
+    q_logic_driver: Query<&LogicDriver>,
+    mut q_structure: Query<&mut Structure>,
+    mut q_logic_data: Query<&mut BlockLogicData>,
+    mut commands: Commands,
+    q_has_data: Query<(), With<BlockLogicData>>,
+    mut q_block_data: Query<&mut BlockData>,
+    mut mw_block_data_changed: MessageWriter<BlockDataChangedMessage>,
+) {
+    for ev in evr_logic_input.read() {
+        let Ok(mut structure) = q_structure.get_mut(ev.block.structure()) else {
+            continue;
+        };
+        if structure.block_at(ev.block.coords(), &blocks).unlocalized_name() != BLOCK_ID {
+            continue;
+        }
+        let Ok(logic_driver) = q_logic_driver.get(ev.block.structure()) else {
+            continue;
+        };
+
+        let coords = ev.block.coords();
+        let rotation = structure.block_rotation(coords);
+        let clock_on = logic_driver.read_input(coords, rotation.direction_of(BlockFace::Left)) != 0;
+
+        let mut data = structure.block_info_at(coords);
+        let og_data = data;
+        let rising_edge = clock_on && data.0 & LAST_CLOCK_BIT == 0;
+
+        if clock_on {
+            data.0 |= LAST_CLOCK_BIT;
+        } else {
+            data.0 &= !LAST_CLOCK_BIT;
+        }
+
+        if og_data != data {
+            structure.set_block_info_at(coords, data, &mut mw_block_data_changed);
+        }
+
+        if !rising_edge {
+            continue;
+        }
+
+        let new_state = BlockLogicData(logic_driver.read_input(coords, rotation.direction_of(BlockFace::Back)));
+
+        if let Some(mut logic_data) = structure.query_block_data_mut(coords, &mut q_logic_data, &mut commands) {
+            if **logic_data != new_state {
+                **logic_data = new_state;
+            }
+        } else if new_state.0 != 0 {
+            structure.insert_block_data(coords, new_state, &mut commands, &mut q_block_data, &q_has_data);
+        }
+    }
+}
+
+fn register_output_event_listener(
+    evr_logic_output: MessageReader<LogicOutputMessage>,
+    evw_queue_logic_input: MessageWriter<QueueLogicInputMessage>,
+    logic_blocks: Res<Registry<LogicBlock>>,
+    blocks: Res<Registry<Block>>,
+    q_structure: Query<(&mut Structure, &mut LogicDriver)>,
+    q_logic_data: Query<&BlockLogicData>,
+) {
+    default_logic_block_output(
+        BLOCK_ID,
+        evr_logic_output,
+        evw_queue_logic_input,
+        &logic_blocks,
+        &blocks,
+        q_structure,
+        q_logic_data,
+    );
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(OnEnter(GameState::PostLoading), register_logic_connections)
+        .add_systems(
+            FixedUpdate,
+            register_input_event_listener
+                .in_set(LogicSystemSet::Consume)
+                .ambiguous_with(LogicSystemSet::Consume),
+        )
+        .add_systems(
+            FixedUpdate,
+            register_output_event_listener
+                .in_set(LogicSystemSet::Produce)
+                .ambiguous_with(LogicSystemSet::Produce),
+        );
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::structure::coordinates::BlockCoordinate;
+
+    use crate::logic::test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_PROBE, TEST_SOURCE};
+
+    const CLOCK: BlockCoordinate = BlockCoordinate::new(4, 5, 5);
+    const REGISTER: BlockCoordinate = BlockCoordinate::new(5, 5, 5);
+    const DATA: BlockCoordinate = BlockCoordinate::new(5, 5, 6);
+    const OUTPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 4);
+
+    #[test]
+    fn test_register_latches_on_rising_clock_edge() {
+        let mut harness = LogicTestHarness::new(
+            &[
+                (CLOCK, TEST_SOURCE),
+                (REGISTER, super::BLOCK_ID),
+                (DATA, TEST_SOURCE),
+                (OUTPUT, TEST_PROBE),
+            ],
+            super::register,
+        );
+
+        // (clock, data, expected output)
+        for (clock, data, expected) in [(0, 7, 0), (1, 7, 7), (1, 3, 7), (0, 3, 7), (0, -12, 7), (1, -12, -12), (0, 0, -12)] {
+            harness.set_source(CLOCK, clock);
+            harness.set_source(DATA, data);
+            harness.step(SETTLE_TICKS);
+
+            assert_eq!(harness.probe(OUTPUT), expected, "clock: {clock}, data: {data}");
+        }
+    }
+}
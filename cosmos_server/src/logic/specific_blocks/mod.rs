@@ -2,18 +2,32 @@ use bevy::prelude::*;
 use cosmos_core::state::GameState;
 
 mod and_gate;
+mod arithmetic;
 mod button;
 mod colored_logic_wires;
+mod dock;
+mod door;
 mod flip_flop;
 mod laser_cannon;
 mod logic_bus;
+mod logic_clock;
+mod logic_constant;
+mod logic_delay;
 mod logic_indicator;
+mod logic_memory;
 mod logic_on;
+mod logic_register;
 mod missile_launcher;
 mod not_gate;
 mod numeric_display;
 mod or_gate;
+mod pulse_extender;
+mod sensors;
+mod shield_generator;
 mod switch;
+mod thruster;
+mod warp_drive;
+mod wireless;
 mod xor_gate;
 
 pub(super) fn register(app: &mut App) {
@@ -28,7 +42,21 @@ pub(super) fn register(app: &mut App) {
     colored_logic_wires::register(app, GameState::PostLoading);
     laser_cannon::register(app, GameState::PostLoading);
     missile_launcher::register(app, GameState::PostLoading);
+    thruster::register(app, GameState::PostLoading);
+    shield_generator::register(app, GameState::PostLoading);
+    door::register(app, GameState::PostLoading);
+    dock::register(app, GameState::PostLoading);
+    warp_drive::register(app, GameState::PostLoading);
     switch::register(app);
     button::register(app);
     flip_flop::register(app);
+    logic_clock::register(app);
+    logic_delay::register(app);
+    pulse_extender::register(app);
+    arithmetic::register(app);
+    logic_constant::register(app);
+    sensors::register(app);
+    wireless::register(app);
+    logic_register::register(app);
+    logic_memory::register(app);
 }
@@ -100,3 +100,32 @@ pub(super) fn register<T: States>(app: &mut App, post_loading_state: T) {
                 .ambiguous_with(LogicSystemSet::Produce),
         );
 }
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::{state::GameState, structure::coordinates::BlockCoordinate};
+
+    use crate::logic::test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_PROBE, TEST_SOURCE};
+
+    const INPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 6);
+    const GATE: BlockCoordinate = BlockCoordinate::new(5, 5, 5);
+    const OUTPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 4);
+
+    #[test]
+    fn test_not_gate_truth_table() {
+        let mut harness = LogicTestHarness::new(&[(INPUT, TEST_SOURCE), (GATE, "cosmos:not_gate"), (OUTPUT, TEST_PROBE)], |app| {
+            super::register(app, GameState::PostLoading)
+        });
+
+        // With nothing driving its input, a not gate is on.
+        assert_eq!(harness.probe(OUTPUT), 1);
+
+        for (input, expected) in [(1, 0), (0, 1), (-2, 0)] {
+            harness.set_source(INPUT, input);
+            harness.step(SETTLE_TICKS);
+
+            assert_eq!(harness.logic_data(GATE), expected, "input: {input}");
+            assert_eq!(harness.probe(OUTPUT), expected, "input: {input}");
+        }
+    }
+}
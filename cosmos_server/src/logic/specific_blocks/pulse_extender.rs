@@ -0,0 +1,179 @@
+//! Logic behavior for the "Pulse Extender", a block with a back input and a front output.
+//! Whenever its input is on, its output turns on and stays on for [`LogicTimerPeriod`] logic ticks
+//! after the input was last on.
+
+use bevy::prelude::*;
+
+use cosmos_core::{
+    block::{Block, block_face::BlockFace, data::BlockData},
+    logic::{BlockLogicData, timer::LogicTimerPeriod},
+    netty::sync::IdentifiableComponent,
+    registry::Registry,
+    state::GameState,
+    structure::Structure,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blocks::data::utils::add_default_block_data_for_block,
+    logic::{
+        LogicBlock, LogicConnection, LogicOutputMessage, LogicSystemSet, PortType, QueueLogicInputMessage, default_logic_block_output,
+        logic_driver::LogicDriver,
+    },
+    persistence::make_persistent::{DefaultPersistentComponent, make_persistent},
+};
+
+const BLOCK_ID: &str = "cosmos:pulse_extender";
+
+#[derive(Component, Debug, Default, Serialize, Deserialize)]
+/// The number of logic ticks this pulse extender will remain on for.
+struct PulseTicksRemaining(u32);
+
+impl IdentifiableComponent for PulseTicksRemaining {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:pulse_ticks_remaining"
+    }
+}
+
+impl DefaultPersistentComponent for PulseTicksRemaining {}
+
+fn register_logic_connections(blocks: Res<Registry<Block>>, mut registry: ResMut<Registry<LogicBlock>>) {
+    if let Some(block) = blocks.from_id(BLOCK_ID) {
+        registry.register(LogicBlock::new(
+            block,
+            [
+                None,
+                None,
+                None,
+                None,
+                Some(LogicConnection::Port(PortType::Output)),
+                Some(LogicConnection::Port(PortType::Input)),
+            ],
+        ));
+    }
+}
+
+fn tick_pulse_extenders(
+    mut q_pulse_extender: Query<(Entity, &LogicTimerPeriod, &mut PulseTicksRemaining)>,
+    mut q_block_data: Query<&mut BlockData>,
+    mut q_structure: Query<(&mut Structure, &LogicDriver)>,
+    mut q_logic_data: Query<&mut BlockLogicData>,
+    q_has_data: Query<(), With<BlockLogicData>>,
+    mut commands: Commands,
+) {
+    for (ent, period, mut remaining) in q_pulse_extender.iter_mut() {
+        let Ok(bd) = q_block_data.get(ent).copied() else {
+            continue;
+        };
+
+        let Ok((mut structure, logic_driver)) = q_structure.get_mut(bd.structure()) else {
+            continue;
+        };
+
+        let coords = bd.coords();
+        let rotation = structure.block_rotation(coords);
+        let input = logic_driver.read_input(coords, rotation.direction_of(BlockFace::Back)) != 0;
+
+        if input {
+            remaining.0 = period.ticks();
+        } else {
+            remaining.0 = remaining.0.saturating_sub(1);
+        }
+
+        let new_state = BlockLogicData((remaining.0 != 0) as i32);
+
+        if let Some(mut logic_data) = structure.query_block_data_mut(coords, &mut q_logic_data, &mut commands) {
+            if **logic_data != new_state {
+                **logic_data = new_state;
+            }
+        } else if new_state.0 != 0 {
+            structure.insert_block_data(coords, new_state, &mut commands, &mut q_block_data, &q_has_data);
+        }
+    }
+}
+
+fn pulse_extender_output_event_listener(
+    evr_logic_output: MessageReader<LogicOutputMessage>,
+    evw_queue_logic_input: MessageWriter<QueueLogicInputMessage>,
+    logic_blocks: Res<Registry<LogicBlock>>,
+    blocks: Res<Registry<Block>>,
+    q_structure: Query<(&mut Structure, &mut LogicDriver)>,
+    q_logic_data: Query<&BlockLogicData>,
+) {
+    default_logic_block_output(
+        BLOCK_ID,
+        evr_logic_output,
+        evw_queue_logic_input,
+        &logic_blocks,
+        &blocks,
+        q_structure,
+        q_logic_data,
+    );
+}
+
+/// Registers how this block behaves in a logic circuit, without any of the block data it needs
+fn register_logic(app: &mut App) {
+    app.add_systems(OnEnter(GameState::PostLoading), register_logic_connections)
+        .add_systems(
+            FixedUpdate,
+            (
+                tick_pulse_extenders.in_set(LogicSystemSet::PreLogicTick),
+                pulse_extender_output_event_listener
+                    .in_set(LogicSystemSet::Produce)
+                    .ambiguous_with(LogicSystemSet::Produce),
+            ),
+        );
+}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<PulseTicksRemaining>(app);
+
+    add_default_block_data_for_block::<LogicTimerPeriod>(app, |_, _| LogicTimerPeriod::default(), BLOCK_ID);
+    add_default_block_data_for_block::<PulseTicksRemaining>(app, |_, _| PulseTicksRemaining::default(), BLOCK_ID);
+
+    register_logic(app);
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::{logic::timer::LogicTimerPeriod, structure::coordinates::BlockCoordinate};
+
+    use crate::logic::test_harness::{LogicTestHarness, SETTLE_TICKS, TEST_PROBE, TEST_SOURCE};
+
+    use super::PulseTicksRemaining;
+
+    const INPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 6);
+    const EXTENDER: BlockCoordinate = BlockCoordinate::new(5, 5, 5);
+    const OUTPUT: BlockCoordinate = BlockCoordinate::new(5, 5, 4);
+
+    /// Holds the input on for `held_ticks`, then returns how many ticks the output was on for
+    fn ticks_on(harness: &mut LogicTestHarness, held_ticks: usize) -> usize {
+        let mut outputs = vec![];
+        for tick in 0..held_ticks + 20 {
+            harness.set_source(INPUT, (tick < held_ticks) as i32);
+            harness.step(1);
+            outputs.push(harness.probe(OUTPUT));
+        }
+
+        let on = outputs.iter().filter(|&&signal| signal != 0).count();
+        let first = outputs.iter().position(|&signal| signal != 0).unwrap_or(0);
+        assert!(outputs[first..first + on].iter().all(|&signal| signal == 1), "outputs: {outputs:?}");
+
+        on
+    }
+
+    #[test]
+    fn test_pulse_extender_extends_pulses() {
+        let mut harness = LogicTestHarness::new(
+            &[(INPUT, TEST_SOURCE), (EXTENDER, super::BLOCK_ID), (OUTPUT, TEST_PROBE)],
+            super::register_logic,
+        );
+        harness.insert_block_data(EXTENDER, LogicTimerPeriod::new(5));
+        harness.insert_block_data(EXTENDER, PulseTicksRemaining::default());
+        harness.step(SETTLE_TICKS);
+
+        // A single tick pulse lasts the whole period, and longer ones stay on for the period after the input turns off
+        assert_eq!(ticks_on(&mut harness, 1), 5);
+        assert_eq!(ticks_on(&mut harness, 10), 10 + 4);
+    }
+}
@@ -0,0 +1,473 @@
+//! Logic behavior for sensor blocks, which output a signal on their faces based on the state of their structure.
+//!
+//! - Energy Sensor: The energy stored by the structure.
+//! - Shield Sensor: The combined strength of every shield on the structure.
+//! - Hull Sensor: The percentage of the structure's hull blocks that are still intact (see [`HullIntegrity`]).
+//! - Speed Sensor: The structure's speed, in blocks per second.
+//! - Fluid Sensor: The amount of fluid stored in the tank behind this block.
+//! - Proximity Sensor: The number of enemy ships and stations within its [`ProximitySensorRange`].
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use bevy_rapier3d::prelude::Velocity;
+
+use cosmos_core::{
+    block::{Block, block_face::BlockFace, data::BlockData},
+    ecs::sets::FixedUpdateSet,
+    entities::EntityId,
+    events::block_events::BlockChangedMessage,
+    faction::{Faction, FactionId, FactionRelation, Factions},
+    fluid::data::BlockFluidData,
+    logic::{
+        BlockLogicData,
+        sensor::{ProximitySensorRange, SetProximitySensorRange},
+    },
+    netty::{
+        server::ServerLobby,
+        sync::{
+            IdentifiableComponent,
+            events::server_event::{NettyMessageReceived, NettyMessageWriter},
+        },
+    },
+    notifications::Notification,
+    physics::location::Location,
+    prelude::{BlockCoordinate, Ship, Station, Structure, StructureLoadedMessage, StructureSystems},
+    registry::{Registry, identifiable::Identifiable},
+    state::GameState,
+    structure::{shields::Shield, systems::dock_system::Docked, systems::energy_storage_system::EnergyStorageSystem},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blocks::data::utils::add_default_block_data_for_block,
+    logic::{
+        LogicBlock, LogicConnection, LogicOutputMessage, LogicSystemSet, Port, PortType, QueueLogicInputMessage, logic_driver::LogicDriver,
+    },
+    persistence::make_persistent::{DefaultPersistentComponent, make_persistent},
+    structure::ownership::StructurePermissions,
+};
+
+const ENERGY_SENSOR_ID: &str = "cosmos:energy_sensor";
+const SHIELD_SENSOR_ID: &str = "cosmos:shield_sensor";
+const HULL_SENSOR_ID: &str = "cosmos:hull_sensor";
+const SPEED_SENSOR_ID: &str = "cosmos:speed_sensor";
+const FLUID_SENSOR_ID: &str = "cosmos:fluid_sensor";
+const PROXIMITY_SENSOR_ID: &str = "cosmos:proximity_sensor";
+
+/// Counting nearby ships and stations looks at every ship and station, so proximity sensors only do it this often.
+const PROXIMITY_SENSOR_INTERVAL_SECS: f32 = 0.5;
+
+const SENSOR_IDS: [&str; 6] = [
+    ENERGY_SENSOR_ID,
+    SHIELD_SENSOR_ID,
+    HULL_SENSOR_ID,
+    SPEED_SENSOR_ID,
+    FLUID_SENSOR_ID,
+    PROXIMITY_SENSOR_ID,
+];
+
+/// Every colored ship hull block starts with this.
+const HULL_BLOCK_ID_PREFIX: &str = "cosmos:ship_hull_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Identifies which piece of structure state a sensor block reads.
+enum LogicSensor {
+    Energy,
+    Shield,
+    Hull,
+    Speed,
+    Fluid,
+    Proximity,
+}
+
+impl LogicSensor {
+    /// The sensor this block is, if it is one.
+    fn of(block: &Block) -> Option<Self> {
+        match block.unlocalized_name() {
+            ENERGY_SENSOR_ID => Some(Self::Energy),
+            SHIELD_SENSOR_ID => Some(Self::Shield),
+            HULL_SENSOR_ID => Some(Self::Hull),
+            SPEED_SENSOR_ID => Some(Self::Speed),
+            FLUID_SENSOR_ID => Some(Self::Fluid),
+            PROXIMITY_SENSOR_ID => Some(Self::Proximity),
+            _ => None,
+        }
+    }
+}
+
+impl DefaultPersistentComponent for ProximitySensorRange {}
+
+#[derive(Component, Debug, Default)]
+/// Every sensor block on this structure, and what it reads.
+///
+/// What a sensor reads only depends on its block, so this is rebuilt from the structure's blocks when it loads
+/// rather than being saved.
+struct StructureSensors(HashMap<BlockCoordinate, LogicSensor>);
+
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+/// How many hull blocks this structure has now, compared to how many it had when it was intact.
+///
+/// Building more hull blocks than the structure has ever had raises what counts as intact.
+struct HullIntegrity {
+    initial: u32,
+    current: u32,
+}
+
+impl HullIntegrity {
+    fn new(n_hull_blocks: u32) -> Self {
+        Self {
+            initial: n_hull_blocks,
+            current: n_hull_blocks,
+        }
+    }
+
+    fn hull_block_added(&mut self) {
+        self.current += 1;
+        self.initial = self.initial.max(self.current);
+    }
+
+    fn hull_block_removed(&mut self) {
+        self.current = self.current.saturating_sub(1);
+    }
+
+    /// The percentage (0-100) of hull blocks that are still intact.
+    ///
+    /// A structure that has never had any hull blocks is considered fully intact.
+    fn percent(&self) -> i32 {
+        if self.initial == 0 {
+            return 100;
+        }
+
+        (self.current as u64 * 100 / self.initial as u64) as i32
+    }
+}
+
+impl IdentifiableComponent for HullIntegrity {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:hull_integrity"
+    }
+}
+
+impl DefaultPersistentComponent for HullIntegrity {}
+
+fn is_hull_block(block: &Block) -> bool {
+    block.unlocalized_name().starts_with(HULL_BLOCK_ID_PREFIX)
+}
+
+fn register_logic_connections(blocks: Res<Registry<Block>>, mut registry: ResMut<Registry<LogicBlock>>) {
+    for id in SENSOR_IDS.into_iter().filter(|id| *id != FLUID_SENSOR_ID) {
+        if let Some(block) = blocks.from_id(id) {
+            registry.register(LogicBlock::new(block, [Some(LogicConnection::Port(PortType::Output)); 6]));
+        }
+    }
+
+    // The back of the fluid sensor is pressed against the tank it reads.
+    if let Some(block) = blocks.from_id(FLUID_SENSOR_ID) {
+        registry.register(LogicBlock::new(
+            block,
+            [
+                Some(LogicConnection::Port(PortType::Output)),
+                Some(LogicConnection::Port(PortType::Output)),
+                Some(LogicConnection::Port(PortType::Output)),
+                Some(LogicConnection::Port(PortType::Output)),
+                Some(LogicConnection::Port(PortType::Output)),
+                None,
+            ],
+        ));
+    }
+}
+
+fn track_sensor_blocks(
+    mut evr_block_changed: MessageReader<BlockChangedMessage>,
+    mut evr_structure_loaded: MessageReader<StructureLoadedMessage>,
+    blocks: Res<Registry<Block>>,
+    mut q_sensors: Query<&mut StructureSensors>,
+    mut q_hull_integrity: Query<&mut HullIntegrity>,
+    q_structure: Query<&Structure, Or<(With<Ship>, With<Station>)>>,
+    mut commands: Commands,
+) {
+    for ev in evr_block_changed.read() {
+        let old_block = blocks.from_numeric_id(ev.old_block);
+        let new_block = blocks.from_numeric_id(ev.new_block);
+
+        if let Ok(mut sensors) = q_sensors.get_mut(ev.block.structure()) {
+            if let Some(sensor) = LogicSensor::of(new_block) {
+                sensors.0.insert(ev.block.coords(), sensor);
+            } else if LogicSensor::of(old_block).is_some() {
+                sensors.0.remove(&ev.block.coords());
+            }
+        }
+
+        if let Ok(mut hull_integrity) = q_hull_integrity.get_mut(ev.block.structure()) {
+            match (is_hull_block(old_block), is_hull_block(new_block)) {
+                (false, true) => hull_integrity.hull_block_added(),
+                (true, false) => hull_integrity.hull_block_removed(),
+                _ => {}
+            }
+        }
+    }
+
+    for ev in evr_structure_loaded.read() {
+        let Ok(structure) = q_structure.get(ev.structure_entity) else {
+            continue;
+        };
+
+        let mut sensors = StructureSensors::default();
+        let mut n_hull_blocks = 0;
+
+        for coords in structure.all_blocks_iter(false) {
+            let block = structure.block_at(coords, &blocks);
+
+            if let Some(sensor) = LogicSensor::of(block) {
+                sensors.0.insert(coords, sensor);
+            }
+            if is_hull_block(block) {
+                n_hull_blocks += 1;
+            }
+        }
+
+        let mut hull_integrity = HullIntegrity::new(n_hull_blocks);
+        // A structure that was damaged before it was saved should still read as damaged.
+        if let Ok(saved_integrity) = q_hull_integrity.get(ev.structure_entity) {
+            hull_integrity.initial = saved_integrity.initial.max(n_hull_blocks);
+        }
+
+        commands.entity(ev.structure_entity).insert((sensors, hull_integrity));
+    }
+}
+
+/// Follows the chain of docked structures to find the structure everything is docked to.
+fn topmost_structure(mut entity: Entity, q_docked: &Query<&Docked>) -> Entity {
+    while let Ok(docked) = q_docked.get(entity) {
+        entity = docked.to;
+    }
+
+    entity
+}
+
+fn is_enemy(this_faction: Option<&Faction>, this_ent_id: &EntityId, other_faction: Option<&Faction>, other_ent_id: &EntityId) -> bool {
+    let relation = if let Some(this_faction) = this_faction {
+        this_faction.relation_with_entity(other_ent_id, other_faction)
+    } else if let Some(other_faction) = other_faction {
+        other_faction.relation_with_entity(this_ent_id, None)
+    } else {
+        FactionRelation::Neutral
+    };
+
+    relation == FactionRelation::Enemy
+}
+
+fn update_sensors(
+    q_sensors: Query<(Entity, &StructureSensors)>,
+    q_proximity_range: Query<&ProximitySensorRange>,
+    mut q_block_data: Query<&mut BlockData>,
+    mut q_structure: Query<(
+        &mut Structure,
+        &Location,
+        &EntityId,
+        Option<&FactionId>,
+        Option<&StructureSystems>,
+        Option<&Velocity>,
+        Option<&HullIntegrity>,
+        Option<&Children>,
+    )>,
+    q_energy_storage: Query<&EnergyStorageSystem>,
+    q_shields: Query<&Shield>,
+    q_fluid_data: Query<&BlockFluidData>,
+    q_targets: Query<(Entity, &Location, &EntityId, Option<&FactionId>), Or<(With<Ship>, With<Station>)>>,
+    q_docked: Query<&Docked>,
+    factions: Res<Factions>,
+    mut q_logic_data: Query<&mut BlockLogicData>,
+    q_has_data: Query<(), With<BlockLogicData>>,
+    mut commands: Commands,
+    mut secs_since_proximity_scan: Local<f32>,
+    time: Res<Time>,
+) {
+    *secs_since_proximity_scan += time.delta_secs();
+    let scan_proximity = *secs_since_proximity_scan >= PROXIMITY_SENSOR_INTERVAL_SECS;
+    if scan_proximity {
+        *secs_since_proximity_scan = 0.0;
+    }
+
+    for (structure_entity, sensors) in q_sensors.iter() {
+        let Ok((mut structure, location, ent_id, faction_id, systems, velocity, hull_integrity, children)) =
+            q_structure.get_mut(structure_entity)
+        else {
+            continue;
+        };
+
+        for (&coords, sensor) in sensors.0.iter() {
+            // Proximity sensors keep their last count until the next scan.
+            if *sensor == LogicSensor::Proximity && !scan_proximity {
+                continue;
+            }
+
+            let value = match sensor {
+                LogicSensor::Energy => systems
+                    .and_then(|systems| systems.query(&q_energy_storage).ok())
+                    .map(|ess| ess.get_energy() as i32)
+                    .unwrap_or(0),
+                // Shields are children of the structure they protect.
+                LogicSensor::Shield => children
+                    .map(|children| q_shields.iter_many(children).map(|shield| shield.strength()).sum::<f32>() as i32)
+                    .unwrap_or(0),
+                LogicSensor::Hull => hull_integrity.map(|integrity| integrity.percent()).unwrap_or(0),
+                LogicSensor::Speed => velocity.map(|vel| vel.linvel.length().round() as i32).unwrap_or(0),
+                LogicSensor::Fluid => {
+                    let back = structure.block_rotation(coords).direction_of(BlockFace::Back);
+                    coords
+                        .step(back)
+                        .ok()
+                        .and_then(|tank_coords| structure.query_block_data(tank_coords, &q_fluid_data))
+                        .map(|fluid_data| match fluid_data {
+                            BlockFluidData::Fluid(stored) => stored.fluid_stored as i32,
+                            BlockFluidData::NoFluid => 0,
+                        })
+                        .unwrap_or(0)
+                }
+                LogicSensor::Proximity => {
+                    let range = structure
+                        .query_block_data(coords, &q_proximity_range)
+                        .copied()
+                        .unwrap_or_default()
+                        .blocks() as f32;
+                    let this_topmost = topmost_structure(structure_entity, &q_docked);
+                    let this_faction = faction_id.and_then(|id| factions.from_id(id));
+
+                    q_targets
+                        .iter()
+                        .filter(|(_, loc, _, _)| loc.is_within(location, range))
+                        .filter(|(target, _, _, _)| topmost_structure(*target, &q_docked) != this_topmost)
+                        .filter(|(_, _, other_ent_id, other_faction_id)| {
+                            let other_faction = other_faction_id.and_then(|id| factions.from_id(id));
+                            is_enemy(this_faction, ent_id, other_faction, other_ent_id)
+                        })
+                        .count() as i32
+                }
+            };
+
+            let new_state = BlockLogicData(value);
+
+            if let Some(mut logic_data) = structure.query_block_data_mut(coords, &mut q_logic_data, &mut commands) {
+                if **logic_data != new_state {
+                    **logic_data = new_state;
+                }
+            } else if new_state.0 != 0 {
+                structure.insert_block_data(coords, new_state, &mut commands, &mut q_block_data, &q_has_data);
+            }
+        }
+    }
+}
+
+fn on_set_proximity_sensor_range(
+    mut nevr_set_range: MessageReader<NettyMessageReceived<SetProximitySensorRange>>,
+    lobby: Res<ServerLobby>,
+    q_structure: Query<&Structure>,
+    mut q_range: Query<&mut ProximitySensorRange>,
+    permissions: StructurePermissions,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    mut commands: Commands,
+) {
+    for ev in nevr_set_range.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            error!("Bad player ({})!", ev.client_id);
+            continue;
+        };
+
+        if let Err(denied) = permissions.check_build(player, ev.block.structure()) {
+            nevw_notification.write(Notification::error(denied.message()), ev.client_id);
+            continue;
+        }
+
+        let Ok(structure) = q_structure.get(ev.block.structure()) else {
+            warn!("Tried to set proximity sensor range on non-valid structure!");
+            continue;
+        };
+
+        let Some(mut range) = structure.query_block_data_mut(ev.block.coords(), &mut q_range, &mut commands) else {
+            warn!("Tried to set proximity sensor range on a block without one!");
+            continue;
+        };
+
+        let new_range = ProximitySensorRange::new(ev.blocks);
+        if **range != new_range {
+            **range = new_range;
+        }
+    }
+}
+
+fn sensor_output_event_listener(
+    mut evr_logic_output: MessageReader<LogicOutputMessage>,
+    mut evw_queue_logic_input: MessageWriter<QueueLogicInputMessage>,
+    logic_blocks: Res<Registry<LogicBlock>>,
+    blocks: Res<Registry<Block>>,
+    mut q_structure: Query<(&mut Structure, &mut LogicDriver)>,
+    q_logic_data: Query<&BlockLogicData>,
+) {
+    // Same as `default_logic_block_output`, but handles every sensor in one pass over the output events.
+    for ev in evr_logic_output.read() {
+        let Ok((structure, mut logic_driver)) = q_structure.get_mut(ev.block.structure()) else {
+            continue;
+        };
+        let block_id = structure.block_at(ev.block.coords(), &blocks).unlocalized_name();
+        if !SENSOR_IDS.contains(&block_id) {
+            continue;
+        }
+        let Some(logic_block) = logic_blocks.from_id(block_id) else {
+            continue;
+        };
+        let BlockLogicData(signal) = structure
+            .query_block_data(ev.block.coords(), &q_logic_data)
+            .copied()
+            .unwrap_or_default();
+
+        for face in logic_block.output_faces() {
+            let port = Port::new(ev.block.coords(), structure.block_rotation(ev.block.coords()).direction_of(face));
+            logic_driver.update_producer(port, signal, &mut evw_queue_logic_input, ev.block.structure());
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<ProximitySensorRange>(app);
+    make_persistent::<HullIntegrity>(app);
+
+    add_default_block_data_for_block::<ProximitySensorRange>(app, |_, _| ProximitySensorRange::default(), PROXIMITY_SENSOR_ID);
+
+    app.add_systems(OnEnter(GameState::PostLoading), register_logic_connections)
+        .add_systems(
+            FixedUpdate,
+            on_set_proximity_sensor_range
+                .in_set(FixedUpdateSet::Main)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                (track_sensor_blocks, update_sensors).chain().in_set(LogicSystemSet::PreLogicTick),
+                sensor_output_event_listener
+                    .in_set(LogicSystemSet::Produce)
+                    .ambiguous_with(LogicSystemSet::Produce),
+            ),
+        );
+}
+
+#[cfg(test)]
+mod test {
+    use super::HullIntegrity;
+
+    #[test]
+    fn hull_integrity_is_current_over_initial() {
+        let mut integrity = HullIntegrity::new(4);
+        assert_eq!(integrity.percent(), 100);
+
+        integrity.hull_block_removed();
+        assert_eq!(integrity.percent(), 75);
+
+        integrity.hull_block_added();
+        integrity.hull_block_added();
+        assert_eq!(integrity.percent(), 100);
+        assert_eq!(integrity.initial, 5);
+
+        assert_eq!(HullIntegrity::new(0).percent(), 100);
+    }
+}
@@ -0,0 +1,290 @@
+//! Logic behavior for wireless transmitters and receivers.
+//!
+//! A transmitter sends the strongest signal on its input faces over its [`LogicChannel`]. Every receiver on the
+//! same channel outputs the strongest signal being transmitted to it on all 6 faces.
+//!
+//! Signals reach receivers on the same structure, or on structures of the same faction or with the same
+//! [`StructureOwner`] within [`WIRELESS_LOGIC_RANGE`]. Signals sent on the [`FACTION_BROADCAST_CHANNEL`] reach all
+//! of those structures, regardless of distance.
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use cosmos_core::{
+    block::{Block, data::BlockData},
+    ecs::sets::FixedUpdateSet,
+    faction::FactionId,
+    logic::{
+        BlockLogicData,
+        wireless::{FACTION_BROADCAST_CHANNEL, LogicChannel, SetLogicChannel},
+    },
+    netty::{
+        server::ServerLobby,
+        sync::{
+            IdentifiableComponent,
+            events::server_event::{NettyMessageReceived, NettyMessageWriter},
+        },
+    },
+    notifications::Notification,
+    physics::location::Location,
+    prelude::Structure,
+    registry::{Registry, identifiable::Identifiable},
+    state::GameState,
+    structure::ownership::StructureOwner,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blocks::data::utils::add_default_block_data_for_block,
+    logic::{
+        LogicBlock, LogicConnection, LogicInputMessage, LogicOutputMessage, LogicSystemSet, PortType, QueueLogicInputMessage,
+        default_logic_block_output, logic_driver::LogicDriver,
+    },
+    persistence::make_persistent::{DefaultPersistentComponent, make_persistent},
+    structure::ownership::StructurePermissions,
+};
+
+const TRANSMITTER_ID: &str = "cosmos:logic_transmitter";
+const RECEIVER_ID: &str = "cosmos:logic_receiver";
+
+/// How far (in blocks) a wireless signal travels to reach other structures of the same faction.
+const WIRELESS_LOGIC_RANGE: f32 = 2000.0;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum WirelessLogicBlock {
+    Transmitter,
+    Receiver,
+}
+
+impl IdentifiableComponent for WirelessLogicBlock {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:wireless_logic_block"
+    }
+}
+
+impl DefaultPersistentComponent for WirelessLogicBlock {}
+
+impl DefaultPersistentComponent for LogicChannel {}
+
+/// Picks the signal with the largest magnitude, so negative signals aren't drowned out by `0`.
+fn strongest_signal(signals: impl Iterator<Item = i32>) -> i32 {
+    signals.max_by_key(|signal| signal.unsigned_abs()).unwrap_or(0)
+}
+
+fn register_logic_connections(blocks: Res<Registry<Block>>, mut registry: ResMut<Registry<LogicBlock>>) {
+    if let Some(block) = blocks.from_id(TRANSMITTER_ID) {
+        registry.register(LogicBlock::new(block, [Some(LogicConnection::Port(PortType::Input)); 6]));
+    }
+
+    if let Some(block) = blocks.from_id(RECEIVER_ID) {
+        registry.register(LogicBlock::new(block, [Some(LogicConnection::Port(PortType::Output)); 6]));
+    }
+}
+
+fn transmitter_input_event_listener(
+    mut evr_logic_input: MessageReader<LogicInputMessage>,
+    blocks: Res<Registry<Block>>,
+    q_logic_driver: Query<&LogicDriver>,
+    mut q_structure: Query<&mut Structure>,
+    mut q_logic_data: Query<&mut BlockLogicData>,
+    mut commands: Commands,
+    q_has_data: Query<(), With<BlockLogicData>>,
+    mut q_block_data: Query<&mut BlockData>,
+) {
+    for ev in evr_logic_input.read() {
+        let Ok(mut structure) = q_structure.get_mut(ev.block.structure()) else {
+            continue;
+        };
+        if structure.block_at(ev.block.coords(), &blocks).unlocalized_name() != TRANSMITTER_ID {
+            continue;
+        }
+        let Ok(logic_driver) = q_logic_driver.get(ev.block.structure()) else {
+            continue;
+        };
+
+        let coords = ev.block.coords();
+        let inputs = logic_driver.read_all_inputs(coords, structure.block_rotation(coords));
+        let new_state = BlockLogicData(strongest_signal(inputs.into_iter()));
+
+        if let Some(mut logic_data) = structure.query_block_data_mut(coords, &mut q_logic_data, &mut commands) {
+            if **logic_data != new_state {
+                **logic_data = new_state;
+            }
+        } else if new_state.0 != 0 {
+            structure.insert_block_data(coords, new_state, &mut commands, &mut q_block_data, &q_has_data);
+        }
+    }
+}
+
+/// A signal being sent by a wireless transmitter on some structure
+struct Transmission {
+    structure: Entity,
+    signal: i32,
+}
+
+fn update_wireless_receivers(
+    q_wireless: Query<(Entity, &WirelessLogicBlock, &LogicChannel)>,
+    q_structure_info: Query<(&Location, Option<&FactionId>, Option<&StructureOwner>)>,
+    mut q_structure: Query<&mut Structure>,
+    mut q_logic_data: Query<&mut BlockLogicData>,
+    mut q_block_data: Query<&mut BlockData>,
+    q_has_data: Query<(), With<BlockLogicData>>,
+    mut commands: Commands,
+) {
+    let mut transmissions: HashMap<LogicChannel, Vec<Transmission>> = HashMap::default();
+
+    for (ent, kind, channel) in q_wireless.iter() {
+        if *kind != WirelessLogicBlock::Transmitter {
+            continue;
+        }
+
+        let Ok(bd) = q_block_data.get(ent) else {
+            continue;
+        };
+        let signal = q_logic_data.get(ent).map(|data| data.0).unwrap_or(0);
+
+        transmissions.entry(*channel).or_default().push(Transmission {
+            structure: bd.structure(),
+            signal,
+        });
+    }
+
+    for (ent, kind, channel) in q_wireless.iter() {
+        if *kind != WirelessLogicBlock::Receiver {
+            continue;
+        }
+
+        let Ok(bd) = q_block_data.get(ent).copied() else {
+            continue;
+        };
+        let receiver_structure = bd.structure();
+        let Ok((receiver_loc, receiver_faction, receiver_owner)) = q_structure_info.get(receiver_structure) else {
+            continue;
+        };
+
+        let reachable = |transmitter_structure: Entity| {
+            if transmitter_structure == receiver_structure {
+                return true;
+            }
+
+            let Ok((transmitter_loc, transmitter_faction, transmitter_owner)) = q_structure_info.get(transmitter_structure) else {
+                return false;
+            };
+
+            let same_faction = receiver_faction.is_some() && receiver_faction == transmitter_faction;
+            let same_owner = receiver_owner.is_some() && receiver_owner == transmitter_owner;
+
+            (same_faction || same_owner)
+                && (channel.0 == FACTION_BROADCAST_CHANNEL || transmitter_loc.is_within(receiver_loc, WIRELESS_LOGIC_RANGE))
+        };
+
+        let signal = strongest_signal(
+            transmissions
+                .get(channel)
+                .into_iter()
+                .flatten()
+                .filter(|transmission| reachable(transmission.structure))
+                .map(|transmission| transmission.signal),
+        );
+
+        let Ok(mut structure) = q_structure.get_mut(receiver_structure) else {
+            continue;
+        };
+
+        let coords = bd.coords();
+        let new_state = BlockLogicData(signal);
+
+        if let Some(mut logic_data) = structure.query_block_data_mut(coords, &mut q_logic_data, &mut commands) {
+            if **logic_data != new_state {
+                **logic_data = new_state;
+            }
+        } else if new_state.0 != 0 {
+            structure.insert_block_data(coords, new_state, &mut commands, &mut q_block_data, &q_has_data);
+        }
+    }
+}
+
+fn receiver_output_event_listener(
+    evr_logic_output: MessageReader<LogicOutputMessage>,
+    evw_queue_logic_input: MessageWriter<QueueLogicInputMessage>,
+    logic_blocks: Res<Registry<LogicBlock>>,
+    blocks: Res<Registry<Block>>,
+    q_structure: Query<(&mut Structure, &mut LogicDriver)>,
+    q_logic_data: Query<&BlockLogicData>,
+) {
+    default_logic_block_output(
+        RECEIVER_ID,
+        evr_logic_output,
+        evw_queue_logic_input,
+        &logic_blocks,
+        &blocks,
+        q_structure,
+        q_logic_data,
+    );
+}
+
+fn on_set_logic_channel(
+    mut nevr_set_channel: MessageReader<NettyMessageReceived<SetLogicChannel>>,
+    lobby: Res<ServerLobby>,
+    q_structure: Query<&Structure>,
+    mut q_channel: Query<&mut LogicChannel>,
+    permissions: StructurePermissions,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    mut commands: Commands,
+) {
+    for ev in nevr_set_channel.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            error!("Bad player ({})!", ev.client_id);
+            continue;
+        };
+
+        if let Err(denied) = permissions.check_build(player, ev.block.structure()) {
+            nevw_notification.write(Notification::error(denied.message()), ev.client_id);
+            continue;
+        }
+
+        let Ok(structure) = q_structure.get(ev.block.structure()) else {
+            warn!("Tried to set logic channel on non-valid structure!");
+            continue;
+        };
+
+        let Some(mut channel) = structure.query_block_data_mut(ev.block.coords(), &mut q_channel, &mut commands) else {
+            warn!("Tried to set logic channel on a block without one!");
+            continue;
+        };
+
+        let new_channel = LogicChannel(ev.channel);
+        if **channel != new_channel {
+            **channel = new_channel;
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<WirelessLogicBlock>(app);
+    make_persistent::<LogicChannel>(app);
+
+    add_default_block_data_for_block::<WirelessLogicBlock>(app, |_, _| WirelessLogicBlock::Transmitter, TRANSMITTER_ID);
+    add_default_block_data_for_block::<WirelessLogicBlock>(app, |_, _| WirelessLogicBlock::Receiver, RECEIVER_ID);
+    add_default_block_data_for_block::<LogicChannel>(app, |_, _| LogicChannel::default(), TRANSMITTER_ID);
+    add_default_block_data_for_block::<LogicChannel>(app, |_, _| LogicChannel::default(), RECEIVER_ID);
+
+    app.add_systems(OnEnter(GameState::PostLoading), register_logic_connections)
+        .add_systems(
+            FixedUpdate,
+            on_set_logic_channel
+                .in_set(FixedUpdateSet::Main)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                update_wireless_receivers.in_set(LogicSystemSet::PreLogicTick),
+                transmitter_input_event_listener
+                    .in_set(LogicSystemSet::Consume)
+                    .ambiguous_with(LogicSystemSet::Consume),
+                receiver_output_event_listener
+                    .in_set(LogicSystemSet::Produce)
+                    .ambiguous_with(LogicSystemSet::Produce),
+            ),
+        );
+}
@@ -0,0 +1,291 @@
+//! A headless logic circuit simulator, used to unit test logic blocks without running a server.
+//!
+//! The harness builds a small structure from a layout of blocks, runs only the logic systems,
+//! and lets tests drive inputs with [`TEST_SOURCE`] blocks and read results with [`TEST_PROBE`] blocks.
+
+use bevy::{
+    ecs::{schedule::ExecutorKind, system::RunSystemOnce},
+    prelude::*,
+};
+
+use cosmos_core::{
+    block::{
+        Block, BlockProperty, block_builder::BlockBuilder, block_direction::BlockDirection, block_rotation::BlockRotation, blocks::COLORS,
+        data::BlockData,
+    },
+    events::block_events::{BlockChangedMessage, BlockChangedReason, BlockDataChangedMessage},
+    logic::BlockLogicData,
+    prelude::StructureLoadedMessage,
+    registry::Registry,
+    state::GameState,
+    structure::{
+        Structure,
+        coordinates::{BlockCoordinate, ChunkCoordinate},
+        full_structure::FullStructure,
+        register_structure_hooks,
+    },
+};
+
+use super::{
+    LogicBlock, LogicConnection, LogicOutputMessage, LogicSystemSet, PortType, QueueLogicInputMessage, default_logic_block_output,
+    logic_driver::LogicDriver, register_logic_graph,
+};
+
+/// A test-only block that outputs its [`BlockLogicData`] on all 6 faces. Change its signal with [`LogicTestHarness::set_source`].
+pub const TEST_SOURCE: &str = "cosmos:test_logic_source";
+/// A test-only block with an input on all 6 faces and no behavior. Read what it receives with [`LogicTestHarness::probe`].
+pub const TEST_PROBE: &str = "cosmos:test_logic_probe";
+
+/// Enough logic ticks for a signal to pass through a few logic blocks.
+pub const SETTLE_TICKS: usize = 10;
+
+fn register_test_blocks(blocks: Res<Registry<Block>>, mut registry: ResMut<Registry<LogicBlock>>) {
+    if let Some(source) = blocks.from_id(TEST_SOURCE) {
+        registry.register(LogicBlock::new(source, [Some(LogicConnection::Port(PortType::Output)); 6]));
+    }
+    if let Some(probe) = blocks.from_id(TEST_PROBE) {
+        registry.register(LogicBlock::new(probe, [Some(LogicConnection::Port(PortType::Input)); 6]));
+    }
+}
+
+fn test_source_output_event_listener(
+    evr_logic_output: MessageReader<LogicOutputMessage>,
+    evw_queue_logic_input: MessageWriter<QueueLogicInputMessage>,
+    logic_blocks: Res<Registry<LogicBlock>>,
+    blocks: Res<Registry<Block>>,
+    q_structure: Query<(&mut Structure, &mut LogicDriver)>,
+    q_logic_data: Query<&BlockLogicData>,
+) {
+    default_logic_block_output(
+        TEST_SOURCE,
+        evr_logic_output,
+        evw_queue_logic_input,
+        &logic_blocks,
+        &blocks,
+        q_structure,
+        q_logic_data,
+    );
+}
+
+/// A single structure with only the logic systems running on it.
+pub struct LogicTestHarness {
+    app: App,
+    structure_entity: Entity,
+}
+
+impl LogicTestHarness {
+    /// Creates a structure containing every block in the `layout`, all placed with [`BlockRotation::IDENTITY`].
+    ///
+    /// * `register_logic` Registers the behavior of the logic blocks being tested, such as `and_gate::register`.
+    ///   The logic graph, logic wires of every color, [`TEST_SOURCE`] and [`TEST_PROBE`] are always available.
+    pub fn new(layout: &[(BlockCoordinate, &str)], register_logic: impl FnOnce(&mut App)) -> Self {
+        let mut blocks = Registry::<Block>::new("cosmos:blocks");
+        blocks.register(
+            BlockBuilder::new("cosmos:air", 0.0, 0.0, 0.0)
+                .add_property(BlockProperty::Empty)
+                .create(),
+        );
+
+        let block_ids = [TEST_SOURCE, TEST_PROBE, "cosmos:logic_bus"]
+            .into_iter()
+            .map(String::from)
+            .chain(COLORS.iter().map(|color| format!("cosmos:logic_wire_{color}")))
+            .chain(layout.iter().map(|(_, id)| id.to_string()));
+        for id in block_ids {
+            if !blocks.contains(&id) {
+                blocks.register(BlockBuilder::new(id, 0.1, 20.0, 5.0).create());
+            }
+        }
+
+        let mut app = App::new();
+        app.insert_resource(blocks)
+            .insert_resource(State::new(GameState::Playing))
+            .add_message::<BlockChangedMessage>()
+            .add_message::<BlockDataChangedMessage>()
+            .add_message::<StructureLoadedMessage>();
+
+        register_logic_graph(&mut app);
+        app.add_systems(OnEnter(GameState::PostLoading), register_test_blocks).add_systems(
+            FixedUpdate,
+            test_source_output_event_listener
+                .in_set(LogicSystemSet::Produce)
+                .ambiguous_with(LogicSystemSet::Produce),
+        );
+        register_logic(&mut app);
+
+        app.edit_schedule(FixedUpdate, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        });
+
+        let world = app.world_mut();
+        register_structure_hooks(world);
+        world.run_schedule(OnEnter(GameState::Loading));
+        world.run_schedule(OnEnter(GameState::PostLoading));
+
+        let mut structure = Structure::Full(FullStructure::new(ChunkCoordinate::new(1, 1, 1)));
+        let chunk_entity = world.spawn_empty().id();
+        structure.set_chunk_entity(ChunkCoordinate::new(0, 0, 0), chunk_entity);
+        let structure_entity = world.spawn((structure, LogicDriver::default())).id();
+        world.entity_mut(chunk_entity).insert(ChildOf(structure_entity));
+
+        let mut harness = Self { app, structure_entity };
+        for &(coords, id) in layout {
+            harness.place(coords, id, BlockRotation::IDENTITY);
+        }
+        harness.step(SETTLE_TICKS);
+
+        harness
+    }
+
+    /// Runs `ticks` logic ticks.
+    pub fn step(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.app.world_mut().run_schedule(FixedUpdate);
+        }
+    }
+
+    /// Places a block, exactly like a player would. The logic graph is updated on the next [`Self::step`].
+    pub fn place(&mut self, coords: BlockCoordinate, block_id: &str, rotation: BlockRotation) {
+        let structure_entity = self.structure_entity;
+        let block_id = block_id.to_owned();
+
+        self.app
+            .world_mut()
+            .run_system_once(
+                move |mut q_structure: Query<&mut Structure>,
+                      blocks: Res<Registry<Block>>,
+                      mut evw_block_changed: MessageWriter<BlockChangedMessage>| {
+                    let mut structure = q_structure.get_mut(structure_entity).expect("Test structure should exist.");
+                    let block = blocks
+                        .from_id(&block_id)
+                        .unwrap_or_else(|| panic!("Block {block_id} should be in the test harness's layout."));
+
+                    structure.set_block_at(
+                        coords,
+                        block,
+                        rotation,
+                        &blocks,
+                        Some((&mut evw_block_changed, BlockChangedReason::Unknown)),
+                    );
+                },
+            )
+            .expect("Placing a block should not fail.");
+    }
+
+    /// Removes a block, exactly like a player would. The logic graph is updated on the next [`Self::step`].
+    pub fn remove(&mut self, coords: BlockCoordinate) {
+        let structure_entity = self.structure_entity;
+
+        self.app
+            .world_mut()
+            .run_system_once(
+                move |mut q_structure: Query<&mut Structure>,
+                      blocks: Res<Registry<Block>>,
+                      mut evw_block_changed: MessageWriter<BlockChangedMessage>| {
+                    let mut structure = q_structure.get_mut(structure_entity).expect("Test structure should exist.");
+
+                    structure.remove_block_at(coords, &blocks, Some((&mut evw_block_changed, BlockChangedReason::Unknown)));
+                },
+            )
+            .expect("Removing a block should not fail.");
+    }
+
+    /// Sets the signal a [`TEST_SOURCE`] block outputs. Takes effect on the next [`Self::step`].
+    pub fn set_source(&mut self, coords: BlockCoordinate, signal: i32) {
+        let structure_entity = self.structure_entity;
+
+        self.app
+            .world_mut()
+            .run_system_once(
+                move |mut q_structure: Query<&mut Structure>,
+                      mut q_logic_data: Query<&mut BlockLogicData>,
+                      q_has_data: Query<(), With<BlockLogicData>>,
+                      mut q_block_data: Query<&mut BlockData>,
+                      mut commands: Commands| {
+                    let mut structure = q_structure.get_mut(structure_entity).expect("Test structure should exist.");
+                    let new_state = BlockLogicData(signal);
+
+                    if let Some(mut logic_data) = structure.query_block_data_mut(coords, &mut q_logic_data, &mut commands) {
+                        if **logic_data != new_state {
+                            **logic_data = new_state;
+                        }
+                    } else if new_state.0 != 0 {
+                        structure.insert_block_data(coords, new_state, &mut commands, &mut q_block_data, &q_has_data);
+                    }
+                },
+            )
+            .expect("Setting a test source should not fail.");
+    }
+
+    /// Gives the block at these coordinates this block data, such as the state a block would normally be given when placed.
+    /// Takes effect on the next [`Self::step`].
+    pub fn insert_block_data<T: Component>(&mut self, coords: BlockCoordinate, data: T) {
+        let structure_entity = self.structure_entity;
+        let mut data = Some(data);
+
+        self.app
+            .world_mut()
+            .run_system_once(
+                move |mut q_structure: Query<&mut Structure>,
+                      q_has_data: Query<(), With<T>>,
+                      mut q_block_data: Query<&mut BlockData>,
+                      mut commands: Commands| {
+                    let mut structure = q_structure.get_mut(structure_entity).expect("Test structure should exist.");
+                    let data = data.take().expect("Block data should only be inserted once.");
+
+                    structure.insert_block_data(coords, data, &mut commands, &mut q_block_data, &q_has_data);
+                },
+            )
+            .expect("Inserting block data should not fail.");
+    }
+
+    /// The signal of the logic group the input port on this side of the block belongs to, or `0` if there is no such port.
+    pub fn read_input(&self, coords: BlockCoordinate, direction: BlockDirection) -> i32 {
+        self.logic_driver().read_input(coords, direction)
+    }
+
+    /// Returns true if any input port of the block at these coordinates is connected to something that can send it a signal.
+    pub fn has_connected_input(&self, coords: BlockCoordinate) -> bool {
+        let structure = self
+            .app
+            .world()
+            .get::<Structure>(self.structure_entity)
+            .expect("Test structure should exist.");
+
+        self.logic_driver().has_connected_input(coords, structure.block_rotation(coords))
+    }
+
+    /// The first non-zero signal any face of this [`TEST_PROBE`] is receiving, or `0` if there is none.
+    pub fn probe(&self, coords: BlockCoordinate) -> i32 {
+        let structure = self
+            .app
+            .world()
+            .get::<Structure>(self.structure_entity)
+            .expect("Test structure should exist.");
+
+        self.logic_driver()
+            .read_all_inputs(coords, structure.block_rotation(coords))
+            .into_iter()
+            .find(|&signal| signal != 0)
+            .unwrap_or(0)
+    }
+
+    /// The [`BlockLogicData`] stored by the block at these coordinates, or `0` if it has none.
+    pub fn logic_data(&self, coords: BlockCoordinate) -> i32 {
+        let world = self.app.world();
+        let structure = world.get::<Structure>(self.structure_entity).expect("Test structure should exist.");
+
+        structure
+            .block_data(coords)
+            .and_then(|data_entity| world.get::<BlockLogicData>(data_entity))
+            .map(|data| data.0)
+            .unwrap_or(0)
+    }
+
+    fn logic_driver(&self) -> &LogicDriver {
+        self.app
+            .world()
+            .get::<LogicDriver>(self.structure_entity)
+            .expect("Test structure should have a logic driver.")
+    }
+}
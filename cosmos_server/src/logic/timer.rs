@@ -0,0 +1,69 @@
+//! Server handling for the periods of timed logic blocks (clock, delay line, pulse extender).
+
+use bevy::prelude::*;
+use cosmos_core::{
+    ecs::sets::FixedUpdateSet,
+    logic::timer::{LogicTimerPeriod, SetLogicTimerPeriod},
+    netty::{
+        server::ServerLobby,
+        sync::events::server_event::{NettyMessageReceived, NettyMessageWriter},
+    },
+    notifications::Notification,
+    state::GameState,
+    structure::Structure,
+};
+
+use crate::{
+    persistence::make_persistent::{DefaultPersistentComponent, make_persistent},
+    structure::ownership::StructurePermissions,
+};
+
+impl DefaultPersistentComponent for LogicTimerPeriod {}
+
+fn on_set_logic_timer_period(
+    mut nevr_set_period: MessageReader<NettyMessageReceived<SetLogicTimerPeriod>>,
+    lobby: Res<ServerLobby>,
+    q_structure: Query<&Structure>,
+    mut q_period: Query<&mut LogicTimerPeriod>,
+    permissions: StructurePermissions,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    mut commands: Commands,
+) {
+    for ev in nevr_set_period.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            error!("Bad player ({})!", ev.client_id);
+            continue;
+        };
+
+        if let Err(denied) = permissions.check_build(player, ev.block.structure()) {
+            nevw_notification.write(Notification::error(denied.message()), ev.client_id);
+            continue;
+        }
+
+        let Ok(structure) = q_structure.get(ev.block.structure()) else {
+            warn!("Tried to set logic timer period on non-valid structure!");
+            continue;
+        };
+
+        let Some(mut period) = structure.query_block_data_mut(ev.block.coords(), &mut q_period, &mut commands) else {
+            warn!("Tried to set logic timer period on a block without one!");
+            continue;
+        };
+
+        let new_period = LogicTimerPeriod::new(ev.ticks);
+        if **period != new_period {
+            **period = new_period;
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<LogicTimerPeriod>(app);
+
+    app.add_systems(
+        FixedUpdate,
+        on_set_logic_timer_period
+            .in_set(FixedUpdateSet::Main)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
@@ -1,10 +1,6 @@
-//! Contains all the logic for the server-side of Cosmos.
+//! Runs the server-side of Cosmos.
 
-#![feature(try_blocks)]
-#![feature(iter_array_chunks)]
 #![warn(missing_docs)]
-// This one has a stupid rule where if you have `fn (&self) -> HasLifetime`, you need to do `fn (&self) -> HasLifetime<'_>`. This is stupid.
-#![allow(mismatched_lifetime_syntaxes)]
 
 use bevy::{
     diagnostic::{EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin, SystemInformationDiagnosticsPlugin},
@@ -18,44 +14,11 @@ use bevy_renet::{RenetServerPlugin, steam::SteamServerPlugin};
 use cosmos_core::{physics::collision_handling::CosmosPhysicsFilter, plugin::cosmos_core_plugin::CosmosCorePluginGroup, state::GameState};
 
 // use iyes_perf_ui::PerfUiPlugin;
-use settings::read_server_settings;
+use cosmos_server::settings::read_server_settings;
 
 #[cfg(feature = "print-schedule")]
 use bevy::log::LogPlugin;
 
-pub mod ai;
-pub mod blocks;
-pub mod chat;
-pub mod commands;
-pub mod coms;
-mod converters;
-pub mod crafting;
-pub mod creative;
-mod economy;
-pub mod entities;
-pub mod faction;
-pub mod fluid;
-pub mod init;
-pub mod inventory;
-pub mod items;
-pub mod local;
-pub mod logic;
-pub mod loot;
-pub mod netty;
-pub mod persistence;
-pub mod physics;
-pub mod plugin;
-pub mod projectiles;
-pub mod quest;
-pub mod rng;
-pub mod server;
-pub mod settings;
-pub mod shop;
-pub mod structure;
-pub mod universe;
-
-mod utility_runs;
-
 fn main() {
     let server_settings = read_server_settings();
 
@@ -1,17 +1,24 @@
 //! Used to backup the current world's save file. This does NOT save any new items, only creates a
 //! backup of all currently saved data.
+//!
+//! Backups are incremental - each backup is a folder in `[world_root]/backups/` named after the time it was
+//! taken, and only contains the files that changed since the previous backup. Each backup also has a manifest
+//! listing every file in the world at the time of the backup and which backup holds its contents, so any
+//! backup can be restored on its own via [`restore_backup`].
 
-use bevy::{prelude::*, time::common_conditions::on_timer};
+use bevy::{platform::collections::HashMap, prelude::*, time::common_conditions::on_timer};
 use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use cosmos_core::utils::timer::UtilsTimer;
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     ffi::OsStr,
-    fs::File,
-    io::{self, Read, Write},
-    path::Path,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
 };
 use walkdir::WalkDir;
-use zip::write::SimpleFileOptions;
 
 use crate::persistence::{WorldRoot, saving::SAVING_SCHEDULE};
 
@@ -22,7 +29,309 @@ use super::saving::SavingSystemSet;
 pub struct CreateWorldBackup;
 
 const DATE_FORMAT: &str = "%Y_%m_%d_%H_%M_%S";
-const BACKUP_ENDING: &str = "_world_backup.zip";
+const BACKUPS_DIRECTORY: &str = "backups";
+const MANIFEST_FILE: &str = "manifest.json";
+const PENDING_RESTORE_FILE: &str = "pending_restore";
+/// Backups used to be full zips of the world. These are no longer created, but are still pruned.
+const LEGACY_BACKUP_ENDING: &str = "_world_backup.zip";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+struct BackupFileEntry {
+    size: u64,
+    modified_millis: u128,
+    /// The backup that contains this version of the file
+    stored_in: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+/// Every file in the world at the time of a backup, keyed by their path relative to the world root
+struct BackupManifest {
+    files: BTreeMap<String, BackupFileEntry>,
+}
+
+fn backups_dir(world_dir: &Path) -> PathBuf {
+    world_dir.join(BACKUPS_DIRECTORY)
+}
+
+fn read_manifest(backups_dir: &Path, backup: &str) -> io::Result<BackupManifest> {
+    let data = fs::read(backups_dir.join(backup).join(MANIFEST_FILE))?;
+    serde_json::from_slice(&data).map_err(io::Error::other)
+}
+
+fn write_manifest(backups_dir: &Path, backup: &str, manifest: &BackupManifest) -> io::Result<()> {
+    fs::write(
+        backups_dir.join(backup).join(MANIFEST_FILE),
+        serde_json::to_vec(manifest).map_err(io::Error::other)?,
+    )
+}
+
+fn parse_backup_time(name: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(name, DATE_FORMAT).ok().map(|x| x.and_utc())
+}
+
+/// Every incremental backup in this directory, oldest first
+fn list_backups(backups_dir: &Path) -> Vec<(DateTime<Utc>, String)> {
+    let mut backups = WalkDir::new(backups_dir)
+        .min_depth(1)
+        .max_depth(1)
+        .into_iter()
+        .flatten()
+        .filter(|x| x.file_type().is_dir() && x.path().join(MANIFEST_FILE).exists())
+        .filter_map(|x| {
+            let name = x.file_name().to_str()?.to_owned();
+            Some((parse_backup_time(&name)?, name))
+        })
+        .collect::<Vec<_>>();
+
+    backups.sort();
+    backups
+}
+
+/// Returns the name of every backup of this world that can be restored, oldest first
+pub fn restorable_backups(world_root: &WorldRoot) -> Vec<String> {
+    list_backups(&backups_dir(Path::new(world_root.get())))
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect()
+}
+
+fn relative_path(world_dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(world_dir).ok()?;
+    Some(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+fn modified_millis(metadata: &fs::Metadata) -> u128 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|x| x.duration_since(UNIX_EPOCH).ok())
+        .map(|x| x.as_millis())
+        .unwrap_or_default()
+}
+
+/// Creates a backup named `backup` that only stores the files changed since the most recent backup.
+///
+/// Returns the number of files that were stored in this backup.
+fn create_incremental_backup(world_dir: &Path, backup: &str) -> io::Result<usize> {
+    let backups_dir = backups_dir(world_dir);
+    let previous = list_backups(&backups_dir)
+        .last()
+        .map(|(_, name)| read_manifest(&backups_dir, name))
+        .transpose()?
+        .unwrap_or_default();
+
+    let dest_dir = backups_dir.join(backup);
+    fs::create_dir_all(&dest_dir)?;
+
+    let result: io::Result<(BackupManifest, usize)> = try {
+        let mut manifest = BackupManifest::default();
+        let mut n_stored = 0;
+
+        for entry in WalkDir::new(world_dir)
+            .into_iter()
+            .filter_entry(|x| x.path() != backups_dir)
+            .flatten()
+            .filter(|x| x.file_type().is_file())
+        {
+            let Some(relative) = relative_path(world_dir, entry.path()) else {
+                continue;
+            };
+
+            let metadata = entry.metadata().map_err(io::Error::other)?;
+            let (size, modified_millis) = (metadata.len(), modified_millis(&metadata));
+
+            let entry_info = match previous.files.get(&relative) {
+                Some(prev) if prev.size == size && prev.modified_millis == modified_millis => prev.clone(),
+                _ => {
+                    let dest = dest_dir.join(&relative);
+                    if let Some(parent) = dest.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::copy(entry.path(), dest)?;
+                    n_stored += 1;
+
+                    BackupFileEntry {
+                        size,
+                        modified_millis,
+                        stored_in: backup.to_owned(),
+                    }
+                }
+            };
+
+            manifest.files.insert(relative, entry_info);
+        }
+
+        (manifest, n_stored)
+    };
+
+    let (manifest, n_stored) = match result {
+        Ok(x) => x,
+        Err(e) => {
+            // Don't leave behind a half-finished backup
+            let _ = fs::remove_dir_all(&dest_dir);
+            return Err(e);
+        }
+    };
+
+    write_manifest(&backups_dir, backup, &manifest)?;
+
+    Ok(n_stored)
+}
+
+/// Replaces the contents of the world folder with the contents of this backup.
+///
+/// The server must NOT be running on this world while this is happening.
+pub fn restore_backup(world_root: &WorldRoot, backup: &str) -> io::Result<()> {
+    let world_dir = Path::new(world_root.get());
+    let backups_dir = backups_dir(world_dir);
+    let manifest = read_manifest(&backups_dir, backup)?;
+
+    // Make sure the entire chain is intact before touching the world
+    for (relative, entry) in manifest.files.iter() {
+        if !backups_dir.join(&entry.stored_in).join(relative).is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Backup {backup} is missing {relative} (expected in backup {})", entry.stored_in),
+            ));
+        }
+    }
+
+    for entry in WalkDir::new(world_dir)
+        .into_iter()
+        .filter_entry(|x| x.path() != backups_dir)
+        .flatten()
+        .filter(|x| x.file_type().is_file())
+    {
+        if relative_path(world_dir, entry.path()).is_none_or(|relative| !manifest.files.contains_key(&relative)) {
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    // Empty directories are used to check if things (like sectors) exist, so make sure none are left over.
+    for entry in WalkDir::new(world_dir)
+        .min_depth(1)
+        .contents_first(true)
+        .into_iter()
+        .filter_entry(|x| x.path() != backups_dir)
+        .flatten()
+        .filter(|x| x.file_type().is_dir())
+    {
+        // Fails if the directory isn't empty, which is what we want
+        let _ = fs::remove_dir(entry.path());
+    }
+
+    for (relative, entry) in manifest.files.iter() {
+        let dest = world_dir.join(relative);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(backups_dir.join(&entry.stored_in).join(relative), &dest)?;
+
+        // Keep the original modification time so the next backup doesn't store every file again
+        let modified = UNIX_EPOCH + std::time::Duration::from_millis(entry.modified_millis as u64);
+        File::options().write(true).open(&dest)?.set_modified(modified)?;
+    }
+
+    Ok(())
+}
+
+/// Deletes these backups while keeping every other backup restorable.
+///
+/// Any files a remaining backup still needs are moved into the oldest remaining backup that needs them.
+fn prune_backups(backups_dir: &Path, to_prune: &[String]) -> io::Result<()> {
+    let all = list_backups(backups_dir);
+    let kept = all
+        .iter()
+        .map(|(_, name)| name.clone())
+        .filter(|name| !to_prune.contains(name))
+        .collect::<Vec<_>>();
+
+    let mut manifests = HashMap::new();
+    for name in kept.iter() {
+        manifests.insert(name.clone(), read_manifest(backups_dir, name)?);
+    }
+    let mut dirty = vec![];
+
+    for pruned in all.iter().map(|(_, name)| name).filter(|name| to_prune.contains(name)) {
+        // The oldest remaining backup that needs a file never stored that file itself, so it can safely take it.
+        let mut new_homes = HashMap::new();
+        for name in kept.iter() {
+            for (relative, _) in manifests[name].files.iter().filter(|(_, entry)| &entry.stored_in == pruned) {
+                new_homes.entry(relative.clone()).or_insert_with(|| name.clone());
+            }
+        }
+
+        for (relative, new_home) in new_homes.iter() {
+            let dest = backups_dir.join(new_home).join(relative);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(backups_dir.join(pruned).join(relative), dest)?;
+        }
+
+        for name in kept.iter() {
+            let manifest = manifests.get_mut(name).expect("Loaded above");
+
+            for (relative, entry) in manifest.files.iter_mut().filter(|(_, entry)| &entry.stored_in == pruned) {
+                entry.stored_in = new_homes[relative].clone();
+                if !dirty.contains(name) {
+                    dirty.push(name.clone());
+                }
+            }
+        }
+
+        // Save the manifests before deleting anything so an interruption never leaves them pointing at deleted files
+        for name in dirty.drain(..) {
+            write_manifest(backups_dir, &name, &manifests[&name])?;
+        }
+
+        fs::remove_dir_all(backups_dir.join(pruned))?;
+    }
+
+    Ok(())
+}
+
+/// Marks this backup to be restored the next time the server starts
+pub fn request_restore_on_next_start(world_root: &WorldRoot, backup: &str) -> io::Result<()> {
+    fs::write(backups_dir(Path::new(world_root.get())).join(PENDING_RESTORE_FILE), backup)
+}
+
+/// Restores the requested backup (or the one marked via [`request_restore_on_next_start`]) if there is one.
+///
+/// The world is backed up before it is restored, so restoring can always be undone. The mark is cleared even if the
+/// backup can't be restored, so a broken backup isn't attempted again on every start.
+///
+/// This must be called before anything from the world is loaded. Returns the backup that was restored.
+pub fn restore_pending_backup(world_root: &WorldRoot, requested: Option<&str>) -> io::Result<Option<String>> {
+    let world_dir = Path::new(world_root.get());
+    let pending_file = backups_dir(world_dir).join(PENDING_RESTORE_FILE);
+
+    let backup = match requested {
+        Some(requested) => requested.to_owned(),
+        None => match fs::read_to_string(&pending_file) {
+            Ok(backup) => backup.trim().to_owned(),
+            Err(_) => return Ok(None),
+        },
+    };
+
+    if pending_file.exists() {
+        fs::remove_file(pending_file)?;
+    }
+
+    let safety_backup = format!("{}", Utc::now().format(DATE_FORMAT));
+    create_incremental_backup(world_dir, &safety_backup)?;
+    info!("Backed up the world as {safety_backup} before restoring it to {backup}");
+
+    restore_backup(world_root, &backup)?;
+
+    Ok(Some(backup))
+}
 
 fn backup_world(mut evr_create_backup: MessageReader<CreateWorldBackup>, world_root: Res<WorldRoot>) {
     if evr_create_backup.is_empty() {
@@ -37,13 +346,9 @@ fn backup_world(mut evr_create_backup: MessageReader<CreateWorldBackup>, world_r
     let timer = UtilsTimer::start();
 
     let formatted = format!("{}", date_time.format(DATE_FORMAT));
-    let _ = std::fs::create_dir_all(world_root.path_for("backups/"));
-    let dest_path = format!("{}/{formatted}{BACKUP_ENDING}", world_root.path_for("backups"));
-    let dest_path = Path::new(&dest_path);
-    if let Err(e) = zip_directory(Path::new(world_root.get()), dest_path, &["backups"]) {
-        error!("Error backing up world!!!\n{e:?}");
-    } else {
-        info!("Backup saved to {dest_path:?}");
+    match create_incremental_backup(Path::new(world_root.get()), &formatted) {
+        Ok(n_stored) => info!("Backup {formatted} saved ({n_stored} changed files)"),
+        Err(e) => error!("Error backing up world!!!\n{e:?}"),
     }
 
     timer.log_duration("Backup took");
@@ -54,9 +359,10 @@ fn cleanup_backups(world_root: Res<WorldRoot>) {
 
     let now = Utc::now();
 
-    let mut backups = vec![];
+    let backups_dir = backups_dir(Path::new(world_root.get()));
+    let mut backups = list_backups(&backups_dir);
 
-    for backup in WalkDir::new(world_root.path_for("backups")).max_depth(1) {
+    for backup in WalkDir::new(&backups_dir).max_depth(1) {
         let Ok(backup) = backup else {
             continue;
         };
@@ -70,24 +376,17 @@ fn cleanup_backups(world_root: Res<WorldRoot>) {
             continue;
         };
 
-        if !file_name.ends_with(BACKUP_ENDING) {
-            continue;
-        }
-
-        let Ok(date_time_parsed) =
-            NaiveDateTime::parse_from_str(&file_name[0..file_name.len() - BACKUP_ENDING.len()], DATE_FORMAT).map(|x| x.and_utc())
-        else {
+        let Some(date_time_parsed) = file_name.strip_suffix(LEGACY_BACKUP_ENDING).and_then(parse_backup_time) else {
             continue;
         };
 
-        if now.signed_duration_since(date_time_parsed).num_milliseconds() < 0 {
-            // Don't delete backups marked as being taken in the future, the system clock is
-            // probably wrong in that case.
-            continue;
-        }
-        backups.push((date_time_parsed, path.to_string_lossy().to_string()));
+        backups.push((date_time_parsed, file_name.to_owned()));
     }
 
+    // Don't delete backups marked as being taken in the future, the system clock is
+    // probably wrong in that case.
+    backups.retain(|(time, _)| now.signed_duration_since(*time).num_milliseconds() >= 0);
+
     backups.sort_by_key(|x| x.0);
 
     backups.reverse();
@@ -99,12 +398,25 @@ fn cleanup_backups(world_root: Res<WorldRoot>) {
 
     if backups.is_empty() {
         info!("No backups to prune.");
+        return;
     }
 
     // If any backups remain in this list, they don't meet our time-span criteria.
-    for (_, path) in backups.into_iter() {
-        info!("Pruning old backup {path}");
-        std::fs::remove_file(&path).unwrap_or_else(|e| panic!("failed to remove file @ {path}!\n{e:?}"));
+    let (legacy, incremental): (Vec<_>, Vec<_>) = backups.into_iter().map(|(_, name)| name).partition(|x| x.ends_with(".zip"));
+
+    for name in legacy {
+        info!("Pruning old backup {name}");
+        let path = backups_dir.join(&name);
+        if let Err(e) = fs::remove_file(&path) {
+            error!("Failed to remove old backup @ {path:?}!\n{e:?}");
+        }
+    }
+
+    if !incremental.is_empty() {
+        info!("Pruning old backups {incremental:?}");
+        if let Err(e) = prune_backups(&backups_dir, &incremental) {
+            error!("Failed to prune old backups!\n{e:?}");
+        }
     }
 }
 
@@ -126,52 +438,10 @@ fn prune_by_interval(backups: &mut Vec<(DateTime<Utc>, String)>, now: DateTime<U
     }
 }
 
-/// Zips the contents of a directory into a zip file.
-///
-/// # Arguments
-///
-/// * `src_dir` - The source directory to zip.
-/// * `dest_file` - The path to the output zip file.
-///
-/// # Errors
-///
-/// Returns an error if the directory traversal or file writing fails.
-pub fn zip_directory(src_dir: &Path, dest_file: &Path, ignore: &[&str]) -> io::Result<()> {
-    let file = File::create(dest_file)?;
-    let mut zip = zip::ZipWriter::new(file);
-    let mut buffer = Vec::new();
-
-    let options = SimpleFileOptions::default();
-
-    for entry in walkdir::WalkDir::new(src_dir) {
-        let entry = entry?;
-        let path = entry.path();
-
-        let name = path.strip_prefix(src_dir).unwrap().to_str().unwrap();
-
-        if ignore.iter().any(|i| name.to_lowercase().starts_with(*i)) {
-            continue;
-        };
-
-        if path.is_file() {
-            zip.start_file(name, options)?;
-            let mut f = File::open(path)?;
-            f.read_to_end(&mut buffer)?;
-            zip.write_all(&buffer)?;
-            buffer.clear();
-        } else if path.is_dir() {
-            zip.add_directory(name, options)?;
-        }
-    }
-
-    zip.finish()?;
-    Ok(())
-}
-
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 /// Responsible for taking a backup of the currently saved world
 pub enum BackupSystemSet {
-    /// Copies the currently saved world into a zip backup
+    /// Copies the files changed since the last backup into a new backup
     PerformBackup,
 }
 
@@ -182,3 +452,91 @@ pub(super) fn register(app: &mut App) {
         .add_systems(FixedUpdate, cleanup_backups.run_if(on_timer(std::time::Duration::from_mins(20))))
         .add_message::<CreateWorldBackup>();
 }
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::Path};
+
+    use super::{
+        backups_dir, create_incremental_backup, list_backups, prune_backups, request_restore_on_next_start, restore_backup,
+        restore_pending_backup,
+    };
+    use crate::persistence::WorldRoot;
+
+    fn read(world: &Path, file: &str) -> Option<String> {
+        fs::read_to_string(world.join(file)).ok()
+    }
+
+    #[test]
+    fn restores_from_incremental_chain() {
+        let world = std::env::temp_dir().join(format!("cosmos_backup_test_{}", uuid::Uuid::new_v4()));
+        let world_root = WorldRoot::dir_for_world_root(world.to_str().unwrap());
+        fs::create_dir_all(world.join("0_0_0")).unwrap();
+        fs::write(world.join("0_0_0/ship.cent"), "ship v1").unwrap();
+        fs::write(world.join("factions.bin"), "factions").unwrap();
+
+        assert_eq!(create_incremental_backup(&world, "2020_01_01_00_00_00").unwrap(), 2);
+
+        fs::write(world.join("0_0_0/ship.cent"), "ship v2 - now bigger").unwrap();
+        fs::remove_file(world.join("factions.bin")).unwrap();
+        fs::create_dir_all(world.join("1_0_0")).unwrap();
+        fs::write(world.join("1_0_0/station.cent"), "station").unwrap();
+
+        // Only the changed & new files are stored
+        assert_eq!(create_incremental_backup(&world, "2020_01_02_00_00_00").unwrap(), 2);
+
+        restore_backup(&world_root, "2020_01_01_00_00_00").unwrap();
+        assert_eq!(read(&world, "0_0_0/ship.cent").as_deref(), Some("ship v1"));
+        assert_eq!(read(&world, "factions.bin").as_deref(), Some("factions"));
+        assert!(!world.join("1_0_0").exists());
+
+        // The restored files are unchanged from the first backup, so nothing needs storing
+        assert_eq!(create_incremental_backup(&world, "2020_01_03_00_00_00").unwrap(), 0);
+
+        // Pruning the first backup must keep the third restorable, even though its files were stored in the first
+        prune_backups(&backups_dir(&world), &["2020_01_01_00_00_00".into()]).unwrap();
+        assert_eq!(list_backups(&backups_dir(&world)).len(), 2);
+
+        restore_backup(&world_root, "2020_01_02_00_00_00").unwrap();
+        assert_eq!(read(&world, "0_0_0/ship.cent").as_deref(), Some("ship v2 - now bigger"));
+        assert_eq!(read(&world, "factions.bin"), None);
+
+        restore_backup(&world_root, "2020_01_03_00_00_00").unwrap();
+        assert_eq!(read(&world, "0_0_0/ship.cent").as_deref(), Some("ship v1"));
+        assert_eq!(read(&world, "factions.bin").as_deref(), Some("factions"));
+
+        fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn pending_restore_is_backed_up_and_cleared() {
+        let world = std::env::temp_dir().join(format!("cosmos_backup_test_{}", uuid::Uuid::new_v4()));
+        let world_root = WorldRoot::dir_for_world_root(world.to_str().unwrap());
+        fs::create_dir_all(&world).unwrap();
+        fs::write(world.join("factions.bin"), "factions v1").unwrap();
+
+        create_incremental_backup(&world, "2020_01_01_00_00_00").unwrap();
+        fs::write(world.join("factions.bin"), "factions v2").unwrap();
+
+        request_restore_on_next_start(&world_root, "2020_01_01_00_00_00").unwrap();
+        assert_eq!(
+            restore_pending_backup(&world_root, None).unwrap().as_deref(),
+            Some("2020_01_01_00_00_00")
+        );
+        assert_eq!(read(&world, "factions.bin").as_deref(), Some("factions v1"));
+
+        // The world was backed up before it was restored
+        let backups = list_backups(&backups_dir(&world));
+        assert_eq!(backups.len(), 2);
+        restore_backup(&world_root, &backups[1].1).unwrap();
+        assert_eq!(read(&world, "factions.bin").as_deref(), Some("factions v2"));
+
+        // A backup that can't be restored is only attempted once
+        request_restore_on_next_start(&world_root, "2020_01_05_00_00_00").unwrap();
+        assert!(restore_pending_backup(&world_root, None).is_err());
+        assert_eq!(restore_pending_backup(&world_root, None).unwrap(), None);
+        assert_eq!(read(&world, "factions.bin").as_deref(), Some("factions v2"));
+
+        fs::remove_dir_all(&world).unwrap();
+    }
+}
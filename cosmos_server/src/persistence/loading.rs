@@ -27,7 +27,7 @@ use cosmos_core::{
 
 use crate::persistence::WorldRoot;
 
-use super::{PreviousSaveFileIdentifier, SaveFileIdentifier, SaveFileIdentifierType, SerializedData};
+use super::{PreviousSaveFileIdentifier, SaveFileIdentifier, SaveFileIdentifierType, SerializedData, registry::register_persistent_data};
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 /// Put anything related to loading entities in from serialized data into this set
@@ -266,6 +266,11 @@ fn load_blueprint_rotation(mut commands: Commands, mut q_needs_blueprint: Query<
 pub const LOADING_SCHEDULE: FixedUpdate = FixedUpdate;
 
 pub(super) fn register(app: &mut App) {
+    register_persistent_data::<Location>(app, "cosmos:location");
+    register_persistent_data::<Velocity>(app, "cosmos:velocity");
+    register_persistent_data::<LoadingDistance>(app, "cosmos:loading_distance");
+    register_persistent_data::<Quat>(app, "cosmos:rotation");
+
     app.configure_sets(
         LOADING_SCHEDULE,
         (
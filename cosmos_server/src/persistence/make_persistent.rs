@@ -5,10 +5,7 @@ use cosmos_core::{
     block::data::{BlockData, persistence::ChunkLoadBlockDataMessage},
     entities::EntityId,
     netty::sync::IdentifiableComponent,
-    structure::{
-        Structure, chunk::netty::SerializedBlockData, coordinates::ChunkBlockCoordinate, loading::StructureLoadingSet,
-        persistence::DeserializationError,
-    },
+    structure::{Structure, chunk::netty::SerializedBlockData, coordinates::ChunkBlockCoordinate, loading::StructureLoadingSet},
     utils::ownership::MaybeOwned,
 };
 use serde::{Serialize, de::DeserializeOwned};
@@ -21,6 +18,8 @@ use crate::{
 use super::{
     SerializedData,
     loading::{LOADING_SCHEDULE, LoadingSystemSet, NeedsLoaded},
+    migrations::PersistenceMigrations,
+    registry::register_persistent_component,
     saving::{NeedsSaved, SAVING_SCHEDULE, SavingSystemSet},
 };
 
@@ -37,7 +36,7 @@ fn save_component<T: PersistentComponent>(
             return;
         };
 
-        serialized_data.serialize_data(T::get_component_unlocalized_name(), save_type.as_ref());
+        serialized_data.serialize_versioned_data(T::get_component_unlocalized_name(), T::VERSION, save_type.as_ref());
     });
 }
 
@@ -59,9 +58,10 @@ fn save_component_block_data<T: PersistentComponent>(
             return;
         };
 
-        serialized_block_data.serialize_data(
+        serialized_block_data.serialize_versioned_data(
             ChunkBlockCoordinate::for_block_coordinate(block_data.identifier.block.coords()),
             T::get_component_unlocalized_name(),
+            T::VERSION,
             save_type.as_ref(),
         );
     });
@@ -80,27 +80,31 @@ fn save_component_itemstack_data<T: PersistentComponent>(
             return;
         };
 
-        sd.serialize_data(T::get_component_unlocalized_name(), save_type.as_ref());
+        sd.serialize_versioned_data(T::get_component_unlocalized_name(), T::VERSION, save_type.as_ref());
     });
 }
 
 fn load_component<T: PersistentComponent>(
     mut commands: Commands,
-    q_needs_loaded: Query<(Entity, &SerializedData), With<NeedsLoaded>>,
+    q_needs_loaded: Query<(Entity, &SerializedData, Option<&EntityId>), With<NeedsLoaded>>,
     entity_id_manager: EntityIdManager,
     q_name: Query<&Name>,
+    migrations: Res<PersistenceMigrations>,
 ) {
-    q_needs_loaded.iter().for_each(|(entity, serialized_data)| {
-        let component_save_data = match serialized_data.deserialize_data::<T::SaveType>(T::get_component_unlocalized_name()) {
-            Ok(data) => data,
-            Err(DeserializationError::NoEntry) => return,
-            Err(DeserializationError::ErrorParsing(e)) => {
-                let id = q_name
+    q_needs_loaded.iter().for_each(|(entity, serialized_data, entity_id)| {
+        let component_save_data = match migrations.read_component::<T>(serialized_data.save_data()) {
+            Ok(Some(data)) => data,
+            Ok(None) => return,
+            Err(e) => {
+                let mut id = q_name
                     .get(entity)
                     .map(|x| format!("{x} ({entity:?})"))
                     .unwrap_or_else(|_| format!("{entity:?}"));
+                if let Some(entity_id) = entity_id {
+                    id.push_str(&format!(" [{entity_id:?}]"));
+                }
                 error!(
-                    "Error deserializing component {} on entity {id}\n{e:?}.",
+                    "Error loading component {} on entity {id} - {e}.",
                     T::get_component_unlocalized_name()
                 );
                 return;
@@ -125,9 +129,10 @@ fn load_component_for_itemstack<T: PersistentComponent>(
     q_needs_loaded: Query<(Entity, &SerializedItemStackData), With<ItemStackDataNeedsLoaded>>,
     entity_id_manager: EntityIdManager,
     q_name: Query<&Name>,
+    migrations: Res<PersistenceMigrations>,
 ) {
     q_needs_loaded.iter().for_each(|(entity, serialized_data)| {
-        deserialize_and_load_component::<T>(&mut commands, &entity_id_manager, &q_name, entity, serialized_data);
+        deserialize_and_load_component::<T>(&mut commands, &entity_id_manager, &q_name, &migrations, entity, serialized_data);
     });
 }
 
@@ -135,19 +140,20 @@ fn deserialize_and_load_component<T: PersistentComponent>(
     commands: &mut Commands,
     entity_id_manager: &EntityIdManager,
     q_name: &Query<&Name>,
+    migrations: &PersistenceMigrations,
     entity: Entity,
     serialized_data: &SerializedItemStackData,
 ) {
-    let component_save_data = match serialized_data.deserialize_data::<T::SaveType>(T::get_component_unlocalized_name()) {
-        Ok(data) => data,
-        Err(DeserializationError::NoEntry) => return,
-        Err(DeserializationError::ErrorParsing(e)) => {
+    let component_save_data = match migrations.read_component::<T>(serialized_data) {
+        Ok(Some(data)) => data,
+        Ok(None) => return,
+        Err(e) => {
             let id = q_name
                 .get(entity)
                 .map(|x| format!("{x} ({entity:?})"))
                 .unwrap_or_else(|_| format!("{entity:?}"));
             error!(
-                "Error deserializing component {} on entity {id}\n{e:?}.",
+                "Error loading component {} on item stack entity {id} - {e}.",
                 T::get_component_unlocalized_name()
             );
             return;
@@ -172,6 +178,7 @@ fn load_component_from_block_data<T: PersistentComponent>(
     mut ev_reader: MessageReader<ChunkLoadBlockDataMessage>,
     q_has_component: Query<(), With<T>>,
     entity_id_manager: EntityIdManager,
+    migrations: Res<PersistenceMigrations>,
 ) {
     for ev in ev_reader.read() {
         let Ok(mut structure) = q_structure.get_mut(ev.structure_entity) else {
@@ -181,13 +188,15 @@ fn load_component_from_block_data<T: PersistentComponent>(
 
         let first = ev.chunk.first_structure_block();
         for (data_coord, serialized) in ev.data.iter() {
-            let component_save_data = match serialized.deserialize_data::<T::SaveType>(T::get_component_unlocalized_name()) {
-                Ok(data) => data,
-                Err(DeserializationError::NoEntry) => continue,
-                Err(DeserializationError::ErrorParsing(e)) => {
+            let component_save_data = match migrations.read_component::<T>(serialized) {
+                Ok(Some(data)) => data,
+                Ok(None) => continue,
+                Err(e) => {
                     error!(
-                        "Error deserializing block data component {} - {e:?}.",
-                        T::get_component_unlocalized_name()
+                        "Error loading block data component {} for block data @ {} in structure {:?} - {e}.",
+                        T::get_component_unlocalized_name(),
+                        first + *data_coord,
+                        ev.structure_entity
                     );
                     continue;
                 }
@@ -244,6 +253,13 @@ pub trait PersistentComponent: IdentifiableComponent + Sized {
     /// runtime.
     type SaveType: Serialize + DeserializeOwned;
 
+    /// The version of [`PersistentComponent::SaveType`]'s format.
+    ///
+    /// Bump this whenever the save type's format changes, and register a migration from the
+    /// previous version via [`super::migrations::add_persistence_migration`] so existing worlds
+    /// can still be loaded.
+    const VERSION: u32 = 0;
+
     /// Initializes this component before adding it to this entity
     ///
     /// Mostly used to clear out any junk data that got saved
@@ -263,6 +279,9 @@ pub trait PersistentComponent: IdentifiableComponent + Sized {
 }
 
 /// This component will be saved & loaded when the entity it is a part of is saved/unloaded.
+///
+/// These are always saved as version 0 - implement [`PersistentComponent`] directly if this
+/// component's format ever needs a [`PersistentComponent::VERSION`] bump.
 pub trait DefaultPersistentComponent: PersistentComponent<SaveType = Self> {
     /// Initializes this component before adding it to this entity
     ///
@@ -293,6 +312,8 @@ where
 ///
 /// When this entity is loaded again, the component will also be loaded.
 pub fn make_persistent<T: PersistentComponent>(app: &mut App) {
+    register_persistent_component::<T>(app);
+
     app.add_systems(SAVING_SCHEDULE, save_component::<T>.in_set(SavingSystemSet::DoSaving))
         .add_systems(LOADING_SCHEDULE, load_component::<T>.in_set(LoadingSystemSet::LoadBasicComponents))
         // Block Data
@@ -0,0 +1,199 @@
+//! Upgrades the saved data of [`PersistentComponent`]s that were saved with an older version of their format.
+//!
+//! Whenever the format of a persistent component changes, bump its [`PersistentComponent::VERSION`] and
+//! register a migration from the previous version via [`add_persistence_migration`].
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use bincode::error::DecodeError;
+use cosmos_core::{netty::cosmos_encoder, structure::persistence::SaveData};
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use super::make_persistent::PersistentComponent;
+
+type MigrationFn = Box<dyn Fn(&[u8]) -> Result<Vec<u8>, Box<DecodeError>> + Send + Sync>;
+
+#[derive(Resource, Default)]
+/// Every migration that has been registered via [`add_persistence_migration`], keyed by the
+/// component's unlocalized name and the version the migration upgrades from.
+pub struct PersistenceMigrations(HashMap<(&'static str, u32), MigrationFn>);
+
+#[derive(Error, Debug)]
+/// Something went wrong reading a [`PersistentComponent`] from its saved data
+pub enum ComponentLoadError {
+    /// The data was saved by a newer version of the game than this one
+    #[error("data was saved as version {found}, but the latest version this server understands is {latest}")]
+    NewerVersion {
+        /// The version the data was saved as
+        found: u32,
+        /// The component's current version
+        latest: u32,
+    },
+    /// There is no way of upgrading the data to the next version
+    #[error("no migration is registered to upgrade data from version {from} to version {}", from + 1)]
+    MissingMigration {
+        /// The version that could not be upgraded
+        from: u32,
+    },
+    /// The data could not be read as the type the migration expected
+    #[error("migrating data from version {from} to version {} failed - {error:?}", from + 1)]
+    Migration {
+        /// The version that could not be upgraded
+        from: u32,
+        /// The underlying parsing error
+        error: Box<DecodeError>,
+    },
+    /// The (fully migrated) data could not be read as the component's save type
+    #[error("unable to parse data saved as version {version} - {error:?}")]
+    Parsing {
+        /// The version the data was in when parsing failed
+        version: u32,
+        /// The underlying parsing error
+        error: Box<DecodeError>,
+    },
+}
+
+impl PersistenceMigrations {
+    /// Registers a migration that upgrades `T`'s saved data from `from_version` to `from_version + 1`.
+    ///
+    /// `Old` is the format the data was saved in at `from_version`, and `New` is the format at `from_version + 1`.
+    pub fn add<T: PersistentComponent, Old: DeserializeOwned, New: Serialize>(&mut self, from_version: u32, migrate: fn(Old) -> New) {
+        let key = (T::get_component_unlocalized_name(), from_version);
+        assert!(
+            !self.0.contains_key(&key),
+            "Duplicate migration registered for {} from version {from_version}",
+            key.0
+        );
+
+        self.0.insert(
+            key,
+            Box::new(move |data| cosmos_encoder::deserialize::<Old>(data).map(|old| cosmos_encoder::serialize(&migrate(old)))),
+        );
+    }
+
+    /// Reads `T`'s save type from this save data, running every migration needed to bring it up to
+    /// [`PersistentComponent::VERSION`].
+    ///
+    /// Returns `Ok(None)` if nothing was saved for `T`.
+    pub fn read_component<T: PersistentComponent>(&self, save_data: &SaveData) -> Result<Option<T::SaveType>, ComponentLoadError> {
+        let id = T::get_component_unlocalized_name();
+        let Some(data) = save_data.read_data(id) else {
+            return Ok(None);
+        };
+
+        let mut version = save_data.data_version(id);
+        if version > T::VERSION {
+            return Err(ComponentLoadError::NewerVersion {
+                found: version,
+                latest: T::VERSION,
+            });
+        }
+
+        let mut migrated = None;
+        while version < T::VERSION {
+            let Some(migration) = self.0.get(&(id, version)) else {
+                return Err(ComponentLoadError::MissingMigration { from: version });
+            };
+
+            let data = migrated.as_deref().unwrap_or(data.as_slice());
+            migrated = Some(migration(data).map_err(|error| ComponentLoadError::Migration { from: version, error })?);
+            version += 1;
+        }
+
+        cosmos_encoder::deserialize(migrated.as_deref().unwrap_or(data.as_slice()))
+            .map(Some)
+            .map_err(|error| ComponentLoadError::Parsing { version, error })
+    }
+}
+
+/// Registers a migration that upgrades `T`'s saved data from `from_version` to `from_version + 1`.
+///
+/// `Old` is the format the data was saved in at `from_version`, and `New` is the format at `from_version + 1`.
+/// There must be a migration for every version between 0 and [`PersistentComponent::VERSION`] that
+/// existing worlds may have saved the component as.
+pub fn add_persistence_migration<T: PersistentComponent, Old: DeserializeOwned, New: Serialize>(
+    app: &mut App,
+    from_version: u32,
+    migrate: fn(Old) -> New,
+) {
+    app.world_mut()
+        .get_resource_or_init::<PersistenceMigrations>()
+        .add::<T, Old, New>(from_version, migrate);
+}
+
+pub(super) fn register(app: &mut App) {
+    app.init_resource::<PersistenceMigrations>();
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::*;
+    use cosmos_core::{
+        entities::EntityId, netty::sync::IdentifiableComponent, structure::persistence::SaveData, utils::ownership::MaybeOwned,
+    };
+    use serde::{Deserialize, Serialize};
+
+    use super::{ComponentLoadError, PersistenceMigrations};
+    use crate::persistence::make_persistent::{EntityIdManager, PersistentComponent};
+
+    #[derive(Component, Serialize, Deserialize, Debug, PartialEq)]
+    struct Health {
+        current: f32,
+        max: f32,
+    }
+
+    impl IdentifiableComponent for Health {
+        fn get_component_unlocalized_name() -> &'static str {
+            "cosmos:test_health"
+        }
+    }
+
+    impl PersistentComponent for Health {
+        type SaveType = Self;
+
+        const VERSION: u32 = 2;
+
+        fn convert_to_save_type<'a>(&'a self, _: &Query<&EntityId>) -> Option<MaybeOwned<'a, Self::SaveType>> {
+            Some(MaybeOwned::Borrowed(self))
+        }
+
+        fn convert_from_save_type(save_type: Self::SaveType, _: &EntityIdManager) -> Option<Self> {
+            Some(save_type)
+        }
+    }
+
+    #[test]
+    fn migrates_old_versions() {
+        let id = Health::get_component_unlocalized_name();
+        let mut migrations = PersistenceMigrations::default();
+
+        // Version 0 only stored the current health
+        let mut save_data = SaveData::default();
+        save_data.serialize_data(id, &50.0_f32);
+        assert!(matches!(
+            migrations.read_component::<Health>(&save_data),
+            Err(ComponentLoadError::MissingMigration { from: 0 })
+        ));
+
+        migrations.add::<Health, f32, (f32, f32)>(0, |current| (current, 100.0));
+        migrations.add::<Health, (f32, f32), Health>(1, |(current, max)| Health { current, max });
+        assert_eq!(
+            migrations.read_component::<Health>(&save_data).unwrap(),
+            Some(Health { current: 50.0, max: 100.0 })
+        );
+
+        save_data.serialize_versioned_data(id, 2, &Health { current: 1.0, max: 2.0 });
+        assert_eq!(
+            migrations.read_component::<Health>(&save_data).unwrap(),
+            Some(Health { current: 1.0, max: 2.0 })
+        );
+
+        save_data.serialize_versioned_data(id, 3, &Health { current: 1.0, max: 2.0 });
+        assert!(matches!(
+            migrations.read_component::<Health>(&save_data),
+            Err(ComponentLoadError::NewerVersion { found: 3, latest: 2 })
+        ));
+
+        assert_eq!(migrations.read_component::<Health>(&SaveData::default()).unwrap(), None);
+    }
+}
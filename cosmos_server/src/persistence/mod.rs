@@ -9,15 +9,8 @@ use bevy::{
     platform::collections::{HashMap, HashSet},
     prelude::*,
 };
-use derive_more::Display;
-use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
-use cosmos_core::{
-    entities::EntityId,
-    netty::sync::IdentifiableComponent,
-    physics::location::{Location, Sector},
-    structure::persistence::*,
-};
+use cosmos_core::{entities::EntityId, physics::location::Sector};
 
 use crate::settings::ServerSettings;
 
@@ -25,36 +18,13 @@ pub mod autosave;
 pub mod backup;
 pub mod loading;
 pub mod make_persistent;
+pub mod migrations;
 pub mod player_loading;
+pub mod registry;
+mod save_file;
 pub mod saving;
 
-#[derive(Resource, Display, Debug, Clone)]
-/// Stores the directory that is the root of the world save
-///
-/// Please use this and never assume the root directry
-pub struct WorldRoot(String);
-
-impl WorldRoot {
-    pub(crate) fn dir_for_world_root(root: &str) -> WorldRoot {
-        WorldRoot(format!("{}/", root.trim_end_matches("/")))
-    }
-
-    /// Gets the root dir. You should generally prefer using [`Self::path_for`].
-    pub fn get(&self) -> &str {
-        self.0.as_str()
-    }
-
-    /// Computes the path this subdirectory would be in for this world root directory
-    pub fn path_for(&self, subdir: &str) -> String {
-        format!("{self}{subdir}")
-    }
-}
-
-/// This extension is given to entities that are loaded + saved following the normal rules.
-pub const NORMAL_ENTITY_EXTENSION: &str = "cent";
-/// This entity should NOT be loaded through the normaly mechanisms, and is fully controlled by its
-/// parent when it gets loaded/saved and what data is put in that file.
-pub const OWNED_ENTITY_EXTENSION: &str = "ocent";
+pub use save_file::*;
 
 #[derive(Debug, Resource, Default, Clone)]
 /// This is a resource that caches the saved entities of different sectors that a player has been near.
@@ -94,254 +64,12 @@ impl SectorsCache {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub(crate) enum SaveFileIdentifierType {
-    /// This entity does not belong to any other entity
-    Base(EntityId, Option<Sector>, Option<u32>),
-    /// Denotes that this entity is a "child" of the parent, but is not "owned" by the parent.
-    ///
-    /// (ChildOf SaveFileIdentifier, this entity id)
-    SubEntity(Box<SaveFileIdentifier>, EntityId),
-    /// Denotes that this entity belongs to another entity, and should be saved
-    /// in that entity's folder. Once this entity is saved, the [`SaveFileIdentifierType`] component will be removed.
-    ///
-    /// ## Note:
-    /// While saving is handled for you, it is up to you to load this yourself.
-    ///
-    /// This will be saved to `[world_root]/x_y_z/belongsToEntityId/thisEntityId.cent`
-    BelongsTo(Box<SaveFileIdentifier>, String),
-}
-
 #[derive(Component)]
 /// Stores the previous save file identifier from when this was last loaded/saved.
 ///
 /// This is used to clean up old versions of this entity from disk as new ones are saved.
 struct PreviousSaveFileIdentifier(pub SaveFileIdentifier);
 
-#[derive(Debug, Component, Clone, Serialize, Deserialize, PartialEq, Eq)]
-/// Used to track where the save file for a given entity is or should be.
-pub struct SaveFileIdentifier {
-    identifier_type: SaveFileIdentifierType,
-}
-
-impl SaveFileIdentifier {
-    /// Creates a new SaveFileIdentifier from this location & entity id
-    pub fn new(sector: Option<Sector>, entity_id: EntityId, load_distance: Option<u32>) -> Self {
-        Self {
-            identifier_type: SaveFileIdentifierType::Base(entity_id, sector, load_distance),
-        }
-    }
-
-    /// Creates a new SaveFileIdentifier from this location & entity id
-    pub fn sub_entity(parent_save_file_identifier: SaveFileIdentifier, entity_id: EntityId) -> Self {
-        Self {
-            identifier_type: SaveFileIdentifierType::SubEntity(Box::new(parent_save_file_identifier), entity_id),
-        }
-    }
-
-    /// If this SaveFileIdentifier is a base identifier (not child),
-    /// this will return its EntityId. Otherwise, returns None.
-    pub fn entity_id(&self) -> Option<&EntityId> {
-        match &self.identifier_type {
-            SaveFileIdentifierType::Base(entity_id, _, _) => Some(entity_id),
-            SaveFileIdentifierType::SubEntity(_, entity_id) => Some(entity_id),
-            _ => None,
-        }
-    }
-
-    /// Creates a new SaveFileIdentifier from this location & entity id
-    pub fn as_child(this_identifier: impl Into<String>, belongs_to: SaveFileIdentifier) -> Self {
-        Self {
-            identifier_type: SaveFileIdentifierType::BelongsTo(Box::new(belongs_to), this_identifier.into()),
-        }
-    }
-
-    /// If this is a SubEntity, this will return the parent.
-    /// Otherwise, returns None.
-    pub fn get_parent(&self) -> Option<&SaveFileIdentifier> {
-        match &self.identifier_type {
-            SaveFileIdentifierType::SubEntity(parent, _) => Some(parent.as_ref()),
-            _ => None,
-        }
-    }
-
-    /// Gets the file path a given entity will be saved to.
-    pub fn get_save_file_path(&self, world_root: &WorldRoot) -> String {
-        let extension = match self.identifier_type {
-            SaveFileIdentifierType::BelongsTo(_, _) => OWNED_ENTITY_EXTENSION,
-            _ => NORMAL_ENTITY_EXTENSION,
-        };
-
-        world_root.path_for(format!("{}.{extension}", self.get_save_file_directory(Self::get_save_file_name)).as_str())
-    }
-
-    /// Gets the save file name without the .cent extension, but not the whole path
-    fn get_save_file_name(&self) -> String {
-        match &self.identifier_type {
-            SaveFileIdentifierType::Base(entity, _, load_distance) => {
-                load_distance.map(|ld| format!("{ld}_{entity}")).unwrap_or(entity.to_string())
-            }
-            SaveFileIdentifierType::SubEntity(_, entity_id) => entity_id.to_string(),
-            SaveFileIdentifierType::BelongsTo(_, name) => name.to_owned(),
-        }
-    }
-
-    /// Gets the save file name without the .cent extension, but not the whole path
-    fn get_save_file_name_no_load_distance(&self) -> String {
-        match &self.identifier_type {
-            SaveFileIdentifierType::Base(entity, _, _) => entity.to_string(),
-            SaveFileIdentifierType::SubEntity(_, entity_id) => entity_id.to_string(),
-            SaveFileIdentifierType::BelongsTo(_, name) => name.to_owned(),
-        }
-    }
-
-    /// Gets the save file name, but not the whole path
-    fn get_save_file_directory(&self, base_get_save_file_name: impl Fn(&Self) -> String) -> String {
-        match &self.identifier_type {
-            SaveFileIdentifierType::Base(_, sector, _) => {
-                let directory = sector.map(Self::get_sector_path).unwrap_or_else(|| {
-                    error!("GOT NO DIRECTORY TO SAVE - THIS IS BAD - SOMETHING WILL NOT BE SAVED IN THE RIGHT SPOT!");
-                    error!("{self:?}");
-
-                    "nowhere".into()
-                });
-
-                format!("{directory}/{}", base_get_save_file_name(self))
-            }
-            SaveFileIdentifierType::SubEntity(belongs_to, _) => {
-                format!(
-                    "{}/{}",
-                    belongs_to.get_save_file_directory(Self::get_save_file_name_no_load_distance),
-                    base_get_save_file_name(self)
-                )
-            }
-            SaveFileIdentifierType::BelongsTo(belongs_to, _) => {
-                format!(
-                    "{}/{}",
-                    belongs_to.get_save_file_directory(Self::get_save_file_name_no_load_distance),
-                    base_get_save_file_name(self)
-                )
-            }
-        }
-    }
-
-    /// Gets the directory path all children of this entity would be saved to
-    pub fn get_children_directory(&self) -> String {
-        self.get_save_file_directory(Self::get_save_file_name_no_load_distance)
-    }
-
-    /// Gets the directory for this sector's save folder
-    fn get_sector_path(sector: Sector) -> String {
-        let (x, y, z) = (sector.x(), sector.y(), sector.z());
-
-        format!("{x}_{y}_{z}")
-    }
-}
-
-#[derive(Component, Debug, Reflect, Serialize, Deserialize)]
-/// Stores the serialized data for an entity.
-///
-/// This is either read from or written to a save file depending on if an entity is being loaded or saved.
-pub struct SerializedData {
-    save_data: SaveData,
-
-    /// Used to identify the location this should be saved under
-    location: Option<Location>,
-    should_save: bool,
-}
-
-impl SerializedData {
-    /// Creates serialized data from an existing [`SaveData`] payload.
-    pub(crate) fn from_save_data(save_data: SaveData) -> Self {
-        Self {
-            save_data,
-            location: None,
-            should_save: true,
-        }
-    }
-
-    /// Use this to set location. This will make sure the folder name
-    /// reflects the actual location.
-    pub fn set_location(&mut self, loc: &Location) {
-        self.serialize_data("cosmos:location", loc);
-        self.location = Some(*loc);
-    }
-}
-
-impl Default for SerializedData {
-    fn default() -> Self {
-        Self {
-            save_data: SaveData::default(),
-            location: None,
-            should_save: true,
-        }
-    }
-}
-
-impl SerializedData {
-    /// Saves the data to that data id. Will overwrite any existing data at that id.
-    ///
-    /// Will only save if `should_save()` returns true.
-    pub fn save(&mut self, data_id: impl Into<String>, data: Vec<u8>) {
-        if self.should_save() {
-            self.save_data.save(data_id, data);
-        }
-    }
-
-    /// Calls `cosmos_encoder::serialize` on the passed in data.
-    /// Then sends that data into the `save` method, with the given data id.
-    ///
-    /// Will only serialize & save if `should_save()` returns true.
-    pub fn serialize_data(&mut self, data_id: impl Into<String>, data: &impl Serialize) {
-        if self.should_save() {
-            self.save_data.serialize_data(data_id, data);
-        }
-    }
-
-    /// Calls `cosmos_encoder::serialize` on the passed in data.
-    /// Then sends that data into the `save` method, with the given data id.
-    ///
-    /// Will only serialize & save if `should_save()` returns true.
-    pub fn serialize_identifiable<T: IdentifiableComponent + Serialize>(&mut self, data: &T) {
-        self.serialize_data(T::get_component_unlocalized_name(), data);
-    }
-
-    /// Reads the data as raw bytes at the given data id. Use `deserialize_data` for a streamlined way to read the data.
-    pub fn read_data(&self, data_id: &str) -> Option<&Vec<u8>> {
-        self.save_data.read_data(data_id)
-    }
-
-    /// Deserializes the data as the given type (via `cosmos_encoder::deserialize`) at the given id. Will panic if the
-    /// data is not properly serialized.
-    pub fn deserialize_data<T: DeserializeOwned>(&self, data_id: &str) -> Result<T, DeserializationError> {
-        self.save_data.deserialize_data(data_id)
-    }
-
-    /// Deserializes the data as the given type (via `cosmos_encoder::deserialize`) at the given id. Will panic if the
-    /// data is not properly serialized.
-    pub fn deserialize_identifiable<T: IdentifiableComponent + DeserializeOwned>(&self) -> Result<T, DeserializationError> {
-        self.deserialize_data(T::get_component_unlocalized_name())
-    }
-
-    /// Sets whether this should actually be saved - if false, when save and serialize_data is called,
-    /// nothing will happen.
-    pub fn set_should_save(&mut self, should_save: bool) {
-        self.should_save = should_save;
-    }
-
-    /// If this is false, no data will be saved/serialized when `save` and `serialize_data` is called.
-    ///
-    /// No data will be written to the disk either if this is false.
-    pub fn should_save(&self) -> bool {
-        self.should_save
-    }
-
-    /// Returns the raw [`SaveData`] backing this
-    pub fn save_data(&self) -> &SaveData {
-        &self.save_data
-    }
-}
-
 /// Returns true if a sector has at some point been generated at this location
 pub fn is_sector_generated(sector: Sector) -> bool {
     fs::exists(SaveFileIdentifier::get_sector_path(sector)).unwrap_or(false)
@@ -357,6 +85,8 @@ pub(super) fn register(app: &mut App) {
     player_loading::register(app);
     autosave::register(app);
     backup::register(app);
+    migrations::register(app);
+    registry::register(app);
 
     app.add_systems(Startup, load_world_path);
 
@@ -0,0 +1,69 @@
+//! Keeps track of every piece of data the server saves, so saves can be read without knowing the
+//! types that wrote them (such as by the `world_inspector`).
+//!
+//! Every [`PersistentComponent`] is registered by [`super::make_persistent::make_persistent`]. Data
+//! that is saved directly via [`super::SerializedData::serialize_data`] should be registered with
+//! [`register_persistent_data`] where it is saved.
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use cosmos_core::netty::cosmos_encoder;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use super::make_persistent::PersistentComponent;
+
+/// Reads the raw saved bytes of some data as JSON. Returns `None` if the bytes are not in the expected format.
+pub type PersistentDataDecoder = fn(&[u8]) -> Option<Value>;
+
+#[derive(Clone, Copy)]
+/// Describes how a piece of saved data is stored
+pub struct PersistentDataEntry {
+    /// The latest version of this data's format. Data saved as any other version cannot be read by [`Self::decode`].
+    pub version: u32,
+    /// Reads data saved as [`Self::version`]
+    pub decode: PersistentDataDecoder,
+}
+
+#[derive(Resource, Default)]
+/// Every piece of data the server saves, keyed by the id it is saved under
+pub struct PersistenceRegistry(HashMap<&'static str, PersistentDataEntry>);
+
+impl PersistenceRegistry {
+    /// Registers data of type `T` that is saved under this id at this version
+    pub fn add<T: Serialize + DeserializeOwned>(&mut self, data_id: &'static str, version: u32) {
+        self.0.insert(
+            data_id,
+            PersistentDataEntry {
+                version,
+                decode: decode::<T>,
+            },
+        );
+    }
+
+    /// Gets how the data saved under this id is stored, if it is registered
+    pub fn get(&self, data_id: &str) -> Option<&PersistentDataEntry> {
+        self.0.get(data_id)
+    }
+}
+
+fn decode<T: Serialize + DeserializeOwned>(data: &[u8]) -> Option<Value> {
+    cosmos_encoder::deserialize::<T>(data)
+        .ok()
+        .and_then(|x| serde_json::to_value(x).ok())
+}
+
+/// Registers data of type `T` that is saved under this id via [`super::SerializedData::serialize_data`].
+pub fn register_persistent_data<T: Serialize + DeserializeOwned>(app: &mut App, data_id: &'static str) {
+    app.world_mut().get_resource_or_init::<PersistenceRegistry>().add::<T>(data_id, 0);
+}
+
+/// Registers this [`PersistentComponent`]'s save type at its current version.
+pub(super) fn register_persistent_component<T: PersistentComponent>(app: &mut App) {
+    app.world_mut()
+        .get_resource_or_init::<PersistenceRegistry>()
+        .add::<T::SaveType>(T::get_component_unlocalized_name(), T::VERSION);
+}
+
+pub(super) fn register(app: &mut App) {
+    app.init_resource::<PersistenceRegistry>();
+}
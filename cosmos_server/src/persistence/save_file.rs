@@ -0,0 +1,360 @@
+//! The on-disk layout of a world's save files.
+//!
+//! This has no dependencies on the rest of the server so that tools that work with save files
+//! outside of a running server (such as the `world_inspector` binary) compute paths the same way.
+
+use bevy::prelude::*;
+use derive_more::Display;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use cosmos_core::{
+    entities::EntityId,
+    netty::sync::IdentifiableComponent,
+    physics::location::{Location, Sector, SectorUnit},
+    structure::persistence::*,
+};
+use uuid::Uuid;
+
+#[derive(Resource, Display, Debug, Clone)]
+/// Stores the directory that is the root of the world save
+///
+/// Please use this and never assume the root directry
+pub struct WorldRoot(String);
+
+impl WorldRoot {
+    /// The root of the world saved in this directory
+    pub fn dir_for_world_root(root: &str) -> WorldRoot {
+        WorldRoot(format!("{}/", root.trim_end_matches("/")))
+    }
+
+    /// Gets the root dir. You should generally prefer using [`Self::path_for`].
+    pub fn get(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Computes the path this subdirectory would be in for this world root directory
+    pub fn path_for(&self, subdir: &str) -> String {
+        format!("{self}{subdir}")
+    }
+}
+
+/// This extension is given to entities that are loaded + saved following the normal rules.
+pub const NORMAL_ENTITY_EXTENSION: &str = "cent";
+/// This entity should NOT be loaded through the normaly mechanisms, and is fully controlled by its
+/// parent when it gets loaded/saved and what data is put in that file.
+pub const OWNED_ENTITY_EXTENSION: &str = "ocent";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) enum SaveFileIdentifierType {
+    /// This entity does not belong to any other entity
+    Base(EntityId, Option<Sector>, Option<u32>),
+    /// Denotes that this entity is a "child" of the parent, but is not "owned" by the parent.
+    ///
+    /// (ChildOf SaveFileIdentifier, this entity id)
+    SubEntity(Box<SaveFileIdentifier>, EntityId),
+    /// Denotes that this entity belongs to another entity, and should be saved
+    /// in that entity's folder. Once this entity is saved, the [`SaveFileIdentifierType`] component will be removed.
+    ///
+    /// ## Note:
+    /// While saving is handled for you, it is up to you to load this yourself.
+    ///
+    /// This will be saved to `[world_root]/x_y_z/belongsToEntityId/thisEntityId.cent`
+    BelongsTo(Box<SaveFileIdentifier>, String),
+}
+
+#[derive(Debug, Component, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Used to track where the save file for a given entity is or should be.
+pub struct SaveFileIdentifier {
+    pub(super) identifier_type: SaveFileIdentifierType,
+}
+
+impl SaveFileIdentifier {
+    /// Creates a new SaveFileIdentifier from this location & entity id
+    pub fn new(sector: Option<Sector>, entity_id: EntityId, load_distance: Option<u32>) -> Self {
+        Self {
+            identifier_type: SaveFileIdentifierType::Base(entity_id, sector, load_distance),
+        }
+    }
+
+    /// Parses the save file name (without its extension) of a base entity saved in this sector.
+    ///
+    /// This is the inverse of how [`Self::get_save_file_path`] names base entities.
+    pub fn from_base_save_file_name(sector: Sector, file_stem: &str) -> Option<Self> {
+        let (load_distance, entity_id) = match file_stem.split_once('_') {
+            Some((load_distance, entity_id)) => (Some(load_distance.parse::<u32>().ok()?), entity_id),
+            None => (None, file_stem),
+        };
+
+        let entity_id = EntityId::new(Uuid::parse_str(entity_id).ok()?);
+
+        Some(Self::new(Some(sector), entity_id, load_distance))
+    }
+
+    /// Creates a new SaveFileIdentifier from this location & entity id
+    pub fn sub_entity(parent_save_file_identifier: SaveFileIdentifier, entity_id: EntityId) -> Self {
+        Self {
+            identifier_type: SaveFileIdentifierType::SubEntity(Box::new(parent_save_file_identifier), entity_id),
+        }
+    }
+
+    /// If this SaveFileIdentifier is a base identifier (not child),
+    /// this will return its EntityId. Otherwise, returns None.
+    pub fn entity_id(&self) -> Option<&EntityId> {
+        match &self.identifier_type {
+            SaveFileIdentifierType::Base(entity_id, _, _) => Some(entity_id),
+            SaveFileIdentifierType::SubEntity(_, entity_id) => Some(entity_id),
+            _ => None,
+        }
+    }
+
+    /// Creates a new SaveFileIdentifier from this location & entity id
+    pub fn as_child(this_identifier: impl Into<String>, belongs_to: SaveFileIdentifier) -> Self {
+        Self {
+            identifier_type: SaveFileIdentifierType::BelongsTo(Box::new(belongs_to), this_identifier.into()),
+        }
+    }
+
+    /// If this is a base identifier, returns the sector it is saved in and its load distance.
+    /// Otherwise, returns None.
+    pub fn base_sector_and_load_distance(&self) -> Option<(Option<Sector>, Option<u32>)> {
+        match &self.identifier_type {
+            SaveFileIdentifierType::Base(_, sector, load_distance) => Some((*sector, *load_distance)),
+            _ => None,
+        }
+    }
+
+    /// If this is a SubEntity, this will return the parent.
+    /// Otherwise, returns None.
+    pub fn get_parent(&self) -> Option<&SaveFileIdentifier> {
+        match &self.identifier_type {
+            SaveFileIdentifierType::SubEntity(parent, _) => Some(parent.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Gets the file path a given entity will be saved to.
+    pub fn get_save_file_path(&self, world_root: &WorldRoot) -> String {
+        let extension = match self.identifier_type {
+            SaveFileIdentifierType::BelongsTo(_, _) => OWNED_ENTITY_EXTENSION,
+            _ => NORMAL_ENTITY_EXTENSION,
+        };
+
+        world_root.path_for(format!("{}.{extension}", self.get_save_file_directory(Self::get_save_file_name)).as_str())
+    }
+
+    /// Gets the save file name without the .cent extension, but not the whole path
+    fn get_save_file_name(&self) -> String {
+        match &self.identifier_type {
+            SaveFileIdentifierType::Base(entity, _, load_distance) => {
+                load_distance.map(|ld| format!("{ld}_{entity}")).unwrap_or(entity.to_string())
+            }
+            SaveFileIdentifierType::SubEntity(_, entity_id) => entity_id.to_string(),
+            SaveFileIdentifierType::BelongsTo(_, name) => name.to_owned(),
+        }
+    }
+
+    /// Gets the save file name without the .cent extension, but not the whole path
+    fn get_save_file_name_no_load_distance(&self) -> String {
+        match &self.identifier_type {
+            SaveFileIdentifierType::Base(entity, _, _) => entity.to_string(),
+            SaveFileIdentifierType::SubEntity(_, entity_id) => entity_id.to_string(),
+            SaveFileIdentifierType::BelongsTo(_, name) => name.to_owned(),
+        }
+    }
+
+    /// Gets the save file name, but not the whole path
+    fn get_save_file_directory(&self, base_get_save_file_name: impl Fn(&Self) -> String) -> String {
+        match &self.identifier_type {
+            SaveFileIdentifierType::Base(_, sector, _) => {
+                let directory = sector.map(Self::get_sector_path).unwrap_or_else(|| {
+                    error!("GOT NO DIRECTORY TO SAVE - THIS IS BAD - SOMETHING WILL NOT BE SAVED IN THE RIGHT SPOT!");
+                    error!("{self:?}");
+
+                    "nowhere".into()
+                });
+
+                format!("{directory}/{}", base_get_save_file_name(self))
+            }
+            SaveFileIdentifierType::SubEntity(belongs_to, _) => {
+                format!(
+                    "{}/{}",
+                    belongs_to.get_save_file_directory(Self::get_save_file_name_no_load_distance),
+                    base_get_save_file_name(self)
+                )
+            }
+            SaveFileIdentifierType::BelongsTo(belongs_to, _) => {
+                format!(
+                    "{}/{}",
+                    belongs_to.get_save_file_directory(Self::get_save_file_name_no_load_distance),
+                    base_get_save_file_name(self)
+                )
+            }
+        }
+    }
+
+    /// Gets the directory path all children of this entity would be saved to
+    pub fn get_children_directory(&self) -> String {
+        self.get_save_file_directory(Self::get_save_file_name_no_load_distance)
+    }
+
+    /// Gets the directory for this sector's save folder
+    pub fn get_sector_path(sector: Sector) -> String {
+        let (x, y, z) = (sector.x(), sector.y(), sector.z());
+
+        format!("{x}_{y}_{z}")
+    }
+
+    /// Parses the sector a sector's save folder is for.
+    ///
+    /// This is the inverse of [`Self::get_sector_path`].
+    pub fn sector_from_path(path: &str) -> Option<Sector> {
+        let mut coords = path.split('_').map(|c| c.parse::<SectorUnit>());
+        let (Some(Ok(x)), Some(Ok(y)), Some(Ok(z)), None) = (coords.next(), coords.next(), coords.next(), coords.next()) else {
+            return None;
+        };
+
+        Some(Sector::new(x, y, z))
+    }
+}
+
+#[derive(Component, Debug, Reflect, Serialize, Deserialize)]
+/// Stores the serialized data for an entity.
+///
+/// This is either read from or written to a save file depending on if an entity is being loaded or saved.
+pub struct SerializedData {
+    pub(super) save_data: SaveData,
+
+    /// Used to identify the location this should be saved under
+    pub(super) location: Option<Location>,
+    pub(super) should_save: bool,
+}
+
+impl SerializedData {
+    /// Creates serialized data from an existing [`SaveData`] payload.
+    pub(crate) fn from_save_data(save_data: SaveData) -> Self {
+        Self {
+            save_data,
+            location: None,
+            should_save: true,
+        }
+    }
+
+    /// Use this to set location. This will make sure the folder name
+    /// reflects the actual location.
+    pub fn set_location(&mut self, loc: &Location) {
+        self.serialize_data("cosmos:location", loc);
+        self.location = Some(*loc);
+    }
+}
+
+impl Default for SerializedData {
+    fn default() -> Self {
+        Self {
+            save_data: SaveData::default(),
+            location: None,
+            should_save: true,
+        }
+    }
+}
+
+impl SerializedData {
+    /// Saves the data to that data id. Will overwrite any existing data at that id.
+    ///
+    /// Will only save if `should_save()` returns true.
+    pub fn save(&mut self, data_id: impl Into<String>, data: Vec<u8>) {
+        if self.should_save() {
+            self.save_data.save(data_id, data);
+        }
+    }
+
+    /// Calls `cosmos_encoder::serialize` on the passed in data.
+    /// Then sends that data into the `save` method, with the given data id.
+    ///
+    /// Will only serialize & save if `should_save()` returns true.
+    pub fn serialize_data(&mut self, data_id: impl Into<String>, data: &impl Serialize) {
+        if self.should_save() {
+            self.save_data.serialize_data(data_id, data);
+        }
+    }
+
+    /// Calls `cosmos_encoder::serialize` on the passed in data and saves it at the given data id,
+    /// alongside the version of the data's format.
+    ///
+    /// Will only serialize & save if `should_save()` returns true.
+    pub fn serialize_versioned_data(&mut self, data_id: impl Into<String>, version: u32, data: &impl Serialize) {
+        if self.should_save() {
+            self.save_data.serialize_versioned_data(data_id, version, data);
+        }
+    }
+
+    /// Calls `cosmos_encoder::serialize` on the passed in data.
+    /// Then sends that data into the `save` method, with the given data id.
+    ///
+    /// Will only serialize & save if `should_save()` returns true.
+    pub fn serialize_identifiable<T: IdentifiableComponent + Serialize>(&mut self, data: &T) {
+        self.serialize_data(T::get_component_unlocalized_name(), data);
+    }
+
+    /// Reads the data as raw bytes at the given data id. Use `deserialize_data` for a streamlined way to read the data.
+    pub fn read_data(&self, data_id: &str) -> Option<&Vec<u8>> {
+        self.save_data.read_data(data_id)
+    }
+
+    /// Deserializes the data as the given type (via `cosmos_encoder::deserialize`) at the given id. Will panic if the
+    /// data is not properly serialized.
+    pub fn deserialize_data<T: DeserializeOwned>(&self, data_id: &str) -> Result<T, DeserializationError> {
+        self.save_data.deserialize_data(data_id)
+    }
+
+    /// Deserializes the data as the given type (via `cosmos_encoder::deserialize`) at the given id. Will panic if the
+    /// data is not properly serialized.
+    pub fn deserialize_identifiable<T: IdentifiableComponent + DeserializeOwned>(&self) -> Result<T, DeserializationError> {
+        self.deserialize_data(T::get_component_unlocalized_name())
+    }
+
+    /// Sets whether this should actually be saved - if false, when save and serialize_data is called,
+    /// nothing will happen.
+    pub fn set_should_save(&mut self, should_save: bool) {
+        self.should_save = should_save;
+    }
+
+    /// If this is false, no data will be saved/serialized when `save` and `serialize_data` is called.
+    ///
+    /// No data will be written to the disk either if this is false.
+    pub fn should_save(&self) -> bool {
+        self.should_save
+    }
+
+    /// Returns the raw [`SaveData`] backing this
+    pub fn save_data(&self) -> &SaveData {
+        &self.save_data
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cosmos_core::{entities::EntityId, physics::location::Sector};
+    use uuid::Uuid;
+
+    use super::{SaveFileIdentifier, WorldRoot};
+
+    #[test]
+    fn save_file_names_round_trip() {
+        let sector = Sector::new(-3, 0, 12);
+        assert_eq!(
+            SaveFileIdentifier::sector_from_path(&SaveFileIdentifier::get_sector_path(sector)),
+            Some(sector)
+        );
+        assert_eq!(SaveFileIdentifier::sector_from_path("1_2"), None);
+
+        let world_root = WorldRoot::dir_for_world_root("world");
+        let entity_id = EntityId::new(Uuid::new_v4());
+        for load_distance in [None, Some(4)] {
+            let sfi = SaveFileIdentifier::new(Some(sector), entity_id, load_distance);
+            let path = sfi.get_save_file_path(&world_root);
+            let stem = path.rsplit('/').next().unwrap().trim_end_matches(".cent");
+
+            assert_eq!(SaveFileIdentifier::from_base_save_file_name(sector, stem), Some(sfi));
+        }
+    }
+}
@@ -1,6 +1,10 @@
 //! Contains all the systems + resources needed for a server
 
-use bevy::{ecs::resource::Resource, log::info, prelude::Plugin};
+use bevy::{
+    ecs::resource::Resource,
+    log::info,
+    prelude::{App, Plugin},
+};
 
 use crate::{
     ai, blocks, chat, commands, coms, converters, crafting, creative, economy, entities, faction, fluid,
@@ -37,36 +41,44 @@ impl Plugin for ServerPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         info!("Setting up server");
         init_server::init(app, self.0);
-        local::register(app);
-        commands::register(app);
-        init::register(app);
-        netty::register(app);
-        physics::register(app);
-        blocks::register(app);
-        items::register(app);
-        structure::register(app);
-        inventory::register(app);
-        super::register(app);
-        projectiles::register(app);
-        persistence::register(app);
-        universe::register(app);
-        shop::register(app);
-        ai::register(app);
-        utility_runs::register(app);
-        fluid::register(app);
-        logic::register(app);
-        chat::register(app);
-        crafting::register(app);
-        entities::register(app);
-        economy::register(app);
-        faction::register(app);
-        coms::register(app);
-        quest::register(app);
-        converters::register(app);
-        loot::register(app);
-        creative::register(app);
-        server::register(app);
+        register_server_logic(app);
 
         info!("Done setting up server!");
     }
 }
+
+/// Adds every system + resource the server uses, without starting up its networking.
+///
+/// Nothing here is run until the app is, so tools can use this to see what the server registers
+/// (such as its [`persistence::registry::PersistenceRegistry`]) without hosting a server.
+pub fn register_server_logic(app: &mut App) {
+    local::register(app);
+    commands::register(app);
+    init::register(app);
+    netty::register(app);
+    physics::register(app);
+    blocks::register(app);
+    items::register(app);
+    structure::register(app);
+    inventory::register(app);
+    super::register(app);
+    projectiles::register(app);
+    persistence::register(app);
+    universe::register(app);
+    shop::register(app);
+    ai::register(app);
+    utility_runs::register(app);
+    fluid::register(app);
+    logic::register(app);
+    chat::register(app);
+    crafting::register(app);
+    entities::register(app);
+    economy::register(app);
+    faction::register(app);
+    coms::register(app);
+    quest::register(app);
+    converters::register(app);
+    loot::register(app);
+    creative::register(app);
+    server::register(app);
+}
@@ -29,9 +29,13 @@ fn update_visulizer_system(
 }
 
 pub(super) fn register(app: &mut App) {
-    let settings = app.world().get_resource::<ServerSettings>().expect("Missing Settings");
+    // Tools that only register the server's logic (such as the world inspector) have no settings
+    let debug_window = app
+        .world()
+        .get_resource::<ServerSettings>()
+        .is_some_and(|settings| settings.debug_window);
 
-    if settings.debug_window {
+    if debug_window {
         app.insert_resource(ServerNettyVisualizer::default())
             .allow_ambiguous_resource::<ServerNettyVisualizer>()
             .add_systems(EguiPrimaryContextPass, update_visulizer_system.run_if(in_state(GameState::Playing)));
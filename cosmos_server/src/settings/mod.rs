@@ -7,7 +7,7 @@ use clap::{ArgAction, Parser};
 use cosmos_core::settings::{WorldGamemode, WorldSettings};
 
 use crate::{
-    persistence::WorldRoot,
+    persistence::{WorldRoot, backup},
     plugin::server_plugin::{ServerPlugin, ServerType},
 };
 
@@ -47,6 +47,14 @@ pub struct Args {
     #[arg(long, action=ArgAction::SetTrue)]
     no_merchant_ships: Option<bool>,
 
+    /// If ships that melt down should disappear completely instead of leaving a derelict wreck
+    #[arg(long, action=ArgAction::SetTrue)]
+    no_derelicts: Option<bool>,
+
+    /// How many seconds a derelict wreck stays around before it is cleaned up
+    #[arg(long)]
+    derelict_lifetime_secs: Option<u64>,
+
     #[arg(long, default_value_t = String::from("world"))]
     /// The world folder to treat as the root - if no folder exists a new folder and world will be created
     world: String,
@@ -63,6 +71,11 @@ pub struct Args {
     #[arg(long, default_value_t = false)]
     /// Should the players drop items on death
     drop_items_on_death: bool,
+
+    #[arg(long)]
+    /// Restores the world to this backup (named by the time it was taken, such as `2025_01_30_14_05_00`)
+    /// before starting the server
+    restore_backup: Option<String>,
 }
 
 #[derive(Resource)]
@@ -84,6 +97,10 @@ pub struct ServerSettings {
     pub world_gamemode: WorldGamemode,
     /// If any merchant ships should spawn
     pub spawn_merchant_ships: bool,
+    /// If ships that melt down should leave a derelict wreck behind
+    pub derelicts: bool,
+    /// How many seconds a derelict wreck stays around before it is cleaned up
+    pub derelict_lifetime_secs: u64,
 
     /// The directory the world contents are stored in (defaults to "world")
     pub world_folder: String,
@@ -112,11 +129,18 @@ impl ServerSettings {
 }
 
 /// Reads the server settings passed in from the command line and world settings
-pub(super) fn read_server_settings() -> ServerSettings {
+pub fn read_server_settings() -> ServerSettings {
     let args = Args::parse();
 
     let root = WorldRoot::dir_for_world_root(&args.world);
 
+    // This must happen before anything (including the world settings) is read from the world
+    match backup::restore_pending_backup(&root, args.restore_backup.as_deref()) {
+        Ok(Some(backup)) => info!("Restored world to backup {backup}."),
+        Ok(None) => {}
+        Err(e) => error!("Failed to restore backup - {e:?}"),
+    }
+
     let settings_file = root.path_for("world_settings.toml");
     let world_settings = fs::read_to_string(&settings_file)
         .ok()
@@ -139,6 +163,8 @@ pub(super) fn read_server_settings() -> ServerSettings {
             .map(|x| if x { WorldGamemode::Creative } else { WorldGamemode::Survival })
             .unwrap_or(world_settings.gamemode),
         spawn_merchant_ships: args.no_merchant_ships.map(|x| !x).unwrap_or(world_settings.merchant_ships),
+        derelicts: args.no_derelicts.map(|x| !x).unwrap_or(world_settings.derelicts),
+        derelict_lifetime_secs: args.derelict_lifetime_secs.unwrap_or(world_settings.derelict_lifetime_secs),
         local: args.local,
         world_folder: args.world,
         requested_seed: args.seed,
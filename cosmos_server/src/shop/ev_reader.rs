@@ -1,10 +1,11 @@
 use bevy::prelude::*;
 use bevy_renet::RenetServer;
 use cosmos_core::{
-    block::{Block, block_events::BlockInteractMessage},
+    block::{Block, block_events::BlockInteractMessage, data::BlockData},
     economy::Credits,
-    entities::player::Player,
+    entities::{EntityId, player::Player},
     events::cancellable::Cancellable,
+    faction::{FactionId, FactionPermission, FactionTransaction, FactionTransactionKind, Factions},
     inventory::{
         Inventory,
         itemstack::{ItemShouldHaveData, ItemStackSystemSet},
@@ -13,49 +14,74 @@ use cosmos_core::{
     netty::{NettyChannelClient, NettyChannelServer, cosmos_encoder, server::ServerLobby, system_sets::NetworkingSystemsSet},
     registry::{Registry, identifiable::Identifiable},
     shop::{
-        Shop,
-        netty::{ClientShopMessages, ServerShopMessages, ShopPurchaseError, ShopSellError},
+        Shop, ShopEntry,
+        netty::{ClientShopMessages, ServerShopMessages, ShopConfigureError, ShopPurchaseError, ShopSellError},
     },
     structure::{Structure, coordinates::BlockCoordinate},
+    time::UniverseTimestamp,
 };
 use renet::ClientId;
 
-use super::prices::DefaultShopEntries;
+use super::{
+    player_shop::{StorageQuery, insert_into_storage, players_by_id, refresh_stock, sell_to_player_shop, take_from_storage},
+    prices::DefaultShopEntries,
+};
 
-use crate::GameState;
+use crate::{
+    GameState,
+    faction::treasury::FactionTransactionLog,
+    structure::ownership::{StructurePermissions, player_entity_id},
+};
 
-fn generate_fake_shop(default: &DefaultShopEntries) -> Shop {
-    Shop {
-        name: "Cool Shop".into(),
-        contents: default.0.clone(),
-    }
+fn generate_default_shop(default: &DefaultShopEntries, now: UniverseTimestamp) -> Shop {
+    Shop::new("Cool Shop", default.0.clone(), now)
 }
 
 fn on_interact_with_shop(
     mut server: ResMut<RenetServer>,
-    q_structure: Query<&Structure>,
-    q_player: Query<&Player>,
+    mut q_structure: Query<&mut Structure>,
+    q_player: Query<(&Player, Option<&EntityId>)>,
     blocks: Res<Registry<Block>>,
     mut ev_reader: MessageReader<Cancellable<BlockInteractMessage>>,
     default_shop_entries: Res<DefaultShopEntries>,
+    timestamp: Res<UniverseTimestamp>,
+    mut q_shop_data: Query<&mut Shop>,
+    q_has_shop: Query<(), With<Shop>>,
+    mut q_data: ParamSet<(Query<&mut BlockData>, StorageQuery)>,
+    items: Res<Registry<Item>>,
+    mut commands: Commands,
 ) {
     for ev in ev_reader.read().flatten() {
         let Some(s_block) = ev.block else {
             continue;
         };
 
-        let Ok(player) = q_player.get(ev.interactor) else {
+        let Ok((player, player_id)) = q_player.get(ev.interactor) else {
             continue;
         };
 
-        let Ok(structure) = q_structure.get(s_block.structure()) else {
+        let Ok(mut structure) = q_structure.get_mut(s_block.structure()) else {
             continue;
         };
 
-        let block = s_block.block(structure, &blocks);
+        let block = s_block.block(&structure, &blocks);
 
         if block.unlocalized_name() == "cosmos:shop" {
-            let fake_shop_data = generate_fake_shop(&default_shop_entries);
+            let shop_data = if let Some(mut shop) = structure
+                .block_data(s_block.coords())
+                .and_then(|data_ent| q_shop_data.get_mut(data_ent).ok())
+            {
+                shop.restock(&default_shop_entries.0, *timestamp);
+                if shop.is_player_owned() {
+                    refresh_stock(&mut shop, &structure, &blocks, &items, &q_data.p1());
+                }
+                shop.clone()
+            } else {
+                // Shops start at their base prices the first time anyone looks at them.
+                let shop = generate_default_shop(&default_shop_entries, *timestamp);
+                structure.insert_block_data(s_block.coords(), shop.clone(), &mut commands, &mut q_data.p0(), &q_has_shop);
+                shop
+            };
 
             server.send_message(
                 player.client_id(),
@@ -63,7 +89,8 @@ fn on_interact_with_shop(
                 cosmos_encoder::serialize(&ServerShopMessages::OpenShop {
                     shop_block: s_block.coords(),
                     structure_entity: s_block.structure(),
-                    shop_data: fake_shop_data,
+                    is_owner: player_id.is_some() && shop_data.owner.as_ref() == player_id,
+                    shop_data,
                 }),
             );
         }
@@ -88,20 +115,42 @@ struct SellMessage {
     quantity: u32,
 }
 
-fn get_shop(
-    _structure_entity: Entity,
-    _shop_block: BlockCoordinate,
+#[derive(Message)]
+struct ClaimMessage {
+    client_id: ClientId,
+    shop_block: BlockCoordinate,
+    structure_entity: Entity,
+}
+
+#[derive(Message)]
+struct ConfigureMessage {
+    client_id: ClientId,
+    shop_block: BlockCoordinate,
+    structure_entity: Entity,
+    name: String,
+    contents: Vec<ShopEntry>,
+}
+
+/// Gets the shop stored on this block, with its prices and stock recovered up to the current time.
+///
+/// Returns `None` if this block has no shop data (shop data is created when the shop is first opened).
+fn get_shop<'a>(
+    structure_entity: Entity,
+    shop_block: BlockCoordinate,
     default_shop_entries: &DefaultShopEntries,
-    _q_structure: &Query<&Structure>,
-    _q_shop_data: &mut Query<&mut Shop>,
-) -> Option<Shop> {
-    // let structure = q_structure.get(structure_entity).ok()?;
+    timestamp: UniverseTimestamp,
+    q_structure: &Query<&Structure>,
+    q_shop_data: &'a mut Query<&mut Shop>,
+) -> Option<Mut<'a, Shop>> {
+    let structure = q_structure.get(structure_entity).ok()?;
+
+    let block_data = structure.block_data(shop_block)?;
 
-    // let block_data = structure.block_data(shop_block)?;
+    let mut shop = q_shop_data.get_mut(block_data).ok()?;
 
-    // let mut shop = q_shop_data.get_mut(block_data).ok()?;
+    shop.restock(&default_shop_entries.0, timestamp);
 
-    Some(generate_fake_shop(default_shop_entries))
+    Some(shop)
 }
 
 fn listen_sell_events(
@@ -110,11 +159,30 @@ fn listen_sell_events(
     q_structure: Query<&Structure>,
     mut q_shop_data: Query<&mut Shop>,
     lobby: Res<ServerLobby>,
-    mut q_player: Query<(&mut Inventory, &mut Credits)>,
+    mut q_players: ParamSet<(
+        Query<(&mut Inventory, &mut Credits), With<Player>>,
+        Query<(&EntityId, &mut Credits), With<Player>>,
+    )>,
+    q_player_ids: Query<(Entity, &EntityId), With<Player>>,
+    mut q_storage: StorageQuery,
     items: Res<Registry<Item>>,
+    blocks: Res<Registry<Block>>,
     default_shop_entries: Res<DefaultShopEntries>,
+    timestamp: Res<UniverseTimestamp>,
     mut commands: Commands,
+    has_data: Res<ItemShouldHaveData>,
+    (q_structure_faction, mut factions, mut transaction_log): (
+        Query<&FactionId, Without<Player>>,
+        ResMut<Factions>,
+        ResMut<FactionTransactionLog>,
+    ),
 ) {
+    if ev_reader.is_empty() {
+        return;
+    }
+
+    let players = players_by_id(q_player_ids.iter());
+
     for &SellMessage {
         client_id,
         shop_block,
@@ -132,17 +200,21 @@ fn listen_sell_events(
             continue;
         };
 
-        let Ok((mut inventory, mut credits)) = q_player.get_mut(player_ent) else {
-            error!("No credits on player entity: {player_ent:?}");
+        let Some(item) = items.try_from_numeric_id(item_id) else {
+            error!("Invalid item id: {item_id}");
             continue;
         };
 
-        let Some(item) = items.try_from_numeric_id(item_id) else {
-            error!("Invalid item id: {item_id}");
+        let Ok(has_items) = q_players
+            .p0()
+            .get(player_ent)
+            .map(|(inventory, _)| inventory.can_take_item(item, quantity as usize))
+        else {
+            error!("No credits on player entity: {player_ent:?}");
             continue;
         };
 
-        if !inventory.can_take_item(item, quantity as usize) {
+        if !has_items {
             server.send_message(
                 client_id,
                 NettyChannelServer::Shop,
@@ -155,22 +227,85 @@ fn listen_sell_events(
             continue;
         }
 
-        let Some(mut shop) = get_shop(structure_entity, shop_block, &default_shop_entries, &q_structure, &mut q_shop_data) else {
+        let Some(mut shop) = get_shop(
+            structure_entity,
+            shop_block,
+            &default_shop_entries,
+            *timestamp,
+            &q_structure,
+            &mut q_shop_data,
+        ) else {
+            continue;
+        };
+
+        let Ok(structure) = q_structure.get(structure_entity) else {
             continue;
         };
 
+        // Player-owned shops on a faction's structures are funded by that faction's treasury instead of their owner.
+        let shop_faction = q_structure_faction
+            .get(structure_entity)
+            .ok()
+            .copied()
+            .filter(|_| shop.is_player_owned())
+            .filter(|fac_id| factions.from_id(fac_id).is_some());
+        let treasury_before = shop_faction.and_then(|fac_id| factions.from_id(&fac_id)).map(|fac| fac.treasury());
+
+        let proceeds = if shop.is_player_owned() {
+            let mut q_owner = q_players.p1();
+            let funds = match shop_faction.and_then(|fac_id| factions.from_id_mut(&fac_id)) {
+                Some(faction) => Some(faction.treasury_mut()),
+                None => shop
+                    .owner
+                    .as_ref()
+                    .and_then(|owner| players.get(owner))
+                    .and_then(|&owner| q_owner.get_mut(owner).ok())
+                    .map(|(_, credits)| credits.into_inner()),
+            };
+
+            sell_to_player_shop(&mut shop, structure, item, quantity, &blocks, &mut q_storage, funds)
+        } else {
+            let mut proceeds = Credits::new(0);
+            shop.sell(item_id, quantity, &mut proceeds).map(|_| proceeds.amount())
+        };
+
         server.send_message(
             client_id,
             NettyChannelServer::Shop,
             cosmos_encoder::serialize(&ServerShopMessages::SellResult {
                 shop_block,
                 structure_entity,
-                details: if let Err(error) = shop.sell(item_id, quantity, &mut credits) {
-                    Err(error)
-                } else {
-                    inventory.take_and_remove_item(item, quantity as usize, &mut commands);
-
-                    Ok(shop.clone())
+                details: match proceeds {
+                    Err(error) => Err(error),
+                    Ok(proceeds) => {
+                        let mut q_seller = q_players.p0();
+                        let (mut inventory, mut credits) = q_seller.get_mut(player_ent).expect("Verified above");
+
+                        credits.increase(proceeds);
+                        inventory.take_and_remove_item(item, quantity as usize, &mut commands);
+
+                        if shop.is_player_owned() {
+                            insert_into_storage(structure, item, quantity, &blocks, &mut q_storage, &mut commands, &has_data);
+                        }
+
+                        if let Some((fac_id, before)) = shop_faction.zip(treasury_before)
+                            && let Some(after) = factions.from_id(&fac_id).map(|fac| fac.treasury())
+                            && let Ok((&seller_id, _)) = q_players.p1().get(player_ent)
+                            && before.amount() > after.amount()
+                        {
+                            transaction_log.record(
+                                fac_id,
+                                FactionTransaction {
+                                    player: seller_id,
+                                    kind: FactionTransactionKind::ShopPayment,
+                                    amount: before.amount() - after.amount(),
+                                    timestamp: *timestamp,
+                                },
+                            );
+                        }
+
+                        Ok(shop.clone())
+                    }
                 },
             }),
         );
@@ -183,12 +318,27 @@ fn listen_buy_events(
     q_structure: Query<&Structure>,
     mut q_shop_data: Query<&mut Shop>,
     lobby: Res<ServerLobby>,
-    mut q_player: Query<(&mut Inventory, &mut Credits)>,
+    mut q_player: Query<(&mut Inventory, &mut Credits, Option<&EntityId>, Option<&FactionId>), With<Player>>,
+    q_player_ids: Query<(Entity, &EntityId), With<Player>>,
+    mut q_storage: StorageQuery,
     items: Res<Registry<Item>>,
+    blocks: Res<Registry<Block>>,
     default_shop_entries: Res<DefaultShopEntries>,
+    timestamp: Res<UniverseTimestamp>,
     mut commands: Commands,
     has_data: Res<ItemShouldHaveData>,
+    (q_structure_faction, mut factions, mut transaction_log): (
+        Query<&FactionId, Without<Player>>,
+        ResMut<Factions>,
+        ResMut<FactionTransactionLog>,
+    ),
 ) {
+    if ev_reader.is_empty() {
+        return;
+    }
+
+    let players = players_by_id(q_player_ids.iter());
+
     for &BuyMessage {
         client_id,
         shop_block,
@@ -206,7 +356,7 @@ fn listen_buy_events(
             continue;
         };
 
-        let Ok((mut inventory, mut credits)) = q_player.get_mut(player_ent) else {
+        let Ok((mut inventory, mut credits, player_id, player_faction)) = q_player.get_mut(player_ent) else {
             error!("No credits on player entity: {player_ent:?}");
             continue;
         };
@@ -229,12 +379,90 @@ fn listen_buy_events(
             continue;
         }
 
-        let Some(mut shop) = get_shop(structure_entity, shop_block, &default_shop_entries, &q_structure, &mut q_shop_data) else {
+        let Some(mut shop) = get_shop(
+            structure_entity,
+            shop_block,
+            &default_shop_entries,
+            *timestamp,
+            &q_structure,
+            &mut q_shop_data,
+        ) else {
+            continue;
+        };
+
+        let Ok(structure) = q_structure.get(structure_entity) else {
             continue;
         };
 
-        match shop.buy(item_id, quantity, &mut credits) {
+        if shop.is_player_owned() {
+            // Player-owned shops can only sell what is actually in their storage.
+            refresh_stock(&mut shop, structure, &blocks, &items, &q_storage);
+        }
+
+        // Purchases from shops on the buyer's own faction's structures are paid for by the faction's treasury,
+        // if their rank allows it. Player-owned shops pay their revenue to their owner, so the treasury never
+        // pays for them - otherwise their owner could drain it into their own pocket.
+        let paying_faction = q_structure_faction
+            .get(structure_entity)
+            .ok()
+            .filter(|_| !shop.is_player_owned())
+            .filter(|&fac_id| player_faction == Some(fac_id))
+            .zip(player_id)
+            .filter(|(fac_id, player_id)| {
+                factions
+                    .from_id(fac_id)
+                    .is_some_and(|fac| fac.has_permission(**player_id, FactionPermission::WithdrawTreasury))
+            });
+
+        let payer = match paying_faction.and_then(|(fac_id, _)| factions.from_id_mut(fac_id)) {
+            Some(faction) => faction.treasury_mut(),
+            None => &mut *credits,
+        };
+
+        let credits_before = payer.amount();
+        let result = shop.buy(item_id, quantity, &mut *payer);
+        let cost = credits_before - payer.amount();
+
+        match result {
             Ok(_) => {
+                if shop.is_player_owned() {
+                    take_from_storage(structure, item, quantity, &blocks, &mut q_storage, &mut commands);
+                    refresh_stock(&mut shop, structure, &blocks, &items, &q_storage);
+
+                    // Shops on a faction's structures earn money for the faction, rather than their owner.
+                    let shop_faction = q_structure_faction.get(structure_entity).ok().copied();
+                    match shop_faction.and_then(|fac_id| factions.from_id_mut(&fac_id).map(|fac| (fac_id, fac))) {
+                        Some((fac_id, faction)) => {
+                            faction.treasury_mut().increase(cost);
+
+                            if let Some(&player_id) = player_id {
+                                transaction_log.record(
+                                    fac_id,
+                                    FactionTransaction {
+                                        player: player_id,
+                                        kind: FactionTransactionKind::ShopRevenue,
+                                        amount: cost,
+                                        timestamp: *timestamp,
+                                    },
+                                );
+                            }
+                        }
+                        None => shop.pending_revenue += cost,
+                    }
+                }
+
+                if let Some((&fac_id, &player_id)) = paying_faction {
+                    transaction_log.record(
+                        fac_id,
+                        FactionTransaction {
+                            player: player_id,
+                            kind: FactionTransactionKind::ShopPurchase,
+                            amount: cost,
+                            timestamp: *timestamp,
+                        },
+                    );
+                }
+
                 server.send_message(
                     client_id,
                     NettyChannelServer::Shop,
@@ -246,6 +474,14 @@ fn listen_buy_events(
                 );
 
                 inventory.insert_item(item, quantity as u16, &mut commands, &has_data);
+
+                // Owners are paid as soon as the sale is made if they are online, and once they join otherwise.
+                if shop.pending_revenue != 0
+                    && let Some(&owner) = shop.owner.as_ref().and_then(|owner| players.get(owner))
+                    && let Ok((_, mut owner_credits, _, _)) = q_player.get_mut(owner)
+                {
+                    owner_credits.increase(std::mem::take(&mut shop.pending_revenue));
+                }
             }
             Err(msg) => {
                 server.send_message(
@@ -262,9 +498,121 @@ fn listen_buy_events(
     }
 }
 
+fn listen_claim_events(
+    mut server: ResMut<RenetServer>,
+    mut ev_reader: MessageReader<ClaimMessage>,
+    q_structure: Query<&Structure>,
+    mut q_shop_data: Query<&mut Shop>,
+    lobby: Res<ServerLobby>,
+    q_entity_id: Query<Option<&EntityId>, With<Player>>,
+    permissions: StructurePermissions,
+    timestamp: Res<UniverseTimestamp>,
+    mut commands: Commands,
+) {
+    for ev in ev_reader.read() {
+        let Some(player_ent) = lobby.player_from_id(ev.client_id) else {
+            error!("Bad player id: {}", ev.client_id);
+            continue;
+        };
+
+        let Ok(entity_id) = q_entity_id.get(player_ent) else {
+            continue;
+        };
+
+        let Some(mut shop) = q_structure
+            .get(ev.structure_entity)
+            .ok()
+            .and_then(|structure| structure.block_data(ev.shop_block))
+            .and_then(|data_ent| q_shop_data.get_mut(data_ent).ok())
+        else {
+            continue;
+        };
+
+        // Only shops on structures the player could have built themselves can be claimed, so NPC merchants can't be taken over.
+        let details = if shop.is_player_owned() || permissions.check_build(player_ent, ev.structure_entity).is_err() {
+            Err(ShopConfigureError::CannotClaim)
+        } else {
+            let owner = player_entity_id(player_ent, entity_id, &mut commands);
+            *shop = Shop::new_player_owned(shop.name.clone(), owner, *timestamp);
+
+            Ok(shop.clone())
+        };
+
+        server.send_message(
+            ev.client_id,
+            NettyChannelServer::Shop,
+            cosmos_encoder::serialize(&ServerShopMessages::ConfigureResult {
+                shop_block: ev.shop_block,
+                structure_entity: ev.structure_entity,
+                details,
+            }),
+        );
+    }
+}
+
+fn listen_configure_events(
+    mut server: ResMut<RenetServer>,
+    mut ev_reader: MessageReader<ConfigureMessage>,
+    q_structure: Query<&Structure>,
+    mut q_shop_data: Query<&mut Shop>,
+    lobby: Res<ServerLobby>,
+    q_entity_id: Query<&EntityId, With<Player>>,
+    mut q_storage: StorageQuery,
+    items: Res<Registry<Item>>,
+    blocks: Res<Registry<Block>>,
+) {
+    for ev in ev_reader.read() {
+        let Some(player_ent) = lobby.player_from_id(ev.client_id) else {
+            error!("Bad player id: {}", ev.client_id);
+            continue;
+        };
+
+        let Ok(structure) = q_structure.get(ev.structure_entity) else {
+            continue;
+        };
+
+        let Some(mut shop) = structure
+            .block_data(ev.shop_block)
+            .and_then(|data_ent| q_shop_data.get_mut(data_ent).ok())
+        else {
+            continue;
+        };
+
+        let invalid_item = ev
+            .contents
+            .iter()
+            .map(|(ShopEntry::Selling { item_id, .. } | ShopEntry::Buying { item_id, .. })| *item_id)
+            .find(|&item_id| items.try_from_numeric_id(item_id).is_none());
+
+        let details = if shop.owner.is_none() || q_entity_id.get(player_ent).ok() != shop.owner.as_ref() {
+            Err(ShopConfigureError::NotOwner)
+        } else if let Some(item_id) = invalid_item {
+            Err(ShopConfigureError::InvalidItem(item_id))
+        } else {
+            shop.name = ev.name.clone();
+            shop.contents = ev.contents.clone();
+            refresh_stock(&mut shop, structure, &blocks, &items, &q_storage);
+
+            Ok(shop.clone())
+        };
+
+        server.send_message(
+            ev.client_id,
+            NettyChannelServer::Shop,
+            cosmos_encoder::serialize(&ServerShopMessages::ConfigureResult {
+                shop_block: ev.shop_block,
+                structure_entity: ev.structure_entity,
+                details,
+            }),
+        );
+    }
+}
+
 fn listen_client_shop_messages(
     mut ev_writer_buy: MessageWriter<BuyMessage>,
     mut ev_writer_sell: MessageWriter<SellMessage>,
+    mut ev_writer_claim: MessageWriter<ClaimMessage>,
+    mut ev_writer_configure: MessageWriter<ConfigureMessage>,
     mut server: ResMut<RenetServer>,
 ) {
     for client_id in server.clients_id() {
@@ -303,6 +651,30 @@ fn listen_client_shop_messages(
                         structure_entity,
                     });
                 }
+                ClientShopMessages::Claim {
+                    shop_block,
+                    structure_entity,
+                } => {
+                    ev_writer_claim.write(ClaimMessage {
+                        client_id,
+                        shop_block,
+                        structure_entity,
+                    });
+                }
+                ClientShopMessages::Configure {
+                    shop_block,
+                    structure_entity,
+                    name,
+                    contents,
+                } => {
+                    ev_writer_configure.write(ConfigureMessage {
+                        client_id,
+                        shop_block,
+                        structure_entity,
+                        name,
+                        contents,
+                    });
+                }
             }
         }
     }
@@ -316,6 +688,8 @@ pub(super) fn register(app: &mut App) {
             listen_client_shop_messages,
             listen_buy_events,
             listen_sell_events,
+            listen_claim_events,
+            listen_configure_events,
         )
             .chain()
             .run_if(in_state(GameState::Playing))
@@ -323,5 +697,7 @@ pub(super) fn register(app: &mut App) {
             .before(ItemStackSystemSet::CreateDataEntity),
     )
     .add_message::<BuyMessage>()
-    .add_message::<SellMessage>();
+    .add_message::<SellMessage>()
+    .add_message::<ClaimMessage>()
+    .add_message::<ConfigureMessage>();
 }
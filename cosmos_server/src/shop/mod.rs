@@ -1,13 +1,22 @@
 //! Server shop logic
 
 use bevy::app::App;
+use cosmos_core::shop::Shop;
+
+use crate::persistence::make_persistent::{DefaultPersistentComponent, make_persistent};
 
 mod ev_reader;
 mod generate_shop;
+mod player_shop;
 pub mod prices;
 
+impl DefaultPersistentComponent for Shop {}
+
 pub(super) fn register(app: &mut App) {
+    make_persistent::<Shop>(app);
+
     ev_reader::register(app);
     generate_shop::register(app);
+    player_shop::register(app);
     prices::register(app);
 }
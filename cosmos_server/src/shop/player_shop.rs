@@ -0,0 +1,261 @@
+//! Shops claimed by players, which trade out of their structure's storage and pay their owner (or their structure's faction).
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use cosmos_core::{
+    block::{Block, data::BlockData},
+    economy::Credits,
+    entities::{EntityId, player::Player},
+    inventory::{Inventory, itemstack::ItemShouldHaveData},
+    item::Item,
+    registry::{Registry, identifiable::Identifiable},
+    shop::{Shop, ShopEntry, netty::ShopSellError},
+    structure::Structure,
+};
+
+use crate::GameState;
+
+const STORAGE_BLOCK_ID: &str = "cosmos:storage";
+
+/// The storage inventories player-owned shops trade out of.
+pub(super) type StorageQuery<'w, 's> = Query<'w, 's, &'static mut Inventory, (With<BlockData>, Without<Player>)>;
+
+/// The block data entities of every storage block on this structure.
+///
+/// Only the structure's own block data is looked at, rather than every inventory in the world.
+fn storage_entities(structure: &Structure, blocks: &Registry<Block>) -> Vec<Entity> {
+    let Some(storage_id) = blocks.from_id(STORAGE_BLOCK_ID).map(|x| x.id()) else {
+        return vec![];
+    };
+
+    structure
+        .chunks()
+        .values()
+        .flat_map(|chunk| chunk.all_block_data_entities().iter())
+        .filter(|((block_id, _), _)| *block_id == storage_id)
+        .map(|(_, &entity)| entity)
+        .collect()
+}
+
+/// Sets the stock of everything this player-owned shop sells to how much of it is in its structure's storage.
+pub(super) fn refresh_stock(
+    shop: &mut Shop,
+    structure: &Structure,
+    blocks: &Registry<Block>,
+    items: &Registry<Item>,
+    q_storage: &StorageQuery,
+) {
+    let selling = shop
+        .contents
+        .iter()
+        .filter_map(|entry| match entry {
+            ShopEntry::Selling { item_id, .. } => Some(*item_id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if selling.is_empty() {
+        return;
+    }
+
+    let storage = storage_entities(structure, blocks);
+
+    for item_id in selling {
+        let Some(item) = items.try_from_numeric_id(item_id) else {
+            continue;
+        };
+
+        let quantity: usize = q_storage
+            .iter_many(storage.iter().copied())
+            .map(|inventory| inventory.quantity_of(item))
+            .sum();
+
+        shop.set_stock(item_id, quantity.min(u32::MAX as usize) as u32);
+    }
+}
+
+/// Removes this many items from the structure's storage. Make sure they are there with [`refresh_stock`] first.
+pub(super) fn take_from_storage(
+    structure: &Structure,
+    item: &Item,
+    quantity: u32,
+    blocks: &Registry<Block>,
+    q_storage: &mut StorageQuery,
+    commands: &mut Commands,
+) {
+    let mut remaining = quantity as usize;
+
+    for entity in storage_entities(structure, blocks) {
+        if remaining == 0 {
+            break;
+        }
+
+        let Ok(mut inventory) = q_storage.get_mut(entity) else {
+            continue;
+        };
+
+        let (left, taken) = inventory.take_item(item, remaining);
+        for mut is in taken {
+            is.remove(commands);
+        }
+        remaining = left;
+    }
+}
+
+/// Returns true if the structure's storage has room for this many items.
+pub(super) fn storage_has_room_for(
+    structure: &Structure,
+    item: &Item,
+    quantity: u32,
+    blocks: &Registry<Block>,
+    q_storage: &mut StorageQuery,
+) -> bool {
+    let mut room = 0;
+
+    for entity in storage_entities(structure, blocks) {
+        if let Ok(mut inventory) = q_storage.get_mut(entity) {
+            room += inventory.bypass_change_detection().max_quantity_can_be_inserted(item) as u64;
+        }
+    }
+
+    room >= quantity as u64
+}
+
+/// Puts this many items into the structure's storage. Make sure there is room with [`storage_has_room_for`] first.
+pub(super) fn insert_into_storage(
+    structure: &Structure,
+    item: &Item,
+    quantity: u32,
+    blocks: &Registry<Block>,
+    q_storage: &mut StorageQuery,
+    commands: &mut Commands,
+    has_data: &ItemShouldHaveData,
+) {
+    let mut remaining = quantity;
+
+    for entity in storage_entities(structure, blocks) {
+        let Ok(mut inventory) = q_storage.get_mut(entity) else {
+            continue;
+        };
+
+        while remaining != 0 {
+            let amount = remaining.min(u16::MAX as u32) as u16;
+            let (overflow, _) = inventory.insert_item(item, amount, commands, has_data);
+            remaining -= (amount - overflow) as u32;
+
+            if overflow != 0 {
+                break;
+            }
+        }
+    }
+}
+
+/// Sells items to a player-owned shop, paid for out of `funds` (its owner's or faction's credits). The shop is only
+/// changed if the sale succeeds.
+///
+/// `funds` is `None` when the owner is offline, in which case only the credits the shop has earned but not yet paid
+/// out can be spent, and [`ShopSellError::OwnerOffline`] is returned if those aren't enough.
+///
+/// Returns the credits the seller should receive. It is up to the caller to move the items into storage.
+pub(super) fn sell_to_player_shop(
+    shop: &mut Shop,
+    structure: &Structure,
+    item: &Item,
+    quantity: u32,
+    blocks: &Registry<Block>,
+    q_storage: &mut StorageQuery,
+    mut funds: Option<&mut Credits>,
+) -> Result<u64, ShopSellError> {
+    let mut after_sale = shop.clone();
+    let mut proceeds = Credits::new(0);
+    after_sale.sell(item.id(), quantity, &mut proceeds)?;
+    let proceeds = proceeds.amount();
+
+    if !storage_has_room_for(structure, item, quantity, blocks, q_storage) {
+        return Err(ShopSellError::NotEnoughInventorySpace);
+    }
+
+    // Credits the shop has earned but not paid out yet are spent first.
+    let funds_amount = funds.as_ref().map(|credits| credits.amount()).unwrap_or(0);
+
+    if after_sale.pending_revenue.saturating_add(funds_amount) < proceeds {
+        return Err(if funds.is_some() {
+            ShopSellError::InsufficientFunds
+        } else {
+            ShopSellError::OwnerOffline
+        });
+    }
+
+    let from_revenue = proceeds.min(after_sale.pending_revenue);
+    after_sale.pending_revenue -= from_revenue;
+    if let Some(credits) = funds.as_mut() {
+        credits.decrease(proceeds - from_revenue);
+    }
+
+    *shop = after_sale;
+
+    Ok(proceeds)
+}
+
+/// Maps the id of every online player to their entity.
+pub(super) fn players_by_id<'a>(players: impl Iterator<Item = (Entity, &'a EntityId)>) -> HashMap<EntityId, Entity> {
+    players.map(|(entity, &id)| (id, entity)).collect()
+}
+
+/// Gives the credits player-owned shops earned while their owner was offline to them once they join.
+///
+/// Revenue earned while the owner is online is paid as soon as the sale is made, and shops on a faction's
+/// structures pay their faction instead, so only revenue earned while the owner was away ends up here.
+fn pay_out_revenue_on_join(
+    q_joined: Query<(Entity, &EntityId), (With<Player>, Added<EntityId>)>,
+    mut q_shop: Query<&mut Shop>,
+    mut q_credits: Query<&mut Credits, With<Player>>,
+) {
+    if q_joined.is_empty() {
+        return;
+    }
+
+    let joined = players_by_id(q_joined.iter());
+
+    for mut shop in q_shop.iter_mut() {
+        pay_out(&mut shop, &joined, &mut q_credits);
+    }
+}
+
+/// Shops that weren't loaded when their owner joined pay them out once they are.
+fn pay_out_revenue_on_load(
+    q_players: Query<(Entity, &EntityId), With<Player>>,
+    mut q_loaded_shop: Query<&mut Shop, Added<Shop>>,
+    mut q_credits: Query<&mut Credits, With<Player>>,
+) {
+    if q_loaded_shop.is_empty() {
+        return;
+    }
+
+    let players = players_by_id(q_players.iter());
+
+    for mut shop in q_loaded_shop.iter_mut() {
+        pay_out(&mut shop, &players, &mut q_credits);
+    }
+}
+
+/// Gives the credits this shop has earned to its owner, if they are one of these players.
+fn pay_out(shop: &mut Mut<Shop>, players: &HashMap<EntityId, Entity>, q_credits: &mut Query<&mut Credits, With<Player>>) {
+    if shop.pending_revenue == 0 {
+        return;
+    }
+
+    let Some(&owner) = shop.owner.as_ref().and_then(|owner| players.get(owner)) else {
+        return;
+    };
+
+    if let Ok(mut credits) = q_credits.get_mut(owner) {
+        credits.increase(std::mem::take(&mut shop.pending_revenue));
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    app.add_systems(
+        Update,
+        (pay_out_revenue_on_join, pay_out_revenue_on_load).run_if(in_state(GameState::Playing)),
+    );
+}
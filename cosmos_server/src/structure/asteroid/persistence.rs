@@ -18,6 +18,7 @@ use crate::{
         SerializedData,
         loading::{LOADING_SCHEDULE, LoadingBlueprintSystemSet, LoadingSystemSet, NeedsBlueprintLoaded, NeedsLoaded},
         make_persistent::{DefaultPersistentComponent, make_persistent},
+        registry::register_persistent_data,
         saving::{BlueprintingSystemSet, NeedsBlueprinted, NeedsSaved, SAVING_SCHEDULE, SavingSystemSet},
     },
     structure::persistence::{chunk::AllBlockData, load_structure_with_palette, save_structure},
@@ -155,6 +156,7 @@ impl DefaultPersistentComponent for MovingAsteroid {}
 
 pub(super) fn register(app: &mut App) {
     make_persistent::<MovingAsteroid>(app);
+    register_persistent_data::<f32>(app, "cosmos:asteroid");
 
     app.add_systems(
         SAVING_SCHEDULE,
@@ -13,13 +13,15 @@ use cosmos_core::{
     state::GameState,
     structure::{
         Structure,
-        block_health::events::{BlockDestroyedMessage, BlockTakeDamageMessage},
+        block_health::events::{BlockDestroyedMessage, BlockRepairedMessage, BlockTakeDamageMessage},
         loading::StructureLoadingSet,
     },
 };
 
 use super::{planet::biosphere::biosphere_generation::BiosphereGenerationSet, shared::MeltingDownSet};
 
+pub mod repair;
+
 fn monitor_block_destroyed(
     mut event_reader: MessageReader<BlockDestroyedMessage>,
     mut structure_query: Query<&mut Structure>,
@@ -56,6 +58,25 @@ fn monitor_block_health_changed(mut server: ResMut<RenetServer>, mut event_reade
     }
 }
 
+fn monitor_block_repaired(mut server: ResMut<RenetServer>, mut event_reader: MessageReader<BlockRepairedMessage>) {
+    let changes = event_reader
+        .read()
+        .map(|ev| BlockHealthUpdate {
+            block: ev.block,
+            new_health: ev.new_health,
+            structure_entity: ev.structure_entity,
+            causer: ev.repairer,
+        })
+        .collect::<Vec<BlockHealthUpdate>>();
+
+    if !changes.is_empty() {
+        server.broadcast_message(
+            NettyChannelServer::Reliable,
+            cosmos_encoder::serialize(&ServerReliableMessages::BlockHealthRestored { changes }),
+        );
+    }
+}
+
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 /// Handles block health changes
 pub enum BlockHealthSet {
@@ -83,11 +104,13 @@ pub(super) fn register(app: &mut App) {
 
     app.add_systems(
         FixedUpdate,
-        (monitor_block_health_changed, monitor_block_destroyed)
+        (monitor_block_health_changed, monitor_block_repaired, monitor_block_destroyed)
             .in_set(BlockHealthSet::RemoveBlocks)
             .in_set(BlockMessagesSet::SendMessagesForNextFrame)
             .ambiguous_with(BlockMessagesSet::SendMessagesForNextFrame) // Order of events doesn't matter
             .chain()
             .run_if(in_state(GameState::Playing)),
     );
+
+    repair::register(app);
 }
@@ -0,0 +1,139 @@
+//! Restoring the health of damaged blocks
+
+use bevy::prelude::*;
+use cosmos_core::{
+    block::Block,
+    entities::player::Player,
+    netty::sync::IdentifiableComponent,
+    prelude::{BlockCoordinate, Ship, Station, Structure, StructureBlock},
+    registry::Registry,
+    structure::block_health::events::BlockRepairedMessage,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::make_persistent::{DefaultPersistentComponent, make_persistent};
+
+/// The item that is used up to repair blocks
+pub const REPAIR_MATERIAL: &str = "cosmos:iron_bar";
+/// How much block health a single [`REPAIR_MATERIAL`] can restore
+pub const HEALTH_PER_REPAIR_MATERIAL: f32 = 200.0;
+
+#[derive(Component, Debug, Default, Serialize, Deserialize)]
+/// Repair material that has already been taken from an inventory, but hasn't been fully used up yet.
+///
+/// This is stored on whatever is doing the repairing, such as a ship with repair beams or a player with a repair tool.
+pub struct StoredRepairMaterial(f32);
+
+impl IdentifiableComponent for StoredRepairMaterial {
+    fn get_component_unlocalized_name() -> &'static str {
+        "cosmos:stored_repair_material"
+    }
+}
+
+impl DefaultPersistentComponent for StoredRepairMaterial {}
+
+impl StoredRepairMaterial {
+    /// Uses up to `amount` health worth of repair material, calling `take_material` each time another
+    /// [`REPAIR_MATERIAL`] is needed.
+    ///
+    /// Returns how much health can be restored, which will be less than `amount` if `take_material` failed.
+    pub fn spend(&mut self, amount: f32, mut take_material: impl FnMut() -> bool) -> f32 {
+        while self.0 < amount && take_material() {
+            self.0 += HEALTH_PER_REPAIR_MATERIAL;
+        }
+
+        let spent = amount.min(self.0);
+        self.0 -= spent;
+        spent
+    }
+}
+
+/// How much health the block at these coordinates has lost
+pub fn missing_health(structure: &Structure, coords: BlockCoordinate, blocks: &Registry<Block>) -> f32 {
+    (structure.block_at(coords, blocks).hardness() - structure.get_block_health(coords, blocks)).max(0.0)
+}
+
+/// Restores up to `amount` health to the block at these coordinates, and sends a [`BlockRepairedMessage`] so clients
+/// are told about it.
+///
+/// A block can never be repaired above its starting health.
+pub fn repair_block(
+    structure: &mut Structure,
+    coords: BlockCoordinate,
+    amount: f32,
+    repairer: Option<Entity>,
+    blocks: &Registry<Block>,
+    evw_block_repaired: &mut MessageWriter<BlockRepairedMessage>,
+) {
+    let Some(structure_entity) = structure.get_entity() else {
+        return;
+    };
+
+    let hardness = structure.block_at(coords, blocks).hardness();
+    let new_health = (structure.get_block_health(coords, blocks) + amount).min(hardness);
+
+    if new_health <= 0.0 {
+        return;
+    }
+
+    structure.set_block_health(coords, new_health, blocks);
+
+    evw_block_repaired.write(BlockRepairedMessage {
+        structure_entity,
+        block: StructureBlock::new(coords, structure_entity),
+        new_health,
+        repairer,
+    });
+}
+
+fn add_stored_repair_material(
+    mut commands: Commands,
+    q_needs_material: Query<Entity, (Or<(With<Ship>, With<Station>, With<Player>)>, Without<StoredRepairMaterial>)>,
+) {
+    for ent in q_needs_material.iter() {
+        commands.entity(ent).insert(StoredRepairMaterial::default());
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<StoredRepairMaterial>(app);
+
+    app.add_systems(FixedUpdate, add_stored_repair_material);
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use super::{HEALTH_PER_REPAIR_MATERIAL, StoredRepairMaterial};
+
+    #[test]
+    fn spends_material_only_when_needed() {
+        let mut stored = StoredRepairMaterial::default();
+        let materials = Cell::new(2);
+
+        let take = || {
+            if materials.get() == 0 {
+                return false;
+            }
+            materials.set(materials.get() - 1);
+            true
+        };
+
+        // The first repair takes one material, and the leftovers are used by the next repair
+        assert_eq!(
+            stored.spend(HEALTH_PER_REPAIR_MATERIAL / 2.0, take),
+            HEALTH_PER_REPAIR_MATERIAL / 2.0
+        );
+        assert_eq!(
+            stored.spend(HEALTH_PER_REPAIR_MATERIAL / 2.0, take),
+            HEALTH_PER_REPAIR_MATERIAL / 2.0
+        );
+        assert_eq!(materials.get(), 1);
+
+        // Once the materials run out, only what's left can be spent
+        assert_eq!(stored.spend(HEALTH_PER_REPAIR_MATERIAL * 2.0, take), HEALTH_PER_REPAIR_MATERIAL);
+        assert_eq!(materials.get(), 0);
+        assert_eq!(stored.spend(1.0, take), 0.0);
+    }
+}
@@ -5,6 +5,7 @@ use bevy::prelude::App;
 pub mod asteroid;
 pub mod block_health;
 mod composite_blueprint;
+pub mod ownership;
 pub mod persistence;
 pub mod planet;
 pub mod shared;
@@ -19,6 +20,7 @@ pub(super) fn register(app: &mut App) {
     block_health::register(app);
     asteroid::register(app);
     composite_blueprint::register(app);
+    ownership::register(app);
 
     persistence::register(app);
     shared::register(app);
@@ -0,0 +1,236 @@
+//! Server logic for who owns a structure and who they trust with it
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use cosmos_core::{
+    ecs::sets::FixedUpdateSet,
+    entities::{EntityId, player::Player},
+    faction::{FactionId, FactionPermission, Factions},
+    netty::{
+        server::ServerLobby,
+        sync::events::server_event::{NettyMessageReceived, NettyMessageWriter},
+    },
+    notifications::Notification,
+    state::GameState,
+    structure::{
+        Structure,
+        ownership::{SetStructureTrustMessage, StructureOwner, StructureTrustList, TrustLevel, is_trusted},
+        shared::MeltingDown,
+    },
+};
+
+use crate::persistence::make_persistent::{DefaultPersistentComponent, make_persistent};
+
+impl DefaultPersistentComponent for StructureOwner {}
+impl DefaultPersistentComponent for StructureTrustList {}
+
+/// Structures whose trust list can be changed
+pub(crate) type TrustStructureQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static StructureOwner>,
+        Option<&'static FactionId>,
+        Option<&'static mut StructureTrustList>,
+    ),
+    (With<Structure>, Without<Player>),
+>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Why a structure's trust list could not be changed
+pub(crate) enum SetTrustError {
+    /// The entity is not a structure
+    NotAStructure,
+    /// The player is not allowed to change who is trusted with this structure
+    NotAllowed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Why a player is not allowed to do something to a structure
+pub(crate) enum PermissionDenied {
+    /// The structure belongs to a faction the player is not in
+    DifferentFaction,
+    /// The player is in the structure's faction, but their rank does not allow this
+    InsufficientFactionRank,
+    /// The structure's owner has not trusted the player enough
+    NotTrusted,
+}
+
+impl PermissionDenied {
+    /// A message explaining this to the player
+    pub(crate) fn message(&self) -> &'static str {
+        match self {
+            Self::DifferentFaction => "This structure belongs to a different faction.",
+            Self::InsufficientFactionRank => "Your faction rank does not allow you to do this.",
+            Self::NotTrusted => "The owner of this structure has not trusted you to do this.",
+        }
+    }
+}
+
+#[derive(SystemParam)]
+/// Checks what players are allowed to do to structures
+pub(crate) struct StructurePermissions<'w, 's> {
+    q_faction: Query<'w, 's, &'static FactionId>,
+    q_entity_id: Query<'w, 's, &'static EntityId>,
+    q_ownership: Query<'w, 's, (Option<&'static StructureOwner>, Option<&'static StructureTrustList>)>,
+    q_melting_down: Query<'w, 's, (), With<MeltingDown>>,
+    factions: Res<'w, Factions>,
+}
+
+impl StructurePermissions<'_, '_> {
+    /// Checks if the player is allowed to do something to this structure.
+    ///
+    /// The structure's owner and players on its trust list with at least `trust_level` are always allowed. Otherwise, structures
+    /// with a faction require the player to be in that faction with a rank that has the `permission` needed for this
+    /// action, and structures without a faction require the player to be their owner. Anyone can do anything to a structure
+    /// that is melting down.
+    pub(crate) fn check(
+        &self,
+        player: Entity,
+        structure: Entity,
+        permission: Option<FactionPermission>,
+        trust_level: TrustLevel,
+    ) -> Result<(), PermissionDenied> {
+        if self.q_melting_down.contains(structure) {
+            return Ok(());
+        }
+
+        let player_id = self.q_entity_id.get(player).ok();
+        let (owner, trust_list) = self.q_ownership.get(structure).unwrap_or_default();
+
+        if player_id.is_some_and(|id| is_trusted(owner, trust_list, id, trust_level)) {
+            return Ok(());
+        }
+
+        if let Some(structure_fac) = self.q_faction.get(structure).ok().and_then(|id| self.factions.from_id(id)) {
+            if self
+                .q_faction
+                .get(player)
+                .ok()
+                .and_then(|id| self.factions.from_id(id))
+                .map(|fac| fac.id() != structure_fac.id())
+                .unwrap_or(true)
+            {
+                Err(PermissionDenied::DifferentFaction)
+            } else if let Some(permission) = permission
+                && !player_id.is_some_and(|ent_id| structure_fac.has_permission(*ent_id, permission))
+            {
+                Err(PermissionDenied::InsufficientFactionRank)
+            } else {
+                Ok(())
+            }
+        } else if owner.is_some() {
+            Err(PermissionDenied::NotTrusted)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks if the player is allowed to build on this structure, exactly like placing or breaking a block is checked
+    pub(crate) fn check_build(&self, player: Entity, structure: Entity) -> Result<(), PermissionDenied> {
+        self.check(player, structure, Some(FactionPermission::BuildOnStructures), TrustLevel::Build)
+    }
+}
+
+/// Returns the player's [`EntityId`], giving them one if they don't have one yet.
+///
+/// Players are normally given their id the first time they are saved, which may not have happened yet.
+pub(crate) fn player_entity_id(player: Entity, entity_id: Option<&EntityId>, commands: &mut Commands) -> EntityId {
+    entity_id.copied().unwrap_or_else(|| {
+        let id = EntityId::generate();
+        commands.entity(player).insert(id);
+        id
+    })
+}
+
+/// Changes how much `trusting` is trusted with this structure on behalf of `player`.
+///
+/// Only the structure's owner can do this, or - for structures in a faction - members of that faction
+/// whose rank allows them to build on its structures.
+pub(crate) fn set_structure_trust(
+    player: Entity,
+    structure: Entity,
+    trusting: EntityId,
+    level: Option<TrustLevel>,
+    q_structure: &mut TrustStructureQuery,
+    q_player: &Query<(&EntityId, Option<&FactionId>), With<Player>>,
+    factions: &Factions,
+    commands: &mut Commands,
+) -> Result<(), SetTrustError> {
+    let Ok((owner, structure_faction, trust_list)) = q_structure.get_mut(structure) else {
+        return Err(SetTrustError::NotAStructure);
+    };
+
+    let Ok((player_id, player_faction)) = q_player.get(player) else {
+        return Err(SetTrustError::NotAllowed);
+    };
+
+    let is_owner = owner.is_some_and(|owner| owner.0 == *player_id);
+    let manages_faction_structure = structure_faction.is_some_and(|structure_faction| {
+        player_faction == Some(structure_faction)
+            && factions
+                .from_id(structure_faction)
+                .is_some_and(|fac| fac.has_permission(*player_id, FactionPermission::BuildOnStructures))
+    });
+
+    if !is_owner && !manages_faction_structure {
+        return Err(SetTrustError::NotAllowed);
+    }
+
+    if let Some(mut trust_list) = trust_list {
+        trust_list.set_trust(trusting, level);
+    } else if level.is_some() {
+        let mut trust_list = StructureTrustList::default();
+        trust_list.set_trust(trusting, level);
+        commands.entity(structure).insert(trust_list);
+    }
+
+    Ok(())
+}
+
+fn on_set_structure_trust(
+    mut nevr_set_trust: MessageReader<NettyMessageReceived<SetStructureTrustMessage>>,
+    lobby: Res<ServerLobby>,
+    mut q_structure: TrustStructureQuery,
+    q_player: Query<(&EntityId, Option<&FactionId>), With<Player>>,
+    factions: Res<Factions>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    mut commands: Commands,
+) {
+    for ev in nevr_set_trust.read() {
+        let Some(player) = lobby.player_from_id(ev.client_id) else {
+            continue;
+        };
+
+        match set_structure_trust(
+            player,
+            ev.structure,
+            ev.player,
+            ev.level,
+            &mut q_structure,
+            &q_player,
+            &factions,
+            &mut commands,
+        ) {
+            Ok(()) => {}
+            Err(SetTrustError::NotAllowed) => {
+                nevw_notification.write(
+                    Notification::error("You are not allowed to change who is trusted with this structure."),
+                    ev.client_id,
+                );
+            }
+            Err(SetTrustError::NotAStructure) => {}
+        }
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<StructureOwner>(app);
+    make_persistent::<StructureTrustList>(app);
+
+    app.add_systems(
+        FixedUpdate,
+        on_set_structure_trust
+            .in_set(FixedUpdateSet::Main)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
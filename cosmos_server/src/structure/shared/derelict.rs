@@ -0,0 +1,168 @@
+//! Turns ships that melt down into derelict wrecks that can be salvaged
+//!
+//! Salvaging is done with ordinary mining lasers, which are allowed to break the blocks of derelicts (see
+//! `dont_mine_alive_ships`). Every broken block becomes its normal [`crate::blocks::drops::BlockDrops`] drop,
+//! which is put into the storage of the ship doing the salvaging.
+
+use bevy::prelude::*;
+use cosmos_core::{
+    block::{Block, data::BlockData},
+    ecs::NeedsDespawned,
+    events::block_events::{BlockChangedMessage, BlockChangedReason},
+    faction::FactionId,
+    inventory::Inventory,
+    registry::Registry,
+    state::GameState,
+    structure::{
+        Structure,
+        ownership::{StructureOwner, StructureTrustList},
+        shared::{Derelict, MeltingDown},
+        ship::Ship,
+    },
+    time::UniverseTimestamp,
+};
+use rand::{Rng, RngExt};
+
+use crate::{
+    persistence::make_persistent::{DefaultPersistentComponent, make_persistent},
+    settings::ServerSettings,
+};
+
+use super::MeltingDownSet;
+
+/// How long a ship melts down for before it becomes a derelict.
+///
+/// This gives everything that reacts to a ship being destroyed (such as bounties) a chance to see it melting down.
+const SECONDS_BEFORE_DERELICT: f32 = 5.0;
+/// The chance each block has of surviving the meltdown
+const BLOCK_SURVIVAL_CHANCE: f64 = 0.6;
+
+#[derive(Component, Debug, Default)]
+/// This ship is melting down, but will become a [`Derelict`] instead of melting away entirely
+pub(super) struct BecomingDerelict(f32);
+
+/// How many items of a stack of `quantity` items survive the meltdown
+fn surviving_quantity(quantity: u16, rng: &mut impl Rng) -> u16 {
+    rng.random_range(0..=quantity)
+}
+
+fn mark_becoming_derelict(
+    mut commands: Commands,
+    q_melting_down: Query<Entity, (Added<MeltingDown>, With<Ship>, Without<Derelict>)>,
+    settings: Res<ServerSettings>,
+) {
+    if !settings.derelicts {
+        return;
+    }
+
+    for ent in q_melting_down.iter() {
+        commands.entity(ent).insert(BecomingDerelict::default());
+    }
+}
+
+fn become_derelict(
+    mut commands: Commands,
+    mut q_becoming_derelict: Query<(Entity, &mut Structure, &Ship, &mut BecomingDerelict)>,
+    mut q_inventories: Query<(&mut Inventory, &BlockData)>,
+    mut evw_block_changed: MessageWriter<BlockChangedMessage>,
+    blocks: Res<Registry<Block>>,
+    settings: Res<ServerSettings>,
+    timestamp: Res<UniverseTimestamp>,
+    time: Res<Time>,
+) {
+    for (ent, mut structure, ship, mut becoming_derelict) in q_becoming_derelict.iter_mut() {
+        becoming_derelict.0 += time.delta_secs();
+        if becoming_derelict.0 < SECONDS_BEFORE_DERELICT {
+            continue;
+        }
+
+        let mut rng = rand::rng();
+
+        // The ship core never survives, otherwise the wreck could be flown away
+        let core = ship.ship_core_block_coords(&structure);
+        let destroyed = structure
+            .all_blocks_iter(false)
+            .filter(|&coords| coords == core || !rng.random_bool(BLOCK_SURVIVAL_CHANCE))
+            .collect::<Vec<_>>();
+
+        for coords in destroyed {
+            structure.remove_block_at(coords, &blocks, Some((&mut evw_block_changed, BlockChangedReason::MeltingDown)));
+        }
+
+        if structure.all_blocks_iter(false).next().is_none() {
+            commands.entity(ent).insert(NeedsDespawned);
+            continue;
+        }
+
+        for (mut inventory, _) in q_inventories
+            .iter_mut()
+            .filter(|(_, block_data)| block_data.identifier.block.structure() == ent)
+        {
+            for slot in 0..inventory.len() {
+                let Some(quantity) = inventory.itemstack_at(slot).map(|is| is.quantity()) else {
+                    continue;
+                };
+
+                let lost = quantity - surviving_quantity(quantity, &mut rng);
+                inventory.decrease_quantity_at(slot, lost, &mut commands);
+            }
+        }
+
+        let mut despawn_at = *timestamp;
+        despawn_at.advance_by(settings.derelict_lifetime_secs);
+
+        commands
+            .entity(ent)
+            .remove::<(MeltingDown, BecomingDerelict, FactionId, StructureOwner, StructureTrustList)>()
+            .insert(Derelict { despawn_at });
+    }
+}
+
+fn clean_up_derelicts(
+    mut commands: Commands,
+    q_derelicts: Query<(Entity, &Derelict), Without<NeedsDespawned>>,
+    timestamp: Res<UniverseTimestamp>,
+) {
+    for (ent, derelict) in q_derelicts.iter() {
+        if *timestamp >= derelict.despawn_at {
+            commands.entity(ent).insert(NeedsDespawned);
+        }
+    }
+}
+
+impl DefaultPersistentComponent for Derelict {}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<Derelict>(app);
+
+    app.add_systems(
+        FixedUpdate,
+        (
+            mark_becoming_derelict.before(super::on_melting_down),
+            become_derelict.after(super::on_melting_down),
+            clean_up_derelicts,
+        )
+            .in_set(MeltingDownSet::ProcessMeltingDown)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::surviving_quantity;
+
+    #[test]
+    fn inventories_are_partially_preserved() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        let survived = (0..100).map(|_| surviving_quantity(64, &mut rng)).collect::<Vec<_>>();
+
+        assert!(survived.iter().all(|&n| n <= 64));
+        assert!(survived.iter().any(|&n| n < 64));
+        assert!(survived.iter().any(|&n| n > 0));
+        assert_eq!(surviving_quantity(0, &mut rng), 0);
+    }
+}
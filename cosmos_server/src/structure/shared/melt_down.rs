@@ -6,7 +6,11 @@ use cosmos_core::{
     events::{block_events::BlockChangedMessage, structure::structure_event::StructureMessage},
     registry::{Registry, identifiable::Identifiable},
     state::GameState,
-    structure::{Structure, shared::MeltingDown, ship::Ship},
+    structure::{
+        Structure,
+        shared::{Derelict, MeltingDown},
+        ship::Ship,
+    },
 };
 
 use crate::persistence::make_persistent::{DefaultPersistentComponent, make_persistent};
@@ -18,8 +22,14 @@ fn monitor_block_events(
     blocks: Res<Registry<Block>>,
     mut event_reader: MessageReader<BlockChangedMessage>,
     q_ship: Query<(&Ship, &Structure)>,
+    q_derelict: Query<(), With<Derelict>>,
 ) {
     for ev in event_reader.read() {
+        // A derelict has already melted down, it shouldn't do so again
+        if q_derelict.contains(ev.block.structure()) {
+            continue;
+        }
+
         let block = blocks.from_numeric_id(ev.old_block);
 
         if block.unlocalized_name() == "cosmos:ship_core"
@@ -12,20 +12,22 @@ use cosmos_core::{
     state::GameState,
     structure::{Structure, loading::StructureLoadingSet, shared::MeltingDown, ship::pilot::Pilot},
 };
+use derelict::BecomingDerelict;
 
 pub mod build_mode;
+pub mod derelict;
 pub mod melt_down;
 
 fn on_melting_down(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Structure, &mut MeltingDown)>,
+    mut query: Query<(Entity, &mut Structure, &mut MeltingDown, Has<BecomingDerelict>)>,
     mut event_writer: MessageWriter<BlockChangedMessage>,
     blocks: Res<Registry<Block>>,
     time: Res<Time>,
     pilot_query: Query<&Pilot>,
     mut change_pilot_event: MessageWriter<ChangePilotMessage>,
 ) {
-    for (entity, mut structure, mut melting_down) in query.iter_mut() {
+    for (entity, mut structure, mut melting_down, becoming_derelict) in query.iter_mut() {
         if pilot_query.contains(entity) {
             change_pilot_event.write(ChangePilotMessage {
                 structure_entity: entity,
@@ -33,6 +35,10 @@ fn on_melting_down(
             });
         }
 
+        if becoming_derelict {
+            continue;
+        }
+
         if melting_down.0 >= 1.0 {
             melting_down.0 -= 1.0;
 
@@ -73,5 +79,6 @@ pub(super) fn register(app: &mut App) {
     app.add_systems(FixedUpdate, on_melting_down.in_set(MeltingDownSet::ProcessMeltingDown));
 
     build_mode::register(app);
+    derelict::register(app);
     melt_down::register(app);
 }
@@ -5,6 +5,7 @@ use bevy_rapier3d::dynamics::Velocity;
 use bevy_renet::RenetServer;
 use cosmos_core::{
     block::block_events::BlockMessagesSet,
+    entities::{EntityId, player::Player},
     events::structure::change_pilot_event::ChangePilotMessage,
     netty::{
         NettyChannelServer, cosmos_encoder, server_reliable_messages::ServerReliableMessages,
@@ -15,11 +16,11 @@ use cosmos_core::{
     state::GameState,
     structure::{
         Structure, StructureTypeSet, coordinates::ChunkCoordinate, full_structure::FullStructure, loading::StructureLoadingSet,
-        ship::ship_movement::ShipMovement,
+        ownership::StructureOwner, ship::ship_movement::ShipMovement,
     },
 };
 
-use crate::ai::AiControlled;
+use crate::{ai::AiControlled, structure::ownership::player_entity_id};
 
 use super::loading::ShipNeedsCreated;
 
@@ -77,10 +78,19 @@ pub struct CreateShipMessage {
     pub velocity: Velocity,
 }
 
-pub(crate) fn create_ship_event_reader(mut event_reader: MessageReader<CreateShipMessage>, mut commands: Commands) {
+pub(crate) fn create_ship_event_reader(
+    mut event_reader: MessageReader<CreateShipMessage>,
+    q_player: Query<Option<&EntityId>, With<Player>>,
+    mut commands: Commands,
+) {
     for ev in event_reader.read() {
         info!("Creating ship!!");
 
+        let owner = q_player
+            .get(ev.creator)
+            .ok()
+            .map(|entity_id| StructureOwner(player_entity_id(ev.creator, entity_id, &mut commands)));
+
         let mut entity = commands.spawn_empty();
 
         let structure = Structure::Full(FullStructure::new(ChunkCoordinate::new(10, 10, 10)));
@@ -94,6 +104,10 @@ pub(crate) fn create_ship_event_reader(mut event_reader: MessageReader<CreateShi
             ShipNeedsCreated::default(),
             Transform::from_rotation(ev.rotation),
         ));
+
+        if let Some(owner) = owner {
+            entity.insert(owner);
+        }
     }
 }
 
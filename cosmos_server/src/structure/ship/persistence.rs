@@ -15,6 +15,7 @@ use crate::{
     persistence::{
         SerializedData,
         loading::{LOADING_SCHEDULE, LoadingBlueprintSystemSet, LoadingSystemSet, NeedsBlueprintLoaded, NeedsLoaded},
+        registry::register_persistent_data,
         saving::{BlueprintingSystemSet, NeedsBlueprinted, NeedsSaved, SAVING_SCHEDULE, SavingSystemSet},
     },
     structure::persistence::{chunk::AllBlockData, load_structure_with_palette, save_structure},
@@ -160,6 +161,8 @@ fn on_load_ship(
 }
 
 pub(super) fn register(app: &mut App) {
+    register_persistent_data::<bool>(app, "cosmos:is_ship");
+
     app.add_systems(
         SAVING_SCHEDULE,
         (
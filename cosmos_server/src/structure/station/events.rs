@@ -2,7 +2,10 @@
 
 use bevy::prelude::*;
 use cosmos_core::{
-    entities::player::{Player, creative::Creative},
+    entities::{
+        EntityId,
+        player::{Player, creative::Creative},
+    },
     inventory::Inventory,
     item::Item,
     netty::sync::events::server_event::NettyMessageWriter,
@@ -11,9 +14,13 @@ use cosmos_core::{
     prelude::Station,
     registry::Registry,
     state::GameState,
-    structure::{Structure, coordinates::ChunkCoordinate, full_structure::FullStructure, loading::StructureLoadingSet},
+    structure::{
+        Structure, coordinates::ChunkCoordinate, full_structure::FullStructure, loading::StructureLoadingSet, ownership::StructureOwner,
+    },
 };
 
+use crate::structure::ownership::player_entity_id;
+
 use super::loading::StationNeedsCreated;
 
 /// This event is done when a station is being created
@@ -32,14 +39,14 @@ pub(crate) fn create_station_message_reader(
     mut commands: Commands,
     q_stations: Query<&Location, With<Station>>,
     mut nevw_notif: NettyMessageWriter<Notification>,
-    q_player: Query<&Player>,
+    q_player: Query<(&Player, Option<&EntityId>)>,
     q_creative: Query<(), With<Creative>>,
     mut q_inventory: Query<&mut Inventory>,
     items: Res<Registry<Item>>,
 ) {
     for ev in event_reader.read() {
         if q_stations.iter().any(|l| l.distance_sqrd(&ev.station_location) < 1000.0 * 1000.0) {
-            if let Ok(player) = q_player.get(ev.creator) {
+            if let Ok((player, _)) = q_player.get(ev.creator) {
                 nevw_notif.write(Notification::error("Another station is too close!"), player.client_id());
             }
             continue;
@@ -63,6 +70,11 @@ pub(crate) fn create_station_message_reader(
             }
         }
 
+        let owner = q_player
+            .get(ev.creator)
+            .ok()
+            .map(|(_, entity_id)| StructureOwner(player_entity_id(ev.creator, entity_id, &mut commands)));
+
         let mut entity = commands.spawn_empty();
 
         let structure = Structure::Full(FullStructure::new(ChunkCoordinate::new(20, 20, 20)));
@@ -74,6 +86,10 @@ pub(crate) fn create_station_message_reader(
             structure,
             Transform::from_rotation(ev.rotation),
         ));
+
+        if let Some(owner) = owner {
+            entity.insert(owner);
+        }
     }
 }
 
@@ -14,6 +14,7 @@ use crate::{
     persistence::{
         SerializedData,
         loading::{LOADING_SCHEDULE, LoadingBlueprintSystemSet, LoadingSystemSet, NeedsBlueprintLoaded, NeedsLoaded},
+        registry::register_persistent_data,
         saving::{BlueprintingSystemSet, NeedsBlueprinted, NeedsSaved, SAVING_SCHEDULE, SavingSystemSet},
     },
     structure::persistence::{chunk::AllBlockData, load_structure_with_palette, save_structure},
@@ -147,6 +148,8 @@ fn on_load_structure(
 }
 
 pub(super) fn register(app: &mut App) {
+    register_persistent_data::<bool>(app, "cosmos:is_station");
+
     app.add_systems(
         SAVING_SCHEDULE,
         (
@@ -35,7 +35,10 @@ use cosmos_core::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::persistence::make_persistent::{DefaultPersistentComponent, PersistentComponent, make_persistent};
+use crate::{
+    logic::{LogicInputMessage, LogicSystemSet, logic_driver::LogicDriver},
+    persistence::make_persistent::{DefaultPersistentComponent, PersistentComponent, make_persistent},
+};
 
 use super::sync::register_structure_system;
 
@@ -437,6 +440,57 @@ fn monitor_removed_dock_blocks(
     }
 }
 
+/// A dock block receiving any non-zero logic signal releases whatever is docked through it.
+fn dock_input_event_listener(
+    mut evr_logic_input: MessageReader<LogicInputMessage>,
+    q_structure: Query<(&Structure, &LogicDriver, Option<&DockedEntities>)>,
+    q_docked: Query<&Docked>,
+    q_velocity: Query<&Velocity>,
+    mut commands: Commands,
+    dock_blocks: Res<DockBlocks>,
+) {
+    for ev in evr_logic_input.read() {
+        let structure_entity = ev.block.structure();
+        let Ok((structure, logic_driver, docked_entities)) = q_structure.get(structure_entity) else {
+            continue;
+        };
+        let coords = ev.block.coords();
+        if !dock_blocks.contains(structure.block_id_at(coords)) {
+            continue;
+        }
+
+        let active = logic_driver
+            .read_all_inputs(coords, structure.block_rotation(coords))
+            .iter()
+            .any(|signal| *signal != 0);
+
+        if !active {
+            continue;
+        }
+
+        let this_docked = q_docked
+            .get(structure_entity)
+            .ok()
+            .filter(|docked| docked.this_block == coords)
+            .map(|_| structure_entity);
+
+        let docked_here = docked_entities.into_iter().flat_map(|x| x.iter()).filter(|&docked_entity| {
+            q_docked
+                .get(docked_entity)
+                .is_ok_and(|docked| docked.to == structure_entity && docked.to_block == coords)
+        });
+
+        for docked_entity in this_docked.into_iter().chain(docked_here) {
+            let Ok(docked) = q_docked.get(docked_entity) else {
+                continue;
+            };
+
+            let vel = q_velocity.get(docked.to).copied().unwrap_or_default();
+            commands.entity(docked_entity).remove::<Docked>().insert(vel);
+        }
+    }
+}
+
 fn add_dock_list(mut commands: Commands, q_needs_list: Query<Entity, (With<Structure>, Without<DockedEntities>)>) {
     for e in q_needs_list.iter() {
         commands.entity(e).insert(DockedEntities::default());
@@ -646,6 +700,12 @@ pub(super) fn register(app: &mut App) {
                 .run_if(in_state(GameState::Playing)),
         ),
     )
+    .add_systems(
+        FixedUpdate,
+        dock_input_event_listener
+            .in_set(LogicSystemSet::Consume)
+            .ambiguous_with(LogicSystemSet::Consume),
+    )
     .add_systems(OnEnter(GameState::PostLoading), add_dock_blocks)
     .register_type::<DockedEntities>()
     .init_resource::<DockBlocks>();
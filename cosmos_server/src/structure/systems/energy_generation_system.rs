@@ -66,7 +66,7 @@ fn update_energy(
         {
             // Prevent unneeded change detection
             if !storage.is_full() {
-                storage.increase_energy(g.energy_generation_rate() * time.delta_secs());
+                storage.generate_energy(g.energy_generation_rate(), time.delta_secs());
             }
         }
     }
@@ -1,6 +1,6 @@
 //! Represents all the energy stored on a structure
 
-use bevy::prelude::*;
+use bevy::{platform::collections::HashSet, prelude::*};
 
 use cosmos_core::{
     block::{Block, block_events::BlockMessagesSet},
@@ -10,9 +10,16 @@ use cosmos_core::{
     structure::{
         Structure,
         events::StructureLoadedMessage,
+        ship::Ship,
+        station::Station,
         systems::{
-            StructureSystemType, StructureSystems, StructureSystemsSet,
-            energy_storage_system::{EnergyStorageBlocks, EnergyStorageProperty, EnergyStorageSystem},
+            StructureSystem, StructureSystemType, StructureSystems, StructureSystemsSet,
+            energy_generation_system::EnergyGenerationBlocks,
+            energy_storage_system::{
+                EnergyStorageBlocks, EnergyStorageProperty, EnergyStorageSystem,
+                power_allocation::{PowerBudget, PowerPriority},
+                power_grid::{PowerBlock, compute_structure_power_grids},
+            },
         },
     },
 };
@@ -81,10 +88,109 @@ fn structure_loaded_event(
     }
 }
 
+/// How often the power grids of structures whose power blocks changed are recomputed.
+///
+/// Building changes many blocks in quick succession, and each recompute looks at every block on the structure, so changes
+/// are batched up rather than recomputing the grids for each one.
+const POWER_GRID_RECOMPUTE_INTERVAL_SECS: f32 = 0.5;
+
+/// Recomputes the power grids of every structure that had a power block change, or just got its energy storage system.
+fn recompute_power_grids(
+    mut evr_block_changed: MessageReader<BlockChangedMessage>,
+    mut evr_structure_loaded: MessageReader<StructureLoadedMessage>,
+    q_added: Query<&StructureSystem, Added<EnergyStorageSystem>>,
+    q_structure: Query<(&Structure, &StructureSystems)>,
+    mut q_ess: Query<&mut EnergyStorageSystem>,
+    blocks: Res<Registry<Block>>,
+    energy_storage_blocks: Res<EnergyStorageBlocks>,
+    energy_generation_blocks: Res<EnergyGenerationBlocks>,
+    mut needs_recomputed: Local<HashSet<Entity>>,
+    mut secs_since_recompute: Local<f32>,
+    time: Res<Time>,
+) {
+    let is_power_block = |id: u16| PowerBlock::of(blocks.from_numeric_id(id), &energy_storage_blocks, &energy_generation_blocks).is_some();
+
+    needs_recomputed.extend(
+        evr_block_changed
+            .read()
+            .filter(|ev| is_power_block(ev.old_block) || is_power_block(ev.new_block))
+            .map(|ev| ev.block.structure()),
+    );
+    needs_recomputed.extend(evr_structure_loaded.read().map(|ev| ev.structure_entity));
+    needs_recomputed.extend(q_added.iter().map(|ss| ss.structure_entity()));
+
+    *secs_since_recompute += time.delta_secs();
+    if *secs_since_recompute < POWER_GRID_RECOMPUTE_INTERVAL_SECS || needs_recomputed.is_empty() {
+        return;
+    }
+    *secs_since_recompute = 0.0;
+
+    for structure_entity in needs_recomputed.drain() {
+        let Ok((structure, systems)) = q_structure.get(structure_entity) else {
+            continue;
+        };
+
+        let Ok(mut ess) = systems.query_mut(&mut q_ess) else {
+            continue;
+        };
+
+        ess.set_power_grids(compute_structure_power_grids(
+            structure,
+            &blocks,
+            &energy_storage_blocks,
+            &energy_generation_blocks,
+        ));
+    }
+}
+
+/// How often the [`PowerBudget`] of each structure is updated, since it changes nearly every tick and is only used for display
+const POWER_BUDGET_SYNC_INTERVAL_SECS: f32 = 0.5;
+
+/// Divides up the energy of each ship and station between its systems for this tick, based on what they used last tick.
+fn allocate_power(
+    mut commands: Commands,
+    mut q_structures: Query<(Entity, &StructureSystems, Option<&PowerPriority>, Option<&mut PowerBudget>), Or<(With<Ship>, With<Station>)>>,
+    mut q_ess: Query<&mut EnergyStorageSystem>,
+    mut secs_since_sync: Local<f32>,
+    time: Res<Time>,
+) {
+    *secs_since_sync += time.delta_secs();
+    let sync_budgets = *secs_since_sync >= POWER_BUDGET_SYNC_INTERVAL_SECS;
+    if sync_budgets {
+        *secs_since_sync = 0.0;
+    }
+
+    for (structure_entity, systems, priority, budget) in q_structures.iter_mut() {
+        let Ok(mut ess) = systems.query_mut(&mut q_ess) else {
+            continue;
+        };
+
+        let default_priority = PowerPriority::default();
+        // The reservations are server-only, so don't trigger a sync of the energy storage system
+        let new_budget = ess
+            .bypass_change_detection()
+            .allocate_power(priority.unwrap_or(&default_priority), time.delta_secs());
+
+        if priority.is_none() {
+            commands.entity(structure_entity).insert(default_priority);
+        }
+
+        if let Some(mut budget) = budget {
+            if sync_budgets {
+                budget.set_if_neq(new_budget);
+            }
+        } else {
+            commands.entity(structure_entity).insert(new_budget);
+        }
+    }
+}
+
 impl DefaultPersistentComponent for EnergyStorageSystem {}
+impl DefaultPersistentComponent for PowerPriority {}
 
 pub(super) fn register(app: &mut App) {
     make_persistent::<EnergyStorageSystem>(app);
+    make_persistent::<PowerPriority>(app);
 
     app.insert_resource(EnergyStorageBlocks::default())
         .add_systems(OnEnter(GameState::PostLoading), register_energy_blocks)
@@ -94,9 +200,13 @@ pub(super) fn register(app: &mut App) {
                 structure_loaded_event
                     .in_set(StructureSystemsSet::InitSystems)
                     .ambiguous_with(StructureSystemsSet::InitSystems),
-                block_update_system
+                (block_update_system, recompute_power_grids)
+                    .chain()
                     .in_set(BlockMessagesSet::ProcessMessages)
                     .in_set(StructureSystemsSet::UpdateSystemsBlocks),
+                allocate_power
+                    .in_set(StructureSystemsSet::UpdateSystemsBlocks)
+                    .after(recompute_power_grids),
             )
                 .run_if(in_state(GameState::Playing)),
         )
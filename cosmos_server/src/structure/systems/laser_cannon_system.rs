@@ -21,7 +21,7 @@ use cosmos_core::{
         systems::{
             StructureSystem, StructureSystems, StructureSystemsSet, SystemActive,
             dock_system::Docked,
-            energy_storage_system::EnergyStorageSystem,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
             laser_cannon_system::{LaserCannonCalculator, LaserCannonProperty, LaserCannonSystem, LineSystemCooldown, SystemCooldown},
             line_system::LineBlocks,
         },
@@ -71,11 +71,6 @@ fn update_system(
 
         cooldown.remove_unused_cooldowns(cannon_system);
 
-        let mut total_energy = {
-            let q_ess_readonly = q_ess.as_readonly();
-            EnergyStorageSystem::compute_total_energy_recursive(ship_entity, &q_ess_readonly, &q_systems)
-        };
-
         for line in cannon_system.lines.iter() {
             let cooldown = cooldown.lines.entry(line.start).or_insert(default_cooldown);
 
@@ -83,14 +78,31 @@ fn update_system(
                 continue;
             }
 
-            if !(system_active || line.active()) || line.property.energy_per_shot > total_energy {
+            if !(system_active || line.active()) {
                 continue;
             }
 
-            total_energy -= line.property.energy_per_shot;
+            // Each cannon can only use the energy of the power grid it is wired into
+            let consumer = PowerConsumer::Block(line.start);
+            let available_energy = EnergyStorageSystem::compute_total_energy_recursive(
+                PowerCategory::Weapons,
+                consumer,
+                ship_entity,
+                &q_ess.as_readonly(),
+                &q_systems,
+            );
+            if line.property.energy_per_shot > available_energy {
+                continue;
+            }
 
-            let leftover =
-                EnergyStorageSystem::decrease_energy_recursive(line.property.energy_per_shot, ship_entity, &mut q_ess, &q_systems);
+            let leftover = EnergyStorageSystem::decrease_energy_recursive(
+                PowerCategory::Weapons,
+                consumer,
+                line.property.energy_per_shot,
+                ship_entity,
+                &mut q_ess,
+                &q_systems,
+            );
 
             if leftover != 0.0 {
                 continue;
@@ -20,14 +20,14 @@ use cosmos_core::{
     structure::{
         Structure,
         coordinates::BlockCoordinate,
-        shared::{DespawnWithStructure, MeltingDown},
+        shared::{Derelict, DespawnWithStructure, MeltingDown},
         shields::SHIELD_COLLISION_GROUP,
         ship::Ship,
         structure_block::StructureBlock,
         systems::{
             StructureSystem, StructureSystems, StructureSystemsSet, SystemActive,
             dock_system::Docked,
-            energy_storage_system::EnergyStorageSystem,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
             line_system::LineBlocks,
             mining_laser_system::{MiningLaserProperty, MiningLaserPropertyCalculator, MiningLaserSystem},
         },
@@ -172,6 +172,8 @@ fn update_mining_beams(
 
             beams.retain(|(beam_ent, beam, _, _)| {
                 if EnergyStorageSystem::decrease_energy_recursive(
+                    PowerCategory::Mining,
+                    PowerConsumer::Block(beam.line_start),
                     beam.property.energy_per_second * delta_time,
                     structure,
                     &mut q_energy_storage_system,
@@ -308,6 +310,8 @@ fn update_mining_beams(
 #[derive(Component)]
 struct MiningBeam {
     property: MiningLaserProperty,
+    /// The first block of the line this beam is coming from, used to find the power grid that powers it
+    line_start: BlockCoordinate,
     system_entity: Entity,
     structure_entity: Entity,
 }
@@ -329,7 +333,15 @@ fn on_activate_system(
             for line in mining_system.lines.iter() {
                 let energy = line.property.energy_per_second * sec;
 
-                if EnergyStorageSystem::decrease_energy_recursive(energy, ship_entity, &mut es_query, &q_docked_systems) == 0.0 {
+                if EnergyStorageSystem::decrease_energy_recursive(
+                    PowerCategory::Mining,
+                    PowerConsumer::Block(line.start),
+                    energy,
+                    ship_entity,
+                    &mut es_query,
+                    &q_docked_systems,
+                ) == 0.0
+                {
                     let beam_direction = line.direction.as_vec3();
 
                     let beam_begin = line.end();
@@ -341,6 +353,7 @@ fn on_activate_system(
                             NoSendEntity,
                             MiningBeam {
                                 property: line.property,
+                                line_start: line.start,
                                 structure_entity: ship_entity,
                                 system_entity,
                             },
@@ -379,10 +392,18 @@ fn dont_mine_alive_ships(
         (
             Or<(With<Ship>, With<Station>)>,
             Without<MeltingDown>,
+            Without<Derelict>,
             Without<CannotBeMinedByMiningLaser>,
         ),
     >,
-    q_melting_ships_and_stations: Query<Entity, (Or<(With<Ship>, With<Station>)>, With<MeltingDown>, With<CannotBeMinedByMiningLaser>)>,
+    q_melting_ships_and_stations: Query<
+        Entity,
+        (
+            Or<(With<Ship>, With<Station>)>,
+            Or<(With<MeltingDown>, With<Derelict>)>,
+            With<CannotBeMinedByMiningLaser>,
+        ),
+    >,
 ) {
     for ent in q_ships_and_stations.iter() {
         commands.entity(ent).insert(CannotBeMinedByMiningLaser);
@@ -39,7 +39,7 @@ use cosmos_core::{
         systems::{
             StructureSystem, StructureSystems, StructureSystemsSet, SystemActive,
             dock_system::Docked,
-            energy_storage_system::EnergyStorageSystem,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
             laser_cannon_system::{LineSystemCooldown, SystemCooldown},
             line_system::LineBlocks,
             missile_launcher_system::{
@@ -225,11 +225,6 @@ fn update_missile_system(
             continue;
         };
 
-        let mut total_energy = {
-            let es_query_readonly = es_query.as_readonly();
-            EnergyStorageSystem::compute_total_energy_recursive(ship_entity, &es_query_readonly, &q_docked_systems)
-        };
-
         for line in missile_launcher_system.lines.iter() {
             let cooldown = cooldown.lines.entry(line.start).or_insert(default_cooldown);
 
@@ -237,7 +232,20 @@ fn update_missile_system(
                 continue;
             }
 
-            if !(system_active || line.active()) || line.property.energy_per_shot > total_energy {
+            if !(system_active || line.active()) {
+                continue;
+            }
+
+            // Each launcher can only use the energy of the power grid it is wired into
+            let consumer = PowerConsumer::Block(line.start);
+            let available_energy = EnergyStorageSystem::compute_total_energy_recursive(
+                PowerCategory::Weapons,
+                consumer,
+                ship_entity,
+                &es_query.as_readonly(),
+                &q_docked_systems,
+            );
+            if line.property.energy_per_shot > available_energy {
                 continue;
             }
 
@@ -255,9 +263,9 @@ fn update_missile_system(
                 break;
             }
 
-            total_energy -= line.property.energy_per_shot;
-
             let leftover = EnergyStorageSystem::decrease_energy_recursive(
+                PowerCategory::Weapons,
+                consumer,
                 line.property.energy_per_shot,
                 ship_entity,
                 &mut es_query,
@@ -15,6 +15,7 @@ mod mining_laser_system;
 pub mod missile_launcher_system;
 mod persistence;
 mod railgun_system;
+mod repair_beam_system;
 pub mod shield_system;
 pub(crate) mod sync;
 mod system_ordering;
@@ -44,6 +45,7 @@ pub(super) fn register(app: &mut App) {
     thruster_system::register(app);
     energy_generation_system::register(app);
     mining_laser_system::register(app);
+    repair_beam_system::register(app);
     energy_storage_system::register(app);
     missile_launcher_system::register(app);
     railgun_system::register(app);
@@ -23,7 +23,7 @@ use cosmos_core::{
         systems::{
             StructureSystemImpl, StructureSystemOrdering, StructureSystemType, StructureSystems, StructureSystemsSet, SystemActive,
             dock_system::Docked,
-            energy_storage_system::EnergyStorageSystem,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
             railgun_system::{
                 InvalidRailgunReason, RailgunBlock, RailgunFiredInfo, RailgunFiredMessage, RailgunSystem, RailgunSystemEntry,
             },
@@ -537,8 +537,14 @@ fn charge_and_cool_railguns(
 
         let charge_rate = (railgun.charge_rate * delta).min((railgun.capacitance - railgun_block.energy_stored) as f32);
 
-        let uncharged =
-            EnergyStorageSystem::decrease_energy_recursive(charge_rate, structure_entity, &mut q_energy_system, &q_structure_systems);
+        let uncharged = EnergyStorageSystem::decrease_energy_recursive(
+            PowerCategory::Weapons,
+            PowerConsumer::Block(railgun.origin),
+            charge_rate,
+            structure_entity,
+            &mut q_energy_system,
+            &q_structure_systems,
+        );
         let amt_charged = charge_rate - uncharged;
         railgun_block.energy_stored += amt_charged.floor() as u32;
         railgun_block.energy_stored = railgun_block.energy_stored.min(railgun.capacitance);
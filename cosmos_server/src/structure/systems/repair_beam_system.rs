@@ -0,0 +1,255 @@
+//! Server-side repair beam logic
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use bevy_rapier3d::{
+    geometry::{CollisionGroups, Group},
+    pipeline::QueryFilter,
+    plugin::{RapierContextEntityLink, ReadRapierContext},
+};
+use cosmos_core::{
+    block::{Block, blocks::fluid::FLUID_COLLISION_GROUP, data::BlockData},
+    ecs::NeedsDespawned,
+    inventory::Inventory,
+    item::Item,
+    netty::NoSendEntity,
+    physics::location::LocationPhysicsSet,
+    registry::Registry,
+    state::GameState,
+    structure::{
+        Structure,
+        block_health::events::BlockRepairedMessage,
+        coordinates::BlockCoordinate,
+        shared::{DespawnWithStructure, MeltingDown},
+        shields::SHIELD_COLLISION_GROUP,
+        systems::{
+            StructureSystem, StructureSystems, StructureSystemsSet, SystemActive,
+            dock_system::Docked,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
+            line_system::LineBlocks,
+            repair_beam_system::{RepairBeamProperty, RepairBeamPropertyCalculator, RepairBeamSystem},
+        },
+    },
+};
+
+use crate::structure::block_health::repair::{REPAIR_MATERIAL, StoredRepairMaterial, missing_health, repair_block};
+
+use super::{line_system::add_line_system, sync::register_structure_system};
+
+const BEAM_MAX_RANGE: f32 = 250.0;
+/// Repair beams take their repair material out of these blocks on their own ship
+const STORAGE_BLOCK_ID: &str = "cosmos:storage";
+
+#[derive(Component)]
+struct RepairBeam {
+    property: RepairBeamProperty,
+    /// The first block of the line this beam is coming from, used to find the power grid that powers it
+    line_start: BlockCoordinate,
+    system_entity: Entity,
+    structure_entity: Entity,
+}
+
+fn on_activate_system(
+    query: Query<(Entity, &RepairBeamSystem, &StructureSystem), Added<SystemActive>>,
+    q_structure: Query<(&Structure, &RapierContextEntityLink)>,
+    mut commands: Commands,
+) {
+    for (system_entity, repair_system, system) in query.iter() {
+        let ship_entity = system.structure_entity();
+
+        let Ok((structure, physics_world)) = q_structure.get(ship_entity) else {
+            continue;
+        };
+
+        for line in repair_system.lines.iter() {
+            let beam_direction = line.direction.as_vec3();
+            let rel_pos = structure.block_relative_position(line.end());
+
+            let repair_beam = commands
+                .spawn((
+                    Name::new("Repair beam"),
+                    NoSendEntity,
+                    RepairBeam {
+                        property: line.property,
+                        line_start: line.start,
+                        structure_entity: ship_entity,
+                        system_entity,
+                    },
+                    DespawnWithStructure,
+                    Transform::from_translation(rel_pos).looking_to(beam_direction, Vec3::Y),
+                    *physics_world,
+                ))
+                .id();
+
+            commands.entity(ship_entity).add_child(repair_beam);
+        }
+    }
+}
+
+fn update_repair_beams(
+    mut commands: Commands,
+    q_repair_beams: Query<(Entity, &RepairBeam, &RapierContextEntityLink, &GlobalTransform)>,
+    q_systems: Query<(&StructureSystems, Option<&Docked>)>,
+    mut q_energy_storage_system: Query<&mut EnergyStorageSystem>,
+    mut q_structure: Query<(&mut Structure, &GlobalTransform), Without<MeltingDown>>,
+    mut q_stored_material: Query<&mut StoredRepairMaterial>,
+    mut q_inventory: Query<&mut Inventory, With<BlockData>>,
+    q_is_system_active: Query<(), With<SystemActive>>,
+    rapier_context_access: ReadRapierContext,
+    q_parent: Query<&ChildOf>,
+    items: Res<Registry<Item>>,
+    blocks: Res<Registry<Block>>,
+    time: Res<Time>,
+    mut evw_block_repaired: MessageWriter<BlockRepairedMessage>,
+) {
+    let Some(repair_material) = items.from_id(REPAIR_MATERIAL) else {
+        return;
+    };
+
+    let storage_id = blocks.from_id(STORAGE_BLOCK_ID).map(|x| x.id());
+    let delta_time = time.delta_secs();
+    // Ships often have many beams, so their storage is only looked up once per tick
+    let mut ship_storage = HashMap::<Entity, Vec<Entity>>::default();
+
+    for (beam_ent, beam, p_world, g_trans) in q_repair_beams.iter() {
+        if !q_is_system_active.contains(beam.system_entity)
+            || EnergyStorageSystem::decrease_energy_recursive(
+                PowerCategory::Utility,
+                PowerConsumer::Block(beam.line_start),
+                beam.property.energy_per_second * delta_time,
+                beam.structure_entity,
+                &mut q_energy_storage_system,
+                &q_systems,
+            ) != 0.0
+        {
+            commands.entity(beam_ent).insert(NeedsDespawned);
+            continue;
+        }
+
+        let ray_start = g_trans.translation();
+        let ray_dir = g_trans.forward();
+
+        let Some((hit_entity, toi)) = rapier_context_access.get(*p_world).cast_ray(
+            ray_start,
+            ray_dir.into(),
+            BEAM_MAX_RANGE,
+            true,
+            QueryFilter::predicate(QueryFilter::default(), &|entity| {
+                if beam.structure_entity == entity {
+                    false
+                } else if let Ok(parent) = q_parent.get(entity) {
+                    parent.parent() != beam.structure_entity
+                } else {
+                    false
+                }
+            })
+            .groups(CollisionGroups::new(
+                Group::ALL & !(SHIELD_COLLISION_GROUP | FLUID_COLLISION_GROUP),
+                Group::ALL & !(SHIELD_COLLISION_GROUP | FLUID_COLLISION_GROUP),
+            )),
+        ) else {
+            continue;
+        };
+
+        let hit_structure = if q_structure.contains(hit_entity) {
+            hit_entity
+        } else if let Ok(parent) = q_parent.get(hit_entity)
+            && q_structure.contains(parent.parent())
+        {
+            parent.parent()
+        } else {
+            continue;
+        };
+
+        let Ok((structure, structure_g_trans)) = q_structure.get(hit_structure) else {
+            continue;
+        };
+
+        let global_point_hit = ray_start + (ray_dir * (toi + 0.01));
+
+        let local_point_hit = Quat::from_affine3(&structure_g_trans.affine())
+            .inverse()
+            .mul_vec3(global_point_hit - structure_g_trans.translation());
+
+        let Ok(block_coord) = structure.relative_coords_to_local_coords_checked(local_point_hit.x, local_point_hit.y, local_point_hit.z)
+        else {
+            continue;
+        };
+
+        let missing = missing_health(&structure, block_coord, &blocks);
+        if missing <= 0.0 {
+            continue;
+        }
+
+        let Ok(mut stored_material) = q_stored_material.get_mut(beam.structure_entity) else {
+            continue;
+        };
+
+        // Repair material is taken from any storage on the ship doing the repairing
+        let storage =
+            ship_storage
+                .entry(beam.structure_entity)
+                .or_insert_with(|| match (q_structure.get(beam.structure_entity), storage_id) {
+                    (Ok((ship, _)), Some(storage_id)) => ship
+                        .chunks()
+                        .values()
+                        .flat_map(|chunk| chunk.all_block_data_entities().iter())
+                        .filter(|((block_id, _), _)| *block_id == storage_id)
+                        .map(|(_, &entity)| entity)
+                        .collect(),
+                    _ => vec![],
+                });
+
+        let restored = stored_material.spend(missing.min(beam.property.repair_per_second * delta_time), || {
+            storage.iter().any(|&entity| {
+                q_inventory
+                    .get_mut(entity)
+                    .is_ok_and(|mut inv| inv.take_and_remove_item(repair_material, 1, &mut commands).0 == 0)
+            })
+        });
+
+        if restored <= 0.0 {
+            continue;
+        }
+
+        let Ok((mut structure, _)) = q_structure.get_mut(hit_structure) else {
+            continue;
+        };
+
+        repair_block(
+            &mut structure,
+            block_coord,
+            restored,
+            Some(beam.structure_entity),
+            &blocks,
+            &mut evw_block_repaired,
+        );
+    }
+}
+
+fn register_repair_beam_blocks(blocks: Res<Registry<Block>>, mut repair_beam: ResMut<LineBlocks<RepairBeamProperty>>) {
+    if let Some(block) = blocks.from_id("cosmos:repair_beam") {
+        repair_beam.insert(
+            block,
+            RepairBeamProperty {
+                energy_per_second: 40.0,
+                repair_per_second: 5.0,
+            },
+        )
+    }
+}
+
+pub(super) fn register(app: &mut App) {
+    add_line_system::<RepairBeamProperty, RepairBeamPropertyCalculator>(app);
+
+    app.add_systems(
+        FixedUpdate,
+        (on_activate_system, update_repair_beams)
+            .chain()
+            .in_set(StructureSystemsSet::UpdateSystemsBlocks)
+            .before(LocationPhysicsSet::DoPhysics)
+            .run_if(in_state(GameState::Playing)),
+    )
+    .add_systems(OnEnter(GameState::PostLoading), register_repair_beam_blocks);
+
+    register_structure_system::<RepairBeamSystem>(app, true, "cosmos:repair_beam");
+}
@@ -25,7 +25,7 @@ use cosmos_core::{
         systems::{
             StructureSystem, StructureSystemType, StructureSystems, StructureSystemsSet,
             dock_system::Docked,
-            energy_storage_system::EnergyStorageSystem,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
             shield_system::{ShieldGeneratorBlocks, ShieldGeneratorProperty, ShieldProjectorBlocks, ShieldProjectorProperty, ShieldSystem},
         },
     },
@@ -34,6 +34,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     ai::AiControlled,
+    logic::{LogicInputMessage, LogicSystemSet, logic_driver::LogicDriver},
     persistence::{
         SerializedData,
         loading::{LOADING_SCHEDULE, LoadingSystemSet, NeedsLoaded},
@@ -115,6 +116,62 @@ fn block_update_system(
     }
 }
 
+/// A shield generator receiving any non-zero logic signal is shut off.
+///
+/// Generators are on by default, so a generator that isn't wired to anything (or has its wire removed) keeps running.
+fn shield_generator_input_event_listener(
+    mut evr_logic_input: MessageReader<LogicInputMessage>,
+    shield_generator_blocks: Res<ShieldGeneratorBlocks>,
+    q_structure: Query<(&Structure, &StructureSystems, &LogicDriver)>,
+    mut q_shield_system: Query<&mut ShieldSystem>,
+) {
+    for ev in evr_logic_input.read() {
+        let Ok((structure, systems, logic_driver)) = q_structure.get(ev.block.structure()) else {
+            continue;
+        };
+        let coords = ev.block.coords();
+        if !shield_generator_blocks.0.contains_key(&structure.block_id_at(coords)) {
+            continue;
+        }
+        let Ok(mut shield_system) = systems.query_mut(&mut q_shield_system) else {
+            continue;
+        };
+
+        let disabled = is_disabled_by_logic(structure, logic_driver, coords);
+        if shield_system.is_generator_enabled(coords) == disabled {
+            shield_system.set_generator_enabled(coords, !disabled);
+        }
+    }
+}
+
+fn is_disabled_by_logic(structure: &Structure, logic_driver: &LogicDriver, coords: BlockCoordinate) -> bool {
+    logic_driver
+        .read_all_inputs(coords, structure.block_rotation(coords))
+        .iter()
+        .any(|signal| *signal != 0)
+}
+
+/// Which generators are disabled isn't saved, so it is read from the structure's logic whenever a shield system is loaded.
+fn disable_generators_on_load(
+    mut q_shield_system: Query<(&mut ShieldSystem, &StructureSystem), Added<ShieldSystem>>,
+    q_structure: Query<(&Structure, &LogicDriver)>,
+) {
+    for (mut shield_system, system) in q_shield_system.iter_mut() {
+        let Ok((structure, logic_driver)) = q_structure.get(system.structure_entity()) else {
+            continue;
+        };
+
+        let disabled = shield_system
+            .generator_coords()
+            .filter(|&coords| is_disabled_by_logic(structure, logic_driver, coords))
+            .collect::<Vec<_>>();
+
+        for coords in disabled {
+            shield_system.set_generator_enabled(coords, false);
+        }
+    }
+}
+
 fn structure_loaded_event(
     mut event_reader: MessageReader<StructureLoadedMessage>,
     mut structure_query: Query<(&Structure, &mut StructureSystems)>,
@@ -267,8 +324,15 @@ fn power_shields(
     mut q_storage_system: Query<&mut EnergyStorageSystem>,
     q_systems: Query<(&StructureSystems, Option<&Docked>)>,
     mut q_shields: Query<(Entity, &mut Shield, &ChildOf, Option<&mut ShieldDowntime>)>,
+    blocks: Res<Registry<Block>>,
     time: Res<Time>,
 ) {
+    // Shields are powered by every power grid a shield generator is wired into
+    let consumer = blocks
+        .from_id("cosmos:shield_generator")
+        .map(|block| PowerConsumer::BlockType(block.id()))
+        .unwrap_or(PowerConsumer::Any);
+
     for (ent, mut shield, parent, shield_downtime) in &mut q_shields {
         if shield.strength < shield.max_strength {
             if shield.strength == 0.0 {
@@ -303,7 +367,14 @@ fn power_shields(
                 continue;
             };
 
-            let not_used = EnergyStorageSystem::decrease_energy_recursive(power_usage, structure_entity, &mut q_storage_system, &q_systems);
+            let not_used = EnergyStorageSystem::decrease_energy_recursive(
+                PowerCategory::Shields,
+                consumer,
+                power_usage,
+                structure_entity,
+                &mut q_storage_system,
+                &q_systems,
+            );
 
             let old_strength = shield.strength;
             shield.strength += (power_usage - not_used) * shield.power_efficiency;
@@ -414,6 +485,13 @@ pub(super) fn register(app: &mut App) {
                 .in_set(ShieldSet::RechargeShields)
                 .run_if(in_state(GameState::Playing)),
         )
+        .add_systems(
+            FixedUpdate,
+            (disable_generators_on_load, shield_generator_input_event_listener)
+                .chain()
+                .in_set(LogicSystemSet::Consume)
+                .ambiguous_with(LogicSystemSet::Consume),
+        )
         .add_systems(
             FixedUpdate,
             send_shield_hits
@@ -1,16 +1,19 @@
 //! The thrusters that move a ship
 
-use bevy::prelude::*;
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
 use bevy_rapier3d::{
     plugin::PhysicsSet,
     prelude::{ExternalImpulse, ReadMassProperties, Velocity},
 };
 use cosmos_core::{
-    block::{Block, block_events::BlockMessagesSet},
+    block::{Block, block_events::BlockMessagesSet, block_face::BlockFace},
     events::block_events::BlockChangedMessage,
     physics::location::Location,
-    prelude::FullStructure,
-    registry::Registry,
+    prelude::{BlockCoordinate, FullStructure},
+    registry::{Registry, identifiable::Identifiable},
     state::GameState,
     structure::{
         Structure, StructureTypeSet,
@@ -23,13 +26,14 @@ use cosmos_core::{
         systems::{
             StructureSystem, StructureSystemType, StructureSystems, StructureSystemsSet,
             dock_system::Docked,
-            energy_storage_system::EnergyStorageSystem,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
             thruster_system::{ThrusterBlocks, ThrusterProperty, ThrusterSystem},
         },
     },
 };
 
 use crate::{
+    logic::{LogicInputMessage, LogicSystemSet, logic_driver::LogicDriver},
     persistence::make_persistent::{DefaultPersistentComponent, make_persistent},
     structure::ship::speed::MaxShipSpeed,
 };
@@ -104,8 +108,15 @@ pub(super) fn update_ship_force_and_velocity(
     q_loc: Query<&Location>,
     mut q_vel: Query<&mut Velocity>,
     mut energy_query: Query<&mut EnergyStorageSystem>,
+    blocks: Res<Registry<Block>>,
     time: Res<Time>,
 ) {
+    // Thrusters share the energy of every power grid they are wired into
+    let consumer = blocks
+        .from_id("cosmos:thruster")
+        .map(|block| PowerConsumer::BlockType(block.id()))
+        .unwrap_or(PowerConsumer::Any);
+
     for (thruster_system, system) in thrusters_query.iter() {
         if let Ok((
             loc,
@@ -188,6 +199,8 @@ pub(super) fn update_ship_force_and_velocity(
 
                 if systems.query(&energy_query.as_readonly()).is_ok() {
                     let total_energy = EnergyStorageSystem::compute_total_energy_recursive(
+                        PowerCategory::Thrusters,
+                        consumer,
                         system.structure_entity(),
                         &energy_query.as_readonly(),
                         &q_systems,
@@ -200,7 +213,14 @@ pub(super) fn update_ship_force_and_velocity(
                         ratio = 1.0;
                     }
 
-                    EnergyStorageSystem::decrease_energy_recursive(energy_used, system.structure_entity(), &mut energy_query, &q_systems);
+                    EnergyStorageSystem::decrease_energy_recursive(
+                        PowerCategory::Thrusters,
+                        consumer,
+                        energy_used,
+                        system.structure_entity(),
+                        &mut energy_query,
+                        &q_systems,
+                    );
 
                     movement_vector * (thruster_system.thrust_total() * ratio)
                 } else {
@@ -254,6 +274,116 @@ pub(super) fn update_ship_force_and_velocity(
     }
 }
 
+#[derive(Component, Default, Debug)]
+/// Thrusters on this structure that are being fired by a logic signal, independently of the pilot's controls.
+struct LogicFiredThrusters(HashSet<BlockCoordinate>);
+
+fn thruster_input_event_listener(
+    mut evr_logic_input: MessageReader<LogicInputMessage>,
+    blocks: Res<Registry<Block>>,
+    thruster_blocks: Res<ThrusterBlocks>,
+    q_structure: Query<(&Structure, &LogicDriver)>,
+    mut q_fired: Query<&mut LogicFiredThrusters>,
+    mut commands: Commands,
+) {
+    let mut newly_fired = HashMap::<Entity, LogicFiredThrusters>::default();
+
+    for ev in evr_logic_input.read() {
+        let Ok((structure, logic_driver)) = q_structure.get(ev.block.structure()) else {
+            continue;
+        };
+        let coords = ev.block.coords();
+        if thruster_blocks.get(structure.block_at(coords, &blocks)).is_none() {
+            continue;
+        }
+
+        let active = logic_driver
+            .read_all_inputs(coords, structure.block_rotation(coords))
+            .iter()
+            .any(|signal| *signal != 0);
+
+        let fired = match q_fired.get_mut(ev.block.structure()) {
+            Ok(fired) => fired.into_inner(),
+            Err(_) => newly_fired.entry(ev.block.structure()).or_default(),
+        };
+
+        if active {
+            fired.0.insert(coords);
+        } else {
+            fired.0.remove(&coords);
+        }
+    }
+
+    for (structure_entity, fired) in newly_fired {
+        commands.entity(structure_entity).insert(fired);
+    }
+}
+
+/// Logic-fired thrusters push the ship towards their front face, since their exhaust is on their back face.
+fn apply_logic_fired_thrusters(
+    mut q_ships: Query<
+        (
+            Entity,
+            &Structure,
+            &StructureSystems,
+            &Transform,
+            &mut ExternalImpulse,
+            &mut LogicFiredThrusters,
+        ),
+        With<Ship>,
+    >,
+    q_systems: Query<(&StructureSystems, Option<&Docked>)>,
+    mut energy_query: Query<&mut EnergyStorageSystem>,
+    thruster_blocks: Res<ThrusterBlocks>,
+    blocks: Res<Registry<Block>>,
+    time: Res<Time>,
+) {
+    for (structure_entity, structure, systems, transform, mut external_impulse, mut fired) in q_ships.iter_mut() {
+        if fired.0.is_empty() {
+            continue;
+        }
+
+        // Thrusters that were removed won't send another logic input, so clean them up here.
+        if fired
+            .0
+            .iter()
+            .any(|&coords| thruster_blocks.get(structure.block_at(coords, &blocks)).is_none())
+        {
+            fired
+                .0
+                .retain(|&coords| thruster_blocks.get(structure.block_at(coords, &blocks)).is_some());
+        }
+
+        if systems.query(&energy_query.as_readonly()).is_err() {
+            continue;
+        }
+
+        let mut thrust = Vec3::ZERO;
+
+        for &coords in fired.0.iter() {
+            let Some(prop) = thruster_blocks.get(structure.block_at(coords, &blocks)) else {
+                continue;
+            };
+
+            // Each thruster only gets the energy of the power grid it is wired into
+            let energy_needed = prop.energy_consupmtion * time.delta_secs();
+            let leftover = EnergyStorageSystem::decrease_energy_recursive(
+                PowerCategory::Thrusters,
+                PowerConsumer::Block(coords),
+                energy_needed,
+                structure_entity,
+                &mut energy_query,
+                &q_systems,
+            );
+            let ratio = if energy_needed > 0.0 { 1.0 - leftover / energy_needed } else { 1.0 };
+
+            thrust += structure.block_rotation(coords).direction_of(BlockFace::Front).as_vec3() * (prop.strength * ratio);
+        }
+
+        external_impulse.impulse += transform.rotation * thrust;
+    }
+}
+
 fn structure_loaded_event(
     mut event_reader: MessageReader<StructureLoadedMessage>,
     mut structure_query: Query<(&Structure, &mut StructureSystems)>,
@@ -320,6 +450,20 @@ pub(super) fn register(app: &mut App) {
                 .in_set(StructureSystemsSet::UpdateSystems)
                 .in_set(StructureTypeSet::Ship),
         )
+        .add_systems(
+            FixedUpdate,
+            (
+                thruster_input_event_listener
+                    .in_set(LogicSystemSet::Consume)
+                    .ambiguous_with(LogicSystemSet::Consume),
+                apply_logic_fired_thrusters
+                    .after(update_ship_force_and_velocity)
+                    .before(PhysicsSet::SyncBackend)
+                    .in_set(ThrusterSystemSet::ApplyThrusters)
+                    .in_set(StructureSystemsSet::UpdateSystems)
+                    .in_set(StructureTypeSet::Ship),
+            ),
+        )
         .register_type::<ThrusterSystem>();
 
     register_structure_system::<ThrusterSystem>(app, false, "cosmos:thruster");
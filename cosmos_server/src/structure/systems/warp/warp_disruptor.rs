@@ -0,0 +1,364 @@
+use bevy::{platform::collections::HashSet, prelude::*};
+use cosmos_core::{
+    block::{Block, block_events::BlockMessagesSet},
+    ecs::types::OwnedOrMut,
+    entities::player::Player,
+    events::{block_events::BlockChangedMessage, structure::structure_event::StructureMessageIterator},
+    faction::{FactionId, FactionRelation, Factions},
+    netty::sync::events::server_event::NettyMessageWriter,
+    notifications::Notification,
+    physics::location::Location,
+    prelude::{Structure, StructureLoadedMessage, StructureSystem, StructureSystems},
+    registry::{Registry, identifiable::Identifiable},
+    state::GameState,
+    structure::{
+        ship::pilot::Pilot,
+        systems::{
+            StructureSystemOrdering, StructureSystemType, StructureSystemsSet,
+            dock_system::Docked,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
+            warp::{
+                warp_disruptor::WarpDisruptorSystem,
+                warp_drive::{WarpCancelledMessage, WarpDriveInitiating},
+            },
+        },
+    },
+    universe::warp::{WarpTo, WarpingSet},
+};
+
+use crate::{
+    persistence::make_persistent::{DefaultPersistentComponent, make_persistent},
+    structure::systems::sync::register_structure_system,
+};
+
+use super::warp_drive::ThenTryWarpTo;
+
+/// Interdicted ships are dropped this far before the edge of the field, so they end up just outside of it
+const INTERDICTION_MARGIN: f32 = 500.0;
+
+#[derive(Resource, Debug, Default)]
+struct WarpDisruptorBlocks(HashSet<u16>);
+
+fn register_warp_disruptor_blocks(mut disruptor_blocks: ResMut<WarpDisruptorBlocks>, blocks: Res<Registry<Block>>) {
+    if let Some(b) = blocks.from_id("cosmos:warp_disruptor") {
+        disruptor_blocks.0.insert(b.id());
+    }
+}
+
+fn block_update_system(
+    mut event: MessageReader<BlockChangedMessage>,
+    disruptor_blocks: Res<WarpDisruptorBlocks>,
+    mut q_system: Query<(&StructureSystem, &mut WarpDisruptorSystem)>,
+    mut q_systems: Query<(&mut StructureSystems, &mut StructureSystemOrdering)>,
+    mut commands: Commands,
+    systems_registry: Res<Registry<StructureSystemType>>,
+) {
+    for (structure, ev) in event.read().group_by_structure() {
+        let Ok((mut systems, mut ordering)) = q_systems.get_mut(structure) else {
+            continue;
+        };
+
+        let (structure_system, mut system) = systems
+            .query_mut(&mut q_system)
+            .map(|(ss, x)| (Some(ss), OwnedOrMut::Mut(x)))
+            .unwrap_or_else(|_| (None, OwnedOrMut::Owned(Default::default())));
+
+        for ev in ev {
+            if disruptor_blocks.0.contains(&ev.old_block) {
+                system.remove_disruptor(ev.block.coords());
+            }
+
+            if disruptor_blocks.0.contains(&ev.new_block) {
+                system.add_disruptor(ev.block.coords());
+            }
+        }
+
+        if system.empty()
+            && let Some(structure_system) = structure_system.copied()
+        {
+            systems.remove_system(&mut commands, &structure_system, &systems_registry, ordering.as_mut());
+        } else if let Some(system) = system.owned()
+            && !system.empty()
+        {
+            systems.add_system(&mut commands, system, &systems_registry);
+        }
+    }
+}
+
+fn structure_loaded_event(
+    mut event_reader: MessageReader<StructureLoadedMessage>,
+    mut structure_query: Query<(&Structure, &mut StructureSystems)>,
+    disruptor_blocks: Res<WarpDisruptorBlocks>,
+    mut commands: Commands,
+    systems_registry: Res<Registry<StructureSystemType>>,
+    q_disruptor_system: Query<(), With<WarpDisruptorSystem>>,
+) {
+    for ev in event_reader.read() {
+        let Ok((structure, mut systems)) = structure_query.get_mut(ev.structure_entity) else {
+            continue;
+        };
+
+        if systems.query(&q_disruptor_system).is_ok() {
+            continue;
+        }
+
+        let mut system = WarpDisruptorSystem::default();
+
+        for block in structure.all_blocks_iter(false) {
+            if disruptor_blocks.0.contains(&structure.block_id_at(block)) {
+                system.add_disruptor(block);
+            }
+        }
+
+        if !system.empty() {
+            systems.add_system(&mut commands, system, &systems_registry);
+        }
+    }
+}
+
+fn power_warp_disruptors(
+    mut q_disruptor: Query<(&mut WarpDisruptorSystem, &StructureSystem)>,
+    mut q_ess: Query<&mut EnergyStorageSystem>,
+    q_docked_systems: Query<(&StructureSystems, Option<&Docked>)>,
+    time: Res<Time>,
+) {
+    for (mut disruptor, ss) in q_disruptor.iter_mut() {
+        let needed = disruptor.energy_per_second() * time.delta_secs();
+        let leftover = EnergyStorageSystem::decrease_energy_recursive(
+            PowerCategory::Utility,
+            PowerConsumer::Any,
+            needed,
+            ss.structure_entity(),
+            &mut q_ess,
+            &q_docked_systems,
+        );
+
+        let powered = leftover == 0.0;
+        if disruptor.powered() != powered {
+            disruptor.set_powered(powered);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Interdiction {
+    /// The ship is already within a field, so it cannot warp at all
+    CannotWarp,
+    /// The ship's path passes through a field, so it should be dropped here instead
+    DropAt(Location),
+}
+
+/// Finds the first field (center, radius) that the straight path between these locations passes through
+fn find_interdiction(from: &Location, to: &Location, fields: impl Iterator<Item = (Location, f32)>) -> Option<Interdiction> {
+    let path = (*to - *from).absolute_coords_f32();
+    let path_len_sqrd = path.length_squared();
+
+    let mut first_entry: Option<f32> = None;
+
+    for (center, radius) in fields {
+        let rel = (*from - center).absolute_coords_f32();
+        let c = rel.length_squared() - radius * radius;
+
+        if c <= 0.0 {
+            return Some(Interdiction::CannotWarp);
+        }
+
+        if path_len_sqrd == 0.0 {
+            continue;
+        }
+
+        // Solves |rel + path * t| = radius for the first t the path touches the field
+        let b = rel.dot(path);
+        let discriminant = b * b - path_len_sqrd * c;
+        if discriminant < 0.0 {
+            continue;
+        }
+
+        let t = (-b - discriminant.sqrt()) / path_len_sqrd;
+        if (0.0..=1.0).contains(&t) && first_entry.is_none_or(|first| t < first) {
+            first_entry = Some(t);
+        }
+    }
+
+    first_entry.map(|t| {
+        let t = (t - INTERDICTION_MARGIN / path_len_sqrd.sqrt()).max(0.0);
+        Interdiction::DropAt(*from + path * t)
+    })
+}
+
+/// A powered warp disruptor field
+struct Field {
+    structure: Entity,
+    location: Location,
+    radius: f32,
+    faction: Option<FactionId>,
+}
+
+/// The fields that would interdict this ship - fields never interdict their own structure or its allies
+fn fields_interdicting<'a>(
+    ship: Entity,
+    ship_faction: Option<&'a FactionId>,
+    fields: &'a [Field],
+    factions: &'a Factions,
+) -> impl Iterator<Item = (Location, f32)> + 'a {
+    fields
+        .iter()
+        .filter(move |field| {
+            field.structure != ship
+                && !field
+                    .faction
+                    .and_then(|f| factions.from_id(&f))
+                    .is_some_and(|fac| fac.relation_with(ship_faction.and_then(|f| factions.from_id(f))) == FactionRelation::Ally)
+        })
+        .map(|field| (field.location, field.radius))
+}
+
+fn interdict_warps(
+    q_disruptors: Query<(&WarpDisruptorSystem, &StructureSystem)>,
+    q_structure: Query<(&Location, Option<&FactionId>)>,
+    mut q_initiating: Query<(Entity, &Location, &mut ThenTryWarpTo, Option<&FactionId>, Option<&Pilot>), Added<WarpDriveInitiating>>,
+    mut q_warping: Query<(Entity, &Location, &mut WarpTo, Option<&FactionId>, Option<&Pilot>), Added<WarpTo>>,
+    factions: Res<Factions>,
+    q_player: Query<&Player>,
+    mut nevw_notification: NettyMessageWriter<Notification>,
+    mut nevw_warp_cancelled: NettyMessageWriter<WarpCancelledMessage>,
+    mut commands: Commands,
+) {
+    let fields = q_disruptors
+        .iter()
+        .filter(|(disruptor, _)| disruptor.powered() && !disruptor.empty())
+        .flat_map(|(disruptor, ss)| {
+            let structure = ss.structure_entity();
+            q_structure.get(structure).ok().map(|(location, faction)| Field {
+                structure,
+                location: *location,
+                radius: disruptor.field_radius(),
+                faction: faction.copied(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if fields.is_empty() {
+        return;
+    }
+
+    let mut notify = |pilot: Option<&Pilot>, notification: Notification| {
+        if let Some(player) = pilot.and_then(|pilot| q_player.get(pilot.entity).ok()) {
+            nevw_notification.write(notification, player.client_id());
+        }
+    };
+
+    for (ship, location, mut then_warp_to, faction, pilot) in q_initiating.iter_mut() {
+        match find_interdiction(location, &then_warp_to.0, fields_interdicting(ship, faction, &fields, &factions)) {
+            None => {}
+            Some(Interdiction::CannotWarp) => {
+                commands.entity(ship).remove::<(WarpDriveInitiating, ThenTryWarpTo)>();
+                nevw_warp_cancelled.broadcast(WarpCancelledMessage { structure_entity: ship });
+                notify(
+                    pilot,
+                    Notification::error("A warp disruptor field is preventing your ship from warping!"),
+                );
+            }
+            Some(Interdiction::DropAt(drop_at)) => {
+                info!("Warp of {ship:?} will be interdicted at {drop_at}.");
+                then_warp_to.0 = drop_at;
+                notify(
+                    pilot,
+                    Notification::error(format!(
+                        "Your warp passes through a warp disruptor field! You will drop out of warp in sector {}.",
+                        drop_at.sector()
+                    )),
+                );
+            }
+        }
+    }
+
+    for (ship, location, mut warp_to, faction, pilot) in q_warping.iter_mut() {
+        match find_interdiction(location, &warp_to.loc, fields_interdicting(ship, faction, &fields, &factions)) {
+            None => {}
+            Some(Interdiction::CannotWarp) => {
+                commands.entity(ship).remove::<WarpTo>();
+                notify(
+                    pilot,
+                    Notification::error("A warp disruptor field is preventing your ship from warping!"),
+                );
+            }
+            Some(Interdiction::DropAt(drop_at)) => {
+                info!("Warp of {ship:?} interdicted at {drop_at}.");
+                warp_to.loc = drop_at;
+                notify(
+                    pilot,
+                    Notification::error(format!(
+                        "Your ship was pulled out of warp by a warp disruptor field in sector {}!",
+                        drop_at.sector()
+                    )),
+                );
+            }
+        }
+    }
+}
+
+impl DefaultPersistentComponent for WarpDisruptorSystem {}
+
+pub(super) fn register(app: &mut App) {
+    make_persistent::<WarpDisruptorSystem>(app);
+
+    app.init_resource::<WarpDisruptorBlocks>()
+        .add_systems(OnEnter(GameState::PostLoading), register_warp_disruptor_blocks)
+        .add_systems(
+            FixedUpdate,
+            power_warp_disruptors
+                .in_set(StructureSystemsSet::UpdateSystems)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            FixedUpdate,
+            interdict_warps
+                .in_set(WarpingSet::StartWarping)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                structure_loaded_event
+                    .in_set(StructureSystemsSet::InitSystems)
+                    .ambiguous_with(StructureSystemsSet::InitSystems),
+                block_update_system
+                    .in_set(BlockMessagesSet::ProcessMessages)
+                    .in_set(StructureSystemsSet::UpdateSystemsBlocks),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+
+    register_structure_system::<WarpDisruptorSystem>(app, false, "cosmos:warp_disruptor");
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::math::Vec3;
+    use cosmos_core::physics::location::Location;
+
+    use super::{INTERDICTION_MARGIN, Interdiction, find_interdiction};
+
+    #[test]
+    fn drops_ships_at_field_edge() {
+        let from = Location::new(Vec3::ZERO, Default::default());
+        let to = Location::new(Vec3::new(10_000.0, 0.0, 0.0), Default::default());
+
+        let field = (Location::new(Vec3::new(6_000.0, 0.0, 0.0), Default::default()), 2_000.0);
+        let Some(Interdiction::DropAt(drop_at)) = find_interdiction(&from, &to, [field].into_iter()) else {
+            panic!("The ship should have been interdicted");
+        };
+        let expected = 4_000.0 - INTERDICTION_MARGIN;
+        assert!((drop_at.absolute_coords_f32().x - expected).abs() < 0.1);
+
+        let missed = (Location::new(Vec3::new(6_000.0, 5_000.0, 0.0), Default::default()), 2_000.0);
+        assert_eq!(find_interdiction(&from, &to, [missed].into_iter()), None);
+
+        let behind = (Location::new(Vec3::new(-6_000.0, 0.0, 0.0), Default::default()), 2_000.0);
+        assert_eq!(find_interdiction(&from, &to, [behind].into_iter()), None);
+
+        let inside = (Location::new(Vec3::new(500.0, 0.0, 0.0), Default::default()), 2_000.0);
+        assert_eq!(find_interdiction(&from, &to, [inside].into_iter()), Some(Interdiction::CannotWarp));
+    }
+}
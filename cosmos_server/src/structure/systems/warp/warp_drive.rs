@@ -1,10 +1,14 @@
-use bevy::{platform::collections::HashMap, prelude::*};
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
 use bevy_rapier3d::prelude::ReadMassProperties;
 use cosmos_core::{
     block::{Block, block_events::BlockMessagesSet},
     ecs::types::OwnedOrMut,
     entities::player::Player,
     events::{block_events::BlockChangedMessage, structure::structure_event::StructureMessageIterator},
+    faction::FactionId,
     netty::sync::events::server_event::NettyMessageWriter,
     notifications::Notification,
     physics::location::{Location, SECTOR_DIMENSIONS},
@@ -12,11 +16,15 @@ use cosmos_core::{
     registry::{Registry, identifiable::Identifiable},
     state::GameState,
     structure::{
-        ship::{pilot::Pilot, warp::DesiredLocation},
+        ship::{
+            Ship,
+            pilot::Pilot,
+            warp::{DesiredLocation, FollowFleetWarp},
+        },
         systems::{
             StructureSystemCharge, StructureSystemOrdering, StructureSystemType, StructureSystemsSet, SystemActive,
             dock_system::Docked,
-            energy_storage_system::EnergyStorageSystem,
+            energy_storage_system::{EnergyStorageSystem, power_allocation::PowerCategory, power_grid::PowerConsumer},
             warp::warp_drive::{WarpBlockProperty, WarpCancelledMessage, WarpDriveInitiating, WarpDriveSystem},
         },
     },
@@ -24,6 +32,7 @@ use cosmos_core::{
 };
 
 use crate::{
+    logic::{LogicInputMessage, LogicSystemSet, logic_driver::LogicDriver},
     persistence::make_persistent::{DefaultPersistentComponent, make_persistent},
     structure::systems::sync::register_structure_system,
     universe::warp::WarpAnchor,
@@ -133,13 +142,65 @@ fn register_warp_blocks(mut warp_blocks: ResMut<WarpDriveBlocks>, blocks: Res<Re
     }
 }
 
+#[derive(Message, Debug)]
+/// Sent when a warp drive block on this structure receives a logic signal, which should make it jump.
+struct LogicWarpJumpMessage(Entity);
+
+/// A warp drive block receiving any non-zero logic signal attempts a jump, as if the pilot had activated the warp drive.
+fn warp_drive_input_event_listener(
+    mut evr_logic_input: MessageReader<LogicInputMessage>,
+    warp_blocks: Res<WarpDriveBlocks>,
+    q_structure: Query<(&Structure, &LogicDriver)>,
+    mut evw_logic_jump: MessageWriter<LogicWarpJumpMessage>,
+) {
+    for ev in evr_logic_input.read() {
+        let Ok((structure, logic_driver)) = q_structure.get(ev.block.structure()) else {
+            continue;
+        };
+        let coords = ev.block.coords();
+        if warp_blocks.get(structure.block_id_at(coords)).is_none() {
+            continue;
+        }
+
+        let active = logic_driver
+            .read_all_inputs(coords, structure.block_rotation(coords))
+            .iter()
+            .any(|signal| *signal != 0);
+
+        if active {
+            evw_logic_jump.write(LogicWarpJumpMessage(ev.block.structure()));
+        }
+    }
+}
+
+/// Ships within this distance of a ship that warps will follow it, if they are set to follow fleet warps
+const FLEET_WARP_RADIUS: f32 = 2_000.0;
+
+#[derive(Component, Debug)]
+/// This ship is warping as part of the fleet led by this ship
+struct FleetWarpingWith(Entity);
+
 fn on_activate_system(
-    mut q_active: Query<(&mut WarpDriveSystem, &StructureSystem, &SystemActive)>,
+    mut q_warp: Query<(&mut WarpDriveSystem, &StructureSystem, Option<&SystemActive>)>,
+    q_structure_systems: Query<&StructureSystems>,
+    mut evr_logic_jump: MessageReader<LogicWarpJumpMessage>,
     q_systems: Query<
-        (&Pilot, Entity, &Location, &Transform, &ReadMassProperties, Option<&DesiredLocation>),
+        (
+            Option<&Pilot>,
+            &Location,
+            &Transform,
+            &ReadMassProperties,
+            Option<&DesiredLocation>,
+            Option<&FactionId>,
+        ),
         (Without<ChildOf>, Without<WarpDriveInitiating>, Without<WarpTo>),
     >,
+    q_followers: Query<
+        (Entity, Option<&Pilot>, &Location, &ReadMassProperties, &FactionId, &FollowFleetWarp),
+        (With<Ship>, Without<ChildOf>, Without<WarpDriveInitiating>, Without<WarpTo>),
+    >,
     q_warping: Query<Entity, With<WarpDriveInitiating>>,
+    q_fleet_warping: Query<(Entity, &FleetWarpingWith)>,
     mut commands: Commands,
     mut notify: NettyMessageWriter<Notification>,
     q_player: Query<&Player>,
@@ -148,33 +209,86 @@ fn on_activate_system(
     const MAX_JUMP_DIST: f32 = SECTOR_DIMENSIONS * 5.0;
     const MIN_JUMP_DIST: f32 = SECTOR_DIMENSIONS * 1.0;
 
-    for (mut warp, ss, active) in q_active.iter_mut() {
+    let mut wants_jump = evr_logic_jump.read().map(|ev| ev.0).collect::<HashSet<Entity>>();
+
+    for (_, ss, active) in q_warp.iter() {
+        let Some(active) = active else {
+            continue;
+        };
+
         if active.secondary() {
             if let Ok(ent_warping) = q_warping.get(ss.structure_entity()) {
                 commands.entity(ent_warping).remove::<WarpDriveInitiating>();
                 nevw_warp_cancelled.broadcast(WarpCancelledMessage {
                     structure_entity: ent_warping,
                 });
+
+                for (follower, _) in q_fleet_warping.iter().filter(|(_, with)| with.0 == ent_warping) {
+                    commands
+                        .entity(follower)
+                        .remove::<(WarpDriveInitiating, ThenTryWarpTo, FleetWarpingWith)>();
+                    nevw_warp_cancelled.broadcast(WarpCancelledMessage {
+                        structure_entity: follower,
+                    });
+                }
             }
             continue;
         }
 
-        let Ok((pilot, ent, loc, trans, mass, desierd_loc)) = q_systems.get(ss.structure_entity()) else {
+        wants_jump.insert(ss.structure_entity());
+    }
+
+    // Ships that are already jumping this tick, either on their own or as part of a fleet
+    let mut jumping = HashSet::new();
+
+    for ent in wants_jump {
+        if jumping.contains(&ent) {
+            continue;
+        }
+
+        let Ok((pilot, loc, trans, mass, desierd_loc, faction)) = q_systems.get(ent) else {
+            continue;
+        };
+        let Ok(systems) = q_structure_systems.get(ent) else {
+            continue;
+        };
+        let Ok((mut warp, _, _)) = systems.query_mut(&mut q_warp) else {
             continue;
         };
 
-        if warp.max_charge() < WarpDriveSystem::compute_jump_charge(mass.get().mass) {
-            if let Ok(player) = q_player.get(pilot.entity) {
-                notify.write(
-                    Notification::error("Not enough warp drives to support this ship's size"),
-                    player.client_id(),
-                );
+        let pilot_player = pilot.and_then(|pilot| q_player.get(pilot.entity).ok());
+
+        let followers = faction
+            .map(|faction| {
+                q_followers
+                    .iter()
+                    .filter(|(follower, _, follower_loc, _, follower_faction, follow)| {
+                        follow.0
+                            && *follower != ent
+                            && !jumping.contains(follower)
+                            && *follower_faction == faction
+                            && follower_loc.distance_sqrd(loc) <= FLEET_WARP_RADIUS * FLEET_WARP_RADIUS
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let fleet_mass = mass.get().mass + followers.iter().map(|(_, _, _, mass, _, _)| mass.get().mass).sum::<f32>();
+
+        if warp.max_charge() < WarpDriveSystem::compute_jump_charge(fleet_mass) {
+            if let Some(player) = pilot_player {
+                let message = if followers.is_empty() {
+                    "Not enough warp drives to support this ship's size"
+                } else {
+                    "Not enough warp drives to support this fleet's size"
+                };
+                notify.write(Notification::error(message), player.client_id());
             }
             continue;
         }
 
-        if !warp.can_jump(mass.get().mass) {
-            if let Ok(player) = q_player.get(pilot.entity) {
+        if !warp.can_jump(fleet_mass) {
+            if let Some(player) = pilot_player {
                 notify.write(Notification::error("This warp drive is not charged"), player.client_id());
             }
             continue;
@@ -183,7 +297,7 @@ fn on_activate_system(
         let warp_to = if let Some(desired_loc) = desierd_loc.and_then(|x| x.0) {
             let dist_sqrd = desired_loc.distance_sqrd(loc);
             if dist_sqrd < MIN_JUMP_DIST * MIN_JUMP_DIST
-                && let Ok(player) = q_player.get(pilot.entity)
+                && let Some(player) = pilot_player
             {
                 notify.write(Notification::error("That is too close to warp to!"), player.client_id());
                 continue;
@@ -206,13 +320,32 @@ fn on_activate_system(
             },
             ThenTryWarpTo(warp_to),
         ));
+        jumping.insert(ent);
+
+        for (follower, follower_pilot, follower_loc, _, _, _) in followers {
+            // Followers keep their position relative to the ship they are following
+            commands.entity(follower).insert((
+                WarpDriveInitiating {
+                    charge: 0.0,
+                    max_charge: 14.5,
+                },
+                ThenTryWarpTo(warp_to + (*follower_loc - *loc)),
+                FleetWarpingWith(ent),
+            ));
+            jumping.insert(follower);
+
+            if let Some(player) = follower_pilot.and_then(|pilot| q_player.get(pilot.entity).ok()) {
+                notify.write(Notification::info("Following fleet warp"), player.client_id());
+            }
+        }
 
         commands.spawn((warp_to, WarpAnchor));
     }
 }
 
 #[derive(Component)]
-struct ThenTryWarpTo(Location);
+/// Once this structure's warp sequence finishes, it will warp to this location
+pub(super) struct ThenTryWarpTo(pub(super) Location);
 
 fn warp_to_after_initialized(
     mut commands: Commands,
@@ -226,6 +359,7 @@ fn warp_to_after_initialized(
                 .entity(ent)
                 .remove::<WarpDriveInitiating>()
                 .remove::<ThenTryWarpTo>()
+                .remove::<FleetWarpingWith>()
                 .insert(WarpTo { loc: then_warp_to.0 });
         }
     }
@@ -267,6 +401,8 @@ fn charge_warp_drive(
         let mut charge_amt = warp.charge_per_tick();
 
         let leftover = (EnergyStorageSystem::decrease_energy_recursive(
+            PowerCategory::Utility,
+            PowerConsumer::Any,
             (charge_amt * 10) as f32,
             ss.structure_entity(),
             &mut q_ess,
@@ -281,11 +417,14 @@ fn charge_warp_drive(
 }
 
 impl DefaultPersistentComponent for WarpDriveSystem {}
+impl DefaultPersistentComponent for FollowFleetWarp {}
 
 pub(super) fn register(app: &mut App) {
     make_persistent::<WarpDriveSystem>(app);
+    make_persistent::<FollowFleetWarp>(app);
 
     app.init_resource::<WarpDriveBlocks>()
+        .add_message::<LogicWarpJumpMessage>()
         .add_systems(OnEnter(GameState::PostLoading), register_warp_blocks)
         .add_systems(
             FixedUpdate,
@@ -293,6 +432,12 @@ pub(super) fn register(app: &mut App) {
                 .chain()
                 .in_set(StructureSystemsSet::UpdateSystems),
         )
+        .add_systems(
+            FixedUpdate,
+            warp_drive_input_event_listener
+                .in_set(LogicSystemSet::Consume)
+                .ambiguous_with(LogicSystemSet::Consume),
+        )
         .add_systems(
             FixedUpdate,
             (